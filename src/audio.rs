@@ -0,0 +1,207 @@
+//! audio.rs is the audio subsystem of the Targeted Vector, it owns the only audio manager and mixes sounds through volume buses.
+use kira::manager::backend::cpal::CpalBackend;
+use kira::manager::{AudioManager, AudioManagerSettings};
+use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+use kira::sound::PlaybackState;
+use kira::track::{TrackBuilder, TrackHandle, TrackRoutes};
+use kira::tween::Tween;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// 同一音效允许同时播放的最大实例数
+pub const MAX_SOUND_INSTANCES: usize = 4;
+// 背景音乐交叉淡入淡出的时长(秒)
+pub const MUSIC_CROSSFADE_SECONDS: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioBus {
+    Master,
+    Sfx,
+    Music,
+    Ui,
+}
+
+impl AudioBus {
+    pub const ALL: [AudioBus; 4] = [
+        AudioBus::Master,
+        AudioBus::Sfx,
+        AudioBus::Music,
+        AudioBus::Ui,
+    ];
+
+    // 在User::settings中保存音量所用的键
+    pub fn setting_key(&self) -> &'static str {
+        match self {
+            AudioBus::Master => "master_volume",
+            AudioBus::Sfx => "sfx_volume",
+            AudioBus::Music => "music_volume",
+            AudioBus::Ui => "ui_volume",
+        }
+    }
+
+    // 在GameText.json中显示名称所用的键
+    pub fn game_text_key(&self) -> &'static str {
+        match self {
+            AudioBus::Master => "game_master_volume",
+            AudioBus::Sfx => "game_sfx_volume",
+            AudioBus::Music => "game_music_volume",
+            AudioBus::Ui => "game_ui_volume",
+        }
+    }
+}
+
+pub struct Audio {
+    pub manager: Option<AudioManager<CpalBackend>>,
+    pub master_track: Option<TrackHandle>,
+    pub bus_track: HashMap<String, TrackHandle>,
+    pub bus_volume: HashMap<String, f64>,
+    pub sound_cache: HashMap<String, StaticSoundData>,
+    pub sound_instance: HashMap<String, Vec<StaticSoundHandle>>,
+    pub music_path: String,
+    pub music_handle: Option<StaticSoundHandle>,
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        let mut audio = Audio {
+            manager: None,
+            master_track: None,
+            bus_track: HashMap::new(),
+            bus_volume: HashMap::new(),
+            sound_cache: HashMap::new(),
+            sound_instance: HashMap::new(),
+            music_path: String::new(),
+            music_handle: None,
+        };
+        for bus in AudioBus::ALL {
+            audio.bus_volume.insert(bus.setting_key().to_string(), 1.0);
+        }
+        // 没有可用的音频设备时静默运行，不影响游戏本身
+        if let Ok(mut manager) = AudioManager::<CpalBackend>::new(AudioManagerSettings::default()) {
+            if let Ok(master_track) = manager.add_sub_track(TrackBuilder::new()) {
+                for bus in [AudioBus::Sfx, AudioBus::Music, AudioBus::Ui] {
+                    if let Ok(track) = manager.add_sub_track(
+                        TrackBuilder::new().routes(TrackRoutes::parent(&master_track)),
+                    ) {
+                        audio.bus_track.insert(bus.setting_key().to_string(), track);
+                    };
+                }
+                audio.master_track = Some(master_track);
+            };
+            audio.manager = Some(manager);
+        };
+        audio
+    }
+
+    pub fn volume(&self, bus: AudioBus) -> f64 {
+        *self.bus_volume.get(bus.setting_key()).unwrap_or(&1.0)
+    }
+
+    pub fn set_volume(&mut self, bus: AudioBus, volume: f64) {
+        let volume = volume.clamp(0.0, 1.0);
+        self.bus_volume
+            .insert(bus.setting_key().to_string(), volume);
+        if let Some(track) = self.output_track(bus) {
+            let _ = track.set_volume(volume, Tween::default());
+        };
+    }
+
+    // 从用户设置中读取各总线音量，缺失的键补为默认值
+    pub fn load_volume(&mut self, settings: &mut HashMap<String, String>) {
+        for bus in AudioBus::ALL {
+            let volume = settings
+                .entry(bus.setting_key().to_string())
+                .or_insert("1".to_string())
+                .parse::<f64>()
+                .unwrap_or(1.0);
+            self.set_volume(bus, volume);
+        }
+    }
+
+    pub fn save_volume(&self, settings: &mut HashMap<String, String>) {
+        for bus in AudioBus::ALL {
+            settings.insert(bus.setting_key().to_string(), self.volume(bus).to_string());
+        }
+    }
+
+    fn sound_data(&mut self, path: &str) -> Option<StaticSoundData> {
+        if !self.sound_cache.contains_key(path) {
+            let sound_data =
+                StaticSoundData::from_file(path, StaticSoundSettings::default()).ok()?;
+            self.sound_cache.insert(path.to_string(), sound_data);
+        };
+        self.sound_cache.get(path).cloned()
+    }
+
+    pub fn play(&mut self, path: &str, bus: AudioBus) {
+        if self.manager.is_none() || path.is_empty() {
+            return;
+        };
+        let instance = self.sound_instance.entry(path.to_string()).or_default();
+        instance.retain(|handle| handle.state() != PlaybackState::Stopped);
+        if instance.len() >= MAX_SOUND_INSTANCES {
+            // 达到上限时停止最早的实例，保证最新的反馈能被听到
+            let _ = instance[0].stop(Tween::default());
+            instance.remove(0);
+        };
+        let Some(sound_data) = self.sound_data(path) else {
+            return;
+        };
+        let mut settings = StaticSoundSettings::new();
+        if let Some(track) = self.output_track(bus) {
+            settings = settings.output_destination(track);
+        };
+        if let Some(manager) = self.manager.as_mut() {
+            if let Ok(handle) = manager.play(sound_data.with_settings(settings)) {
+                self.sound_instance
+                    .entry(path.to_string())
+                    .or_default()
+                    .push(handle);
+            };
+        };
+    }
+
+    // 切换背景音乐，传入空路径时淡出当前音乐
+    pub fn play_music(&mut self, path: &str) {
+        if self.manager.is_none() || self.music_path == path {
+            return;
+        };
+        let crossfade = Tween {
+            duration: Duration::from_secs_f64(MUSIC_CROSSFADE_SECONDS),
+            ..Default::default()
+        };
+        if let Some(mut handle) = self.music_handle.take() {
+            let _ = handle.stop(crossfade);
+        };
+        self.music_path = path.to_string();
+        if path.is_empty() {
+            return;
+        };
+        let Some(sound_data) = self.sound_data(path) else {
+            return;
+        };
+        let mut settings = StaticSoundSettings::new()
+            .loop_region(..)
+            .fade_in_tween(crossfade);
+        if let Some(track) = self.output_track(AudioBus::Music) {
+            settings = settings.output_destination(track);
+        };
+        if let Some(manager) = self.manager.as_mut() {
+            self.music_handle = manager.play(sound_data.with_settings(settings)).ok();
+        };
+    }
+
+    fn output_track(&self, bus: AudioBus) -> Option<&TrackHandle> {
+        if bus == AudioBus::Master {
+            self.master_track.as_ref()
+        } else {
+            self.bus_track.get(bus.setting_key())
+        }
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,97 @@
+//! cli.rs is the command line of the Targeted Vector, it parses the launch flags used to jump straight into a page or an operation.
+use crate::function::Config;
+
+pub const CLI_USAGE: &str = "Usage: Targeted-Vector [options]
+  --user <name>       log in as the given user
+  --level <path>      open the given level file as an operation
+  --page <name>       switch to the given page after launching
+  --resources <dir>   use the Resources folder inside the given directory
+  --windowed <WxH>    start in a window of the given size instead of maximized
+  --debug             enable the debug panel (F3)
+  --strict            enable strict mode for configuration errors
+  --validate          validate the files in Resources/config and exit
+  --help              print this message and exit";
+
+#[derive(Debug, Clone, Default)]
+pub struct CliOptions {
+    pub user: Option<String>,
+    pub level: Option<String>,
+    pub page: Option<String>,
+    pub resources: Option<String>,
+    pub windowed: Option<[f32; 2]>,
+    pub debug: bool,
+    pub strict: bool,
+    pub validate: bool,
+    pub help: bool,
+}
+
+impl CliOptions {
+    // args不包含程序路径
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<CliOptions, String> {
+        let mut options = CliOptions::default();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .filter(|x| !x.starts_with("--"))
+                    .ok_or(format!("{} requires a value", flag))
+            };
+            match arg.as_str() {
+                "--user" => options.user = Some(value("--user")?),
+                "--level" => options.level = Some(value("--level")?),
+                "--page" => options.page = Some(value("--page")?),
+                "--resources" => options.resources = Some(value("--resources")?),
+                "--windowed" => {
+                    let size = value("--windowed")?;
+                    options.windowed = Some(
+                        parse_window_size(&size)
+                            .ok_or(format!("--windowed expects WxH, got \"{}\"", size))?,
+                    );
+                }
+                "--debug" => options.debug = true,
+                "--strict" => options.strict = true,
+                "--validate" => options.validate = true,
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("unknown option \"{}\"", arg)),
+            };
+        }
+        Ok(options)
+    }
+
+    // 覆盖Preferences.json中的设置，只对本次运行生效
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(user) = &self.user {
+            config.login_user_name = user.clone();
+        };
+        if self.debug {
+            config.enable_debug_mode = true;
+        };
+        if self.strict {
+            config.rc_strict_mode = true;
+        };
+    }
+
+    // 启动后要直接进入的页面，指定关卡时默认进入作战
+    pub fn launch_page(&self) -> Option<String> {
+        if let Some(page) = &self.page {
+            Some(page.clone())
+        } else if self.level.is_some() {
+            Some("Operation".to_string())
+        } else if self.user.is_some() {
+            Some("Home_Page".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+// 写法为"1600x900"
+fn parse_window_size(size: &str) -> Option<[f32; 2]> {
+    let (width, height) = size.split_once(['x', 'X'])?;
+    let width = width.trim().parse::<f32>().ok()?;
+    let height = height.trim().parse::<f32>().ok()?;
+    if width > 0_f32 && height > 0_f32 {
+        Some([width, height])
+    } else {
+        None
+    }
+}
@@ -0,0 +1,332 @@
+//! endless.rs is the survival mode of the Targeted Vector, it generates escalating waves from the enemy and path pools.
+use crate::function::{
+    list_files_recursive, read_from_json, JsonReadEnemy, MovePath, OperationTargetEnemy,
+    PathEndBehavior,
+};
+use crate::load_error::{JsonField, LoadError};
+use crate::simulation::OPERATION_SIZE;
+use json::JsonValue;
+use std::path::Path;
+
+// 进入作战后第一波敌人出现前的准备时间(秒)
+pub const ENDLESS_FIRST_WAVE_DELAY: f32 = 3_f32;
+// 场上敌人被清空后，至少等待该时长(秒)才提前进入下一波
+pub const ENDLESS_CLEAR_DELAY: f32 = 2_f32;
+
+// SplitMix64，同一种子总是生成相同的波次，保证回放可以复现
+#[derive(Debug, Clone)]
+pub struct EndlessRng {
+    pub state: u64,
+}
+
+impl EndlessRng {
+    pub fn new(seed: u32) -> EndlessRng {
+        EndlessRng { state: seed as u64 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // [0, 1)之间的随机数
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1_u64 << 24) as f32
+    }
+
+    // [0, len)之间的随机序号
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len.max(1) as u64) as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MapEndless {
+    // 提供目标线、背景、弹药等全局设置的关卡文件，其中的敌人会被忽略
+    pub endless_level: String,
+    // 为空时使用全部enemy_*.json
+    pub endless_enemy_pool: Vec<String>,
+    // 为空时使用全部朝目标线行进的path_*.json
+    pub endless_path_pool: Vec<String>,
+    pub endless_enemy_size: [f32; 2],
+    // 第一波的难度预算，之后每波乘以endless_budget_growth
+    pub endless_base_budget: f32,
+    pub endless_budget_growth: f32,
+    // 每波敌人数量的上限，超出上限的预算用于提高敌人的生命值
+    pub endless_max_enemy: u32,
+    pub endless_wave_interval: f32,
+    pub endless_spawn_interval: f32,
+    // 固定种子，缺省时每次进入随机生成
+    pub endless_seed: Option<u32>,
+}
+
+impl MapEndless {
+    pub fn from_json_value(value: &JsonValue) -> Result<MapEndless, LoadError> {
+        Ok(MapEndless {
+            endless_level: value.field_str("endless_level")?,
+            // 以下字段均可省略，省略时使用默认值；存在但类型不符时仍为错误
+            endless_enemy_pool: value
+                .optional("endless_enemy_pool", JsonValue::field_str_array)?
+                .unwrap_or_default(),
+            endless_path_pool: value
+                .optional("endless_path_pool", JsonValue::field_str_array)?
+                .unwrap_or_default(),
+            endless_enemy_size: value
+                .optional("endless_enemy_size", JsonValue::field_pair)?
+                .unwrap_or([90_f32, 90_f32]),
+            endless_base_budget: value
+                .optional("endless_base_budget", JsonValue::field_f32)?
+                .unwrap_or(4_f32),
+            endless_budget_growth: value
+                .optional("endless_budget_growth", JsonValue::field_f32)?
+                .unwrap_or(1.3),
+            endless_max_enemy: value
+                .optional("endless_max_enemy", JsonValue::field_u32)?
+                .unwrap_or(12)
+                .max(1),
+            endless_wave_interval: value
+                .optional("endless_wave_interval", JsonValue::field_f32)?
+                .unwrap_or(20_f32),
+            endless_spawn_interval: value
+                .optional("endless_spawn_interval", JsonValue::field_f32)?
+                .unwrap_or(1_f32),
+            endless_seed: value.optional("endless_seed", JsonValue::field_u32)?,
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        let mut value = json::object! {
+            endless_level: self.endless_level.clone(),
+            endless_enemy_pool: self.endless_enemy_pool.clone(),
+            endless_path_pool: self.endless_path_pool.clone(),
+            endless_enemy_size: [self.endless_enemy_size[0], self.endless_enemy_size[1]],
+            endless_base_budget: self.endless_base_budget,
+            endless_budget_growth: self.endless_budget_growth,
+            endless_max_enemy: self.endless_max_enemy,
+            endless_wave_interval: self.endless_wave_interval,
+            endless_spawn_interval: self.endless_spawn_interval,
+        };
+        if let Some(seed) = self.endless_seed {
+            value["endless_seed"] = seed.into();
+        };
+        value
+    }
+}
+
+// 敌人的难度消耗，由生命值与防御力估算
+pub fn enemy_budget_cost(enemy: &JsonReadEnemy) -> u32 {
+    ((enemy.enemy_hp + enemy.enemy_def * 5_f32) / 15_f32)
+        .round()
+        .max(1_f32) as u32
+}
+
+// 文件名中"prefix"之后、".json"之前的部分
+fn pool_name(path: &Path, prefix: &str) -> Option<String> {
+    Some(
+        path.file_name()?
+            .to_str()?
+            .strip_prefix(prefix)?
+            .strip_suffix(".json")?
+            .to_string(),
+    )
+}
+
+// 无尽模式的敌人从作战区域上边缘出现，只有循环且每轮净向下移动的路线才能把敌人带到目标线
+fn path_toward_target_line(name: &str) -> bool {
+    let Ok(json_value) = read_from_json(format!(
+        "Resources/config/path_{}.json",
+        name.to_lowercase()
+    )) else {
+        return false;
+    };
+    let Ok(path) = MovePath::from_json_value(&json_value, 1_f32) else {
+        return false;
+    };
+    path.path_end_behavior == PathEndBehavior::Loop
+        && path
+            .path_segment
+            .iter()
+            .map(|x| x.segment_points[x.segment_points.len() - 1][1])
+            .sum::<f32>()
+            > 0_f32
+}
+
+#[derive(Debug, Clone)]
+pub struct Endless {
+    pub setting: MapEndless,
+    pub rng: EndlessRng,
+    // [敌人识别名, 难度消耗]
+    pub enemy_pool: Vec<(String, u32)>,
+    pub path_pool: Vec<String>,
+    pub wave: u32,
+    // 当前波次敌人生命值的倍率
+    pub wave_hp_multiplier: f32,
+    pub next_wave_time: f32,
+    pub earliest_wave_time: f32,
+}
+
+impl Endless {
+    pub fn new(setting: &MapEndless, seed: u32) -> Endless {
+        let enemy_name = if setting.endless_enemy_pool.is_empty() {
+            list_files_recursive(Path::new("Resources/config"), "enemy_")
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|x| pool_name(x, "enemy_"))
+                .collect()
+        } else {
+            setting.endless_enemy_pool.clone()
+        };
+        let mut enemy_pool = Vec::new();
+        for name in enemy_name {
+            if let Ok(json_value) = read_from_json(format!(
+                "Resources/config/enemy_{}.json",
+                name.to_lowercase()
+            )) {
+                if let Ok(enemy) = JsonReadEnemy::from_json_value(&json_value) {
+                    enemy_pool.push((
+                        enemy.enemy_recognition_name.clone(),
+                        enemy_budget_cost(&enemy),
+                    ));
+                };
+            };
+        }
+        // 按识别名排序，保证同一种子在不同系统上的文件顺序下结果一致
+        enemy_pool.sort();
+        let mut path_pool = if setting.endless_path_pool.is_empty() {
+            list_files_recursive(Path::new("Resources/config"), "path_")
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|x| pool_name(x, "path_"))
+                .filter(|x| path_toward_target_line(x))
+                .collect()
+        } else {
+            setting.endless_path_pool.clone()
+        };
+        path_pool.sort();
+        Endless {
+            setting: setting.clone(),
+            rng: EndlessRng::new(seed),
+            enemy_pool,
+            path_pool,
+            wave: 0,
+            wave_hp_multiplier: 1_f32,
+            next_wave_time: ENDLESS_FIRST_WAVE_DELAY,
+            earliest_wave_time: ENDLESS_FIRST_WAVE_DELAY,
+        }
+    }
+
+    // 第wave波(从1开始)的难度预算
+    pub fn wave_budget(&self, wave: u32) -> u32 {
+        (self.setting.endless_base_budget
+            * self
+                .setting
+                .endless_budget_growth
+                .powi(wave.saturating_sub(1) as i32))
+        .round()
+        .max(1_f32) as u32
+    }
+
+    // 到达波次间隔，或场上敌人已清空且超过最短等待时间时进入下一波
+    pub fn wave_ready(&self, runtime: f32, field_cleared: bool) -> bool {
+        runtime >= self.next_wave_time || field_cleared && runtime >= self.earliest_wave_time
+    }
+
+    // 在预算内随机挑选敌人与路线，生成下一波敌人
+    pub fn generate_wave(&mut self, runtime: f32) -> Vec<OperationTargetEnemy> {
+        self.wave += 1;
+        let wave_budget = self.wave_budget(self.wave);
+        let mut budget = wave_budget;
+        let mut wave = Vec::new();
+        let size = self.setting.endless_enemy_size;
+        while let Some(cheapest) = self.enemy_pool.iter().map(|x| x.1).min() {
+            if budget < cheapest || wave.len() >= self.setting.endless_max_enemy as usize {
+                break;
+            };
+            let affordable: Vec<(String, u32)> = self
+                .enemy_pool
+                .iter()
+                .filter(|x| x.1 <= budget)
+                .cloned()
+                .collect();
+            let (name, cost) = affordable[self.rng.index(affordable.len())].clone();
+            budget -= cost;
+            let x =
+                size[0] / 2_f32 + self.rng.next_f32() * (OPERATION_SIZE[0] - size[0]).max(0_f32);
+            wave.push(OperationTargetEnemy {
+                enemy_recognition_name: name,
+                enemy_position: [x, 0_f32],
+                enemy_size: size,
+                enemy_path: if self.path_pool.is_empty() {
+                    vec![]
+                } else {
+                    vec![self.path_pool[self.rng.index(self.path_pool.len())].clone()]
+                },
+                enemy_approach_time: runtime
+                    + wave.len() as f32 * self.setting.endless_spawn_interval,
+                enemy_approach_alpha: 0,
+                enemy_increase_alpha_speed: 5,
+            });
+        }
+        // 敌人数量达到上限后，剩余的预算按比例提高这一波敌人的生命值
+        let spent = wave_budget - budget;
+        self.wave_hp_multiplier =
+            if wave.len() >= self.setting.endless_max_enemy as usize && spent > 0 {
+                wave_budget as f32 / spent as f32
+            } else {
+                1_f32
+            };
+        let spawn_end = runtime + wave.len() as f32 * self.setting.endless_spawn_interval;
+        self.next_wave_time = spawn_end.max(runtime + self.setting.endless_wave_interval);
+        self.earliest_wave_time = spawn_end + ENDLESS_CLEAR_DELAY;
+        wave
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(max_enemy: u32) -> MapEndless {
+        MapEndless::from_json_value(&json::object! {
+            endless_level: "tutorial",
+            endless_enemy_pool: ["Train_Target"],
+            endless_path_pool: ["down"],
+            endless_base_budget: 6,
+            endless_max_enemy: max_enemy,
+            endless_spawn_interval: 0,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn wave_caps_enemy_count_and_scales_hp() {
+        // 训练靶的预算花费为1，第一波预算6、上限3个敌人，剩余预算使生命值翻倍
+        let mut endless = Endless::new(&setting(3), 1);
+        assert_eq!(endless.generate_wave(0_f32).len(), 3);
+        assert_eq!(endless.wave_hp_multiplier, 2_f32);
+        // 未达到上限时预算全部用于敌人数量
+        let mut endless = Endless::new(&setting(12), 1);
+        assert_eq!(endless.generate_wave(0_f32).len(), 6);
+        assert_eq!(endless.wave_hp_multiplier, 1_f32);
+    }
+
+    #[test]
+    fn wrongly_typed_setting_is_load_error() {
+        let error = MapEndless::from_json_value(&json::object! {
+            endless_level: "tutorial",
+            endless_max_enemy: "many",
+        })
+        .unwrap_err();
+        assert_eq!(error.pointer, "/endless_max_enemy");
+        // 省略的字段使用默认值
+        let setting = MapEndless::from_json_value(&json::object! {
+            endless_level: "tutorial",
+        })
+        .unwrap();
+        assert_eq!(setting.endless_max_enemy, 12);
+        assert!(setting.endless_enemy_pool.is_empty());
+    }
+}
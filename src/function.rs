@@ -1,13 +1,32 @@
 //! function.rs is the functional module of the Targeted Vector, including function declarations, struct definitions, and some auxiliary content.
+use crate::audio::{Audio, AudioBus};
+use crate::cli::CliOptions;
+use crate::endless::MapEndless;
+use crate::gamepad::{
+    Gamepad, GamepadButton, AIM_ASSIST_FRICTION, AIM_ASSIST_PULL, AIM_ASSIST_RANGE, GAMEPAD_ACTION,
+};
+use crate::hot_reload::{HotReload, HotReloadKind};
+use crate::input::{InputAction, InputBinding, InputBindings};
+use crate::load_error::{parse_members, FieldKind, JsonField, LoadError};
+use crate::logger::Logger;
+use crate::profile::ProfileArchive;
+use crate::score::OperationScore;
+use crate::script::{LevelTrigger, Wave};
+use crate::simulation::{
+    Simulation, SimulationEvent, SimulationInput, SimulationShot, OPERATION_SIZE, SIMULATION_DT,
+};
+use crate::status::{parse_resistance, ActiveStatus, DamageType, StatusEffect, StatusKind};
+use crate::viewport::{ScaleMode, Viewport};
 use anyhow::Context;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::Local;
 use eframe::emath::Rect;
 use eframe::epaint::textures::TextureOptions;
 use eframe::epaint::Stroke;
-use egui::{Color32, FontId, Frame, PointerButton, Pos2, Ui, Vec2};
+use egui::{Align2, Color32, FontId, Frame, PointerButton, Pos2, Ui, Vec2};
 use json::JsonValue;
-use kira::manager::backend::cpal;
-use kira::manager::AudioManager;
-use kira::sound::static_sound::StaticSoundData;
 use std::collections::hash_map;
 use std::collections::HashMap;
 use std::fs;
@@ -16,7 +35,7 @@ use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::exit;
-use std::thread;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::vec::Vec;
 use walkdir::WalkDir;
@@ -141,21 +160,26 @@ pub fn read_from_json<P: AsRef<Path>>(path: P) -> anyhow::Result<JsonValue> {
     json::parse(&content).with_context(|| format!("解析 JSON 失败: {}", path.as_ref().display()))
 }
 
-pub fn kira_play_wav(path: &str) -> anyhow::Result<f64> {
-    let mut manager: kira::manager::AudioManager<cpal::CpalBackend> =
-        AudioManager::new(kira::manager::AudioManagerSettings::default())?;
-    let sound_data = StaticSoundData::from_file(path, Default::default())?;
-    let duration = sound_data.duration().as_secs_f64();
-
-    manager.play(sound_data)?;
-    std::thread::sleep(std::time::Duration::from_secs_f64(duration));
-    Ok(duration)
+// 使用随机盐的Argon2生成密码散列（PHC格式字符串）
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("生成密码散列失败: {}", e))?
+        .to_string())
 }
 
-pub fn general_click_feedback() {
-    std::thread::spawn(|| {
-        kira_play_wav("Resources/assets/sounds/Click.wav").unwrap();
-    });
+// 校验密码，返回[是否通过, 是否需要重新散列]；旧版存档中的明文密码在此兼容
+pub fn verify_password(stored_password: &str, password: &str) -> [bool; 2] {
+    match PasswordHash::new(stored_password) {
+        Ok(password_hash) => [
+            Argon2::default()
+                .verify_password(password.as_bytes(), &password_hash)
+                .is_ok(),
+            false,
+        ],
+        Err(_) => [stored_password == password, true],
+    }
 }
 
 pub fn count_files_recursive(dir: &Path, target: &str) -> std::io::Result<usize> {
@@ -195,6 +219,44 @@ pub fn list_files_recursive(path: &Path, prefix: &str) -> Result<Vec<PathBuf>, s
     Ok(matches)
 }
 
+// 按照敌人的速度与路径文件推算其一轮完整的行进轨迹（相对于敌人出现的位置）
+pub fn enemy_path_preview(enemy_recognition_name: &str, enemy_path: &[String]) -> Vec<[f32; 2]> {
+    let mut points = vec![[0_f32, 0_f32]];
+    let mut enemy_speed = 0_f32;
+    let mut enemy_walk_interval = 0_f32;
+    if let Ok(json_value) = read_from_json(format!(
+        "Resources/config/enemy_{}.json",
+        enemy_recognition_name.to_lowercase()
+    )) {
+        if let Ok(read_enemy) = JsonReadEnemy::from_json_value(&json_value) {
+            enemy_speed = read_enemy.enemy_speed;
+            enemy_walk_interval = read_enemy.enemy_walk_interval;
+        };
+    };
+    // 敌人每次刷新（至少间隔0.01秒）才会移动一次
+    let legacy_speed_per_second = enemy_speed / enemy_walk_interval.max(0.01);
+    for path_name in enemy_path {
+        if let Ok(json_value) = read_from_json(format!(
+            "Resources/config/path_{}.json",
+            path_name.to_lowercase()
+        )) {
+            if let Ok(read_path) = MovePath::from_json_value(&json_value, legacy_speed_per_second) {
+                for segment in read_path.path_segment {
+                    let origin = points[points.len() - 1];
+                    points.extend(
+                        segment
+                            .polyline()
+                            .iter()
+                            .skip(1)
+                            .map(|x| [origin[0] + x[0], origin[1] + x[1]]),
+                    );
+                }
+            };
+        };
+    }
+    points
+}
+
 fn load_fonts(ctx: &egui::Context) {
     let mut fonts = egui::FontDefinitions::default();
     fonts.font_data.insert(
@@ -222,17 +284,23 @@ pub struct Config {
     pub amount_languages: u8,
     pub rc_strict_mode: bool,
     pub enable_debug_mode: bool,
+    pub page_music: HashMap<String, String>,
 }
 
 impl Config {
-    pub fn from_json_value(value: &JsonValue) -> Option<Config> {
-        Some(Config {
-            launch_path: value["launch_path"].as_str()?.to_string(),
-            language: value["language"].as_u8()?,
-            login_user_name: value["login_user_name"].as_str()?.to_string(),
-            amount_languages: value["amount_languages"].as_u8()?,
-            rc_strict_mode: value["rc_strict_mode"].as_bool()?,
-            enable_debug_mode: value["enable_debug_mode"].as_bool()?,
+    pub fn from_json_value(value: &JsonValue) -> Result<Config, LoadError> {
+        Ok(Config {
+            launch_path: value.field_str("launch_path")?,
+            language: value.field_u8("language")?,
+            login_user_name: value.field_str("login_user_name")?,
+            amount_languages: value.field_u8("amount_languages")?,
+            rc_strict_mode: value.field_bool("rc_strict_mode")?,
+            enable_debug_mode: value.field_bool("enable_debug_mode")?,
+            // 各页面的背景音乐，未配置的页面保持静音
+            page_music: value["page_music"]
+                .entries()
+                .filter_map(|(k, v)| Some((k.to_string(), v.as_str()?.to_string())))
+                .collect(),
         })
     }
     pub fn to_json_value(&self) -> JsonValue {
@@ -243,6 +311,10 @@ impl Config {
             amount_languages: self.amount_languages,
             rc_strict_mode: self.rc_strict_mode,
             enable_debug_mode: self.enable_debug_mode,
+            page_music: self.page_music.iter().fold(json::object! {}, |mut obj, (k, v)| {
+                obj.insert(k, v.clone()).expect("插入页面音乐失败");
+                obj
+            }),
         }
     }
 }
@@ -260,27 +332,51 @@ pub struct OperationGlobal {
     pub operation_background_expand: String,
     pub operation_start_background: String,
     pub operation_over_background: String,
+    pub operation_reward: u32,
+    pub star_threshold: Vec<u32>,
 }
 
 impl OperationGlobal {
     #[allow(dead_code)]
-    pub fn from_json_value(value: &JsonValue) -> Option<OperationGlobal> {
-        Some(OperationGlobal {
-            target_point: value["target_point"].as_u32()?,
-            storage_bullet: value["storage_bullet"].as_u32()?,
-            cost: value["cost"].as_u32()?,
-            cost_recover_speed: value["cost_recover_speed"].as_f32()?,
-            instrument_ceiling: value["instrument_ceiling"].as_u32()?,
+    pub fn from_json_value(value: &JsonValue) -> Result<OperationGlobal, LoadError> {
+        Ok(OperationGlobal {
+            target_point: value.field_u32("target_point")?,
+            storage_bullet: value.field_u32("storage_bullet")?,
+            cost: value.field_u32("cost")?,
+            cost_recover_speed: value.field_f32("cost_recover_speed")?,
+            instrument_ceiling: value.field_u32("instrument_ceiling")?,
             target_line: value["target_line"]
                 .members()
                 .filter_map(|arr| Some([arr[0].as_f32()?, arr[1].as_f32()?]))
                 .collect(),
-            operation_background: value["operation_background"].as_str()?.to_string(),
-            operation_background_expand: value["operation_background_expand"].as_str()?.to_string(),
-            operation_start_background: value["operation_start_background"].as_str()?.to_string(),
-            operation_over_background: value["operation_over_background"].as_str()?.to_string(),
+            operation_background: value.field_str("operation_background")?,
+            operation_background_expand: value.field_str("operation_background_expand")?,
+            operation_start_background: value.field_str("operation_start_background")?,
+            operation_over_background: value.field_str("operation_over_background")?,
+            operation_reward: value["operation_reward"].as_u32().unwrap_or(100),
+            star_threshold: value["star_threshold"]
+                .members()
+                .filter_map(|x| x.as_u32())
+                .collect(),
         })
     }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        json::object! {
+            target_point: self.target_point,
+            storage_bullet: self.storage_bullet,
+            cost: self.cost,
+            cost_recover_speed: self.cost_recover_speed,
+            instrument_ceiling: self.instrument_ceiling,
+            target_line: self.target_line.iter().map(|x| json::array![x[0], x[1]]).collect::<Vec<_>>(),
+            operation_background: self.operation_background.clone(),
+            operation_background_expand: self.operation_background_expand.clone(),
+            operation_start_background: self.operation_start_background.clone(),
+            operation_over_background: self.operation_over_background.clone(),
+            operation_reward: self.operation_reward,
+            star_threshold: self.star_threshold.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -295,26 +391,32 @@ pub struct OperationTargetEnemy {
 }
 
 impl OperationTargetEnemy {
-    pub fn from_json_value(value: &JsonValue) -> Option<OperationTargetEnemy> {
-        Some(OperationTargetEnemy {
-            enemy_recognition_name: value["enemy_recognition_name"].as_str()?.to_string(),
-            enemy_position: [
-                value["enemy_position"][0].as_f32()?,
-                value["enemy_position"][1].as_f32()?,
-            ],
-            enemy_size: [
-                value["enemy_size"][0].as_f32()?,
-                value["enemy_size"][1].as_f32()?,
-            ],
+    pub fn from_json_value(value: &JsonValue) -> Result<OperationTargetEnemy, LoadError> {
+        Ok(OperationTargetEnemy {
+            enemy_recognition_name: value.field_str("enemy_recognition_name")?,
+            enemy_position: value.field_pair("enemy_position")?,
+            enemy_size: value.field_pair("enemy_size")?,
             enemy_path: value["enemy_path"]
                 .members()
                 .map(|s| s.to_string())
                 .collect(),
-            enemy_approach_time: value["enemy_approach_time"].as_f32()?,
-            enemy_approach_alpha: value["enemy_approach_alpha"].as_u8()?,
-            enemy_increase_alpha_speed: value["enemy_increase_alpha_speed"].as_u8()?,
+            enemy_approach_time: value.field_f32("enemy_approach_time")?,
+            enemy_approach_alpha: value.field_u8("enemy_approach_alpha")?,
+            enemy_increase_alpha_speed: value.field_u8("enemy_increase_alpha_speed")?,
         })
     }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        json::object! {
+            enemy_recognition_name: self.enemy_recognition_name.clone(),
+            enemy_position: [self.enemy_position[0], self.enemy_position[1]],
+            enemy_size: [self.enemy_size[0], self.enemy_size[1]],
+            enemy_path: self.enemy_path.clone(),
+            enemy_approach_time: self.enemy_approach_time,
+            enemy_approach_alpha: self.enemy_approach_alpha,
+            enemy_increase_alpha_speed: self.enemy_increase_alpha_speed,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -328,16 +430,15 @@ pub struct OperationMessageBox {
     pub box_existing_time: f32,
     pub box_appear_time: f32,
     pub box_enable: bool,
+    // 不为空时改为在Boss进入同名阶段或触发器显示同名消息框时出现，box_appear_time被忽略
+    pub box_trigger_phase: String,
 }
 
 impl OperationMessageBox {
-    pub fn from_json_value(value: &JsonValue) -> Option<OperationMessageBox> {
-        Some(OperationMessageBox {
-            box_size: [
-                value["box_size"][0].as_f32()?,
-                value["box_size"][1].as_f32()?,
-            ],
-            box_image_path: value["box_image_path"].as_str()?.to_string(),
+    pub fn from_json_value(value: &JsonValue) -> Result<OperationMessageBox, LoadError> {
+        Ok(OperationMessageBox {
+            box_size: value.field_pair("box_size")?,
+            box_image_path: value.field_str("box_image_path")?,
             box_title: value["box_title"]
                 .members()
                 .map(|s| s.to_string())
@@ -346,23 +447,34 @@ impl OperationMessageBox {
                 .members()
                 .map(|s| s.to_string())
                 .collect(),
-            box_title_color: [
-                value["box_title_color"][0].as_u8()?,
-                value["box_title_color"][1].as_u8()?,
-                value["box_title_color"][2].as_u8()?,
-                value["box_title_color"][3].as_u8()?,
-            ],
-            box_content_color: [
-                value["box_content_color"][0].as_u8()?,
-                value["box_content_color"][1].as_u8()?,
-                value["box_content_color"][2].as_u8()?,
-                value["box_content_color"][3].as_u8()?,
-            ],
-            box_existing_time: value["box_existing_time"].as_f32()?,
-            box_appear_time: value["box_appear_time"].as_f32()?,
+            box_title_color: value.field_color("box_title_color")?,
+            box_content_color: value.field_color("box_content_color")?,
+            box_existing_time: value.field_f32("box_existing_time")?,
+            box_appear_time: value.field_f32("box_appear_time")?,
             box_enable: true,
+            box_trigger_phase: value["box_trigger_phase"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
         })
     }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        let mut value = json::object! {
+            box_size: [self.box_size[0], self.box_size[1]],
+            box_image_path: self.box_image_path.clone(),
+            box_title: self.box_title.clone(),
+            box_content: self.box_content.clone(),
+            box_title_color: self.box_title_color.to_vec(),
+            box_content_color: self.box_content_color.to_vec(),
+            box_existing_time: self.box_existing_time,
+            box_appear_time: self.box_appear_time,
+        };
+        if !self.box_trigger_phase.is_empty() {
+            value["box_trigger_phase"] = self.box_trigger_phase.clone().into();
+        };
+        value
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -370,44 +482,414 @@ pub struct Operation {
     pub global: OperationGlobal,
     pub target_enemy: Vec<OperationTargetEnemy>,
     pub message_box: Vec<OperationMessageBox>,
+    pub wave: Vec<Wave>,
+    pub trigger: Vec<LevelTrigger>,
 }
 
 impl Operation {
-    pub fn from_json_value(value: &JsonValue) -> Option<Operation> {
-        Some(Operation {
-            global: OperationGlobal::from_json_value(&value["global"])?,
-            target_enemy: value["target_enemy"]
-                .members()
-                .map(OperationTargetEnemy::from_json_value)
-                .collect::<Option<Vec<_>>>()?,
-            message_box: value["message_box"]
-                .members()
-                .map(OperationMessageBox::from_json_value)
-                .collect::<Option<Vec<_>>>()?,
+    pub fn from_json_value(value: &JsonValue) -> Result<Operation, LoadError> {
+        Ok(Operation {
+            global: OperationGlobal::from_json_value(&value["global"])
+                .map_err(|e| e.at("global"))?,
+            target_enemy: parse_members(
+                value,
+                "target_enemy",
+                OperationTargetEnemy::from_json_value,
+            )?,
+            message_box: parse_members(value, "message_box", OperationMessageBox::from_json_value)?,
+            wave: parse_members(value, "wave", Wave::from_json_value)?,
+            trigger: parse_members(value, "trigger", LevelTrigger::from_json_value)?,
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        let mut value = json::object! {
+            global: self.global.to_json_value(),
+            target_enemy: self.target_enemy.iter().map(|x| x.to_json_value()).collect::<Vec<_>>(),
+            message_box: self.message_box.iter().map(|x| x.to_json_value()).collect::<Vec<_>>(),
+        };
+        // 没有脚本的关卡保持原有格式
+        if !self.wave.is_empty() {
+            value["wave"] = self
+                .wave
+                .iter()
+                .map(|x| x.to_json_value())
+                .collect::<Vec<_>>()
+                .into();
+        };
+        if !self.trigger.is_empty() {
+            value["trigger"] = self
+                .trigger
+                .iter()
+                .map(|x| x.to_json_value())
+                .collect::<Vec<_>>()
+                .into();
+        };
+        value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCurve {
+    Linear,
+    Bezier,
+    CatmullRom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathEndBehavior {
+    Loop,
+    PingPong,
+    Stop,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathSegment {
+    // 控制点均相对于路段起点，最后一个点为路段终点
+    pub segment_points: Vec<[f32; 2]>,
+    pub segment_curve: PathCurve,
+    // 相对于敌人速度的倍率
+    pub segment_speed: f32,
+    // 抵达终点后停留的时间
+    pub segment_wait: f32,
+}
+
+impl PathSegment {
+    pub fn from_json_value(value: &JsonValue) -> Result<PathSegment, LoadError> {
+        let segment_points = parse_members(value, "segment_points", |point| {
+            match (point[0].as_f32(), point[1].as_f32()) {
+                (Some(x), Some(y)) => Ok([x, y]),
+                _ => Err(LoadError::expected(FieldKind::NumberPair, point)),
+            }
+        })?;
+        if segment_points.is_empty() {
+            return Err(
+                LoadError::new("a non-empty array of points", &value["segment_points"])
+                    .at("segment_points"),
+            );
+        };
+        Ok(PathSegment {
+            segment_points,
+            segment_curve: match value["segment_curve"].as_str().unwrap_or("Linear") {
+                "Linear" => PathCurve::Linear,
+                "Bezier" => PathCurve::Bezier,
+                "CatmullRom" => PathCurve::CatmullRom,
+                _ => {
+                    return Err(LoadError::new(
+                        "one of Linear, Bezier, CatmullRom",
+                        &value["segment_curve"],
+                    )
+                    .at("segment_curve"))
+                }
+            },
+            segment_speed: value["segment_speed"].as_f32().unwrap_or(1_f32),
+            segment_wait: value["segment_wait"].as_f32().unwrap_or(0_f32),
         })
     }
+
+    // 将路段展开为折线（相对路段起点，首个点恒为原点）
+    pub fn polyline(&self) -> Vec<[f32; 2]> {
+        let mut control_points = vec![[0_f32, 0_f32]];
+        control_points.extend(self.segment_points.iter().copied());
+        match self.segment_curve {
+            PathCurve::Linear => control_points,
+            PathCurve::Bezier => (0..=32)
+                .map(|x| {
+                    // de Casteljau算法
+                    let t = x as f32 / 32_f32;
+                    let mut points = control_points.clone();
+                    while points.len() > 1 {
+                        points = points
+                            .windows(2)
+                            .map(|p| {
+                                [
+                                    p[0][0] + (p[1][0] - p[0][0]) * t,
+                                    p[0][1] + (p[1][1] - p[0][1]) * t,
+                                ]
+                            })
+                            .collect();
+                    }
+                    points[0]
+                })
+                .collect(),
+            PathCurve::CatmullRom => {
+                let mut points = vec![control_points[0]];
+                for i in 0..control_points.len() - 1 {
+                    let p0 = control_points[i.saturating_sub(1)];
+                    let p1 = control_points[i];
+                    let p2 = control_points[i + 1];
+                    let p3 = control_points[(i + 2).min(control_points.len() - 1)];
+                    for x in 1..=16 {
+                        let t = x as f32 / 16_f32;
+                        let t2 = t * t;
+                        let t3 = t2 * t;
+                        let mut point = [0_f32; 2];
+                        for u in 0..2 {
+                            point[u] = 0.5
+                                * (2_f32 * p1[u]
+                                    + (p2[u] - p0[u]) * t
+                                    + (2_f32 * p0[u] - 5_f32 * p1[u] + 4_f32 * p2[u] - p3[u]) * t2
+                                    + (3_f32 * p1[u] - p0[u] - 3_f32 * p2[u] + p3[u]) * t3);
+                        }
+                        points.push(point);
+                    }
+                }
+                points
+            }
+        }
+    }
+}
+
+// 计算折线长度
+pub fn polyline_length(polyline: &[[f32; 2]]) -> f32 {
+    polyline
+        .windows(2)
+        .map(|p| ((p[1][0] - p[0][0]).powi(2) + (p[1][1] - p[0][1]).powi(2)).sqrt())
+        .sum()
+}
+
+// 取折线上距起点指定长度处的点
+pub fn polyline_point_at(polyline: &[[f32; 2]], distance: f32) -> [f32; 2] {
+    let mut rest = distance.max(0_f32);
+    for p in polyline.windows(2) {
+        let length = ((p[1][0] - p[0][0]).powi(2) + (p[1][1] - p[0][1]).powi(2)).sqrt();
+        if rest <= length && length > 0_f32 {
+            return [
+                p[0][0] + (p[1][0] - p[0][0]) * rest / length,
+                p[0][1] + (p[1][1] - p[0][1]) * rest / length,
+            ];
+        };
+        rest -= length;
+    }
+    polyline[polyline.len() - 1]
 }
 
 #[derive(Debug, Clone)]
 pub struct MovePath {
-    pub move_status: [bool; 4],
-    pub move_time: f32,
+    pub path_segment: Vec<PathSegment>,
+    pub path_end_behavior: PathEndBehavior,
 }
 
 impl MovePath {
-    pub fn from_json_value(value: &JsonValue) -> Option<MovePath> {
-        Some(MovePath {
-            move_status: [
-                value["move_status"][0].as_bool()?,
-                value["move_status"][1].as_bool()?,
-                value["move_status"][2].as_bool()?,
-                value["move_status"][3].as_bool()?,
-            ],
-            move_time: value["move_time"].as_f32()?,
+    // 旧版路径文件只记录方向与时长，需借助敌人每秒移动的距离换算为路点
+    pub fn from_json_value(
+        value: &JsonValue,
+        legacy_speed_per_second: f32,
+    ) -> Result<MovePath, LoadError> {
+        if value["path_segment"].is_array() {
+            Ok(MovePath {
+                path_segment: parse_members(value, "path_segment", PathSegment::from_json_value)?,
+                path_end_behavior: match value["path_end_behavior"].as_str().unwrap_or("Loop") {
+                    "Loop" => PathEndBehavior::Loop,
+                    "PingPong" => PathEndBehavior::PingPong,
+                    "Stop" => PathEndBehavior::Stop,
+                    _ => {
+                        return Err(LoadError::new(
+                            "one of Loop, PingPong, Stop",
+                            &value["path_end_behavior"],
+                        )
+                        .at("path_end_behavior"))
+                    }
+                },
+            })
+        } else {
+            // [上, 下, 左, 右]
+            let move_status = value.field("move_status", FieldKind::Array, |x| {
+                Some([
+                    x[0].as_bool()?,
+                    x[1].as_bool()?,
+                    x[2].as_bool()?,
+                    x[3].as_bool()?,
+                ])
+            })?;
+            let move_time = value.field_f32("move_time")?;
+            let distance = legacy_speed_per_second * move_time;
+            let mut end_point = [0_f32, 0_f32];
+            if move_status[0] {
+                end_point[1] -= distance;
+            };
+            if move_status[1] {
+                end_point[1] += distance;
+            };
+            if move_status[2] {
+                end_point[0] -= distance;
+            };
+            if move_status[3] {
+                end_point[0] += distance;
+            };
+            let length = end_point[0].hypot(end_point[1]);
+            Ok(MovePath {
+                path_segment: vec![PathSegment {
+                    segment_points: vec![end_point],
+                    segment_curve: PathCurve::Linear,
+                    // 旧版斜向移动的两个分量各自按敌人速度前进，提高倍率使路段仍在move_time内走完
+                    segment_speed: if length > 0_f32 {
+                        length / distance
+                    } else {
+                        1_f32
+                    },
+                    // 原地停留或方向相互抵消的旧版路径以等待代替移动
+                    segment_wait: if length > 0_f32 { 0_f32 } else { move_time },
+                }],
+                path_end_behavior: PathEndBehavior::Loop,
+            })
+        }
+    }
+}
+
+// 可识别的敌人标签，写法为"名称:参数1:参数2"，参数可省略
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnemyTag {
+    // 仅受带有指定标签的伤害来源影响
+    Armored(Vec<String>),
+    // 护盾先于生命值承受伤害，一段时间未受击后回复
+    Shielded {
+        shield: f32,
+        regen_delay: f32,
+        regen_speed: f32,
+    },
+    // 被击毁时在原地生成子敌人
+    Splitter {
+        child: String,
+        count: u32,
+    },
+    // 未被侦测前无法被攻击，接近防线或进入仪器射程后显形
+    Stealth(f32),
+    // 每秒回复的生命值
+    Regenerating(f32),
+}
+
+impl EnemyTag {
+    pub fn from_tag(tag: &str, enemy_hp: f32) -> Option<EnemyTag> {
+        let mut parts = tag.split(':');
+        let name = parts.next()?;
+        let args: Vec<&str> = parts.collect();
+        let arg_f = |index: usize, default: f32| {
+            args.get(index)
+                .and_then(|x| x.parse::<f32>().ok())
+                .unwrap_or(default)
+        };
+        match name {
+            "armored" => Some(EnemyTag::Armored(if args.is_empty() {
+                vec!["armor_piercing".to_string()]
+            } else {
+                args.iter().map(|x| x.to_string()).collect()
+            })),
+            "shielded" => {
+                let shield = arg_f(0, enemy_hp * 0.5);
+                Some(EnemyTag::Shielded {
+                    shield,
+                    regen_delay: arg_f(1, 2_f32),
+                    regen_speed: arg_f(2, shield / 2_f32),
+                })
+            }
+            "splitter" => Some(EnemyTag::Splitter {
+                child: args.first()?.to_string(),
+                count: args.get(1).and_then(|x| x.parse().ok()).unwrap_or(2),
+            }),
+            "stealth" => Some(EnemyTag::Stealth(arg_f(0, 200_f32))),
+            "regenerating" => Some(EnemyTag::Regenerating(arg_f(0, enemy_hp * 0.05))),
+            _ => None,
+        }
+    }
+}
+
+// Boss阶段的触发条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BossPhaseTrigger {
+    // 生命值比例降至该值及以下
+    Hp(f32),
+    // 出场后经过的秒数
+    Time(f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct EnemyBossPhase {
+    pub phase_name: String,
+    pub phase_trigger: BossPhaseTrigger,
+    // 以下字段缺省时沿用上一阶段的值
+    pub phase_image: Option<String>,
+    pub phase_image_count: Option<u32>,
+    pub phase_speed: Option<f32>,
+    pub phase_def: Option<f32>,
+    // 不为空时从当前位置开始沿新路线行进
+    pub phase_path: Vec<String>,
+    // [敌人识别名, 数量]
+    pub phase_minion: Vec<(String, u32)>,
+}
+
+impl EnemyBossPhase {
+    pub fn from_json_value(value: &JsonValue) -> Result<EnemyBossPhase, LoadError> {
+        let threshold = value.field_f32("phase_threshold")?;
+        Ok(EnemyBossPhase {
+            phase_name: value.field_str("phase_name")?,
+            phase_trigger: match value.field_str("phase_trigger")?.as_str() {
+                "hp" => BossPhaseTrigger::Hp(threshold),
+                "time" => BossPhaseTrigger::Time(threshold),
+                _ => {
+                    return Err(
+                        LoadError::new("\"hp\" or \"time\"", &value["phase_trigger"])
+                            .at("phase_trigger"),
+                    )
+                }
+            },
+            phase_image: value["phase_image"].as_str().map(String::from),
+            phase_image_count: value["phase_image_count"].as_u32(),
+            phase_speed: value["phase_speed"].as_f32(),
+            phase_def: value["phase_def"].as_f32(),
+            phase_path: value["phase_path"]
+                .members()
+                .filter_map(|x| x.as_str().map(String::from))
+                .collect(),
+            phase_minion: parse_members(value, "phase_minion", |x| {
+                Ok((
+                    x.field_str("minion_recognition_name")?,
+                    x.field_u32("minion_count")?,
+                ))
+            })?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnemyBoss {
+    // 显示在Boss血条上的名称
+    pub boss_name: Vec<String>,
+    pub boss_phase: Vec<EnemyBossPhase>,
+}
+
+impl EnemyBoss {
+    pub fn from_json_value(value: &JsonValue) -> Result<EnemyBoss, LoadError> {
+        Ok(EnemyBoss {
+            boss_name: value["boss_name"]
+                .members()
+                .map(|s| s.to_string())
+                .collect(),
+            boss_phase: parse_members(value, "boss_phase", EnemyBossPhase::from_json_value)?,
         })
     }
 }
 
+// 依次读取路径文件并首尾相接，结束行为以最后一个文件为准
+pub fn read_move_path(
+    path_name: &[String],
+    legacy_speed_per_second: f32,
+) -> Result<(Vec<PathSegment>, PathEndBehavior), LoadError> {
+    let mut move_path = Vec::new();
+    let mut path_end_behavior = PathEndBehavior::Loop;
+    for name in path_name {
+        let path_file = format!("Resources/config/path_{}.json", name.to_lowercase());
+        let read_path = MovePath::from_json_value(
+            &read_from_json(&path_file).map_err(|e| LoadError::unreadable(&path_file, &e))?,
+            legacy_speed_per_second,
+        )
+        .map_err(|e| e.in_file(&path_file))?;
+        move_path.extend(read_path.path_segment);
+        path_end_behavior = read_path.path_end_behavior;
+    }
+    Ok((move_path, path_end_behavior))
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Enemy {
@@ -422,7 +904,10 @@ pub struct Enemy {
     pub enemy_image_type: String,
     pub enemy_minus_target_point: u32,
     pub enemy_position: [f32; 2],
-    pub enemy_move_path: Vec<MovePath>,
+    pub enemy_move_path: Vec<PathSegment>,
+    pub enemy_path_end_behavior: PathEndBehavior,
+    pub enemy_path_origin: [f32; 2],
+    pub enemy_path_reverse: bool,
     pub enemy_detected: bool,
     pub enemy_activated: bool,
     pub enemy_activated_time: f32,
@@ -440,6 +925,294 @@ pub struct Enemy {
     pub enemy_hit_time: f32,
     pub enemy_initial_hp: f32,
     pub enemy_memory_hp: f32,
+    pub enemy_alpha: u8,
+    pub enemy_behavior: Vec<EnemyTag>,
+    pub enemy_shield: f32,
+    pub enemy_max_shield: f32,
+    pub enemy_boss: Option<EnemyBoss>,
+    // 各Boss阶段是否已进入
+    pub enemy_phase_entered: Vec<bool>,
+    // 贴图名称的前缀，Boss切换贴图后改用新的前缀
+    pub enemy_texture_name: String,
+    pub enemy_resistance: HashMap<DamageType, f32>,
+    pub enemy_status: Vec<ActiveStatus>,
+}
+
+impl Enemy {
+    // 按照关卡中的敌人配置读取敌人文件与路径文件，生成初始状态
+    pub fn from_target_enemy(
+        target_enemy: &OperationTargetEnemy,
+        index: usize,
+    ) -> Result<Enemy, LoadError> {
+        let enemy_file = format!(
+            "Resources/config/enemy_{}.json",
+            target_enemy.enemy_recognition_name.to_lowercase()
+        );
+        let read_enemy = JsonReadEnemy::from_json_value(
+            &read_from_json(&enemy_file).map_err(|e| LoadError::unreadable(&enemy_file, &e))?,
+        )
+        .map_err(|e| e.in_file(&enemy_file))?;
+        let (move_path, path_end_behavior) = read_move_path(
+            &target_enemy.enemy_path,
+            read_enemy.enemy_speed / read_enemy.enemy_walk_interval.max(0.01),
+        )?;
+        let enemy_behavior: Vec<EnemyTag> = read_enemy
+            .enemy_tag
+            .iter()
+            .filter_map(|x| EnemyTag::from_tag(x, read_enemy.enemy_hp))
+            .collect();
+        let enemy_max_shield = enemy_behavior
+            .iter()
+            .map(|x| match x {
+                EnemyTag::Shielded { shield, .. } => *shield,
+                _ => 0_f32,
+            })
+            .sum();
+        Ok(Enemy {
+            enemy_name: format!("Enemy_json_{}", index),
+            enemy_hp: read_enemy.enemy_hp,
+            enemy_def: read_enemy.enemy_def,
+            enemy_speed: read_enemy.enemy_speed,
+            enemy_invincible_time: read_enemy.enemy_invincible_time,
+            enemy_image_count: read_enemy.enemy_image_count,
+            enemy_tag: read_enemy.enemy_tag,
+            enemy_image: read_enemy.enemy_image,
+            enemy_image_type: read_enemy.enemy_image_type,
+            enemy_minus_target_point: read_enemy.enemy_minus_target_point,
+            enemy_position: target_enemy.enemy_position,
+            enemy_move_path: move_path,
+            enemy_path_end_behavior: path_end_behavior,
+            enemy_path_origin: target_enemy.enemy_position,
+            enemy_path_reverse: false,
+            enemy_detected: !enemy_behavior
+                .iter()
+                .any(|x| matches!(x, EnemyTag::Stealth(_))),
+            enemy_activated: false,
+            enemy_activated_time: target_enemy.enemy_approach_time,
+            enemy_size: target_enemy.enemy_size,
+            enemy_current_walk_status: 0,
+            enemy_start_walk_time: 0_f32,
+            enemy_increase_alpha_speed: target_enemy.enemy_increase_alpha_speed,
+            enemy_animation_forward: true,
+            enemy_current_animation_count: 0,
+            enemy_walk_interval: read_enemy.enemy_walk_interval,
+            enemy_walk_time: 0_f32,
+            enemy_animation_interval: read_enemy.enemy_animation_interval,
+            enemy_animation_change_time: 0_f32,
+            enemy_out: false,
+            enemy_hit_time: 0_f32,
+            enemy_initial_hp: read_enemy.enemy_hp,
+            enemy_memory_hp: read_enemy.enemy_hp,
+            enemy_alpha: target_enemy.enemy_approach_alpha,
+            enemy_behavior,
+            enemy_shield: enemy_max_shield,
+            enemy_max_shield,
+            enemy_phase_entered: read_enemy
+                .enemy_boss
+                .as_ref()
+                .map(|x| vec![false; x.boss_phase.len()])
+                .unwrap_or_default(),
+            enemy_boss: read_enemy.enemy_boss,
+            enemy_texture_name: format!("Enemy_json_{}", index),
+            enemy_resistance: read_enemy.enemy_resistance,
+            enemy_status: vec![],
+        })
+    }
+
+    // 施加状态；已有同种状态时取较高的强度与较晚的结束时间
+    pub fn apply_status(&mut self, effect: &StatusEffect, runtime: f32) {
+        let expire_time = runtime + effect.status_duration;
+        if let Some(status) = self
+            .enemy_status
+            .iter_mut()
+            .find(|x| x.status_kind == effect.status_kind)
+        {
+            status.status_strength = status.status_strength.max(effect.status_strength);
+            status.status_expire_time = status.status_expire_time.max(expire_time);
+        } else {
+            self.enemy_status.push(ActiveStatus {
+                status_kind: effect.status_kind,
+                status_strength: effect.status_strength,
+                status_expire_time: expire_time,
+            });
+        };
+    }
+
+    pub fn status_strength(&self, kind: StatusKind) -> Option<f32> {
+        self.enemy_status
+            .iter()
+            .find(|x| x.status_kind == kind)
+            .map(|x| x.status_strength)
+    }
+
+    // 破甲后的防御力
+    pub fn effective_def(&self) -> f32 {
+        (self.enemy_def
+            - self
+                .status_strength(StatusKind::ArmorBreak)
+                .unwrap_or(0_f32))
+        .max(0_f32)
+    }
+
+    // 抗性与标记共同决定的伤害倍率
+    pub fn damage_multiplier(&self, damage_type: DamageType) -> f32 {
+        (1_f32
+            - self
+                .enemy_resistance
+                .get(&damage_type)
+                .copied()
+                .unwrap_or(0_f32))
+        .max(0_f32)
+            * (1_f32 + self.status_strength(StatusKind::Mark).unwrap_or(0_f32))
+    }
+
+    // 眩晕时为0，减速时取剩余速度的比例
+    pub fn movement_factor(&self) -> f32 {
+        if self.status_strength(StatusKind::Stun).is_some() {
+            0_f32
+        } else {
+            1_f32 - self.status_strength(StatusKind::Slow).unwrap_or(0_f32)
+        }
+    }
+
+    // 护盾先承受伤害
+    pub fn take_damage(&mut self, damage: f32) {
+        let absorbed = damage.min(self.enemy_shield);
+        self.enemy_shield -= absorbed;
+        self.enemy_hp -= damage - absorbed;
+    }
+
+    pub fn texture_name(&self, frame: u32) -> String {
+        format!("{}_{}", self.enemy_texture_name, frame)
+    }
+
+    // 满足触发条件且尚未进入的Boss阶段；被击毁的Boss不再进入新阶段
+    pub fn pending_boss_phase(&self, runtime: f32) -> Vec<usize> {
+        let Some(boss) = &self.enemy_boss else {
+            return vec![];
+        };
+        if self.enemy_hp <= 0_f32 {
+            return vec![];
+        };
+        boss.boss_phase
+            .iter()
+            .enumerate()
+            .filter(|(i, phase)| {
+                !self.enemy_phase_entered[*i]
+                    && match phase.phase_trigger {
+                        BossPhaseTrigger::Hp(ratio) => {
+                            self.enemy_hp / self.enemy_initial_hp <= ratio
+                        }
+                        BossPhaseTrigger::Time(time) => runtime - self.enemy_activated_time >= time,
+                    }
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // 判断伤害来源能否对该敌人造成伤害
+    pub fn damageable_by(&self, source_tag: &[String]) -> bool {
+        self.enemy_behavior.iter().all(|x| match x {
+            EnemyTag::Armored(pierce_tag) => pierce_tag.iter().any(|t| source_tag.contains(t)),
+            _ => true,
+        })
+    }
+
+    // 行走动画在首尾帧之间往返
+    pub fn animation_step(&mut self) {
+        if self.enemy_animation_forward {
+            if self.enemy_current_animation_count < self.enemy_image_count {
+                self.enemy_current_animation_count += 1;
+            } else {
+                self.enemy_animation_forward = false;
+                if self.enemy_current_animation_count > 0 {
+                    self.enemy_current_animation_count -= 1;
+                };
+            };
+        } else if self.enemy_current_animation_count > 0 {
+            self.enemy_current_animation_count -= 1;
+        } else {
+            self.enemy_animation_forward = true;
+            if self.enemy_current_animation_count < self.enemy_image_count {
+                self.enemy_current_animation_count += 1;
+            };
+        };
+    }
+
+    // 按照路段推算当前位置，返回敌人是否正在移动
+    pub fn path_walk(&mut self, operation_runtime: f32) -> bool {
+        if self.enemy_current_walk_status as usize >= self.enemy_move_path.len() {
+            return false;
+        };
+        let speed_per_second = self.enemy_speed / self.enemy_walk_interval.max(0.01);
+        loop {
+            let segment = self.enemy_move_path[self.enemy_current_walk_status as usize].clone();
+            let mut polyline = segment.polyline();
+            if self.enemy_path_reverse {
+                // 反向行进时以路段终点为原点
+                let end_point = polyline[polyline.len() - 1];
+                polyline = polyline
+                    .iter()
+                    .rev()
+                    .map(|x| [x[0] - end_point[0], x[1] - end_point[1]])
+                    .collect();
+            };
+            let length = polyline_length(&polyline);
+            let speed = speed_per_second * segment.segment_speed;
+            let duration = if length == 0_f32 {
+                0_f32
+            } else if speed > 0_f32 {
+                length / speed
+            } else {
+                f32::INFINITY
+            };
+            let elapsed = operation_runtime - self.enemy_start_walk_time;
+            if elapsed < duration + segment.segment_wait {
+                let point = polyline_point_at(&polyline, elapsed * speed);
+                self.enemy_position = [
+                    self.enemy_path_origin[0] + point[0],
+                    self.enemy_path_origin[1] + point[1],
+                ];
+                return elapsed < duration;
+            };
+            // 进入下一路段
+            let end_point = polyline[polyline.len() - 1];
+            self.enemy_path_origin = [
+                self.enemy_path_origin[0] + end_point[0],
+                self.enemy_path_origin[1] + end_point[1],
+            ];
+            self.enemy_position = self.enemy_path_origin;
+            self.enemy_start_walk_time += duration + segment.segment_wait;
+            let last_status = self.enemy_move_path.len() as u32 - 1;
+            if self.enemy_path_reverse {
+                if self.enemy_current_walk_status > 0 {
+                    self.enemy_current_walk_status -= 1;
+                } else {
+                    self.enemy_path_reverse = false;
+                };
+            } else if self.enemy_current_walk_status < last_status {
+                self.enemy_current_walk_status += 1;
+            } else {
+                match self.enemy_path_end_behavior {
+                    PathEndBehavior::Loop => self.enemy_current_walk_status = 0,
+                    PathEndBehavior::PingPong => self.enemy_path_reverse = true,
+                    PathEndBehavior::Stop => {
+                        self.enemy_current_walk_status = self.enemy_move_path.len() as u32;
+                        return false;
+                    }
+                };
+            };
+            // 防止全部路段耗时为零时陷入死循环
+            if duration + segment.segment_wait == 0_f32
+                && self
+                    .enemy_move_path
+                    .iter()
+                    .all(|x| x.segment_wait == 0_f32 && polyline_length(&x.polyline()) == 0_f32)
+            {
+                return false;
+            };
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -458,31 +1231,43 @@ pub struct JsonReadEnemy {
     pub enemy_minus_target_point: u32,
     pub enemy_walk_interval: f32,
     pub enemy_animation_interval: f32,
+    pub enemy_boss: Option<EnemyBoss>,
+    pub enemy_resistance: HashMap<DamageType, f32>,
 }
 
 impl JsonReadEnemy {
     #[allow(dead_code)]
-    pub fn from_json_value(value: &JsonValue) -> Option<JsonReadEnemy> {
-        Some(JsonReadEnemy {
-            enemy_recognition_name: value["enemy_recognition_name"].as_str()?.to_string(),
-            enemy_hp: value["enemy_hp"].as_f32()?,
-            enemy_def: value["enemy_def"].as_f32()?,
-            enemy_speed: value["enemy_speed"].as_f32()?,
-            enemy_invincible_time: value["enemy_invincible_time"].as_f32()?,
-            enemy_image_count: value["enemy_image_count"].as_u32()?,
+    pub fn from_json_value(value: &JsonValue) -> Result<JsonReadEnemy, LoadError> {
+        Ok(JsonReadEnemy {
+            enemy_recognition_name: value.field_str("enemy_recognition_name")?,
+            enemy_hp: value.field_f32("enemy_hp")?,
+            enemy_def: value.field_f32("enemy_def")?,
+            enemy_speed: value.field_f32("enemy_speed")?,
+            enemy_invincible_time: value.field_f32("enemy_invincible_time")?,
+            enemy_image_count: value.field_u32("enemy_image_count")?,
             enemy_tag: value["enemy_tag"]
                 .members()
                 .map(|s| s.to_string())
                 .collect(),
-            enemy_image: value["enemy_image"].as_str()?.to_string(),
-            enemy_image_type: value["enemy_image_type"].as_str()?.to_string(),
-            enemy_minus_target_point: value["enemy_minus_target_point"].as_u32()?,
+            enemy_image: value.field_str("enemy_image")?,
+            enemy_image_type: value.field_str("enemy_image_type")?,
+            enemy_minus_target_point: value.field_u32("enemy_minus_target_point")?,
             enemy_name: value["enemy_name"]
                 .members()
                 .map(|s| s.to_string())
                 .collect(),
-            enemy_walk_interval: value["enemy_walk_interval"].as_f32()?,
-            enemy_animation_interval: value["enemy_animation_interval"].as_f32()?,
+            enemy_walk_interval: value.field_f32("enemy_walk_interval")?,
+            enemy_animation_interval: value.field_f32("enemy_animation_interval")?,
+            enemy_boss: if value["enemy_boss"].is_null() {
+                None
+            } else {
+                Some(
+                    EnemyBoss::from_json_value(&value["enemy_boss"])
+                        .map_err(|e| e.at("enemy_boss"))?,
+                )
+            },
+            enemy_resistance: parse_resistance(&value["enemy_resistance"])
+                .map_err(|e| e.at("enemy_resistance"))?,
         })
     }
 }
@@ -493,10 +1278,10 @@ pub struct GameText {
 }
 
 impl GameText {
-    pub fn from_json_value(value: &JsonValue) -> Option<GameText> {
+    pub fn from_json_value(value: &JsonValue) -> Result<GameText, LoadError> {
         // 检查 game_text 字段是否为对象
         if !value["game_text"].is_object() {
-            return None;
+            return Err(LoadError::expected(FieldKind::Object, &value["game_text"]).at("game_text"));
         }
 
         // 遍历对象键值对
@@ -511,7 +1296,7 @@ impl GameText {
             }
         }
 
-        Some(GameText { game_text: parsed })
+        Ok(GameText { game_text: parsed })
     }
 }
 
@@ -522,46 +1307,226 @@ pub struct PauseMessage {
     pub mentioned: bool,
 }
 
+// 回放中记录的单条输入，指针坐标均已换算为1280x720作战区域内的坐标
 #[derive(Debug, Clone)]
-pub struct Level {
-    pub level_name: String,
-    pub level_name_expand: Vec<String>,
-    pub level_description: Vec<String>,
-    pub level_type: String,
-    pub level_position: [f32; 2],
-    pub level_initial_status: bool,
-    pub unlock_map: Vec<UnlockMap>,
-    pub unlock_level: Vec<UnlockLevel>,
+pub enum ReplayInput {
+    PointerMoved([f32; 2]),
+    PointerButton {
+        position: [f32; 2],
+        button: PointerButton,
+        pressed: bool,
+    },
+    MouseWheel {
+        unit: egui::MouseWheelUnit,
+        delta: [f32; 2],
+    },
+    Key {
+        key: egui::Key,
+        pressed: bool,
+    },
 }
 
-#[derive(Debug, Clone)]
-pub struct UnlockMap {
-    pub map_name: String,
-    pub require_perfect_clear: bool,
-}
+impl ReplayInput {
+    pub fn from_json_value(value: &JsonValue) -> Result<ReplayInput, LoadError> {
+        let position = value.field_pair("p")?;
+        match value.field_str("t")?.as_str() {
+            "move" => Ok(ReplayInput::PointerMoved(position)),
+            "button" => Ok(ReplayInput::PointerButton {
+                position,
+                button: match value.field_str("b")?.as_str() {
+                    "Primary" => PointerButton::Primary,
+                    "Secondary" => PointerButton::Secondary,
+                    "Middle" => PointerButton::Middle,
+                    "Extra1" => PointerButton::Extra1,
+                    "Extra2" => PointerButton::Extra2,
+                    _ => return Err(LoadError::new("a pointer button", &value["b"]).at("b")),
+                },
+                pressed: value.field_bool("d")?,
+            }),
+            "wheel" => Ok(ReplayInput::MouseWheel {
+                unit: match value.field_str("u")?.as_str() {
+                    "Point" => egui::MouseWheelUnit::Point,
+                    "Line" => egui::MouseWheelUnit::Line,
+                    "Page" => egui::MouseWheelUnit::Page,
+                    _ => return Err(LoadError::new("a mouse wheel unit", &value["u"]).at("u")),
+                },
+                delta: position,
+            }),
+            "key" => Ok(ReplayInput::Key {
+                key: value.field("k", FieldKind::Str, |x| egui::Key::from_name(x.as_str()?))?,
+                pressed: value.field_bool("d")?,
+            }),
+            _ => Err(LoadError::new("one of move, button, wheel, key", &value["t"]).at("t")),
+        }
+    }
 
-impl UnlockMap {
-    pub fn from_json_value(value: &JsonValue) -> Option<UnlockMap> {
-        Some(Self {
-            map_name: value["map_name"].as_str()?.to_string(),
-            require_perfect_clear: value["require_perfect_clear"].as_bool()?,
-        })
+    pub fn to_json_value(&self) -> JsonValue {
+        match self {
+            ReplayInput::PointerMoved(position) => json::object! {
+                t: "move",
+                p: position.to_vec(),
+            },
+            ReplayInput::PointerButton {
+                position,
+                button,
+                pressed,
+            } => json::object! {
+                t: "button",
+                p: position.to_vec(),
+                b: format!("{:?}", button),
+                d: *pressed,
+            },
+            ReplayInput::MouseWheel { unit, delta } => json::object! {
+                t: "wheel",
+                p: delta.to_vec(),
+                u: format!("{:?}", unit),
+            },
+            ReplayInput::Key { key, pressed } => json::object! {
+                t: "key",
+                p: vec![0_f32, 0_f32],
+                k: key.name(),
+                d: *pressed,
+            },
+        }
     }
 }
 
+// 同一帧内发生的输入，以operation_runtime为时间轴
 #[derive(Debug, Clone)]
-pub struct UnlockLevel {
-    pub level_name: String,
-    pub level_map: String,
-    pub require_perfect_clear: bool,
+pub struct ReplayFrame {
+    pub runtime: f32,
+    pub inputs: Vec<ReplayInput>,
 }
 
-impl UnlockLevel {
-    pub fn from_json_value(value: &JsonValue) -> Option<UnlockLevel> {
-        Some(Self {
-            level_name: value["level_name"].as_str()?.to_string(),
-            level_map: value["level_map"].as_str()?.to_string(),
-            require_perfect_clear: value["require_perfect_clear"].as_bool()?,
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub level_path: String,
+    pub user_name: String,
+    pub record_time: String,
+    pub gun_list: Vec<String>,
+    pub gun_level: Vec<i32>,
+    pub gun_selected: u32,
+    pub endless_seed: Option<u32>,
+    pub endless_map: String,
+    // 录制时的按键绑定，回放时按此解释录制的输入
+    pub input_binding: HashMap<String, String>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn from_json_value(value: &JsonValue) -> Result<Replay, LoadError> {
+        Ok(Replay {
+            level_path: value.field_str("level_path")?,
+            user_name: value.field_str("user_name")?,
+            record_time: value.field_str("record_time")?,
+            gun_list: value.field("gun_list", FieldKind::StrArray, |x| {
+                x.members()
+                    .map(|x| x.as_str().map(|s| s.to_string()))
+                    .collect::<Option<Vec<String>>>()
+            })?,
+            gun_level: value["gun_level"]
+                .members()
+                .filter_map(|x| x.as_i32())
+                .collect(),
+            gun_selected: value.field_u32("gun_selected")?,
+            endless_seed: value["endless_seed"].as_u32(),
+            endless_map: value["endless_map"].as_str().unwrap_or("").to_string(),
+            input_binding: value["input_binding"]
+                .entries()
+                .filter_map(|(k, v)| Some((k.to_string(), v.as_str()?.to_string())))
+                .collect(),
+            frames: parse_members(value, "frames", |x| {
+                Ok(ReplayFrame {
+                    runtime: x.field_f32("r")?,
+                    inputs: parse_members(x, "i", ReplayInput::from_json_value)?,
+                })
+            })?,
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        json::object! {
+            level_path: self.level_path.clone(),
+            user_name: self.user_name.clone(),
+            record_time: self.record_time.clone(),
+            gun_list: self.gun_list.clone(),
+            gun_level: self.gun_level.clone(),
+            gun_selected: self.gun_selected,
+            endless_seed: self.endless_seed,
+            endless_map: self.endless_map.clone(),
+            input_binding: self.input_binding.iter().fold(json::object! {}, |mut obj, (k, v)| {
+                obj.insert(k, v.clone()).expect("插入按键绑定失败");
+                obj
+            }),
+            frames: self.frames.iter().map(|f| json::object! {
+                r: f.runtime,
+                i: f.inputs.iter().map(|x| x.to_json_value()).collect::<Vec<_>>(),
+            }).collect::<Vec<_>>(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub level_name: String,
+    pub level_name_expand: Vec<String>,
+    pub level_description: Vec<String>,
+    pub level_type: String,
+    pub level_position: [f32; 2],
+    pub level_initial_status: bool,
+    pub unlock_map: Vec<UnlockMap>,
+    pub unlock_level: Vec<UnlockLevel>,
+}
+
+impl Level {
+    pub fn from_json_value(value: &JsonValue) -> Result<Level, LoadError> {
+        Ok(Level {
+            level_name: value.field_str("level_name")?,
+            level_name_expand: value["level_name_expand"]
+                .members()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+            level_description: value["level_description"]
+                .members()
+                .filter_map(|d| d.as_str().map(String::from))
+                .collect(),
+            level_type: value.field_str("level_type")?,
+            level_position: value.field_pair("level_position")?,
+            level_initial_status: value.field_bool("level_initial_status")?,
+            unlock_map: parse_members(value, "unlock_map", UnlockMap::from_json_value)?,
+            unlock_level: parse_members(value, "unlock_level", UnlockLevel::from_json_value)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnlockMap {
+    pub map_name: String,
+    pub require_perfect_clear: bool,
+}
+
+impl UnlockMap {
+    pub fn from_json_value(value: &JsonValue) -> Result<UnlockMap, LoadError> {
+        Ok(Self {
+            map_name: value.field_str("map_name")?,
+            require_perfect_clear: value.field_bool("require_perfect_clear")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnlockLevel {
+    pub level_name: String,
+    pub level_map: String,
+    pub require_perfect_clear: bool,
+}
+
+impl UnlockLevel {
+    pub fn from_json_value(value: &JsonValue) -> Result<UnlockLevel, LoadError> {
+        Ok(Self {
+            level_name: value.field_str("level_name")?,
+            level_map: value.field_str("level_map")?,
+            require_perfect_clear: value.field_bool("require_perfect_clear")?,
         })
     }
 }
@@ -580,54 +1545,27 @@ pub struct Map {
     pub map_initial_unlock_status: bool,
     pub map_unlock_description: Vec<String>,
     pub map_lock_intro: String,
+    pub map_music: String,
+    pub map_endless: Option<MapEndless>,
 }
 
 impl Map {
-    pub fn from_json_value(value: &JsonValue) -> Option<Self> {
-        Some(Self {
+    pub fn from_json_value(value: &JsonValue) -> Result<Self, LoadError> {
+        Ok(Self {
             map_name: value["map_name"]
                 .members()
                 .filter_map(|v| v.as_str().map(String::from))
                 .collect(),
-            map_author: value["map_author"].as_str()?.to_string(),
-            map_image: value["map_image"].as_str()?.to_string(),
-            map_width: value["map_width"].as_f32()?,
-            map_scroll_offset: value["map_scroll_offset"].as_f32()?,
+            map_author: value.field_str("map_author")?,
+            map_image: value.field_str("map_image")?,
+            map_width: value.field_f32("map_width")?,
+            map_scroll_offset: value.field_f32("map_scroll_offset")?,
             map_description: value["map_description"]
                 .members()
                 .filter_map(|v| v.as_str().map(String::from))
                 .collect(),
-            map_intro: value["map_intro"].as_str()?.to_string(),
-            map_content: value["map_content"]
-                .members()
-                .filter_map(|v| {
-                    Some(Level {
-                        level_name: v["level_name"].as_str()?.to_string(),
-                        level_name_expand: v["level_name_expand"]
-                            .members()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect(),
-                        level_description: v["level_description"]
-                            .members()
-                            .filter_map(|d| d.as_str().map(String::from))
-                            .collect(),
-                        level_type: v["level_type"].as_str()?.to_string(),
-                        level_position: [
-                            v["level_position"][0].as_f32()?,
-                            v["level_position"][1].as_f32()?,
-                        ],
-                        level_initial_status: v["level_initial_status"].as_bool()?,
-                        unlock_map: v["unlock_map"]
-                            .members()
-                            .filter_map(UnlockMap::from_json_value)
-                            .collect(),
-                        unlock_level: v["unlock_level"]
-                            .members()
-                            .filter_map(UnlockLevel::from_json_value)
-                            .collect(),
-                    })
-                })
-                .collect(),
+            map_intro: value.field_str("map_intro")?,
+            map_content: parse_members(value, "map_content", Level::from_json_value)?,
             map_connecting_line: value["map_connecting_line"]
                 .members()
                 .filter_map(|v| {
@@ -639,16 +1577,26 @@ impl Map {
                     Some(pair)
                 })
                 .collect(),
-            map_initial_unlock_status: value["map_initial_unlock_status"].as_bool()?,
+            map_initial_unlock_status: value.field_bool("map_initial_unlock_status")?,
             map_unlock_description: value["map_unlock_description"]
                 .members()
                 .filter_map(|v| v.as_str().map(String::from))
                 .collect(),
-            map_lock_intro: value["map_lock_intro"].as_str()?.to_string(),
+            map_lock_intro: value.field_str("map_lock_intro")?,
+            map_music: value["map_music"].as_str().unwrap_or("").to_string(),
+            // 未声明时地图不提供无尽模式
+            map_endless: if value["map_endless"].is_null() {
+                None
+            } else {
+                Some(
+                    MapEndless::from_json_value(&value["map_endless"])
+                        .map_err(|e| e.at("map_endless"))?,
+                )
+            },
         })
     }
     pub fn to_json_value(&self) -> JsonValue {
-        json::object! {
+        let mut value = json::object! {
             map_name: self.map_name.clone(),
             map_author: self.map_author.clone(),
             map_image: self.map_image.clone(),
@@ -674,10 +1622,102 @@ impl Map {
             map_initial_unlock_status: self.map_initial_unlock_status,
             map_unlock_description: self.map_unlock_description.clone(),
             map_lock_intro: self.map_lock_intro.clone(),
+            map_music: self.map_music.clone(),
+        };
+        if let Some(map_endless) = &self.map_endless {
+            value["map_endless"] = map_endless.to_json_value();
+        };
+        value
+    }
+}
+
+// 可识别的枪械射击标签，写法与敌人标签相同，其余标签仅作为伤害来源标签使用
+#[derive(Debug, Clone, PartialEq)]
+pub enum GunTag {
+    // 松开左键时射击
+    ReleasedShoot,
+    // 按住左键时连续射击
+    DownShoot,
+    // 每次扣动扳机连续射出的发数
+    Burst(u32),
+    // 按住蓄力，松开时按蓄力时长提高伤害：蓄满所需时间、最大伤害倍率
+    Charge { time: f32, multiplier: f32 },
+    // 一次射出多枚弹丸：弹丸数量、散布半径
+    Spread { count: u32, radius: f32 },
+    // 命中准星下的所有敌人，而不仅是最上层的敌人
+    Piercing,
+    // 对准星中心一定半径内的敌人造成伤害
+    Splash(f32),
+    // 按住时持续输出伤害，每个射击间隔消耗一发子弹
+    Beam,
+    // 伤害类型，缺省为动能伤害
+    Damage(DamageType),
+    // 命中并造成伤害时施加的状态
+    Status(StatusEffect),
+}
+
+impl GunTag {
+    pub fn from_tag(tag: &str) -> Option<GunTag> {
+        let mut parts = tag.split(':');
+        let name = parts.next()?;
+        let args: Vec<&str> = parts.collect();
+        let arg_f = |index: usize, default: f32| {
+            args.get(index)
+                .and_then(|x| x.parse::<f32>().ok())
+                .unwrap_or(default)
+        };
+        let arg_u = |index: usize, default: u32| {
+            args.get(index)
+                .and_then(|x| x.parse::<u32>().ok())
+                .unwrap_or(default)
+        };
+        match name {
+            "released_shoot" => Some(GunTag::ReleasedShoot),
+            "down_shoot" => Some(GunTag::DownShoot),
+            "burst" => Some(GunTag::Burst(arg_u(0, 3).max(1))),
+            "charge" => Some(GunTag::Charge {
+                time: arg_f(0, 1_f32).max(0.01),
+                multiplier: arg_f(1, 3_f32),
+            }),
+            "spread" => Some(GunTag::Spread {
+                count: arg_u(0, 5).max(1),
+                radius: arg_f(1, 40_f32),
+            }),
+            "piercing" => Some(GunTag::Piercing),
+            "splash" => Some(GunTag::Splash(arg_f(0, 60_f32))),
+            "beam" => Some(GunTag::Beam),
+            "damage" => Some(GunTag::Damage(DamageType::from_name(args.first()?)?)),
+            _ => Some(GunTag::Status(StatusEffect::from_tag(name, &args)?)),
         }
     }
 }
 
+// 枪械的一级升级，各项数值为相对上一级的增量
+#[derive(Debug, Clone)]
+pub struct GunUpgrade {
+    pub upgrade_cost: u32,
+    pub upgrade_basic_damage: f32,
+    pub upgrade_catridge_clip: i32,
+    pub upgrade_recoil: f32,
+    pub upgrade_shoot_speed: f32,
+    pub upgrade_reload_time: f32,
+    pub upgrade_temperature_degree: i32,
+}
+
+impl GunUpgrade {
+    pub fn from_json_value(value: &JsonValue) -> Result<GunUpgrade, LoadError> {
+        Ok(GunUpgrade {
+            upgrade_cost: value.field_u32("upgrade_cost")?,
+            upgrade_basic_damage: value["upgrade_basic_damage"].as_f32().unwrap_or(0_f32),
+            upgrade_catridge_clip: value["upgrade_catridge_clip"].as_i32().unwrap_or(0),
+            upgrade_recoil: value["upgrade_recoil"].as_f32().unwrap_or(0_f32),
+            upgrade_shoot_speed: value["upgrade_shoot_speed"].as_f32().unwrap_or(0_f32),
+            upgrade_reload_time: value["upgrade_reload_time"].as_f32().unwrap_or(0_f32),
+            upgrade_temperature_degree: value["upgrade_temperature_degree"].as_i32().unwrap_or(0),
+        })
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Gun {
@@ -693,7 +1733,10 @@ pub struct Gun {
     pub gun_recoil: f32,
     pub gun_temperature_degree: u32,
     pub gun_tag: Vec<String>,
+    pub gun_firing: Vec<GunTag>,
     pub gun_initial_unlock: bool,
+    pub gun_unlock_cost: u32,
+    pub gun_upgrade: Vec<GunUpgrade>,
     pub gun_no_bullet_shoot_sound: String,
     pub gun_reload_sound: String,
     pub gun_reload_bullet_sound: String,
@@ -702,9 +1745,9 @@ pub struct Gun {
 }
 
 impl Gun {
-    pub fn from_json_value(value: &JsonValue) -> Option<Gun> {
-        Some(Gun {
-            gun_recognition_name: value["gun_recognition_name"].as_str()?.to_string(),
+    pub fn from_json_value(value: &JsonValue) -> Result<Gun, LoadError> {
+        Ok(Gun {
+            gun_recognition_name: value.field_str("gun_recognition_name")?,
             gun_name: value["gun_name"]
                 .members()
                 .filter_map(|v| v.as_str().map(String::from))
@@ -716,33 +1759,213 @@ impl Gun {
                 .try_into()
                 .ok()
                 .unwrap_or([0.0, 0.0]),
-            gun_image: value["gun_image"].as_str()?.to_string(),
-            gun_shoot_sound: value["gun_shoot_sound"].as_str()?.to_string(),
-            gun_shoot_speed: value["gun_shoot_speed"].as_f32()?,
-            gun_reload_time: value["gun_reload_time"].as_f32()?,
-            gun_basic_damage: value["gun_basic_damage"].as_f32()?,
-            gun_catridge_clip: value["gun_catridge_clip"].as_u32()?,
-            gun_recoil: value["gun_recoil"].as_f32()?,
-            gun_temperature_degree: value["gun_temperature_degree"].as_u32()?,
+            gun_image: value.field_str("gun_image")?,
+            gun_shoot_sound: value.field_str("gun_shoot_sound")?,
+            gun_shoot_speed: value.field_f32("gun_shoot_speed")?,
+            gun_reload_time: value.field_f32("gun_reload_time")?,
+            gun_basic_damage: value.field_f32("gun_basic_damage")?,
+            gun_catridge_clip: value.field_u32("gun_catridge_clip")?,
+            gun_recoil: value.field_f32("gun_recoil")?,
+            gun_temperature_degree: value.field_u32("gun_temperature_degree")?,
             gun_tag: value["gun_tag"]
                 .members()
                 .filter_map(|v| v.as_str().map(String::from))
                 .collect(),
-            gun_initial_unlock: value["gun_initial_unlock"].as_bool()?,
-            gun_no_bullet_shoot_sound: value["gun_no_bullet_shoot_sound"].as_str()?.to_string(),
-            gun_reload_bullet_sound: value["gun_reload_bullet_sound"].as_str()?.to_string(),
-            gun_reload_sound: value["gun_reload_sound"].as_str()?.to_string(),
-            gun_reload_interval: value["gun_reload_interval"].as_f32()?,
-            gun_overheating_sound: value["gun_overheating_sound"].as_str()?.to_string(),
+            gun_firing: value["gun_tag"]
+                .members()
+                .filter_map(|v| GunTag::from_tag(v.as_str()?))
+                .collect(),
+            gun_initial_unlock: value.field_bool("gun_initial_unlock")?,
+            gun_unlock_cost: value["gun_unlock_cost"].as_u32().unwrap_or(0),
+            gun_upgrade: parse_members(value, "gun_upgrade", GunUpgrade::from_json_value)?,
+            gun_no_bullet_shoot_sound: value.field_str("gun_no_bullet_shoot_sound")?,
+            gun_reload_bullet_sound: value.field_str("gun_reload_bullet_sound")?,
+            gun_reload_sound: value.field_str("gun_reload_sound")?,
+            gun_reload_interval: value.field_f32("gun_reload_interval")?,
+            gun_overheating_sound: value.field_str("gun_overheating_sound")?,
+        })
+    }
+
+    // 枪械可达到的最高等级，0级为刚解锁时的状态
+    pub fn max_level(&self) -> i32 {
+        self.gun_upgrade.len() as i32
+    }
+
+    // 从当前等级升到下一级的花费，已满级时返回None
+    pub fn upgrade_cost(&self, gun_level: i32) -> Option<u32> {
+        if gun_level < 0 {
+            return None;
+        };
+        self.gun_upgrade
+            .get(gun_level as usize)
+            .map(|x| x.upgrade_cost)
+    }
+
+    // 按用户的枪械等级叠加各级升级后的枪械数值
+    pub fn levelled(&self, gun_level: i32) -> Gun {
+        let mut gun = self.clone();
+        for upgrade in self
+            .gun_upgrade
+            .iter()
+            .take(gun_level.clamp(0, self.max_level()) as usize)
+        {
+            gun.gun_basic_damage += upgrade.upgrade_basic_damage;
+            gun.gun_catridge_clip =
+                (gun.gun_catridge_clip as i32 + upgrade.upgrade_catridge_clip).max(1) as u32;
+            gun.gun_recoil += upgrade.upgrade_recoil;
+            gun.gun_shoot_speed += upgrade.upgrade_shoot_speed;
+            gun.gun_reload_time += upgrade.upgrade_reload_time;
+            gun.gun_temperature_degree = (gun.gun_temperature_degree as i32
+                + upgrade.upgrade_temperature_degree)
+                .max(0) as u32;
+        }
+        gun.gun_basic_damage = gun.gun_basic_damage.max(0_f32);
+        gun.gun_recoil = gun.gun_recoil.max(0_f32);
+        gun.gun_shoot_speed = gun.gun_shoot_speed.max(0.01);
+        gun.gun_reload_time = gun.gun_reload_time.max(0_f32);
+        gun
+    }
+
+    // 按住左键即可射击，否则需要松开左键
+    pub fn firing_hold(&self) -> bool {
+        self.gun_firing
+            .iter()
+            .any(|x| *x == GunTag::DownShoot || *x == GunTag::Beam)
+    }
+
+    pub fn firing_released(&self) -> bool {
+        self.gun_firing.contains(&GunTag::ReleasedShoot)
+    }
+
+    pub fn firing_burst(&self) -> u32 {
+        self.gun_firing
+            .iter()
+            .find_map(|x| match x {
+                GunTag::Burst(count) => Some(*count),
+                _ => None,
+            })
+            .unwrap_or(1)
+    }
+
+    pub fn firing_charge(&self) -> Option<(f32, f32)> {
+        self.gun_firing.iter().find_map(|x| match x {
+            GunTag::Charge { time, multiplier } => Some((*time, *multiplier)),
+            _ => None,
+        })
+    }
+
+    pub fn firing_spread(&self) -> Option<(u32, f32)> {
+        self.gun_firing.iter().find_map(|x| match x {
+            GunTag::Spread { count, radius } => Some((*count, *radius)),
+            _ => None,
+        })
+    }
+
+    pub fn firing_splash(&self) -> f32 {
+        self.gun_firing
+            .iter()
+            .find_map(|x| match x {
+                GunTag::Splash(radius) => Some(*radius),
+                _ => None,
+            })
+            .unwrap_or(0_f32)
+    }
+
+    pub fn firing_piercing(&self) -> bool {
+        self.gun_firing.contains(&GunTag::Piercing)
+    }
+
+    pub fn firing_beam(&self) -> bool {
+        self.gun_firing.contains(&GunTag::Beam)
+    }
+
+    pub fn firing_damage_type(&self) -> DamageType {
+        self.gun_firing
+            .iter()
+            .find_map(|x| match x {
+                GunTag::Damage(damage_type) => Some(*damage_type),
+                _ => None,
+            })
+            .unwrap_or(DamageType::Kinetic)
+    }
+
+    pub fn firing_status(&self) -> Vec<StatusEffect> {
+        self.gun_firing
+            .iter()
+            .filter_map(|x| match x {
+                GunTag::Status(effect) => Some(*effect),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instrument {
+    pub instrument_recognition_name: String,
+    pub instrument_name: Vec<String>,
+    pub instrument_cost: u32,
+    pub instrument_size: [f32; 2],
+    pub instrument_range: f32,
+    // 两次开火之间的冷却秒数
+    pub instrument_fire_interval: f32,
+    pub instrument_damage: f32,
+    pub instrument_image: String,
+    pub instrument_shoot_sound: String,
+}
+
+impl Instrument {
+    pub fn from_json_value(value: &JsonValue) -> Result<Instrument, LoadError> {
+        Ok(Instrument {
+            instrument_recognition_name: value.field_str("instrument_recognition_name")?,
+            instrument_name: value["instrument_name"]
+                .members()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+            instrument_cost: value.field_u32("instrument_cost")?,
+            instrument_size: value.field_pair("instrument_size")?,
+            instrument_range: value.field_f32("instrument_range")?,
+            instrument_fire_interval: value.field_f32("instrument_fire_interval")?,
+            instrument_damage: value.field_f32("instrument_damage")?,
+            instrument_image: value.field_str("instrument_image")?,
+            instrument_shoot_sound: value.field_str("instrument_shoot_sound")?,
         })
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PlacedInstrument {
+    pub instrument_name: String,
+    pub instrument_index: usize,
+    pub instrument_position: [f32; 2],
+    pub instrument_last_fire_time: f32,
+    pub instrument_target: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct UserLevelStatus {
     pub level_name: String,
     pub level_map: String,
     pub level_status: i8,
+    pub level_best_score: u32,
+    pub level_best_star: u8,
+}
+
+impl UserLevelStatus {
+    pub fn from_json_value(value: &JsonValue) -> Result<UserLevelStatus, LoadError> {
+        Ok(UserLevelStatus {
+            level_name: value.field_str("level_name")?,
+            level_map: value.field_str("level_map")?,
+            level_status: value.field("level_status", FieldKind::Int, |x| x.as_i8())?,
+            // 评分之前的存档没有以下两项
+            level_best_score: value
+                .optional("level_best_score", JsonValue::field_u32)?
+                .unwrap_or(0),
+            level_best_star: value
+                .optional("level_best_star", JsonValue::field_u8)?
+                .unwrap_or(0),
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -751,12 +1974,150 @@ pub struct UserGunStatus {
     pub gun_level: i32,
 }
 
+impl UserGunStatus {
+    pub fn from_json_value(value: &JsonValue) -> Result<UserGunStatus, LoadError> {
+        Ok(UserGunStatus {
+            gun_recognition_name: value.field_str("gun_recognition_name")?,
+            gun_level: value.field("gun_level", FieldKind::Int, |x| x.as_i32())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UserEndlessStatus {
+    pub endless_map: String,
+    pub endless_best_wave: u32,
+    pub endless_best_score: u32,
+}
+
+impl UserEndlessStatus {
+    pub fn from_json_value(value: &JsonValue) -> Result<UserEndlessStatus, LoadError> {
+        Ok(UserEndlessStatus {
+            endless_map: value.field_str("endless_map")?,
+            endless_best_wave: value.field_u32("endless_best_wave")?,
+            endless_best_score: value.field_u32("endless_best_score")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UserMapStatus {
     pub map_name: String,
     pub map_unlock_status: bool,
 }
 
+impl UserMapStatus {
+    pub fn from_json_value(value: &JsonValue) -> Result<UserMapStatus, LoadError> {
+        Ok(UserMapStatus {
+            map_name: value.field_str("map_name")?,
+            // 旧版User::to_json_value以map_status保存解锁状态
+            map_unlock_status: match value.optional("map_unlock_status", JsonValue::field_bool)? {
+                Some(status) => status,
+                None => value.field_bool("map_status")?,
+            },
+        })
+    }
+}
+
+// 新用户与旧版存档使用的存档槽名称
+pub const DEFAULT_SAVE_SLOT: &str = "1";
+
+// 一条独立的游戏进度；当前存档槽的进度保存在User的同名字段中，其余存档槽保存在User::save_slot中
+#[derive(Debug, Clone)]
+pub struct UserSaveSlot {
+    pub slot_name: String,
+    pub current_map: String,
+    pub current_level: String,
+    pub level_status: Vec<UserLevelStatus>,
+    pub gun_status: Vec<UserGunStatus>,
+    pub map_status: Vec<UserMapStatus>,
+    pub currency: u32,
+    pub endless_status: Vec<UserEndlessStatus>,
+}
+
+impl UserSaveSlot {
+    // 与注册新用户时相同的初始进度
+    pub fn new(slot_name: &str) -> UserSaveSlot {
+        UserSaveSlot {
+            slot_name: slot_name.to_string(),
+            current_map: "map_tutorial".to_string(),
+            current_level: "".to_string(),
+            level_status: vec![],
+            gun_status: vec![],
+            map_status: vec![],
+            currency: 0,
+            endless_status: vec![],
+        }
+    }
+
+    pub fn from_json_value(value: &JsonValue) -> Result<UserSaveSlot, LoadError> {
+        UserSaveSlot::progress_from_json_value(value, value.field_str("slot_name")?)
+    }
+
+    // 解析进度字段；User与存档槽以相同的字段名保存进度
+    pub fn progress_from_json_value(
+        value: &JsonValue,
+        slot_name: String,
+    ) -> Result<UserSaveSlot, LoadError> {
+        Ok(UserSaveSlot {
+            slot_name,
+            current_map: value.field_str("current_map")?,
+            current_level: value.field_str("current_level")?,
+            level_status: parse_members(value, "level_status", UserLevelStatus::from_json_value)?,
+            gun_status: parse_members(value, "gun_status", UserGunStatus::from_json_value)?,
+            map_status: parse_members(value, "map_status", UserMapStatus::from_json_value)?,
+            currency: value
+                .optional("currency", JsonValue::field_u32)?
+                .unwrap_or(0),
+            endless_status: parse_members(
+                value,
+                "endless_status",
+                UserEndlessStatus::from_json_value,
+            )?,
+        })
+    }
+
+    // 将进度字段写入object
+    pub fn insert_progress(&self, object: &mut JsonValue) {
+        let progress = json::object! {
+            current_map: self.current_map.clone(),
+            current_level: self.current_level.clone(),
+            level_status: self.level_status.iter().map(|l| json::object! {
+                level_name: l.level_name.clone(),
+                level_map: l.level_map.clone(),
+                level_status: l.level_status,
+                level_best_score: l.level_best_score,
+                level_best_star: l.level_best_star,
+            }).collect::<Vec<_>>(),
+            gun_status: self.gun_status.iter().map(|l| json::object! {
+                gun_recognition_name: l.gun_recognition_name.clone(),
+                gun_level: l.gun_level,
+            }).collect::<Vec<_>>(),
+            map_status: self.map_status.iter().map(|l| json::object! {
+                map_name: l.map_name.clone(),
+                map_unlock_status: l.map_unlock_status,
+            }).collect::<Vec<_>>(),
+            currency: self.currency,
+            endless_status: self.endless_status.iter().map(|l| json::object! {
+                endless_map: l.endless_map.clone(),
+                endless_best_wave: l.endless_best_wave,
+                endless_best_score: l.endless_best_score,
+            }).collect::<Vec<_>>(),
+        };
+        for (key, val) in progress.entries() {
+            object.insert(key, val.clone()).expect("插入进度字段失败");
+        }
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        let mut object = json::object! {
+            slot_name: self.slot_name.clone(),
+        };
+        self.insert_progress(&mut object);
+        object
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct User {
@@ -770,80 +2131,114 @@ pub struct User {
     pub map_status: Vec<UserMapStatus>,
     pub settings: HashMap<String, String>,
     pub current_level: String,
+    pub currency: u32,
+    pub endless_status: Vec<UserEndlessStatus>,
+    pub current_slot: String,
+    // 未在使用的存档槽
+    pub save_slot: Vec<UserSaveSlot>,
 }
 
 #[allow(dead_code)]
 impl User {
-    pub fn from_json_value(value: &JsonValue) -> Option<User> {
+    pub fn from_json_value(value: &JsonValue) -> Result<User, LoadError> {
         let mut parsed = HashMap::new();
         for (key, val) in value["settings"].entries() {
             parsed.insert(key.to_string(), val.to_string());
         }
-        Some(User {
-            name: value["name"].as_str()?.to_string(),
-            password: value["password"].as_str()?.to_string(),
-            language: value["language"].as_u8()?,
-            wallpaper: value["wallpaper"].as_str()?.to_string(),
-            current_map: value["current_map"].as_str()?.to_string(),
-            level_status: value["level_status"]
-                .members()
-                .filter_map(|v| {
-                    Some(UserLevelStatus {
-                        level_name: v["level_name"].as_str()?.to_string(),
-                        level_map: v["level_map"].as_str()?.to_string(),
-                        level_status: v["level_status"].as_i8()?,
-                    })
-                })
-                .collect(),
-            gun_status: value["gun_status"]
-                .members()
-                .filter_map(|v| {
-                    Some(UserGunStatus {
-                        gun_recognition_name: v["gun_recognition_name"].as_str()?.to_string(),
-                        gun_level: v["gun_level"].as_i32()?,
-                    })
-                })
-                .collect(),
-            map_status: value["map_status"]
-                .members()
-                .filter_map(|v| {
-                    Some(UserMapStatus {
-                        map_name: v["map_name"].as_str()?.to_string(),
-                        map_unlock_status: v["map_unlock_status"].as_bool()?,
-                    })
-                })
-                .collect(),
+        // 存档槽之前的用户文件没有current_slot
+        let progress = UserSaveSlot::progress_from_json_value(
+            value,
+            value
+                .optional("current_slot", JsonValue::field_str)?
+                .unwrap_or(DEFAULT_SAVE_SLOT.to_string()),
+        )?;
+        Ok(User {
+            name: value.field_str("name")?,
+            password: value.field_str("password")?,
+            language: value.field_u8("language")?,
+            wallpaper: value.field_str("wallpaper")?,
+            current_map: progress.current_map,
+            level_status: progress.level_status,
+            gun_status: progress.gun_status,
+            map_status: progress.map_status,
             settings: parsed,
-            current_level: value["current_level"].as_str()?.to_string(),
+            current_level: progress.current_level,
+            currency: progress.currency,
+            endless_status: progress.endless_status,
+            current_slot: progress.slot_name,
+            save_slot: parse_members(value, "save_slot", UserSaveSlot::from_json_value)?,
         })
     }
 
+    // 当前存档槽的进度
+    pub fn active_save_slot(&self) -> UserSaveSlot {
+        UserSaveSlot {
+            slot_name: self.current_slot.clone(),
+            current_map: self.current_map.clone(),
+            current_level: self.current_level.clone(),
+            level_status: self.level_status.clone(),
+            gun_status: self.gun_status.clone(),
+            map_status: self.map_status.clone(),
+            currency: self.currency,
+            endless_status: self.endless_status.clone(),
+        }
+    }
+
+    // 全部存档槽的名称，按名称排序
+    pub fn save_slot_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .save_slot
+            .iter()
+            .map(|x| x.slot_name.clone())
+            .chain([self.current_slot.clone()])
+            .collect();
+        names.sort();
+        names
+    }
+
+    // 切换到指定存档槽，不存在时以初始进度新建；当前进度存入save_slot
+    pub fn switch_save_slot(&mut self, slot_name: &str) -> bool {
+        if slot_name.is_empty() || slot_name == self.current_slot {
+            return false;
+        };
+        let slot = match self.save_slot.iter().position(|x| x.slot_name == slot_name) {
+            Some(index) => self.save_slot.remove(index),
+            None => UserSaveSlot::new(slot_name),
+        };
+        self.save_slot.push(self.active_save_slot());
+        self.current_slot = slot.slot_name;
+        self.current_map = slot.current_map;
+        self.current_level = slot.current_level;
+        self.level_status = slot.level_status;
+        self.gun_status = slot.gun_status;
+        self.map_status = slot.map_status;
+        self.currency = slot.currency;
+        self.endless_status = slot.endless_status;
+        true
+    }
+
+    // 只能删除未在使用的存档槽
+    pub fn delete_save_slot(&mut self, slot_name: &str) -> bool {
+        let len = self.save_slot.len();
+        self.save_slot.retain(|x| x.slot_name != slot_name);
+        self.save_slot.len() != len
+    }
+
     pub fn to_json_value(&self) -> JsonValue {
-        json::object! {
+        let mut object = json::object! {
             name: self.name.clone(),
             password: self.password.clone(),
             language: self.language,
             wallpaper: self.wallpaper.clone(),
-            current_map: self.current_map.clone(),
-            level_status: self.level_status.iter().map(|l| json::object! {
-                level_name: l.level_name.clone(),
-                level_map: l.level_map.clone(),
-                level_status: l.level_status,
-            }).collect::<Vec<_>>(),
-            gun_status: self.gun_status.iter().map(|l| json::object! {
-                gun_recognition_name: l.gun_recognition_name.clone(),
-                gun_level: l.gun_level,
-            }).collect::<Vec<_>>(),
-            map_status: self.map_status.iter().map(|l| json::object! {
-                map_name: l.map_name.clone(),
-                map_status: l.map_unlock_status,
-            }).collect::<Vec<_>>(),
             settings: self.settings.iter().fold(json::object! {}, |mut obj, (k, v)| {
                 obj.insert(k, v.clone()).expect("插入设置项失败");
                 obj
             }),
-            current_level: self.current_level.clone(),
-        }
+            current_slot: self.current_slot.clone(),
+            save_slot: self.save_slot.iter().map(|x| x.to_json_value()).collect::<Vec<_>>(),
+        };
+        self.active_save_slot().insert_progress(&mut object);
+        object
     }
 }
 
@@ -917,40 +2312,257 @@ pub enum SeverityLevel {
 }
 
 pub fn check_resource_exist<T: RustConstructorResource>(
-    resource_list: Vec<T>,
+    resource_list: &ResourceRegistry<T>,
     resource_name: &str,
 ) -> bool {
-    resource_list.iter().any(|x| x.name() == resource_name)
+    resource_list.contains(resource_name)
 }
 
-pub trait RustConstructorResource {
-    fn name(&self) -> &str;
+/// 以名称建立索引的资源表，按名称查找资源时无需克隆或遍历整个列表
+#[derive(Debug, Clone)]
+pub struct ResourceRegistry<T> {
+    resource_list: Vec<T>,
+    // 同名资源只记录最先加入的一个，与按顺序查找的结果一致
+    resource_index: HashMap<String, usize>,
+}
 
-    fn expose_type(&self) -> &str;
+impl<T> Default for ResourceRegistry<T> {
+    fn default() -> Self {
+        ResourceRegistry {
+            resource_list: Vec::new(),
+            resource_index: HashMap::new(),
+        }
+    }
+}
 
-    fn reg_render_resource(&self, render_list: &mut Vec<RenderResource>);
+impl<T: RustConstructorResource> From<Vec<T>> for ResourceRegistry<T> {
+    fn from(resource_list: Vec<T>) -> Self {
+        let mut registry = ResourceRegistry {
+            resource_list,
+            resource_index: HashMap::new(),
+        };
+        registry.rebuild_index();
+        registry
+    }
 }
 
-impl RustConstructorResource for PageData {
-    fn name(&self) -> &str {
-        &self.name
+// 只读访问，资源的顺序与名称只能通过ResourceRegistry的方法修改，避免索引失效
+impl<T> std::ops::Deref for ResourceRegistry<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.resource_list
     }
+}
 
-    fn expose_type(&self) -> &str {
-        &self.discern_type
+// 按列表中的位置读取，遍历资源表时使用
+impl<T> std::ops::Index<usize> for ResourceRegistry<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.resource_list[index]
     }
+}
 
-    fn reg_render_resource(&self, render_list: &mut Vec<RenderResource>) {
-        render_list.push(RenderResource {
-            discern_type: self.expose_type().to_string(),
-            name: self.name.to_string(),
-        });
+impl<T> std::ops::Index<ResourceId<T>> for ResourceRegistry<T> {
+    type Output = T;
+
+    fn index(&self, id: ResourceId<T>) -> &T {
+        &self.resource_list[id.index]
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct PageData {
-    pub discern_type: String,
+/// 指向某一资源表中资源的句柄，不同类型资源的句柄不能混用
+#[derive(Debug)]
+pub struct ResourceId<T> {
+    index: usize,
+    resource_type: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> ResourceId<T> {
+    fn new(index: usize) -> Self {
+        ResourceId {
+            index,
+            resource_type: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for ResourceId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ResourceId<T> {}
+
+impl<T> PartialEq for ResourceId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T: RustConstructorResource> ResourceRegistry<T> {
+    pub fn new() -> Self {
+        ResourceRegistry::default()
+    }
+
+    pub fn push(&mut self, resource: T) {
+        self.resource_index
+            .entry(resource.name().to_string())
+            .or_insert(self.resource_list.len());
+        self.resource_list.push(resource);
+    }
+
+    pub fn find(&self, resource_name: &str) -> Option<ResourceId<T>> {
+        self.resource_index
+            .get(resource_name)
+            .map(|index| ResourceId::new(*index))
+    }
+
+    // 按列表中的位置取得句柄，用于遍历资源表时修改资源
+    pub fn id_at(&self, index: usize) -> Option<ResourceId<T>> {
+        (index < self.resource_list.len()).then(|| ResourceId::new(index))
+    }
+
+    // 资源的名称需通过rename修改
+    pub fn get_mut(&mut self, id: ResourceId<T>) -> &mut T {
+        &mut self.resource_list[id.index]
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.resource_list.iter_mut()
+    }
+
+    pub fn rename(&mut self, id: ResourceId<T>, resource_name: &str) {
+        self.resource_list[id.index].set_name(resource_name.to_string());
+        self.rebuild_index();
+    }
+
+    pub fn contains(&self, resource_name: &str) -> bool {
+        self.resource_index.contains_key(resource_name)
+    }
+
+    pub fn remove(&mut self, id: ResourceId<T>) -> T {
+        let resource = self.resource_list.remove(id.index);
+        self.rebuild_index();
+        resource
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.resource_list.retain(f);
+        self.rebuild_index();
+    }
+
+    fn rebuild_index(&mut self) {
+        self.resource_index.clear();
+        for (i, resource) in self.resource_list.iter().enumerate() {
+            self.resource_index
+                .entry(resource.name().to_string())
+                .or_insert(i);
+        }
+    }
+}
+
+/// 使App可以按资源类型取得对应的资源表
+pub trait ResourceRegistryAccess<T> {
+    fn resource_registry(&self) -> &ResourceRegistry<T>;
+}
+
+impl ResourceRegistryAccess<PageData> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<PageData> {
+        &self.resource_page
+    }
+}
+
+impl ResourceRegistryAccess<Image> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<Image> {
+        &self.resource_image
+    }
+}
+
+impl ResourceRegistryAccess<Text> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<Text> {
+        &self.resource_text
+    }
+}
+
+impl ResourceRegistryAccess<CustomRect> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<CustomRect> {
+        &self.resource_rect
+    }
+}
+
+impl ResourceRegistryAccess<ScrollBackground> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<ScrollBackground> {
+        &self.resource_scroll_background
+    }
+}
+
+impl ResourceRegistryAccess<Variable> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<Variable> {
+        &self.variables
+    }
+}
+
+impl ResourceRegistryAccess<ImageTexture> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<ImageTexture> {
+        &self.resource_image_texture
+    }
+}
+
+impl ResourceRegistryAccess<Switch> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<Switch> {
+        &self.resource_switch
+    }
+}
+
+impl ResourceRegistryAccess<MessageBox> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<MessageBox> {
+        &self.resource_message_box
+    }
+}
+
+impl ResourceRegistryAccess<SplitTime> for App {
+    fn resource_registry(&self) -> &ResourceRegistry<SplitTime> {
+        &self.timer.split_time
+    }
+}
+
+pub trait RustConstructorResource {
+    fn name(&self) -> &str;
+
+    fn set_name(&mut self, name: String);
+
+    fn expose_type(&self) -> &str;
+
+    fn reg_render_resource(&self, render_list: &mut Vec<RenderResource>);
+}
+
+impl RustConstructorResource for PageData {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    fn expose_type(&self) -> &str {
+        &self.discern_type
+    }
+
+    fn reg_render_resource(&self, render_list: &mut Vec<RenderResource>) {
+        render_list.push(RenderResource {
+            discern_type: self.expose_type().to_string(),
+            name: self.name.to_string(),
+        });
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PageData {
+    pub discern_type: String,
     pub name: String,
     pub forced_update: bool,
     pub change_page_updated: bool,
@@ -962,7 +2574,7 @@ pub struct Timer {
     pub total_time: f32,
     pub timer: Instant,
     pub now_time: f32,
-    pub split_time: Vec<SplitTime>,
+    pub split_time: ResourceRegistry<SplitTime>,
 }
 
 impl RustConstructorResource for ImageTexture {
@@ -970,6 +2582,10 @@ impl RustConstructorResource for ImageTexture {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -995,6 +2611,10 @@ impl RustConstructorResource for CustomRect {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1028,6 +2648,10 @@ impl RustConstructorResource for Image {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1062,6 +2686,10 @@ impl RustConstructorResource for Text {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1097,6 +2725,10 @@ impl RustConstructorResource for ScrollBackground {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1126,6 +2758,10 @@ impl RustConstructorResource for Variable {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1150,6 +2786,10 @@ impl RustConstructorResource for SplitTime {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1174,6 +2814,10 @@ impl RustConstructorResource for Switch {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1240,6 +2884,10 @@ impl RustConstructorResource for MessageBox {
         &self.name
     }
 
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     fn expose_type(&self) -> &str {
         &self.discern_type
     }
@@ -1260,29 +2908,57 @@ pub struct App {
     pub render_resource_list: Vec<RenderResource>,
     pub problem_list: Vec<Problem>,
     pub storage_gun_content: Vec<Gun>,
+    pub armoury_gun_content: Vec<Gun>,
+    // 无尽模式的随机种子，为None时进入普通关卡
+    pub endless_seed: Option<u32>,
+    pub hot_reload: HotReload,
+    pub input_bindings: InputBindings,
+    // 设置页中正在等待新按键的操作
+    pub input_rebinding: Option<InputAction>,
+    pub gamepad: Gamepad,
     pub login_user_config: User,
     pub frame: Frame,
     pub vertrefresh: f32,
     pub page: String,
-    pub resource_page: Vec<PageData>,
-    pub resource_image: Vec<Image>,
-    pub resource_text: Vec<Text>,
-    pub resource_rect: Vec<CustomRect>,
-    pub resource_scroll_background: Vec<ScrollBackground>,
+    pub resource_page: ResourceRegistry<PageData>,
+    pub resource_image: ResourceRegistry<Image>,
+    pub resource_text: ResourceRegistry<Text>,
+    pub resource_rect: ResourceRegistry<CustomRect>,
+    pub resource_scroll_background: ResourceRegistry<ScrollBackground>,
     pub timer: Timer,
-    pub variables: Vec<Variable>,
-    pub resource_image_texture: Vec<ImageTexture>,
-    pub resource_switch: Vec<Switch>,
+    pub variables: ResourceRegistry<Variable>,
+    pub resource_image_texture: ResourceRegistry<ImageTexture>,
+    pub resource_switch: ResourceRegistry<Switch>,
     pub frame_times: Vec<f32>,
     pub last_frame_time: Option<f64>,
-    pub enemy_list: Vec<Enemy>,
+    pub operation_simulation: Simulation,
+    // 尚未被模拟处理的输入，帧率高于模拟步长时留到下一步
+    pub operation_input: SimulationInput,
     pub pause_list: Vec<PauseMessage>,
-    pub resource_message_box: Vec<MessageBox>,
+    pub resource_message_box: ResourceRegistry<MessageBox>,
     pub operation_preload_message_box: Vec<OperationMessageBox>,
+    pub storage_instrument_content: Vec<Instrument>,
+    pub editor_operation: Option<Operation>,
+    // 编辑器中敌人行进路线的预览，以[敌人识别名, 路径]为键，载入关卡时清空
+    pub editor_path_preview: HashMap<(String, Vec<String>), Vec<[f32; 2]>>,
+    pub replay_record: Option<Replay>,
+    pub replay_playback: Option<Replay>,
+    pub replay_playback_index: usize,
+    pub audio: Arc<Mutex<Audio>>,
+    // 严格模式下导致游戏停在错误页面的读取错误
+    pub fatal_load_error: Option<LoadError>,
+    // 与本机用户重名、等待选择处理方式的导入存档
+    pub profile_import: Option<ProfileArchive>,
+    pub logger: Logger,
+    // Preferences.json中的设置，命令行参数覆盖的设置不会写回
+    pub preferences: Config,
+    pub cli_options: CliOptions,
+    // 命令行要求启动后直接进入的页面，到达后清空
+    pub launch_page: Option<String>,
 }
 
 impl App {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, cli_options: CliOptions) -> Self {
         load_fonts(&cc.egui_ctx);
         let mut config = Config {
             launch_path: "".to_string(),
@@ -1291,26 +2967,39 @@ impl App {
             amount_languages: 0,
             rc_strict_mode: false,
             enable_debug_mode: false,
+            page_music: HashMap::new(),
         };
         let mut game_text = GameText {
             game_text: HashMap::new(),
         };
+        // 启动时的读取错误在App建立后统一报告
+        let mut load_error = Vec::new();
         if let Ok(json_value) = read_from_json("Resources/config/Preferences.json") {
-            if let Some(read_config) = Config::from_json_value(&json_value) {
-                config = read_config;
-            }
+            match Config::from_json_value(&json_value) {
+                Ok(read_config) => config = read_config,
+                Err(e) => load_error.push(e.in_file("Resources/config/Preferences.json")),
+            };
         }
+        let preferences = config.clone();
+        cli_options.apply(&mut config);
         if let Ok(json_value) = read_from_json("Resources/config/GameText.json") {
-            if let Some(read_game_text) = GameText::from_json_value(&json_value) {
-                game_text = read_game_text;
-            }
+            match GameText::from_json_value(&json_value) {
+                Ok(read_game_text) => game_text = read_game_text,
+                Err(e) => load_error.push(e.in_file("Resources/config/GameText.json")),
+            };
         }
-        Self {
+        let mut app = Self {
             config,
             game_text,
             render_resource_list: Vec::new(),
             problem_list: Vec::new(),
             storage_gun_content: Vec::new(),
+            armoury_gun_content: Vec::new(),
+            endless_seed: None,
+            hot_reload: HotReload::default(),
+            input_bindings: InputBindings::default(),
+            input_rebinding: None,
+            gamepad: Gamepad::default(),
             login_user_config: User {
                 name: "".to_string(),
                 password: "".to_string(),
@@ -1322,13 +3011,17 @@ impl App {
                 map_status: vec![],
                 settings: hash_map::HashMap::new(),
                 current_level: "".to_string(),
+                currency: 0,
+                endless_status: vec![],
+                current_slot: DEFAULT_SAVE_SLOT.to_string(),
+                save_slot: vec![],
             },
             frame: Frame {
                 ..Default::default()
             },
             vertrefresh: 0.01,
             page: "Launch".to_string(),
-            resource_page: vec![
+            resource_page: ResourceRegistry::from(vec![
                 PageData {
                     discern_type: "PageData".to_string(),
                     name: "Launch".to_string(),
@@ -1353,6 +3046,12 @@ impl App {
                     forced_update: true,
                     change_page_updated: false,
                 },
+                PageData {
+                    discern_type: "PageData".to_string(),
+                    name: "Home_Armoury".to_string(),
+                    forced_update: true,
+                    change_page_updated: false,
+                },
                 PageData {
                     discern_type: "PageData".to_string(),
                     name: "Home_Select_Map".to_string(),
@@ -1389,34 +3088,216 @@ impl App {
                     forced_update: true,
                     change_page_updated: false,
                 },
-            ],
-            resource_image: Vec::new(),
-            resource_text: Vec::new(),
-            resource_rect: Vec::new(),
-            resource_scroll_background: Vec::new(),
+            ]),
+            resource_image: ResourceRegistry::new(),
+            resource_text: ResourceRegistry::new(),
+            resource_rect: ResourceRegistry::new(),
+            resource_scroll_background: ResourceRegistry::new(),
             timer: Timer {
                 start_time: 0.0,
                 total_time: 0.0,
                 timer: Instant::now(),
                 now_time: 0.0,
-                split_time: Vec::new(),
+                split_time: ResourceRegistry::new(),
             },
-            variables: Vec::new(),
-            resource_image_texture: Vec::new(),
-            resource_switch: Vec::new(),
+            variables: ResourceRegistry::new(),
+            resource_image_texture: ResourceRegistry::new(),
+            resource_switch: ResourceRegistry::new(),
             frame_times: Vec::new(),
             last_frame_time: None,
-            enemy_list: Vec::new(),
+            operation_simulation: Simulation::default(),
+            operation_input: SimulationInput::default(),
             pause_list: Vec::new(),
-            resource_message_box: Vec::new(),
+            resource_message_box: ResourceRegistry::new(),
             operation_preload_message_box: Vec::new(),
+            storage_instrument_content: Vec::new(),
+            editor_operation: None,
+            editor_path_preview: HashMap::new(),
+            replay_record: None,
+            replay_playback: None,
+            replay_playback_index: 0,
+            audio: Arc::new(Mutex::new(Audio::new())),
+            fatal_load_error: None,
+            profile_import: None,
+            logger: Logger::new(),
+            preferences,
+            launch_page: cli_options.launch_page(),
+            cli_options,
+        };
+        app.logger.install_panic_hook();
+        for load_error in load_error {
+            app.load_error_report(&load_error);
         }
+        app.check_cli_options();
+        app
+    }
+
+    // 命令行指定的用户或页面不存在时报告问题并忽略
+    pub fn check_cli_options(&mut self) {
+        let game_text = self.game_text.game_text.clone();
+        if let Some(user) = self.cli_options.user.clone() {
+            if !check_file_exists(format!("Resources/config/user_{}.json", user)) {
+                self.config.login_user_name = self.preferences.login_user_name.clone();
+                self.problem_report(
+                    &game_text["error_cli_user"][self.config.language as usize].clone(),
+                    SeverityLevel::SevereWarning,
+                    &user,
+                );
+            };
+        };
+        if let Some(page) = self.launch_page.clone() {
+            if self.resource_page.find(&page).is_none() {
+                self.launch_page = None;
+                self.problem_report(
+                    &game_text["error_cli_page"][self.config.language as usize].clone(),
+                    SeverityLevel::SevereWarning,
+                    &page,
+                );
+            } else {
+                self.logger.log("INFO", &format!("Launch page: {}", page));
+            };
+        };
+    }
+
+    // 读取记住登录的用户并进入主页
+    pub fn login_remembered_user(&mut self) {
+        if let Ok(json_value) = read_from_json(format!(
+            "Resources/config/user_{}.json",
+            self.config.login_user_name
+        )) {
+            if let Some(read_user) = self.report_load_result(
+                &format!("Resources/config/user_{}.json", self.config.login_user_name),
+                User::from_json_value(&json_value),
+            ) {
+                self.login_user_config = read_user;
+            };
+        };
+        self.config.language = self.login_user_config.language;
+        self.switch_page("Home_Page");
+    }
+
+    // 按启动页面、主页、选择关卡的顺序逐页前往命令行指定的页面，每个页面完成首帧初始化后再离开
+    pub fn launch_page_step(&mut self) {
+        let Some(launch_page) = self.launch_page.clone() else {
+            return;
+        };
+        if self.fatal_load_error.is_some() {
+            self.launch_page = None;
+            return;
+        };
+        if !self
+            .resource_page
+            .find(&self.page)
+            .is_some_and(|id| self.resource_page[id].change_page_updated)
+        {
+            return;
+        };
+        match &*self.page.clone() {
+            "Launch" if launch_page == "Launch" => self.launch_page = None,
+            "Launch" => {
+                if self.config.login_user_name.is_empty() || launch_page == "Login" {
+                    self.switch_page("Login");
+                } else {
+                    self.login_remembered_user();
+                };
+            }
+            "Login" if launch_page == "Login" => self.launch_page = None,
+            // 等待用户登录后继续
+            "Login" => {}
+            "Home_Page" => {
+                if let Some(level) = self.cli_options.level.clone() {
+                    self.login_user_config.current_level = level;
+                };
+                if launch_page == "Operation" {
+                    self.switch_page("Select_Level");
+                } else {
+                    if launch_page != "Home_Page" {
+                        self.switch_page(&launch_page);
+                    };
+                    self.launch_page = None;
+                };
+            }
+            "Select_Level" if launch_page == "Operation" => {
+                self.switch_page("Operation");
+                self.modify_var("prepared_operation", false);
+                self.launch_page = None;
+            }
+            _ => self.launch_page = None,
+        };
+    }
+
+    // 用户登录或退出登录，此后写回Preferences.json时以此为准
+    pub fn set_login_user(&mut self, name: &str) {
+        self.config.login_user_name = name.to_string();
+        self.preferences.login_user_name = name.to_string();
+    }
+
+    // 命令行参数覆盖的设置不会写回Preferences.json
+    pub fn save_preferences(&self) {
+        let mut config = self.config.clone();
+        config.login_user_name = self.preferences.login_user_name.clone();
+        config.rc_strict_mode = self.preferences.rc_strict_mode;
+        config.enable_debug_mode = self.preferences.enable_debug_mode;
+        write_to_json("Resources/config/Preferences.json", config.to_json_value()).unwrap();
     }
 
     pub fn switch_page(&mut self, page: &str) {
+        self.logger
+            .log("PAGE", &format!("{} -> {}", self.page, page));
+        self.logger.update_context(
+            page,
+            &self.login_user_config.name,
+            &self.login_user_config.current_level,
+        );
         self.page = page.to_string();
         self.timer.start_time = self.timer.total_time;
         self.update_timer();
+        self.update_page_music();
+    }
+
+    // 根据当前页面切换背景音乐，地图内的页面优先使用地图自带的音乐
+    pub fn update_page_music(&mut self) {
+        let mut music = self
+            .config
+            .page_music
+            .get(&self.page)
+            .cloned()
+            .unwrap_or_default();
+        if ["Select_Level", "Operation"].contains(&self.page.as_str()) {
+            if let Ok(json_value) = read_from_json(&self.login_user_config.current_map) {
+                if let Ok(map) = Map::from_json_value(&json_value) {
+                    if !map.map_music.is_empty() {
+                        music = map.map_music;
+                    };
+                };
+            };
+        };
+        if let Ok(mut audio) = self.audio.lock() {
+            audio.play_music(&music);
+        };
+    }
+
+    // 按当前的按键绑定查询操作，代替直接读取按键与鼠标
+    pub fn action_pressed(&self, ctx: &egui::Context, action: InputAction) -> bool {
+        ctx.input(|i| self.input_bindings.pressed(i, action))
+    }
+
+    pub fn action_down(&self, ctx: &egui::Context, action: InputAction) -> bool {
+        ctx.input(|i| self.input_bindings.down(i, action))
+    }
+
+    pub fn action_released(&self, ctx: &egui::Context, action: InputAction) -> bool {
+        ctx.input(|i| self.input_bindings.released(i, action))
+    }
+
+    pub fn play_sound(&self, path: &str, bus: AudioBus) {
+        if let Ok(mut audio) = self.audio.lock() {
+            audio.play(path, bus);
+        };
+    }
+
+    pub fn general_click_feedback(&self) {
+        self.play_sound("Resources/assets/sounds/Click.wav", AudioBus::Ui);
     }
 
     pub fn launch_page_preload(&mut self, ctx: &egui::Context) {
@@ -1528,9 +3409,7 @@ impl App {
             [0, 0, 0, 255, 255, 255, 255, 255],
             0.0,
         );
-        std::thread::spawn(|| {
-            kira_play_wav("Resources/assets/sounds/Launch.wav").unwrap();
-        });
+        self.play_sound("Resources/assets/sounds/Launch.wav", AudioBus::Ui);
         for i in 0..self.config.amount_languages {
             self.add_image_texture(
                 &format!("{}_Title", i),
@@ -1548,8 +3427,8 @@ impl App {
                 &format!("{}_Title", i),
             );
         }
-        let id = self.track_resource(self.resource_image.clone(), "1_Title");
-        self.resource_image[id].image_size = [900_f32, 130_f32];
+        let id = self.track_resource::<Image>("1_Title");
+        self.resource_image.get_mut(id).image_size = [900_f32, 130_f32];
         self.add_image_texture(
             "Background",
             "Resources/assets/images/wallpaper.png",
@@ -1789,10 +3668,17 @@ impl App {
             true,
             ctx,
         );
+        self.add_image_texture(
+            "Armoury",
+            "Resources/assets/images/logo_gun.png",
+            [false, false],
+            true,
+            ctx,
+        );
         self.add_image(
             "Home_Home",
             [0_f32, -20_f32, 50_f32, 50_f32],
-            [2, 5, 1, 1],
+            [2, 6, 1, 1],
             [true, false, true, false, false],
             [255, 0, 0, 0, 0],
             "Home",
@@ -1800,7 +3686,7 @@ impl App {
         self.add_image(
             "Home_Settings",
             [0_f32, -20_f32, 50_f32, 50_f32],
-            [4, 5, 1, 1],
+            [5, 6, 1, 1],
             [true, false, true, false, false],
             [255, 0, 0, 0, 0],
             "Settings",
@@ -1808,15 +3694,23 @@ impl App {
         self.add_image(
             "Home_Journey",
             [0_f32, -20_f32, 50_f32, 50_f32],
-            [3, 5, 1, 1],
+            [3, 6, 1, 1],
             [true, false, true, false, false],
             [255, 0, 0, 0, 0],
             "Power",
         );
+        self.add_image(
+            "Home_Armoury",
+            [0_f32, -20_f32, 50_f32, 50_f32],
+            [4, 6, 1, 1],
+            [true, false, true, false, false],
+            [255, 0, 0, 0, 0],
+            "Armoury",
+        );
         self.add_image(
             "Home_Power",
             [0_f32, -20_f32, 50_f32, 50_f32],
-            [1, 5, 1, 1],
+            [1, 6, 1, 1],
             [true, false, true, false, false],
             [255, 0, 0, 0, 0],
             "Power",
@@ -1867,6 +3761,29 @@ impl App {
                 action: true,
             }],
         );
+        self.add_switch(
+            ["Home_Armoury", "Home_Armoury"],
+            vec![
+                SwitchData {
+                    texture: "Armoury".to_string(),
+                    color: [255, 255, 255, 255],
+                },
+                SwitchData {
+                    texture: "Armoury".to_string(),
+                    color: [180, 180, 180, 255],
+                },
+                SwitchData {
+                    texture: "Armoury".to_string(),
+                    color: [150, 150, 150, 255],
+                },
+            ],
+            [true, true, true],
+            1,
+            vec![SwitchClickAction {
+                click_method: PointerButton::Primary,
+                action: true,
+            }],
+        );
         self.add_switch(
             ["Home_Settings", "Home_Settings"],
             vec![
@@ -2515,44 +4432,113 @@ impl App {
                 action: false,
             }],
         );
-        self.add_rect(
-            "Editor_Left_Sidebar",
-            [0_f32, 0_f32, 300_f32, ctx.available_rect().height(), 0_f32],
-            [0, 1, 1, 2],
-            [true, false, false, true],
-            [100, 100, 100, 255, 0, 0, 0, 255],
-            0.5,
-        );
-        self.add_rect(
-            "Editor_Right_Sidebar",
-            [0_f32, 0_f32, 300_f32, ctx.available_rect().height(), 0_f32],
-            [1, 1, 1, 2],
-            [false, false, false, true],
-            [100, 100, 100, 255, 0, 0, 0, 255],
-            0.5,
-        );
-        self.add_image_texture(
-            "Editor_Background",
-            "Resources/assets/images/editor_background.png",
-            [false, false],
-            true,
-            ctx,
-        );
         self.add_image(
-            "Editor_Background",
-            [0_f32, 0_f32, 300_f32, 300_f32],
-            [1, 2, 1, 2],
-            [false, false, true, true, false],
+            "Replay",
+            [180_f32, 10_f32, 50_f32, 50_f32],
+            [0, 1, 0, 1],
+            [true, true, false, false, false],
             [255, 0, 0, 0, 0],
-            "Editor_Background",
+            "Refresh",
         );
-        self.add_rect(
-            "Editor_Background",
-            [
-                0_f32,
-                0_f32,
-                ctx.available_rect().width(),
-                ctx.available_rect().height(),
+        self.add_switch(
+            ["Replay", "Replay"],
+            vec![
+                SwitchData {
+                    texture: "Refresh".to_string(),
+                    color: [255, 255, 255, 255],
+                },
+                SwitchData {
+                    texture: "Refresh".to_string(),
+                    color: [180, 180, 180, 255],
+                },
+                SwitchData {
+                    texture: "Refresh".to_string(),
+                    color: [150, 150, 150, 255],
+                },
+            ],
+            [true, true, true],
+            1,
+            vec![SwitchClickAction {
+                click_method: PointerButton::Primary,
+                action: false,
+            }],
+        );
+        self.add_image_texture(
+            "Endless",
+            "Resources/assets/images/target_enemy.png",
+            [false, false],
+            true,
+            ctx,
+        );
+        self.add_image(
+            "Endless",
+            [240_f32, 10_f32, 50_f32, 50_f32],
+            [0, 1, 0, 1],
+            [true, true, false, false, false],
+            [255, 0, 0, 0, 0],
+            "Endless",
+        );
+        self.add_switch(
+            ["Endless", "Endless"],
+            vec![
+                SwitchData {
+                    texture: "Endless".to_string(),
+                    color: [255, 255, 255, 255],
+                },
+                SwitchData {
+                    texture: "Endless".to_string(),
+                    color: [180, 180, 180, 255],
+                },
+                SwitchData {
+                    texture: "Endless".to_string(),
+                    color: [150, 150, 150, 255],
+                },
+            ],
+            [true, true, true],
+            1,
+            vec![SwitchClickAction {
+                click_method: PointerButton::Primary,
+                action: false,
+            }],
+        );
+        self.add_rect(
+            "Editor_Left_Sidebar",
+            [0_f32, 0_f32, 300_f32, ctx.available_rect().height(), 0_f32],
+            [0, 1, 1, 2],
+            [true, false, false, true],
+            [100, 100, 100, 255, 0, 0, 0, 255],
+            0.5,
+        );
+        self.add_rect(
+            "Editor_Right_Sidebar",
+            [0_f32, 0_f32, 300_f32, ctx.available_rect().height(), 0_f32],
+            [1, 1, 1, 2],
+            [false, false, false, true],
+            [100, 100, 100, 255, 0, 0, 0, 255],
+            0.5,
+        );
+        self.add_image_texture(
+            "Editor_Background",
+            "Resources/assets/images/editor_background.png",
+            [false, false],
+            true,
+            ctx,
+        );
+        self.add_image(
+            "Editor_Background",
+            [0_f32, 0_f32, 300_f32, 300_f32],
+            [1, 2, 1, 2],
+            [false, false, true, true, false],
+            [255, 0, 0, 0, 0],
+            "Editor_Background",
+        );
+        self.add_rect(
+            "Editor_Background",
+            [
+                0_f32,
+                0_f32,
+                ctx.available_rect().width(),
+                ctx.available_rect().height(),
                 0_f32,
             ],
             [1, 2, 1, 2],
@@ -2599,6 +4585,14 @@ impl App {
             false,
             [0, 1, 0, 1],
         );
+        self.add_text(
+            ["Instrument_Text", ""],
+            [0_f32, 0_f32, 20_f32, 400_f32, 0.0],
+            [255, 255, 255, 255, 0, 0, 0],
+            [true, true, true, true],
+            true,
+            [0, 0, 0, 0],
+        );
     }
 
     pub fn fade(
@@ -2610,20 +4604,20 @@ impl App {
         resource_name: &str,
         fade_speed: u8,
     ) -> u8 {
-        let cut_to_rect_id = self.track_resource(self.resource_rect.clone(), resource_name);
-        self.resource_rect[cut_to_rect_id].size =
+        let cut_to_rect_id = self.track_resource::<CustomRect>(resource_name);
+        self.resource_rect.get_mut(cut_to_rect_id).size =
             [ctx.available_rect().width(), ctx.available_rect().height()];
         if self.timer.now_time - self.split_time(split_time_name)[0] >= self.vertrefresh {
             self.add_split_time(split_time_name, true);
             if fade_in_or_out {
-                self.resource_rect[cut_to_rect_id].color[3] =
+                self.resource_rect.get_mut(cut_to_rect_id).color[3] =
                     if self.resource_rect[cut_to_rect_id].color[3] > 255 - fade_speed {
                         255
                     } else {
                         self.resource_rect[cut_to_rect_id].color[3] + fade_speed
                     };
             } else {
-                self.resource_rect[cut_to_rect_id].color[3] =
+                self.resource_rect.get_mut(cut_to_rect_id).color[3] =
                     self.resource_rect[cut_to_rect_id].color[3].saturating_sub(fade_speed)
             };
         };
@@ -2651,105 +4645,235 @@ impl App {
         time
     }
 
-    pub fn add_enemy(
-        &mut self,
-        enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval: [f32; 11],
-        enemy_image_count_minus_target_point_alpha_and_increase_alpha_speed: [u32; 4],
-        enemy_tag_and_move_path: [Vec<String>; 2],
-        enemy_name_image_and_type: [String; 3],
-        enemy_detected_and_activated: [bool; 2],
-        ctx: &egui::Context,
-    ) {
-        let mut move_path = Vec::new();
-        for i in 0..enemy_tag_and_move_path[1].len() {
-            if let Ok(json_value) = read_from_json(
-                format!(
-                    "Resources/config/path_{}.json",
-                    enemy_tag_and_move_path[1][i]
-                )
-                .to_lowercase(),
-            ) {
-                if let Some(read_path) = MovePath::from_json_value(&json_value) {
-                    move_path.push(read_path);
-                };
-            };
-        }
-        self.enemy_list.push(Enemy {
-            enemy_name: format!("Enemy_{}", enemy_name_image_and_type[0]),
-            enemy_hp: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[0],
-            enemy_def: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[1],
-            enemy_speed: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[2],
-            enemy_invincible_time:
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[3],
-            enemy_image_count: enemy_image_count_minus_target_point_alpha_and_increase_alpha_speed[0],
-            enemy_tag: enemy_tag_and_move_path[0].clone(),
-            enemy_image: enemy_name_image_and_type[1].clone(),
-            enemy_image_type: enemy_name_image_and_type[2].clone(),
-            enemy_minus_target_point: enemy_image_count_minus_target_point_alpha_and_increase_alpha_speed[1],
-            enemy_position: [
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[4],
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[5],
-            ],
-            enemy_move_path: move_path,
-            enemy_detected: enemy_detected_and_activated[0],
-            enemy_activated: enemy_detected_and_activated[1],
-            enemy_activated_time:
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[6],
-            enemy_size: [
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[7],
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[8],
-            ],
-            enemy_current_walk_status: 0,
-            enemy_start_walk_time: 0_f32,
-            enemy_increase_alpha_speed: enemy_image_count_minus_target_point_alpha_and_increase_alpha_speed[3] as u8,
-            enemy_animation_forward: true,
-            enemy_current_animation_count: 0,
-            enemy_walk_interval: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[9],
-            enemy_walk_time: 0_f32,
-            enemy_animation_interval: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[10],
-            enemy_animation_change_time: 0_f32,
-            enemy_out: false,
-            enemy_hit_time: 0_f32,
-            enemy_initial_hp: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[0],
-            enemy_memory_hp: enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[0]
-        });
-        for i in 0..enemy_image_count_minus_target_point_alpha_and_increase_alpha_speed[0] {
-            if !check_resource_exist(
-                self.resource_image_texture.clone(),
-                &format!("Enemy_{}_{}", enemy_name_image_and_type[0], i),
-            ) {
+    // 读取敌人当前贴图组中尚未读取的帧
+    pub fn add_enemy_texture(&mut self, enemy: &Enemy, ctx: &egui::Context) {
+        for i in 0..enemy.enemy_image_count {
+            if !check_resource_exist(&self.resource_image_texture, &enemy.texture_name(i)) {
                 self.add_image_texture(
-                    &format!("Enemy_{}_{}", enemy_name_image_and_type[0], i),
-                    &format!(
-                        "{}_{}{}",
-                        enemy_name_image_and_type[1], i, enemy_name_image_and_type[2]
-                    ),
+                    &enemy.texture_name(i),
+                    &format!("{}_{}{}", enemy.enemy_image, i, enemy.enemy_image_type),
                     [false, false],
                     true,
                     ctx,
                 );
             };
         }
+    }
+
+    pub fn add_enemy(&mut self, enemy: &Enemy, ctx: &egui::Context) {
+        self.add_enemy_texture(enemy, ctx);
         self.add_image(
-            &format!("Enemy_{}", enemy_name_image_and_type[0]),
+            &enemy.enemy_name,
             [
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[4],
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[5],
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[7],
-                enemy_hp_def_speed_invincible_time_position_activated_time_size_walk_interval_and_animation_interval[8],
+                enemy.enemy_position[0],
+                enemy.enemy_position[1],
+                enemy.enemy_size[0],
+                enemy.enemy_size[1],
             ],
             [0, 0, 0, 0],
             [false, false, true, false, true],
-            [enemy_image_count_minus_target_point_alpha_and_increase_alpha_speed[2] as u8, 0, 0, 0, 255],
-            &format!("Enemy_{}_0", enemy_name_image_and_type[0]),
+            [enemy.enemy_alpha, 0, 0, 0, 255],
+            &enemy.texture_name(0),
         );
     }
 
+    pub fn editor_load_level(&mut self, path: &str, ctx: &egui::Context) {
+        if let Some(read_operation) = self.load_config(path, Operation::from_json_value) {
+            self.add_image_texture(
+                "Editor_Operation",
+                &read_operation.global.operation_background,
+                [false, false],
+                false,
+                ctx,
+            );
+            if !check_resource_exist(&self.resource_image, "Editor_Operation") {
+                self.add_image(
+                    "Editor_Operation",
+                    [0_f32, 0_f32, 1280_f32, 720_f32],
+                    [0, 0, 0, 0],
+                    [true, true, false, false, false],
+                    [255, 0, 0, 0, 0],
+                    "Editor_Operation",
+                );
+            } else {
+                let id = self.track_resource::<Image>("Editor_Operation");
+                let id2 = self.track_resource::<ImageTexture>("Editor_Operation");
+                self.resource_image.get_mut(id).image_texture =
+                    self.resource_image_texture[id2].texture.clone();
+            };
+            let mut enemy_pool = Vec::new();
+            for enemy_file in
+                list_files_recursive(Path::new("Resources/config"), "enemy_").unwrap_or_default()
+            {
+                if let Some(read_enemy) = self.load_config(
+                    &enemy_file.to_string_lossy(),
+                    JsonReadEnemy::from_json_value,
+                ) {
+                    self.add_image_texture(
+                        &format!("Editor_Enemy_{}", read_enemy.enemy_recognition_name),
+                        &format!(
+                            "{}_0{}",
+                            read_enemy.enemy_image, read_enemy.enemy_image_type
+                        ),
+                        [false, false],
+                        false,
+                        ctx,
+                    );
+                    enemy_pool.push(read_enemy.enemy_recognition_name);
+                };
+            }
+            let mut path_pool = Vec::new();
+            for path_file in
+                list_files_recursive(Path::new("Resources/config"), "path_").unwrap_or_default()
+            {
+                if let Some(file_stem) = path_file.file_stem() {
+                    path_pool.push(file_stem.to_string_lossy().replacen("path_", "", 1));
+                };
+            }
+            self.modify_var("editor_enemy_pool", enemy_pool);
+            self.modify_var("editor_path_pool", path_pool);
+            self.modify_var("editor_level_path", path.to_string());
+            self.modify_var("editor_selected_type", "Global".to_string());
+            self.modify_var("editor_selected_index", -1);
+            self.modify_var("editor_dragging", false);
+            self.modify_var("editor_state", "Editing".to_string());
+            self.editor_path_preview.clear();
+            self.editor_operation = Some(read_operation);
+        } else {
+            self.problem_report(
+                &format!(
+                    "{}{}",
+                    self.game_text.game_text["error_editor_load_level"]
+                        [self.config.language as usize]
+                        .clone(),
+                    path
+                ),
+                SeverityLevel::MildWarning,
+                &self.game_text.game_text["error_editor_load_level_annotation"]
+                    [self.config.language as usize]
+                    .clone(),
+            );
+        };
+    }
+
+    // 返回用户对该枪械的等级，-1为未解锁；没有记录时按枪械的初始解锁状态补上
+    pub fn user_gun_level(&mut self, gun: &Gun) -> i32 {
+        if let Some(status) = self
+            .login_user_config
+            .gun_status
+            .iter()
+            .find(|x| x.gun_recognition_name == gun.gun_recognition_name)
+        {
+            return status.gun_level;
+        };
+        let gun_level = if gun.gun_initial_unlock { 0 } else { -1 };
+        self.login_user_config.gun_status.push(UserGunStatus {
+            gun_recognition_name: gun.gun_recognition_name.clone(),
+            gun_level,
+        });
+        gun_level
+    }
+
+    // 花费货币解锁或升级枪械，成功时立即保存用户配置
+    pub fn purchase_gun_level(&mut self, gun: &Gun) -> bool {
+        let gun_level = self.user_gun_level(gun);
+        let cost = if gun_level == -1 {
+            gun.gun_unlock_cost
+        } else if let Some(cost) = gun.upgrade_cost(gun_level) {
+            cost
+        } else {
+            return false;
+        };
+        if self.login_user_config.currency < cost {
+            return false;
+        };
+        let set_gun_level = |user: &mut User, level: i32| {
+            for status in user.gun_status.iter_mut() {
+                if status.gun_recognition_name == gun.gun_recognition_name {
+                    status.gun_level = level;
+                };
+            }
+        };
+        self.login_user_config.currency -= cost;
+        set_gun_level(&mut self.login_user_config, gun_level + 1);
+        let user_file = format!("Resources/config/user_{}.json", self.config.login_user_name);
+        if let Err(e) = write_to_json(&user_file, self.login_user_config.to_json_value()) {
+            // 保存失败时撤销本次购买，避免进度与文件不一致
+            self.login_user_config.currency += cost;
+            set_gun_level(&mut self.login_user_config, gun_level);
+            self.problem_report(
+                &format!(
+                    "{}{}",
+                    self.game_text.game_text["error_save_user"][self.config.language as usize]
+                        .clone(),
+                    user_file
+                ),
+                SeverityLevel::MildWarning,
+                &format!("{:#}", e),
+            );
+            return false;
+        };
+        true
+    }
+
+    pub fn scale_mode(&self) -> ScaleMode {
+        ScaleMode::from_setting(
+            self.login_user_config
+                .settings
+                .get("scale_mode")
+                .map(String::as_str)
+                .unwrap_or_default(),
+        )
+    }
+
+    // 汇总本次作战的评分数据
+    pub fn operation_score(&mut self) -> OperationScore {
+        OperationScore {
+            kills: self.operation_simulation.enemy_killed,
+            shots_fired: self.operation_simulation.shots_fired,
+            shots_hit: self.operation_simulation.shots_hit,
+            target_point: self.operation_simulation.target_point,
+            storage_bullet: self.var_u("storage_bullet"),
+            runtime: self.operation_simulation.runtime,
+            overheats: self.var_u("overheat_count"),
+        }
+    }
+
+    // 当前关卡在用户关卡记录中的位置
+    pub fn current_level_status_index(&self) -> Option<usize> {
+        let current_level = &self.login_user_config.current_level;
+        let level_name = current_level[current_level.rfind("_")? + 1..].strip_suffix(".json")?;
+        self.login_user_config
+            .level_status
+            .iter()
+            .position(|x| x.level_name == level_name)
+    }
+
+    // 作战区域到屏幕的映射，敌人、装置、目标线与命中判定共用
+    pub fn operation_viewport(&self, ctx: &egui::Context) -> Viewport {
+        Viewport::new(ctx.available_rect(), self.scale_mode())
+    }
+
+    /// 返回编辑器画布在窗口中的原点与缩放比例：[x, y, scale]。
+    pub fn editor_canvas(&self, ctx: &egui::Context) -> [f32; 3] {
+        let scale = ((ctx.available_rect().width() - 640_f32) / 1280_f32)
+            .min((ctx.available_rect().height() - 40_f32) / 720_f32)
+            .max(0.1);
+        [
+            300_f32 + (ctx.available_rect().width() - 600_f32 - 1280_f32 * scale) / 2_f32,
+            (ctx.available_rect().height() - 720_f32 * scale) / 2_f32,
+            scale,
+        ]
+    }
+
     pub fn operation_message_box_display(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
         for i in 0..self.operation_preload_message_box.len() {
             if self.var_f("operation_runtime")
                 >= self.operation_preload_message_box[i].box_appear_time
                 && self.operation_preload_message_box[i].box_enable
+                && self.operation_preload_message_box[i]
+                    .box_trigger_phase
+                    .is_empty()
             {
                 self.operation_preload_message_box[i].box_enable = false;
                 self.add_image_texture(
@@ -2809,442 +4933,942 @@ impl App {
                     false,
                     [0, 0, 0, 0],
                 );
-                self.add_message_box(
-                    [
-                        &format!("Json_MessageBox{}", i),
-                        &format!("Json_MessageBox{}_Title", i),
-                        &format!("Json_MessageBox{}_Content", i),
-                        &format!("Json_MessageBox{}", i),
+                self.add_message_box(
+                    [
+                        &format!("Json_MessageBox{}", i),
+                        &format!("Json_MessageBox{}_Title", i),
+                        &format!("Json_MessageBox{}_Content", i),
+                        &format!("Json_MessageBox{}", i),
+                    ],
+                    self.operation_preload_message_box[i].box_size,
+                    self.operation_preload_message_box[i].box_existing_time == 0_f32,
+                    self.operation_preload_message_box[i].box_existing_time,
+                    [30_f32, 10_f32],
+                );
+            };
+        }
+        self.message_box_display(ctx, ui);
+    }
+
+    // 射出一发子弹：播放音效并累计后坐力、温度与弹药消耗
+    pub fn gun_fire_round(&mut self, id_id: usize) {
+        let sound = self.storage_gun_content[id_id].gun_shoot_sound.clone();
+        self.add_split_time("gun_shooting_time", true);
+        self.play_sound(&sound, AudioBus::Sfx);
+        self.operation_simulation.fire_round();
+        let recoil = self.var_f(&format!("gun{}_recoil", id_id));
+        self.modify_var(
+            &format!("gun{}_recoil", id_id),
+            Value::Float(recoil + self.storage_gun_content[id_id].gun_recoil),
+        );
+        for _ in 0..self.storage_gun_content[id_id].gun_temperature_degree {
+            if self.var_u(&format!("gun{}_temperature", id_id)) < 255 {
+                let temperature = self.var_u(&format!("gun{}_temperature", id_id));
+                self.modify_var(
+                    &format!("gun{}_temperature", id_id),
+                    Value::UInt(temperature + 1),
+                );
+            } else {
+                break;
+            };
+        }
+        let surplus_bullets = self.var_u(&format!("gun{}_surplus_bullets", id_id));
+        self.modify_var(
+            &format!("gun{}_surplus_bullets", id_id),
+            surplus_bullets - 1,
+        );
+        if self.var_u(&format!("gun{}_surplus_bullets", id_id)) == 0 {
+            self.modify_var(&format!("gun{}_reload", id_id), true);
+        };
+        if self.var_u(&format!("gun{}_temperature", id_id)) == 255 {
+            let gun_overheating_sound = self.storage_gun_content[id_id]
+                .gun_overheating_sound
+                .clone();
+            self.play_sound(&gun_overheating_sound, AudioBus::Sfx);
+            self.modify_var("forced_cooling", true);
+            let overheat_count = self.var_u("overheat_count");
+            self.modify_var("overheat_count", Value::UInt(overheat_count + 1));
+        };
+    }
+
+    // 将界面上的操作整理为模拟输入并推进作战，随后同步变量与音效
+    pub fn operation_step(&mut self, ctx: &egui::Context) {
+        let mut input = std::mem::take(&mut self.operation_input);
+        if !self.storage_gun_content.is_empty() {
+            let gun_selected = self.var_u("gun_selected") as usize;
+            let gun_name = format!(
+                "Gun_{}",
+                self.storage_gun_content[gun_selected].gun_recognition_name
+            );
+            let gun_image = self.track_resource::<Image>(&gun_name);
+            let gun_switch = self.track_resource::<Switch>(&gun_name);
+            if self.resource_switch[gun_switch].state == 1 {
+                // 枪械图片以中心定位，换算为作战区域坐标
+                let gun = self.storage_gun_content[gun_selected].clone();
+                let viewport = self.operation_viewport(ctx);
+                let center = viewport.to_level(Pos2 {
+                    x: self.resource_image[gun_image].origin_position[0],
+                    y: self.resource_image[gun_image].origin_position[1],
+                });
+                let mut size = [
+                    self.resource_image[gun_image].image_size[0] / viewport.scale,
+                    self.resource_image[gun_image].image_size[1] / viewport.scale,
+                ];
+                // 散射时弹丸均匀分布在准星周围的圆上
+                let pellet = if let Some((count, radius)) = gun.firing_spread() {
+                    size = [size[0] / 2_f32, size[1] / 2_f32];
+                    (0..count)
+                        .map(|x| {
+                            let angle = std::f32::consts::TAU * x as f32 / count as f32;
+                            [
+                                center[0] + radius * angle.cos(),
+                                center[1] + radius * angle.sin(),
+                            ]
+                        })
+                        .collect::<Vec<[f32; 2]>>()
+                } else {
+                    vec![center]
+                };
+                for position in pellet {
+                    input.shots.push(SimulationShot {
+                        shot_rect: [
+                            position[0] - size[0] / 2_f32,
+                            position[1] - size[1] / 2_f32,
+                            size[0],
+                            size[1],
+                        ],
+                        shot_damage: gun.gun_basic_damage * self.var_f("gun_damage_multiplier"),
+                        shot_damage_type: gun.firing_damage_type(),
+                        shot_status: gun.firing_status(),
+                        shot_tag: gun.gun_tag.clone(),
+                        shot_pierce: gun.firing_piercing(),
+                        shot_splash: gun.firing_splash(),
+                    });
+                }
+            };
+        };
+        // 以固定步长追赶作战时间，不足一步的剩余时间留到下一帧
+        let runtime = self.operation_simulation.runtime;
+        let duration = self.var_f("operation_runtime") - runtime;
+        let events = self
+            .operation_simulation
+            .run_for(duration, SIMULATION_DT, &input);
+        if self.operation_simulation.runtime == runtime {
+            // 射击判定每帧都会重新生成，只保留其余输入
+            input.shots.clear();
+            self.operation_input = input;
+        };
+        self.operation_feedback(ctx, &events);
+    }
+
+    // 将手柄输入转换为指针、按键与滚轮事件；在回放录制之前注入，录制与回放无需区分输入设备
+    pub fn gamepad_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        self.gamepad.poll();
+        if !self.gamepad.connected() {
+            self.gamepad.cursor = None;
+            return;
+        };
+        let screen = raw_input.screen_rect.unwrap_or(ctx.screen_rect());
+        let dt = raw_input.predicted_dt;
+        // 鼠标移动时准星跟随鼠标
+        let mut last_position = self.gamepad.cursor;
+        for event in &raw_input.events {
+            if let egui::Event::PointerMoved(position) = event {
+                last_position = Some(*position);
+            };
+        }
+        let mut cursor = last_position
+            .or(ctx.input(|i| i.pointer.latest_pos()))
+            .unwrap_or(screen.center());
+        let mut velocity = self.gamepad.cursor_velocity(dt);
+        if velocity != [0_f32, 0_f32]
+            && self.page == "Operation"
+            && check_resource_exist(&self.variables, "in_operation")
+            && self.var_b("in_operation")
+            && self
+                .login_user_config
+                .settings
+                .get("gamepad_aim_assist")
+                .is_some_and(|x| x == "true")
+        {
+            let viewport = Viewport::new(screen, self.scale_mode());
+            if let Some(target) = self.aim_assist_target(viewport, cursor) {
+                velocity = [
+                    velocity[0] * AIM_ASSIST_FRICTION,
+                    velocity[1] * AIM_ASSIST_FRICTION,
+                ];
+                cursor += (target - cursor) * (AIM_ASSIST_PULL * dt).min(1_f32);
+            };
+        };
+        cursor += Vec2::new(velocity[0], velocity[1]) * dt;
+        // 方向键将准星移动到该方向上最近的开关，作为菜单的焦点
+        for (button, direction) in [
+            (GamepadButton::DpadUp, Vec2::new(0_f32, -1_f32)),
+            (GamepadButton::DpadDown, Vec2::new(0_f32, 1_f32)),
+            (GamepadButton::DpadLeft, Vec2::new(-1_f32, 0_f32)),
+            (GamepadButton::DpadRight, Vec2::new(1_f32, 0_f32)),
+        ] {
+            if self.gamepad.pressed(button) {
+                if let Some(target) = self.focus_target(cursor, direction) {
+                    cursor = target;
+                };
+            };
+        }
+        cursor = screen.clamp(cursor);
+        if last_position != Some(cursor) {
+            raw_input.events.push(egui::Event::PointerMoved(cursor));
+        };
+        self.gamepad.cursor = Some(cursor);
+        let mut gamepad_binding = vec![(
+            GamepadButton::South,
+            InputBinding::Mouse(PointerButton::Primary),
+        )];
+        for (button, action) in GAMEPAD_ACTION {
+            if let Some(binding) = self.input_bindings.get(action).first() {
+                gamepad_binding.push((button, *binding));
+            };
+        }
+        for (button, binding) in gamepad_binding {
+            let pressed = self.gamepad.pressed(button);
+            if pressed || self.gamepad.released(button) {
+                if let Some(event) = binding.event(pressed, cursor) {
+                    raw_input.events.push(event);
+                };
+            };
+        }
+    }
+
+    // 辅助瞄准：准星附近最近的已出场敌人的屏幕中心
+    pub fn aim_assist_target(&self, viewport: Viewport, cursor: Pos2) -> Option<Pos2> {
+        self.operation_simulation
+            .enemy_list
+            .iter()
+            .filter(|x| x.enemy_activated && x.enemy_hp > 0_f32)
+            .filter_map(|x| {
+                let center = viewport.to_screen(x.enemy_position);
+                let size = viewport.to_screen_size(x.enemy_size);
+                let distance = center.distance(cursor);
+                if distance < size[0].max(size[1]) / 2_f32 * AIM_ASSIST_RANGE {
+                    Some((center, distance))
+                } else {
+                    None
+                }
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|x| x.0)
+    }
+
+    // 上一帧绘制的开关中，位于direction方向上且最接近的一个的中心；偏离方向的开关距离加倍计算
+    pub fn focus_target(&self, cursor: Pos2, direction: Vec2) -> Option<Pos2> {
+        self.render_resource_list
+            .iter()
+            .filter(|x| x.discern_type == "Switch")
+            .filter_map(|x| {
+                let switch = &self.resource_switch[self.resource_switch.find(&x.name)?];
+                let image =
+                    &self.resource_image[self.resource_image.find(&switch.switch_image_name)?];
+                let rect = Rect::from_min_size(
+                    Pos2::new(image.image_position[0], image.image_position[1]),
+                    Vec2::new(image.image_size[0], image.image_size[1]),
+                );
+                if rect.contains(cursor) {
+                    return None;
+                };
+                let offset = rect.center() - cursor;
+                let along = offset.dot(direction);
+                if along <= 0_f32 {
+                    return None;
+                };
+                let across = offset.dot(direction.rot90()).abs();
+                Some((rect.center(), along + across * 2_f32))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|x| x.0)
+    }
+
+    // 在egui处理输入前录制或回放作战输入，回放时以录制内容替换实时输入
+    pub fn operation_replay_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        if self.page != "Operation"
+            || !check_resource_exist(&self.variables, "in_operation")
+            || !self.var_b("in_operation")
+        {
+            return;
+        };
+        let viewport = Viewport::new(
+            raw_input.screen_rect.unwrap_or(ctx.screen_rect()),
+            self.scale_mode(),
+        );
+        let runtime = self.var_f("operation_runtime");
+        if let Some(replay) = &mut self.replay_playback {
+            raw_input.events.retain(|event| {
+                !matches!(
+                    event,
+                    egui::Event::PointerMoved(_)
+                        | egui::Event::MouseMoved(_)
+                        | egui::Event::PointerButton { .. }
+                        | egui::Event::PointerGone
+                        | egui::Event::MouseWheel { .. }
+                        | egui::Event::Key { .. }
+                        | egui::Event::Text(_)
+                        | egui::Event::Zoom(_)
+                )
+            });
+            // 按键与点击各占一帧，避免按下与松开在同一帧内被合并
+            while self.replay_playback_index < replay.frames.len()
+                && replay.frames[self.replay_playback_index].runtime <= runtime
+            {
+                let mut discrete = false;
+                for input in &replay.frames[self.replay_playback_index].inputs {
+                    raw_input.events.push(match input {
+                        ReplayInput::PointerMoved(position) => {
+                            egui::Event::PointerMoved(viewport.to_screen(*position))
+                        }
+                        ReplayInput::PointerButton {
+                            position,
+                            button,
+                            pressed,
+                        } => {
+                            discrete = true;
+                            egui::Event::PointerButton {
+                                pos: viewport.to_screen(*position),
+                                button: *button,
+                                pressed: *pressed,
+                                modifiers: egui::Modifiers::default(),
+                            }
+                        }
+                        ReplayInput::MouseWheel { unit, delta } => egui::Event::MouseWheel {
+                            unit: *unit,
+                            delta: Vec2::new(delta[0], delta[1]),
+                            modifiers: egui::Modifiers::default(),
+                        },
+                        ReplayInput::Key { key, pressed } => {
+                            discrete = true;
+                            egui::Event::Key {
+                                key: *key,
+                                physical_key: None,
+                                pressed: *pressed,
+                                repeat: false,
+                                modifiers: egui::Modifiers::default(),
+                            }
+                        }
+                    });
+                }
+                self.replay_playback_index += 1;
+                if discrete {
+                    break;
+                };
+            }
+        } else if let Some(replay) = &mut self.replay_record {
+            let mut inputs = Vec::new();
+            if replay.frames.is_empty() {
+                // 记录开局时的指针位置，保证回放时枪械初始位置一致
+                if let Some(position) = ctx.input(|i| i.pointer.hover_pos()) {
+                    inputs.push(ReplayInput::PointerMoved(viewport.to_level(position)));
+                };
+            };
+            for event in &raw_input.events {
+                match event {
+                    egui::Event::PointerMoved(position) => {
+                        inputs.push(ReplayInput::PointerMoved(viewport.to_level(*position)));
+                    }
+                    egui::Event::PointerButton {
+                        pos,
+                        button,
+                        pressed,
+                        ..
+                    } => {
+                        inputs.push(ReplayInput::PointerButton {
+                            position: viewport.to_level(*pos),
+                            button: *button,
+                            pressed: *pressed,
+                        });
+                    }
+                    egui::Event::MouseWheel { unit, delta, .. } => {
+                        inputs.push(ReplayInput::MouseWheel {
+                            unit: *unit,
+                            delta: [delta.x, delta.y],
+                        });
+                    }
+                    egui::Event::Key { key, pressed, .. } => {
+                        inputs.push(ReplayInput::Key {
+                            key: *key,
+                            pressed: *pressed,
+                        });
+                    }
+                    _ => {}
+                };
+            }
+            if !inputs.is_empty() {
+                replay.frames.push(ReplayFrame { runtime, inputs });
+            };
+        };
+    }
+
+    // 作战结束时将录制的回放写入Resources/replay
+    pub fn save_replay(&mut self) {
+        if let Some(replay) = self.replay_record.take() {
+            let level_name = Path::new(&replay.level_path)
+                .file_stem()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let path = format!(
+                "Resources/replay/replay_{}_{}.json",
+                level_name,
+                Local::now().format("%Y%m%d%H%M%S")
+            );
+            // 回放数据量较大，不使用带缩进的格式
+            if let Err(e) = fs::create_dir_all("Resources/replay")
+                .and_then(|_| fs::write(&path, json::stringify(replay.to_json_value())))
+            {
+                self.problem_report(
+                    &format!(
+                        "{}{}",
+                        self.game_text.game_text["error_save_replay"]
+                            [self.config.language as usize]
+                            .clone(),
+                        path
+                    ),
+                    SeverityLevel::MildWarning,
+                    &e.to_string(),
+                );
+            };
+        };
+    }
+
+    // 读取回放文件，成功后由Select_Level页面进入作战
+    pub fn load_replay(&mut self, path: &Path) -> bool {
+        if let Ok(json_value) = read_from_json(path) {
+            if let Some(replay) = self.report_load_result(
+                &path.to_string_lossy(),
+                Replay::from_json_value(&json_value),
+            ) {
+                if check_file_exists(&replay.level_path) {
+                    self.login_user_config.current_level = replay.level_path.clone();
+                    if replay.endless_seed.is_some() {
+                        self.login_user_config.current_map = replay.endless_map.clone();
+                    };
+                    self.endless_seed = replay.endless_seed;
+                    self.replay_playback = Some(replay);
+                    self.replay_playback_index = 0;
+                    return true;
+                };
+            };
+        };
+        self.problem_report(
+            &format!(
+                "{}{}",
+                self.game_text.game_text["error_load_replay"][self.config.language as usize]
+                    .clone(),
+                path.display()
+            ),
+            SeverityLevel::MildWarning,
+            &self.game_text.game_text["error_load_replay_annotation"]
+                [self.config.language as usize]
+                .clone(),
+        );
+        false
+    }
+
+    // 调试模式下重新读取改动过的配置文件，作战中的相关改动会重新开始当前作战
+    pub fn hot_reload_step(&mut self) {
+        if !self.config.enable_debug_mode {
+            return;
+        };
+        let mut restart_operation = false;
+        for (path, kind) in self.hot_reload.scan(Path::new("Resources/config")) {
+            let json_value = match read_from_json(&path) {
+                Ok(json_value) => json_value,
+                Err(e) => {
+                    self.problem_report(
+                        &format!(
+                            "{}{}",
+                            self.game_text.game_text["error_hot_reload"]
+                                [self.config.language as usize]
+                                .clone(),
+                            path.display()
+                        ),
+                        SeverityLevel::MildWarning,
+                        &e.to_string(),
+                    );
+                    continue;
+                }
+            };
+            let reloaded = match kind {
+                HotReloadKind::GameText => {
+                    GameText::from_json_value(&json_value).map(|game_text| {
+                        self.game_text = game_text;
+                    })
+                }
+                HotReloadKind::Level => Operation::from_json_value(&json_value).map(|_| {
+                    if path == Path::new(&self.login_user_config.current_level) {
+                        restart_operation = true;
+                    };
+                }),
+                HotReloadKind::Enemy => JsonReadEnemy::from_json_value(&json_value).map(|_| ()),
+                HotReloadKind::Gun => Gun::from_json_value(&json_value).map(|_| ()),
+                HotReloadKind::Path => MovePath::from_json_value(&json_value, 1_f32).map(|_| ()),
+                HotReloadKind::Map => Map::from_json_value(&json_value).map(|_| ()),
+            };
+            if let Err(e) = reloaded {
+                // 编辑中的文件可能暂时不完整，热重载失败不会被严格模式视为致命错误
+                self.problem_report(
+                    &format!(
+                        "{}{}",
+                        self.game_text.game_text["error_hot_reload"][self.config.language as usize]
+                            .clone(),
+                        path.display()
+                    ),
+                    SeverityLevel::MildWarning,
+                    &format!(
+                        "{}\n{}",
+                        self.game_text.game_text["error_hot_reload_annotation"]
+                            [self.config.language as usize],
+                        e.in_file(&path.to_string_lossy())
+                    ),
+                );
+            } else if matches!(
+                kind,
+                HotReloadKind::Enemy | HotReloadKind::Gun | HotReloadKind::Path
+            ) {
+                restart_operation = true;
+            };
+        }
+        // 作战数据在进入作战时读取，重新准备作战即可应用改动
+        if restart_operation && self.page == "Operation" {
+            self.modify_var("prepared_operation", false);
+        };
+    }
+
+    pub fn operation_feedback(&mut self, ctx: &egui::Context, events: &[SimulationEvent]) {
+        for load_error in std::mem::take(&mut self.operation_simulation.load_error) {
+            self.load_error_report(&load_error);
+        }
+        for event in events {
+            let sound = match event {
+                SimulationEvent::EnemyHit(_) => "Resources/assets/sounds/Hit.wav".to_string(),
+                SimulationEvent::EnemyHitNoDamage(_) | SimulationEvent::InstrumentRejected(_) => {
+                    "Resources/assets/sounds/Hit_No_Damage.wav".to_string()
+                }
+                SimulationEvent::EnemyDeath(_) => {
+                    "Resources/assets/sounds/Enemy_Death.wav".to_string()
+                }
+                SimulationEvent::EnemyBreach(_) => "Resources/assets/sounds/Alert.wav".to_string(),
+                SimulationEvent::EnemySpawn(i) => {
+                    let enemy = self.operation_simulation.enemy_list[*i].clone();
+                    self.add_enemy(&enemy, ctx);
+                    continue;
+                }
+                SimulationEvent::EnemyRevealed(_) => {
+                    "Resources/assets/sounds/Notification.wav".to_string()
+                }
+                SimulationEvent::BossPhase(i, phase) => {
+                    let enemy = self.operation_simulation.enemy_list[*i].clone();
+                    self.add_enemy_texture(&enemy, ctx);
+                    if let Some(boss) = &enemy.enemy_boss {
+                        self.release_message_box(&boss.boss_phase[*phase].phase_name);
+                    };
+                    "Resources/assets/sounds/Alert.wav".to_string()
+                }
+                SimulationEvent::WaveStart(_) => {
+                    "Resources/assets/sounds/Notification.wav".to_string()
+                }
+                SimulationEvent::MessageBox(name) => {
+                    self.release_message_box(name);
+                    continue;
+                }
+                SimulationEvent::Music(path) => {
+                    if let Ok(mut audio) = self.audio.lock() {
+                        audio.play_music(path);
+                    };
+                    continue;
+                }
+                SimulationEvent::GrantBullet(amount) => {
+                    let storage_bullet = self.var_u("storage_bullet");
+                    self.modify_var("storage_bullet", Value::UInt(storage_bullet + amount));
+                    "Resources/assets/sounds/Reload_Bullet.wav".to_string()
+                }
+                SimulationEvent::InstrumentFire(i) => self.operation_simulation.instrument_content
+                    [self.operation_simulation.instrument_list[*i].instrument_index]
+                    .instrument_shoot_sound
+                    .clone(),
+                SimulationEvent::InstrumentPlaced(i) => {
+                    let placed = self.operation_simulation.instrument_list[*i].clone();
+                    let instrument = self.operation_simulation.instrument_content
+                        [placed.instrument_index]
+                        .clone();
+                    self.add_image(
+                        &placed.instrument_name,
+                        [
+                            placed.instrument_position[0],
+                            placed.instrument_position[1],
+                            instrument.instrument_size[0],
+                            instrument.instrument_size[1],
+                        ],
+                        [0, 0, 0, 0],
+                        [true, true, true, true, false],
+                        [255, 0, 0, 0, 0],
+                        &format!("Instrument_{}", instrument.instrument_recognition_name),
+                    );
+                    "Resources/assets/sounds/Reload.wav".to_string()
+                }
+            };
+            self.play_sound(&sound, AudioBus::Sfx);
+        }
+        self.modify_var(
+            "target_point",
+            Value::UInt(self.operation_simulation.target_point),
+        );
+        self.modify_var(
+            "current_killed_target_enemy",
+            Value::UInt(self.operation_simulation.current_killed_target_enemy),
+        );
+        self.modify_var(
+            "target_enemy",
+            Value::UInt(self.operation_simulation.target_enemy),
+        );
+        self.modify_var("perfect_clear", self.operation_simulation.perfect_clear);
+        self.modify_var("cost", Value::UInt(self.operation_simulation.cost));
+    }
+
+    // 等待同名Boss阶段或触发器的消息框在下一帧出现
+    pub fn release_message_box(&mut self, name: &str) {
+        let runtime = self.var_f("operation_runtime");
+        for message_box in self.operation_preload_message_box.iter_mut() {
+            if message_box.box_trigger_phase == name {
+                message_box.box_trigger_phase.clear();
+                message_box.box_appear_time = runtime;
+            };
+        }
+    }
+
+    // 在作战区域顶部绘制场上Boss的血条，标出各生命值阶段的分界
+    pub fn boss_health_bar(&self, ctx: &egui::Context, ui: &Ui) {
+        let viewport = self.operation_viewport(ctx);
+        let mut row = 0_f32;
+        for enemy in &self.operation_simulation.enemy_list {
+            let Some(boss) = &enemy.enemy_boss else {
+                continue;
+            };
+            if !enemy.enemy_activated || enemy.enemy_hp <= 0_f32 {
+                continue;
+            };
+            let left = viewport.to_screen([OPERATION_SIZE[0] * 0.25, 30_f32 + row * 50_f32]);
+            let right = viewport.to_screen([OPERATION_SIZE[0] * 0.75, 30_f32 + row * 50_f32]);
+            let width = right.x - left.x;
+            let hp_multiple = (enemy.enemy_hp / enemy.enemy_initial_hp).clamp(0_f32, 1_f32);
+            ui.painter().line(
+                vec![left, right],
+                Stroke {
+                    width: 14.0 * viewport.scale,
+                    color: Color32::from_rgba_unmultiplied(0, 0, 0, 200),
+                },
+            );
+            ui.painter().line(
+                vec![left, Pos2::new(left.x + width * hp_multiple, left.y)],
+                Stroke {
+                    width: 10.0 * viewport.scale,
+                    color: Color32::from_rgb(220, 40, 40),
+                },
+            );
+            if enemy.enemy_max_shield > 0_f32 {
+                ui.painter().line(
+                    vec![
+                        Pos2::new(left.x, left.y + 9_f32 * viewport.scale),
+                        Pos2::new(
+                            left.x + width * (enemy.enemy_shield / enemy.enemy_max_shield),
+                            left.y + 9_f32 * viewport.scale,
+                        ),
                     ],
-                    self.operation_preload_message_box[i].box_size,
-                    self.operation_preload_message_box[i].box_existing_time == 0_f32,
-                    self.operation_preload_message_box[i].box_existing_time,
-                    [30_f32, 10_f32],
+                    Stroke {
+                        width: 4.0 * viewport.scale,
+                        color: Color32::from_rgb(64, 160, 255),
+                    },
                 );
             };
+            for phase in &boss.boss_phase {
+                if let BossPhaseTrigger::Hp(ratio) = phase.phase_trigger {
+                    let x = left.x + width * ratio.clamp(0_f32, 1_f32);
+                    ui.painter().line(
+                        vec![
+                            Pos2::new(x, left.y - 7_f32 * viewport.scale),
+                            Pos2::new(x, left.y + 7_f32 * viewport.scale),
+                        ],
+                        Stroke {
+                            width: 2.0 * viewport.scale,
+                            color: Color32::WHITE,
+                        },
+                    );
+                };
+            }
+            let language = self.config.language as usize;
+            let boss_name = boss
+                .boss_name
+                .get(language)
+                .or(boss.boss_name.first())
+                .cloned()
+                .unwrap_or_default();
+            // 当前阶段为已进入的阶段数，初始形态记为第1阶段
+            let phase_count = enemy.enemy_phase_entered.iter().filter(|x| **x).count();
+            ui.painter().text(
+                Pos2::new(left.x, left.y - 10_f32 * viewport.scale),
+                Align2::LEFT_BOTTOM,
+                boss_name,
+                FontId::proportional(18_f32 * viewport.scale),
+                Color32::WHITE,
+            );
+            ui.painter().text(
+                Pos2::new(right.x, right.y - 10_f32 * viewport.scale),
+                Align2::RIGHT_BOTTOM,
+                format!(
+                    "{} {}/{}",
+                    self.game_text.game_text["boss_phase"][language],
+                    phase_count + 1,
+                    boss.boss_phase.len() + 1
+                ),
+                FontId::proportional(16_f32 * viewport.scale),
+                Color32::WHITE,
+            );
+            row += 1_f32;
         }
-        self.message_box_display(ctx, ui);
     }
 
     pub fn enemy_refresh(&mut self, ctx: &egui::Context, ui: &Ui, refresh: bool) {
-        if let Ok(json_value) = read_from_json(self.login_user_config.current_level.clone()) {
-            if let Some(read_operation) = Operation::from_json_value(&json_value) {
-                for i in 0..self.enemy_list.len() {
-                    let id = self.track_resource(
-                        self.resource_image.clone(),
-                        &self.enemy_list[i].enemy_name.clone(),
+        let viewport = self.operation_viewport(ctx);
+        for i in 0..self.operation_simulation.enemy_list.len() {
+            let enemy = self.operation_simulation.enemy_list[i].clone();
+            let id = self.track_resource::<Image>(&enemy.enemy_name);
+            let enemy_position = viewport.to_screen(enemy.enemy_position);
+            self.resource_image.get_mut(id).origin_position = [enemy_position.x, enemy_position.y];
+            self.resource_image.get_mut(id).image_size = viewport.to_screen_size(enemy.enemy_size);
+            // 未被侦测的隐形敌人仅显示模糊轮廓
+            self.resource_image.get_mut(id).alpha = if enemy.enemy_detected {
+                enemy.enemy_alpha
+            } else {
+                enemy.enemy_alpha.min(50)
+            };
+            self.resource_image.get_mut(id).overlay_color =
+                if enemy.enemy_activated && enemy.enemy_alpha == 255 {
+                    if self.operation_simulation.enemy_invincible(i) {
+                        [125, 125, 125, 255]
+                    } else {
+                        [255, 255, 255, 255]
+                    }
+                } else if enemy.enemy_out && enemy.enemy_alpha == 255 {
+                    [255, 255, 255, 255]
+                } else {
+                    // 显现与消失时叠加色随透明度变化
+                    [enemy.enemy_alpha, enemy.enemy_alpha, enemy.enemy_alpha, 255]
+                };
+            if let Some(index) = self
+                .resource_image_texture
+                .find(&enemy.texture_name(enemy.enemy_current_animation_count))
+            {
+                self.resource_image.get_mut(id).image_texture =
+                    self.resource_image_texture[index].texture.clone();
+            };
+            if self.resource_image[id].alpha != 0 {
+                self.image(ui, &enemy.enemy_name, ctx);
+            };
+            if enemy.enemy_activated && enemy.enemy_detected {
+                if enemy.enemy_max_shield > 0_f32 {
+                    ui.painter().line(
+                        vec![
+                            Pos2 {
+                                x: self.resource_image[id].image_position[0],
+                                y: self.resource_image[id].image_position[1] - 25_f32,
+                            },
+                            Pos2 {
+                                x: self.resource_image[id].image_position[0]
+                                    + self.resource_image[id].image_size[0]
+                                        * (enemy.enemy_shield / enemy.enemy_max_shield),
+                                y: self.resource_image[id].image_position[1] - 25_f32,
+                            },
+                        ],
+                        Stroke {
+                            width: 5.0,
+                            color: Color32::from_rgba_unmultiplied(
+                                64,
+                                160,
+                                255,
+                                self.resource_image[id].alpha,
+                            ),
+                        },
                     );
-                    if self.enemy_list[i].enemy_activated {
-                        self.resource_image[id].origin_position = [
-                            (ctx.available_rect().width() - 1280_f32) / 2_f32
-                                + self.enemy_list[i].enemy_position[0],
-                            (ctx.available_rect().height() - 720_f32) / 2_f32
-                                + self.enemy_list[i].enemy_position[1],
-                        ];
-                        if refresh {
-                            if self.resource_image[id].alpha == 255 {
-                                let enemy_rect = egui::Rect::from_min_size(
-                                    Pos2 {
-                                        x: self.resource_image[id].image_position[0],
-                                        y: self.resource_image[id].image_position[1],
-                                    },
-                                    Vec2 {
-                                        x: self.resource_image[id].image_size[0],
-                                        y: self.resource_image[id].image_size[1],
-                                    },
-                                );
-                                for u in 0..read_operation.global.target_line.len() - 1 {
-                                    if self.line_intersects_rect(
-                                        &enemy_rect,
-                                        Pos2 {
-                                            x: read_operation.global.target_line[u][0]
-                                                + (ctx.available_rect().width() - 1280_f32) / 2_f32,
-                                            y: read_operation.global.target_line[u][1]
-                                                + (ctx.available_rect().height() - 720_f32) / 2_f32,
-                                        },
-                                        Pos2 {
-                                            x: read_operation.global.target_line[u + 1][0]
-                                                + (ctx.available_rect().width() - 1280_f32) / 2_f32,
-                                            y: read_operation.global.target_line[u + 1][1]
-                                                + (ctx.available_rect().height() - 720_f32) / 2_f32,
-                                        },
-                                    ) {
-                                        self.modify_var("perfect_clear", false);
-                                        self.enemy_list[i].enemy_out = true;
-                                        self.enemy_list[i].enemy_activated = false;
-                                        self.resource_image[id].overlay_color =
-                                            [255, 255, 255, 255];
-                                        let target_point = self.var_u("target_point");
-                                        if target_point
-                                            > self.enemy_list[i].enemy_minus_target_point
-                                        {
-                                            self.modify_var(
-                                                "target_point",
-                                                Value::UInt(
-                                                    target_point
-                                                        - self.enemy_list[i]
-                                                            .enemy_minus_target_point,
-                                                ),
-                                            );
-                                        } else {
-                                            self.modify_var("target_point", Value::UInt(0));
-                                        };
-                                        if self.enemy_list[i].enemy_detected {
-                                            let current_killed_target_enemy =
-                                                self.var_u("current_killed_target_enemy");
-                                            self.modify_var(
-                                                "current_killed_target_enemy",
-                                                Value::UInt(current_killed_target_enemy + 1),
-                                            );
-                                        };
-                                        std::thread::spawn(|| {
-                                            kira_play_wav("Resources/assets/sounds/Alert.wav")
-                                                .unwrap();
-                                        });
-                                        return;
-                                    };
-                                }
-                                let id2_id = self.var_u("gun_selected") as usize;
-                                let id2 = self.track_resource(
-                                    self.resource_image.clone(),
-                                    &format!(
-                                        "Gun_{}",
-                                        self.storage_gun_content[id2_id]
-                                            .gun_recognition_name
-                                            .clone()
-                                    ),
-                                );
-                                let gun_id = self.track_resource(
-                                    self.resource_switch.clone(),
-                                    &format!(
-                                        "Gun_{}",
-                                        self.storage_gun_content[id2_id]
-                                            .gun_recognition_name
-                                            .clone()
-                                    ),
-                                );
-                                let gun_rect = egui::Rect::from_center_size(
-                                    egui::Pos2 {
-                                        x: self.resource_image[id2].origin_position[0],
-                                        y: self.resource_image[id2].origin_position[1],
-                                    },
-                                    Vec2 {
-                                        x: self.resource_image[id2].image_size[0],
-                                        y: self.resource_image[id2].image_size[1],
-                                    },
-                                );
-                                if self.var_f("operation_runtime")
-                                    - self.enemy_list[i].enemy_hit_time
-                                    < self.enemy_list[i].enemy_invincible_time
-                                {
-                                    self.resource_image[id].overlay_color = [125, 125, 125, 255];
-                                } else {
-                                    self.resource_image[id].overlay_color = [255, 255, 255, 255];
-                                    if self.rect_intersects_rect(&gun_rect, &enemy_rect)
-                                        && self.resource_switch[gun_id].state == 1
-                                    {
-                                        self.enemy_list[i].enemy_hit_time =
-                                            self.var_f("operation_runtime");
-                                        if self.storage_gun_content[id2_id].gun_basic_damage
-                                            > self.enemy_list[i].enemy_def
-                                        {
-                                            self.enemy_list[i].enemy_hp -=
-                                                self.storage_gun_content[id2_id].gun_basic_damage
-                                                    - self.enemy_list[i].enemy_def;
-                                            thread::spawn(|| {
-                                                kira_play_wav("Resources/assets/sounds/Hit.wav")
-                                                    .unwrap();
-                                            });
-                                        } else {
-                                            thread::spawn(|| {
-                                                kira_play_wav(
-                                                    "Resources/assets/sounds/Hit_No_Damage.wav",
-                                                )
-                                                .unwrap();
-                                            });
-                                        };
-                                    };
-                                };
-                                if self.enemy_list[i].enemy_hp <= 0_f32 {
-                                    self.enemy_list[i].enemy_out = true;
-                                    self.enemy_list[i].enemy_activated = false;
-                                    self.resource_image[id].overlay_color = [255, 255, 255, 255];
-                                    if self.enemy_list[i].enemy_detected {
-                                        let current_killed_target_enemy =
-                                            self.var_u("current_killed_target_enemy");
-                                        self.modify_var(
-                                            "current_killed_target_enemy",
-                                            Value::UInt(current_killed_target_enemy + 1),
-                                        );
-                                    };
-                                    std::thread::spawn(|| {
-                                        kira_play_wav("Resources/assets/sounds/Enemy_Death.wav")
-                                            .unwrap();
-                                    });
-                                    return;
-                                };
-                                if self.var_f("operation_runtime")
-                                    - self.enemy_list[i].enemy_walk_time
-                                    >= self.enemy_list[i].enemy_walk_interval
-                                {
-                                    self.enemy_list[i].enemy_walk_time =
-                                        self.var_f("operation_runtime");
-                                    if self.var_f("operation_runtime")
-                                        - self.enemy_list[i].enemy_start_walk_time
-                                        >= self.enemy_list[i].enemy_move_path
-                                            [self.enemy_list[i].enemy_current_walk_status as usize]
-                                            .move_time
-                                    {
-                                        if self.enemy_list[i].enemy_current_walk_status
-                                            < (self.enemy_list[i].enemy_move_path.len() - 1) as u32
-                                        {
-                                            self.enemy_list[i].enemy_current_walk_status += 1;
-                                        } else {
-                                            self.enemy_list[i].enemy_current_walk_status = 0;
-                                        };
-                                        self.enemy_list[i].enemy_start_walk_time =
-                                            self.var_f("operation_runtime");
-                                    };
-                                    if self.enemy_list[i].enemy_move_path
-                                        [self.enemy_list[i].enemy_current_walk_status as usize]
-                                        .move_status[0]
-                                    {
-                                        self.enemy_list[i].enemy_position[1] -=
-                                            self.enemy_list[i].enemy_speed;
-                                    };
-                                    if self.enemy_list[i].enemy_move_path
-                                        [self.enemy_list[i].enemy_current_walk_status as usize]
-                                        .move_status[1]
-                                    {
-                                        self.enemy_list[i].enemy_position[1] +=
-                                            self.enemy_list[i].enemy_speed;
-                                    };
-                                    if self.enemy_list[i].enemy_move_path
-                                        [self.enemy_list[i].enemy_current_walk_status as usize]
-                                        .move_status[2]
-                                    {
-                                        self.enemy_list[i].enemy_position[0] -=
-                                            self.enemy_list[i].enemy_speed;
-                                    };
-                                    if self.enemy_list[i].enemy_move_path
-                                        [self.enemy_list[i].enemy_current_walk_status as usize]
-                                        .move_status[3]
-                                    {
-                                        self.enemy_list[i].enemy_position[0] +=
-                                            self.enemy_list[i].enemy_speed;
-                                    };
-                                    if self.enemy_list[i].enemy_move_path
-                                        [self.enemy_list[i].enemy_current_walk_status as usize]
-                                        .move_status
-                                        .iter()
-                                        .any(|&x| x)
-                                        && self.var_f("operation_runtime")
-                                            - self.enemy_list[i].enemy_animation_change_time
-                                            >= self.enemy_list[i].enemy_animation_interval
-                                    {
-                                        self.enemy_list[i].enemy_animation_change_time =
-                                            self.var_f("operation_runtime");
-                                        if self.enemy_list[i].enemy_animation_forward {
-                                            if self.enemy_list[i].enemy_current_animation_count
-                                                < self.enemy_list[i].enemy_image_count
-                                            {
-                                                self.enemy_list[i].enemy_current_animation_count +=
-                                                    1;
-                                            } else {
-                                                self.enemy_list[i].enemy_animation_forward = false;
-                                                if self.enemy_list[i].enemy_current_animation_count
-                                                    > 0
-                                                {
-                                                    self.enemy_list[i]
-                                                        .enemy_current_animation_count -= 1;
-                                                };
-                                            };
-                                        } else if self.enemy_list[i].enemy_current_animation_count
-                                            > 0
-                                        {
-                                            self.enemy_list[i].enemy_current_animation_count -= 1;
-                                        } else {
-                                            self.enemy_list[i].enemy_animation_forward = true;
-                                            if self.enemy_list[i].enemy_current_animation_count
-                                                < self.enemy_list[i].enemy_image_count
-                                            {
-                                                self.enemy_list[i].enemy_current_animation_count +=
-                                                    1;
-                                            };
-                                        };
-                                    };
-                                };
-                            } else {
-                                if self.enemy_list[i].enemy_increase_alpha_speed
-                                    > 255 - self.resource_image[id].alpha
-                                {
-                                    self.resource_image[id].alpha = 255;
-                                    self.resource_image[id].overlay_color = [255, 255, 255, 255];
-                                } else {
-                                    self.resource_image[id].alpha +=
-                                        self.enemy_list[i].enemy_increase_alpha_speed;
-                                    self.resource_image[id].overlay_color[0] +=
-                                        self.enemy_list[i].enemy_increase_alpha_speed;
-                                    self.resource_image[id].overlay_color[1] +=
-                                        self.enemy_list[i].enemy_increase_alpha_speed;
-                                    self.resource_image[id].overlay_color[2] +=
-                                        self.enemy_list[i].enemy_increase_alpha_speed;
-                                };
-                                if self.resource_image[id].alpha == 255 {
-                                    self.enemy_list[i].enemy_start_walk_time =
-                                        self.var_f("operation_runtime");
-                                };
-                            };
-                        };
-                        if let Some(index) = self.resource_image_texture.iter().position(|x| {
-                            x.name
-                                == format!(
-                                    "{}_{}",
-                                    self.enemy_list[i].enemy_name,
-                                    self.enemy_list[i].enemy_current_animation_count
-                                )
-                        }) {
-                            self.resource_image[id].image_texture =
-                                self.resource_image_texture[index].texture.clone();
-                        };
-                    } else if self.var_f("operation_runtime")
-                        >= self.enemy_list[i].enemy_activated_time
-                        && refresh
-                    {
-                        if self.enemy_list[i].enemy_out {
-                            if self.resource_image[id].alpha
-                                < self.enemy_list[i].enemy_increase_alpha_speed
-                            {
-                                self.resource_image[id].alpha = 0;
-                                self.resource_image[id].overlay_color = [0, 0, 0, 255];
-                            } else {
-                                self.resource_image[id].alpha -=
-                                    self.enemy_list[i].enemy_increase_alpha_speed;
-                                self.resource_image[id].overlay_color[0] -=
-                                    self.enemy_list[i].enemy_increase_alpha_speed;
-                                self.resource_image[id].overlay_color[1] -=
-                                    self.enemy_list[i].enemy_increase_alpha_speed;
-                                self.resource_image[id].overlay_color[2] -=
-                                    self.enemy_list[i].enemy_increase_alpha_speed;
-                            };
+                };
+                ui.painter().line(
+                    vec![
+                        Pos2 {
+                            x: self.resource_image[id].image_position[0],
+                            y: self.resource_image[id].image_position[1] - 15_f32,
+                        },
+                        Pos2 {
+                            x: self.resource_image[id].image_position[0]
+                                + self.resource_image[id].image_size[0],
+                            y: self.resource_image[id].image_position[1] - 15_f32,
+                        },
+                    ],
+                    Stroke {
+                        width: 10.0,
+                        color: Color32::from_rgba_unmultiplied(
+                            0,
+                            0,
+                            0,
+                            self.resource_image[id].alpha,
+                        ),
+                    },
+                );
+                let enemy_hp_multiple = if enemy.enemy_hp / enemy.enemy_initial_hp < 0_f32 {
+                    0_f32
+                } else {
+                    enemy.enemy_hp / enemy.enemy_initial_hp
+                };
+                let enemy_hp_bar_rgb = if enemy_hp_multiple > 0.6 {
+                    [94, 203, 118]
+                } else if 0.2 < enemy_hp_multiple && enemy_hp_multiple <= 0.6 {
+                    [255, 240, 59]
+                } else {
+                    [255, 52, 40]
+                };
+                if enemy.enemy_memory_hp != enemy.enemy_hp {
+                    let enemy = &mut self.operation_simulation.enemy_list[i];
+                    if refresh {
+                        if enemy.enemy_memory_hp - enemy.enemy_hp <= 0.5 {
+                            enemy.enemy_memory_hp = enemy.enemy_hp;
                         } else {
-                            self.enemy_list[i].enemy_activated = true;
+                            enemy.enemy_memory_hp -= 0.5;
                         };
                     };
-                    if self.resource_image[id].alpha != 0 {
-                        self.image(ui, &self.enemy_list[i].enemy_name.clone(), ctx);
-                    };
-                    if self.enemy_list[i].enemy_activated {
-                        ui.painter().line(
-                            vec![
-                                Pos2 {
-                                    x: self.resource_image[id].image_position[0],
-                                    y: self.resource_image[id].image_position[1] - 15_f32,
-                                },
-                                Pos2 {
-                                    x: self.resource_image[id].image_position[0]
-                                        + self.resource_image[id].image_size[0],
-                                    y: self.resource_image[id].image_position[1] - 15_f32,
-                                },
-                            ],
-                            Stroke {
-                                width: 10.0,
-                                color: Color32::from_rgba_unmultiplied(
-                                    0,
-                                    0,
-                                    0,
-                                    self.resource_image[id].alpha,
-                                ),
-                            },
-                        );
-                        let enemy_hp_multiple = if self.enemy_list[i].enemy_hp
-                            / self.enemy_list[i].enemy_initial_hp
-                            < 0_f32
-                        {
+                    let enemy_memory_hp_multiple =
+                        if enemy.enemy_memory_hp / enemy.enemy_initial_hp < 0_f32 {
                             0_f32
                         } else {
-                            self.enemy_list[i].enemy_hp / self.enemy_list[i].enemy_initial_hp
-                        };
-                        let enemy_hp_bar_rgb = if enemy_hp_multiple > 0.6 {
-                            [94, 203, 118]
-                        } else if 0.2 < enemy_hp_multiple && enemy_hp_multiple <= 0.6 {
-                            [255, 240, 59]
-                        } else {
-                            [255, 52, 40]
+                            enemy.enemy_memory_hp / enemy.enemy_initial_hp
                         };
-                        if self.enemy_list[i].enemy_memory_hp != self.enemy_list[i].enemy_hp {
-                            if refresh {
-                                if self.enemy_list[i].enemy_memory_hp - self.enemy_list[i].enemy_hp
-                                    <= 0.5
-                                {
-                                    self.enemy_list[i].enemy_memory_hp =
-                                        self.enemy_list[i].enemy_hp;
-                                } else {
-                                    self.enemy_list[i].enemy_memory_hp -= 0.5;
-                                };
-                            };
-                            let enemy_memory_hp_multiple = if self.enemy_list[i].enemy_memory_hp
-                                / self.enemy_list[i].enemy_initial_hp
-                                < 0_f32
-                            {
-                                0_f32
-                            } else {
-                                self.enemy_list[i].enemy_memory_hp
-                                    / self.enemy_list[i].enemy_initial_hp
-                            };
-                            ui.painter().line(
-                                vec![
-                                    Pos2 {
-                                        x: self.resource_image[id].image_position[0] + 3_f32,
-                                        y: self.resource_image[id].image_position[1] - 15_f32,
-                                    },
-                                    Pos2 {
-                                        x: self.resource_image[id].image_position[0]
-                                            + 3_f32
-                                            + (self.resource_image[id].image_size[0] - 6_f32)
-                                                * enemy_memory_hp_multiple,
-                                        y: self.resource_image[id].image_position[1] - 15_f32,
-                                    },
-                                ],
-                                Stroke {
-                                    width: 5.0,
-                                    color: Color32::from_rgba_unmultiplied(
-                                        91,
-                                        0,
-                                        0,
-                                        self.resource_image[id].alpha,
-                                    ),
-                                },
-                            );
-                        };
-                        ui.painter().line(
-                            vec![
-                                Pos2 {
-                                    x: self.resource_image[id].image_position[0] + 3_f32,
-                                    y: self.resource_image[id].image_position[1] - 15_f32,
-                                },
-                                Pos2 {
-                                    x: self.resource_image[id].image_position[0]
-                                        + 3_f32
-                                        + (self.resource_image[id].image_size[0] - 6_f32)
-                                            * enemy_hp_multiple,
-                                    y: self.resource_image[id].image_position[1] - 15_f32,
-                                },
-                            ],
-                            Stroke {
-                                width: 5.0,
-                                color: Color32::from_rgba_unmultiplied(
-                                    enemy_hp_bar_rgb[0],
-                                    enemy_hp_bar_rgb[1],
-                                    enemy_hp_bar_rgb[2],
-                                    self.resource_image[id].alpha,
-                                ),
+                    ui.painter().line(
+                        vec![
+                            Pos2 {
+                                x: self.resource_image[id].image_position[0] + 3_f32,
+                                y: self.resource_image[id].image_position[1] - 15_f32,
                             },
-                        );
+                            Pos2 {
+                                x: self.resource_image[id].image_position[0]
+                                    + 3_f32
+                                    + (self.resource_image[id].image_size[0] - 6_f32)
+                                        * enemy_memory_hp_multiple,
+                                y: self.resource_image[id].image_position[1] - 15_f32,
+                            },
+                        ],
+                        Stroke {
+                            width: 5.0,
+                            color: Color32::from_rgba_unmultiplied(
+                                91,
+                                0,
+                                0,
+                                self.resource_image[id].alpha,
+                            ),
+                        },
+                    );
+                };
+                ui.painter().line(
+                    vec![
+                        Pos2 {
+                            x: self.resource_image[id].image_position[0] + 3_f32,
+                            y: self.resource_image[id].image_position[1] - 15_f32,
+                        },
+                        Pos2 {
+                            x: self.resource_image[id].image_position[0]
+                                + 3_f32
+                                + (self.resource_image[id].image_size[0] - 6_f32)
+                                    * enemy_hp_multiple,
+                            y: self.resource_image[id].image_position[1] - 15_f32,
+                        },
+                    ],
+                    Stroke {
+                        width: 5.0,
+                        color: Color32::from_rgba_unmultiplied(
+                            enemy_hp_bar_rgb[0],
+                            enemy_hp_bar_rgb[1],
+                            enemy_hp_bar_rgb[2],
+                            self.resource_image[id].alpha,
+                        ),
+                    },
+                );
+                // 状态图标沿贴图上沿排列，即将结束时闪烁
+                let runtime = self.operation_simulation.runtime;
+                for (n, status) in enemy.enemy_status.iter().enumerate() {
+                    let (rgb, glyph) = status.status_kind.icon();
+                    let alpha = if status.status_expire_time - runtime < 1_f32
+                        && ((runtime * 8_f32) as u32).is_multiple_of(2)
+                    {
+                        self.resource_image[id].alpha / 3
+                    } else {
+                        self.resource_image[id].alpha
+                    };
+                    let center = Pos2 {
+                        x: self.resource_image[id].image_position[0] + 10_f32 + n as f32 * 22_f32,
+                        y: self.resource_image[id].image_position[1] + 10_f32,
                     };
+                    ui.painter().circle_filled(
+                        center,
+                        9_f32,
+                        Color32::from_rgba_unmultiplied(rgb[0], rgb[1], rgb[2], alpha),
+                    );
+                    ui.painter().text(
+                        center,
+                        Align2::CENTER_CENTER,
+                        glyph,
+                        FontId::proportional(12_f32),
+                        Color32::from_rgba_unmultiplied(0, 0, 0, alpha),
+                    );
                 }
             };
-        };
+        }
+    }
+
+    pub fn instrument_refresh(&mut self, ctx: &egui::Context, ui: &Ui) {
+        let viewport = self.operation_viewport(ctx);
+        for i in 0..self.operation_simulation.instrument_list.len() {
+            let placed = self.operation_simulation.instrument_list[i].clone();
+            let instrument =
+                self.operation_simulation.instrument_content[placed.instrument_index].clone();
+            let id = self.track_resource::<Image>(&placed.instrument_name);
+            let instrument_center = viewport.to_screen(placed.instrument_position);
+            self.resource_image.get_mut(id).origin_position =
+                [instrument_center.x, instrument_center.y];
+            self.resource_image.get_mut(id).image_size =
+                viewport.to_screen_size(instrument.instrument_size);
+            if self.var_b("instrument_placing") {
+                ui.painter().circle_stroke(
+                    instrument_center,
+                    instrument.instrument_range * viewport.scale,
+                    Stroke {
+                        width: 2.0,
+                        color: Color32::from_rgba_unmultiplied(35, 94, 150, 125),
+                    },
+                );
+            };
+            self.image(ui, &placed.instrument_name, ctx);
+            if let Some(u) = placed.instrument_target {
+                let enemy = &self.operation_simulation.enemy_list[u];
+                if enemy.enemy_activated
+                    && self.operation_simulation.runtime - placed.instrument_last_fire_time < 0.1
+                {
+                    ui.painter().line(
+                        vec![
+                            instrument_center,
+                            viewport.to_screen([
+                                enemy.enemy_position[0],
+                                enemy.enemy_position[1] - enemy.enemy_size[1] / 2_f32,
+                            ]),
+                        ],
+                        Stroke {
+                            width: 3.0,
+                            color: Color32::from_rgba_unmultiplied(255, 240, 59, 200),
+                        },
+                    );
+                };
+            };
+        }
     }
 
     pub fn problem_report(
@@ -3253,9 +5877,14 @@ impl App {
         severity_level: SeverityLevel,
         annotation: &str,
     ) {
-        std::thread::spawn(|| {
-            kira_play_wav("Resources/assets/sounds/Error.wav").unwrap();
-        });
+        self.play_sound("Resources/assets/sounds/Error.wav", AudioBus::Ui);
+        self.logger.problem(
+            &format!("{:?}", severity_level),
+            &format!(
+                "{} ({}) [page: {}, total runtime: {:.2}, page runtime: {:.2}]",
+                problem, annotation, self.page, self.timer.total_time, self.timer.now_time
+            ),
+        );
         self.problem_list.push(Problem {
             severity_level,
             problem: problem.to_string(),
@@ -3268,26 +5897,81 @@ impl App {
         });
     }
 
-    /// The method to get the index of the resource in the vector.
+    // 报告配置文件的读取错误；严格模式下视为致命错误，游戏将停在错误页面
+    pub fn load_error_report(&mut self, load_error: &LoadError) {
+        // GameText本身也可能读取失败，因此不直接按键取值
+        let text = |key: &str| {
+            self.game_text
+                .game_text
+                .get(key)
+                .and_then(|x| x.get(self.config.language as usize))
+                .cloned()
+                .unwrap_or_default()
+        };
+        let problem = format!("{}{}", text("error_load_config"), load_error);
+        let annotation = if self.config.rc_strict_mode {
+            text("error_load_config_strict_annotation")
+        } else {
+            text("error_load_config_annotation")
+        };
+        // 每帧都会读取的文件出错时只报告一次
+        if self.problem_list.iter().any(|x| x.problem == problem) {
+            return;
+        };
+        if self.config.rc_strict_mode {
+            self.problem_report(&problem, SeverityLevel::Error, &annotation);
+            if self.fatal_load_error.is_none() {
+                self.fatal_load_error = Some(load_error.clone());
+            };
+        } else {
+            self.problem_report(&problem, SeverityLevel::SevereWarning, &annotation);
+        };
+    }
+
+    // 读取并解析配置文件，失败时报告错误并返回None
+    pub fn load_config<T>(
+        &mut self,
+        path: &str,
+        parse: impl FnOnce(&JsonValue) -> Result<T, LoadError>,
+    ) -> Option<T> {
+        match read_from_json(path) {
+            Ok(json_value) => self.report_load_result(path, parse(&json_value)),
+            Err(e) => {
+                self.load_error_report(&LoadError::unreadable(path, &e));
+                None
+            }
+        }
+    }
+
+    // 报告解析结果中的错误，file为被解析内容所在的文件
+    pub fn report_load_result<T>(&mut self, file: &str, result: Result<T, LoadError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.load_error_report(&e.in_file(file));
+                None
+            }
+        }
+    }
+
+    /// The method to get the handle of the resource in its registry.
     /// # Arguments
-    /// * `resource_list` - target vector.
+    /// * `T` - target resource type, it decides which registry is searched.
     /// * `resource_name` - target resource name.
     /// * `error_log` - if method didn't find your resource, it will print the error message.
     /// # Returns
-    /// your resource's index.
+    /// your resource's handle.
     /// # Panics
     /// if resource doesn't exist, it will panic.
-    pub fn track_resource<T: RustConstructorResource + std::clone::Clone>(
+    pub fn track_resource<T: RustConstructorResource>(
         &mut self,
-        resource_list: Vec<T>,
         resource_name: &str,
-    ) -> usize {
-        let list = resource_list.clone();
-        if check_resource_exist(list, resource_name) {
-            resource_list
-                .iter()
-                .position(|x| x.name() == resource_name)
-                .unwrap()
+    ) -> ResourceId<T>
+    where
+        App: ResourceRegistryAccess<T>,
+    {
+        if let Some(id) = self.resource_registry().find(resource_name) {
+            id
         } else {
             if self.config.rc_strict_mode {
                 panic!(
@@ -3312,12 +5996,12 @@ impl App {
                         .clone(),
                 );
             };
-            0
+            ResourceId::new(0)
         }
     }
 
     pub fn check_updated(&mut self, name: &str) -> bool {
-        let id = self.track_resource(self.resource_page.clone(), name);
+        let id = self.track_resource::<PageData>(name);
         if self.resource_page[id].change_page_updated {
             true
         } else {
@@ -3328,29 +6012,26 @@ impl App {
 
     pub fn new_page_update(&mut self, name: &str) {
         self.timer.start_time = self.timer.total_time;
-        let page = self.resource_page.clone();
         self.update_timer();
-        let id = self.track_resource(page, name);
-        self.resource_page[id].change_page_updated = true;
+        let id = self.track_resource::<PageData>(name);
+        self.resource_page.get_mut(id).change_page_updated = true;
     }
 
     pub fn wallpaper(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        let image = self.resource_image.clone();
-        let id = self.track_resource(image, "Home_Wallpaper");
-        self.resource_image[id].image_size =
+        let id = self.track_resource::<Image>("Home_Wallpaper");
+        self.resource_image.get_mut(id).image_size =
             [ctx.available_rect().width(), ctx.available_rect().height()];
         self.image(ui, "Home_Wallpaper", ctx);
     }
 
     pub fn dock(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
-        let id = self.track_resource(self.resource_rect.clone(), "Dock_Background");
+        let id = self.track_resource::<CustomRect>("Dock_Background");
         if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
             let rect = egui::Rect::from_min_size(
                 egui::Pos2::new(0_f32, ctx.available_rect().height() - 80_f32),
                 egui::Vec2::new(ctx.available_rect().width(), 80_f32),
             );
             self.modify_var("dock_active_status", rect.contains(mouse_pos));
-            let image = self.resource_image.clone();
             if self.timer.now_time - self.split_time("dock_animation")[0] >= self.vertrefresh {
                 self.add_split_time("dock_animation", true);
                 if self.var_b("dock_active_status") {
@@ -3358,14 +6039,13 @@ impl App {
                         if self.resource_rect[id].origin_position[1] > -10_f32 {
                             for i in 0..self.resource_switch.len() {
                                 if self.resource_switch[i].name.contains("Home_") {
-                                    let id = self.track_resource(
-                                        image.clone(),
+                                    let id = self.track_resource::<Image>(
                                         &self.resource_switch[i].switch_image_name.clone(),
                                     );
-                                    self.resource_image[id].origin_position[1] -= 1_f32;
+                                    self.resource_image.get_mut(id).origin_position[1] -= 1_f32;
                                 };
                             }
-                            self.resource_rect[id].origin_position[1] -= 1_f32;
+                            self.resource_rect.get_mut(id).origin_position[1] -= 1_f32;
                         } else {
                             break;
                         };
@@ -3375,14 +6055,13 @@ impl App {
                         if self.resource_rect[id].origin_position[1] < 80_f32 {
                             for i in 0..self.resource_switch.len() {
                                 if self.resource_switch[i].name.contains("Home_") {
-                                    let id = self.track_resource(
-                                        image.clone(),
+                                    let id = self.track_resource::<Image>(
                                         &self.resource_switch[i].switch_image_name.clone(),
                                     );
-                                    self.resource_image[id].origin_position[1] += 1_f32;
+                                    self.resource_image.get_mut(id).origin_position[1] += 1_f32;
                                 };
                             }
-                            self.resource_rect[id].origin_position[1] += 1_f32;
+                            self.resource_rect.get_mut(id).origin_position[1] += 1_f32;
                         } else {
                             break;
                         };
@@ -3399,7 +6078,11 @@ impl App {
                 self.switch_page("Home_Setting");
                 self.add_split_time("dock_animation", true);
             };
-            let id2 = self.track_resource(self.resource_switch.clone(), "Home_Power");
+            if self.switch("Home_Armoury", ui, ctx, true, true)[0] == 0 {
+                self.switch_page("Home_Armoury");
+                self.add_split_time("dock_animation", true);
+            };
+            let id2 = self.track_resource::<Switch>("Home_Power");
             if self.switch("Home_Power", ui, ctx, true, true)[0] == 0 {
                 write_to_json(
                     format!("Resources/config/user_{}.json", self.config.login_user_name),
@@ -3407,79 +6090,21 @@ impl App {
                 )
                 .unwrap();
                 if self.resource_switch[id2].state == 1 {
-                    self.config.login_user_name = "".to_string();
+                    self.set_login_user("");
                 };
-                write_to_json(
-                    "Resources/config/Preferences.json",
-                    self.config.to_json_value(),
-                )
-                .unwrap();
+                self.save_preferences();
+                self.logger.log("INFO", "Targeted Vector exited");
                 exit(0);
             };
             if self.switch("Home_Journey", ui, ctx, true, true)[0] == 0 {
                 self.switch_page("Home_Select_Map");
                 self.add_split_time("dock_animation", true);
-                if check_resource_exist(self.timer.split_time.clone(), "map_select_animation") {
+                if check_resource_exist(&self.timer.split_time, "map_select_animation") {
                     self.add_split_time("map_select_animation", true);
                 };
             };
         };
-        self.resource_rect[id].size[0] = ctx.available_rect().width() - 100_f32;
-    }
-
-    pub fn rect_intersects_rect(&self, rect1: &Rect, rect2: &Rect) -> bool {
-        // 检查X轴重叠
-        let x_overlap = rect1.max.x > rect2.min.x && rect1.min.x < rect2.max.x;
-
-        // 检查Y轴重叠
-        let y_overlap = rect1.max.y > rect2.min.y && rect1.min.y < rect2.max.y;
-
-        // 两个轴都有重叠时矩形相交
-        x_overlap && y_overlap
-    }
-
-    pub fn line_intersects_rect(&self, rect: &Rect, start: Pos2, end: Pos2) -> bool {
-        // 检查线段端点是否在矩形内
-        if rect.contains(start) || rect.contains(end) {
-            return true;
-        }
-
-        // 检查线段是否与矩形的四条边相交
-        let top_left = rect.min;
-        let top_right = Pos2::new(rect.max.x, rect.min.y);
-        let bottom_left = Pos2::new(rect.min.x, rect.max.y);
-        let bottom_right = rect.max;
-
-        // 检查与上边相交
-        if self.line_segments_intersect(start, end, top_left, top_right) {
-            return true;
-        }
-
-        // 检查与右边相交
-        if self.line_segments_intersect(start, end, top_right, bottom_right) {
-            return true;
-        }
-
-        // 检查与下边相交
-        if self.line_segments_intersect(start, end, bottom_right, bottom_left) {
-            return true;
-        }
-
-        // 检查与左边相交
-        if self.line_segments_intersect(start, end, bottom_left, top_left) {
-            return true;
-        }
-
-        false
-    }
-
-    pub fn line_segments_intersect(&self, a1: Pos2, a2: Pos2, b1: Pos2, b2: Pos2) -> bool {
-        let v1 = (b1.x - a1.x) * (a2.y - a1.y) - (b1.y - a1.y) * (a2.x - a1.x);
-        let v2 = (b2.x - a1.x) * (a2.y - a1.y) - (b2.y - a1.y) * (a2.x - a1.x);
-        let v3 = (a1.x - b1.x) * (b2.y - b1.y) - (a1.y - b1.y) * (b2.x - b1.x);
-        let v4 = (a2.x - b1.x) * (b2.y - b1.y) - (a2.y - b1.y) * (b2.x - b1.x);
-
-        (v1 * v2 < 0.0) && (v3 * v4 < 0.0)
+        self.resource_rect.get_mut(id).size[0] = ctx.available_rect().width() - 100_f32;
     }
 
     pub fn update_frame_stats(&mut self, ctx: &egui::Context) {
@@ -3506,12 +6131,12 @@ impl App {
 
     pub fn add_split_time(&mut self, name: &str, reset: bool) {
         if reset {
-            for i in 0..self.timer.split_time.len() {
-                if self.timer.split_time[i].name == name {
-                    self.timer.split_time.remove(i);
-                    break;
-                }
-            }
+            // 原地重置，避免删除后重建索引
+            if let Some(id) = self.timer.split_time.find(name) {
+                self.timer.split_time.get_mut(id).time =
+                    [self.timer.now_time, self.timer.total_time];
+                return;
+            };
         };
         self.timer.split_time.push(SplitTime {
             discern_type: "SplitTime".to_string(),
@@ -3521,7 +6146,7 @@ impl App {
     }
 
     pub fn split_time(&mut self, name: &str) -> [f32; 2] {
-        let id = self.track_resource(self.timer.split_time.clone(), name);
+        let id = self.track_resource::<SplitTime>(name);
         self.timer.split_time[id].time
     }
 
@@ -3559,9 +6184,9 @@ impl App {
     }
 
     pub fn rect(&mut self, ui: &mut Ui, name: &str, ctx: &egui::Context) {
-        let id = self.track_resource(self.resource_rect.clone(), name);
+        let id = self.track_resource::<CustomRect>(name);
         self.resource_rect[id].reg_render_resource(&mut self.render_resource_list);
-        self.resource_rect[id].position[0] = match self.resource_rect[id].x_grid[1] {
+        self.resource_rect.get_mut(id).position[0] = match self.resource_rect[id].x_grid[1] {
             0 => self.resource_rect[id].origin_position[0],
             _ => {
                 (ctx.available_rect().width() as f64 / self.resource_rect[id].x_grid[1] as f64
@@ -3569,7 +6194,7 @@ impl App {
                     + self.resource_rect[id].origin_position[0]
             }
         };
-        self.resource_rect[id].position[1] = match self.resource_rect[id].y_grid[1] {
+        self.resource_rect.get_mut(id).position[1] = match self.resource_rect[id].y_grid[1] {
             0 => self.resource_rect[id].origin_position[1],
             _ => {
                 (ctx.available_rect().height() as f64 / self.resource_rect[id].y_grid[1] as f64
@@ -3654,7 +6279,7 @@ impl App {
     }
 
     pub fn text(&mut self, ui: &mut Ui, name: &str, ctx: &egui::Context) {
-        let id = self.track_resource(self.resource_text.clone(), name);
+        let id = self.track_resource::<Text>(name);
         self.resource_text[id].reg_render_resource(&mut self.render_resource_list);
         // 计算文本大小
         let galley = ui.fonts(|f| {
@@ -3671,7 +6296,7 @@ impl App {
             )
         });
         let text_size = galley.size();
-        self.resource_text[id].position[0] = match self.resource_text[id].x_grid[1] {
+        self.resource_text.get_mut(id).position[0] = match self.resource_text[id].x_grid[1] {
             0 => self.resource_text[id].origin_position[0],
             _ => {
                 (ctx.available_rect().width() as f64 / self.resource_text[id].x_grid[1] as f64
@@ -3679,7 +6304,7 @@ impl App {
                     + self.resource_text[id].origin_position[0]
             }
         };
-        self.resource_text[id].position[1] = match self.resource_text[id].y_grid[1] {
+        self.resource_text.get_mut(id).position[1] = match self.resource_text[id].y_grid[1] {
             0 => self.resource_text[id].origin_position[1],
             _ => {
                 (ctx.available_rect().height() as f64 / self.resource_text[id].y_grid[1] as f64
@@ -3732,8 +6357,8 @@ impl App {
     }
 
     pub fn get_text_size(&mut self, resource_name: &str, ui: &mut Ui) -> [f32; 2] {
-        if check_resource_exist(self.resource_text.clone(), resource_name) {
-            let id = self.track_resource(self.resource_text.clone(), resource_name);
+        if check_resource_exist(&self.resource_text, resource_name) {
+            let id = self.track_resource::<Text>(resource_name);
             let galley = ui.fonts(|f| {
                 f.layout(
                     self.resource_text[id].text_content.to_string(),
@@ -3794,19 +6419,19 @@ impl App {
 
     #[allow(dead_code)]
     pub fn modify_var<T: Into<Value>>(&mut self, name: &str, value: T) {
-        let id = self.track_resource(self.variables.clone(), name);
-        self.variables[id].value = value.into();
+        let id = self.track_resource::<Variable>(name);
+        self.variables.get_mut(id).value = value.into();
     }
 
     #[allow(dead_code)]
     pub fn var(&mut self, name: &str) -> Value {
-        let id = self.track_resource(self.variables.clone(), name);
+        let id = self.track_resource::<Variable>(name);
         self.variables[id].clone().value
     }
 
     pub fn var_i(&mut self, name: &str) -> i32 {
-        if check_resource_exist(self.variables.clone(), name) {
-            let id = self.track_resource(self.variables.clone(), name);
+        if check_resource_exist(&self.variables, name) {
+            let id = self.track_resource::<Variable>(name);
             match &self.variables[id].value {
                 // 直接访问 value 字段
                 Value::Int(i) => *i,
@@ -3865,8 +6490,8 @@ impl App {
 
     #[allow(dead_code)]
     pub fn var_u(&mut self, name: &str) -> u32 {
-        if check_resource_exist(self.variables.clone(), name) {
-            let id = self.track_resource(self.variables.clone(), name);
+        if check_resource_exist(&self.variables, name) {
+            let id = self.track_resource::<Variable>(name);
             match &self.variables[id].value {
                 // 直接访问 value 字段
                 Value::UInt(u) => *u,
@@ -3925,8 +6550,8 @@ impl App {
 
     #[allow(dead_code)]
     pub fn var_f(&mut self, name: &str) -> f32 {
-        if check_resource_exist(self.variables.clone(), name) {
-            let id = self.track_resource(self.variables.clone(), name);
+        if check_resource_exist(&self.variables, name) {
+            let id = self.track_resource::<Variable>(name);
             match &self.variables[id].value {
                 // 直接访问 value 字段
                 Value::Float(f) => *f,
@@ -3984,8 +6609,8 @@ impl App {
     }
 
     pub fn var_b(&mut self, name: &str) -> bool {
-        if check_resource_exist(self.variables.clone(), name) {
-            let id = self.track_resource(self.variables.clone(), name);
+        if check_resource_exist(&self.variables, name) {
+            let id = self.track_resource::<Variable>(name);
             match &self.variables[id].value {
                 // 直接访问 value 字段
                 Value::Bool(b) => *b,
@@ -4043,8 +6668,8 @@ impl App {
     }
 
     pub fn var_v(&mut self, name: &str) -> Vec<Value> {
-        if check_resource_exist(self.variables.clone(), name) {
-            let id = self.track_resource(self.variables.clone(), name);
+        if check_resource_exist(&self.variables, name) {
+            let id = self.track_resource::<Variable>(name);
             match &self.variables[id].value {
                 // 直接访问 value 字段
                 Value::Vec(v) => v.clone(),
@@ -4102,8 +6727,8 @@ impl App {
     }
 
     pub fn var_s(&mut self, name: &str) -> String {
-        if check_resource_exist(self.variables.clone(), name) {
-            let id = self.track_resource(self.variables.clone(), name);
+        if check_resource_exist(&self.variables, name) {
+            let id = self.track_resource::<Variable>(name);
             match &self.variables[id].value {
                 // 直接访问 value 字段
                 Value::String(s) => s.clone(),
@@ -4350,14 +6975,15 @@ impl App {
     ) {
         let mut image_id = vec![];
         for i in image_name.clone().into_iter() {
-            image_id.push(self.track_resource(self.resource_image.clone(), &i));
+            image_id.push(self.track_resource::<Image>(&i));
             continue;
         }
         for (count, _i) in image_id.clone().into_iter().enumerate() {
-            self.resource_image[image_id[count]].x_grid = [0, 0];
-            self.resource_image[image_id[count]].y_grid = [0, 0];
-            self.resource_image[image_id[count]].center_display = [true, true, false, false];
-            self.resource_image[image_id[count]].image_size =
+            self.resource_image.get_mut(image_id[count]).x_grid = [0, 0];
+            self.resource_image.get_mut(image_id[count]).y_grid = [0, 0];
+            self.resource_image.get_mut(image_id[count]).center_display =
+                [true, true, false, false];
+            self.resource_image.get_mut(image_id[count]).image_size =
                 [size_position_boundary[0], size_position_boundary[1]];
             let mut temp_position;
             if horizontal_or_vertical {
@@ -4373,7 +6999,7 @@ impl App {
                         temp_position -= size_position_boundary[0];
                     };
                 }
-                self.resource_image[image_id[count]].origin_position =
+                self.resource_image.get_mut(image_id[count]).origin_position =
                     [temp_position, size_position_boundary[3]];
             } else {
                 for _j in 0..count {
@@ -4383,7 +7009,7 @@ impl App {
                         temp_position -= size_position_boundary[1];
                     };
                 }
-                self.resource_image[image_id[count]].origin_position =
+                self.resource_image.get_mut(image_id[count]).origin_position =
                     [size_position_boundary[2], temp_position];
             };
         }
@@ -4405,9 +7031,9 @@ impl App {
     }
 
     pub fn scroll_background(&mut self, ui: &mut Ui, name: &str, ctx: &egui::Context) {
-        let id = self.track_resource(self.resource_scroll_background.clone(), name);
+        let id = self.track_resource::<ScrollBackground>(name);
         self.resource_scroll_background[id].reg_render_resource(&mut self.render_resource_list);
-        if !check_resource_exist(self.timer.split_time.clone(), name) {
+        if !check_resource_exist(&self.timer.split_time, name) {
             self.add_split_time(name, false);
         };
         let mut id2;
@@ -4421,30 +7047,29 @@ impl App {
         if self.timer.now_time - self.split_time(name)[0] >= self.vertrefresh {
             self.add_split_time(name, true);
             for i in 0..self.resource_scroll_background[id].image_name.len() {
-                id2 = self.track_resource(
-                    self.resource_image.clone(),
+                id2 = self.track_resource::<Image>(
                     &self.resource_scroll_background[id].image_name[i].clone(),
                 );
                 if self.resource_scroll_background[id].horizontal_or_vertical {
                     if self.resource_scroll_background[id].left_and_top_or_right_and_bottom {
                         for _j in 0..self.resource_scroll_background[id].scroll_speed {
-                            self.resource_image[id2].origin_position[0] -= 1_f32;
+                            self.resource_image.get_mut(id2).origin_position[0] -= 1_f32;
                             self.scroll_background_check_boundary(id, id2);
                         }
                     } else {
                         for _j in 0..self.resource_scroll_background[id].scroll_speed {
-                            self.resource_image[id2].origin_position[0] += 1_f32;
+                            self.resource_image.get_mut(id2).origin_position[0] += 1_f32;
                             self.scroll_background_check_boundary(id, id2);
                         }
                     };
                 } else if self.resource_scroll_background[id].left_and_top_or_right_and_bottom {
                     for _j in 0..self.resource_scroll_background[id].scroll_speed {
-                        self.resource_image[id2].origin_position[1] -= 1_f32;
+                        self.resource_image.get_mut(id2).origin_position[1] -= 1_f32;
                         self.scroll_background_check_boundary(id, id2);
                     }
                 } else {
                     for _j in 0..self.resource_scroll_background[id].scroll_speed {
-                        self.resource_image[id2].origin_position[1] += 1_f32;
+                        self.resource_image.get_mut(id2).origin_position[1] += 1_f32;
                         self.scroll_background_check_boundary(id, id2);
                     }
                 };
@@ -4452,32 +7077,36 @@ impl App {
         };
     }
 
-    fn scroll_background_check_boundary(&mut self, id: usize, id2: usize) {
+    fn scroll_background_check_boundary(
+        &mut self,
+        id: ResourceId<ScrollBackground>,
+        id2: ResourceId<Image>,
+    ) {
         if self.resource_scroll_background[id].horizontal_or_vertical {
             if self.resource_scroll_background[id].left_and_top_or_right_and_bottom {
                 if self.resource_image[id2].origin_position[0]
                     <= self.resource_scroll_background[id].boundary
                 {
-                    self.resource_image[id2].origin_position[0] =
+                    self.resource_image.get_mut(id2).origin_position[0] =
                         self.resource_scroll_background[id].resume_point;
                 };
             } else if self.resource_image[id2].origin_position[0]
                 >= self.resource_scroll_background[id].boundary
             {
-                self.resource_image[id2].origin_position[0] =
+                self.resource_image.get_mut(id2).origin_position[0] =
                     self.resource_scroll_background[id].resume_point;
             };
         } else if self.resource_scroll_background[id].left_and_top_or_right_and_bottom {
             if self.resource_image[id2].origin_position[1]
                 <= self.resource_scroll_background[id].boundary
             {
-                self.resource_image[id2].origin_position[1] =
+                self.resource_image.get_mut(id2).origin_position[1] =
                     self.resource_scroll_background[id].resume_point;
             };
         } else if self.resource_image[id2].origin_position[1]
             >= self.resource_scroll_background[id].boundary
         {
-            self.resource_image[id2].origin_position[1] =
+            self.resource_image.get_mut(id2).origin_position[1] =
                 self.resource_scroll_background[id].resume_point;
         };
     }
@@ -4504,10 +7133,10 @@ impl App {
         let color_image =
             egui::ColorImage::from_rgba_unmultiplied([w as usize, h as usize], &raw_data);
         let image_texture = Some(ctx.load_texture(name, color_image, TextureOptions::LINEAR));
-        if !create_new_resource && check_resource_exist(self.resource_image_texture.clone(), name) {
-            let id = self.track_resource(self.resource_image_texture.clone(), name);
-            self.resource_image_texture[id].texture = image_texture;
-            self.resource_image_texture[id].cite_path = path.to_string();
+        if !create_new_resource && check_resource_exist(&self.resource_image_texture, name) {
+            let id = self.track_resource::<ImageTexture>(name);
+            self.resource_image_texture.get_mut(id).texture = image_texture;
+            self.resource_image_texture.get_mut(id).cite_path = path.to_string();
         } else {
             self.resource_image_texture.push(ImageTexture {
                 discern_type: "ImageTexture".to_string(),
@@ -4527,7 +7156,7 @@ impl App {
         alpha_and_overlay_color: [u8; 5],
         image_texture_name: &str,
     ) {
-        let id = self.track_resource(self.resource_image_texture.clone(), image_texture_name);
+        let id = self.track_resource::<ImageTexture>(image_texture_name);
         self.resource_image.push(Image {
             discern_type: "Image".to_string(),
             name: name.to_string(),
@@ -4556,9 +7185,10 @@ impl App {
     }
 
     pub fn image(&mut self, ui: &Ui, name: &str, ctx: &egui::Context) {
-        let id = self.track_resource(self.resource_image.clone(), name);
+        let id = self.track_resource::<Image>(name);
         self.resource_image[id].reg_render_resource(&mut self.render_resource_list);
-        self.resource_image[id].image_position[0] = match self.resource_image[id].x_grid[1] {
+        self.resource_image.get_mut(id).image_position[0] = match self.resource_image[id].x_grid[1]
+        {
             0 => self.resource_image[id].origin_position[0],
             _ => {
                 (ctx.available_rect().width() as f64 / self.resource_image[id].x_grid[1] as f64
@@ -4566,7 +7196,8 @@ impl App {
                     + self.resource_image[id].origin_position[0]
             }
         };
-        self.resource_image[id].image_position[1] = match self.resource_image[id].y_grid[1] {
+        self.resource_image.get_mut(id).image_position[1] = match self.resource_image[id].y_grid[1]
+        {
             0 => self.resource_image[id].origin_position[1],
             _ => {
                 (ctx.available_rect().height() as f64 / self.resource_image[id].y_grid[1] as f64
@@ -4575,16 +7206,18 @@ impl App {
             }
         };
         if self.resource_image[id].center_display[2] {
-            self.resource_image[id].image_position[0] -=
+            self.resource_image.get_mut(id).image_position[0] -=
                 self.resource_image[id].image_size[0] / 2.0;
         } else if !self.resource_image[id].center_display[0] {
-            self.resource_image[id].image_position[0] -= self.resource_image[id].image_size[0];
+            self.resource_image.get_mut(id).image_position[0] -=
+                self.resource_image[id].image_size[0];
         };
         if self.resource_image[id].center_display[3] {
-            self.resource_image[id].image_position[1] -=
+            self.resource_image.get_mut(id).image_position[1] -=
                 self.resource_image[id].image_size[1] / 2.0;
         } else if !self.resource_image[id].center_display[1] {
-            self.resource_image[id].image_position[1] -= self.resource_image[id].image_size[1];
+            self.resource_image.get_mut(id).image_position[1] -=
+                self.resource_image[id].image_size[1];
         };
         if let Some(texture) = &self.resource_image[id].image_texture {
             let rect = Rect::from_min_size(
@@ -4630,39 +7263,40 @@ impl App {
         box_normal_and_restore_speed: [f32; 2],
     ) {
         if !check_resource_exist(
-            self.resource_message_box.clone(),
+            &self.resource_message_box,
             box_itself_title_content_image_name[0],
         ) {
             let id = self
                 .resource_image
-                .iter()
-                .position(|x| x.name == box_itself_title_content_image_name[3])
+                .find(box_itself_title_content_image_name[3])
                 .unwrap();
-            self.resource_image[id].image_size = [box_size[1] - 15_f32, box_size[1] - 15_f32];
-            self.resource_image[id].center_display = [true, false, false, true];
-            self.resource_image[id].x_grid = [1, 1];
-            self.resource_image[id].y_grid = [0, 1];
+            self.resource_image.get_mut(id).image_size =
+                [box_size[1] - 15_f32, box_size[1] - 15_f32];
+            self.resource_image.get_mut(id).center_display = [true, false, false, true];
+            self.resource_image.get_mut(id).x_grid = [1, 1];
+            self.resource_image.get_mut(id).y_grid = [0, 1];
             let id2 = self
                 .resource_text
-                .iter()
-                .position(|x| x.name == box_itself_title_content_image_name[1])
+                .find(box_itself_title_content_image_name[1])
                 .unwrap();
             let id3 = self
                 .resource_text
-                .iter()
-                .position(|x| x.name == box_itself_title_content_image_name[2])
+                .find(box_itself_title_content_image_name[2])
                 .unwrap();
-            self.resource_text[id2].center_display = [true, true, false, false];
-            self.resource_text[id3].center_display = [true, true, false, false];
-            self.resource_text[id2].x_grid = [1, 1];
-            self.resource_text[id2].y_grid = [0, 1];
-            self.resource_text[id3].x_grid = [1, 1];
-            self.resource_text[id3].y_grid = [0, 1];
-            self.resource_text[id2].wrap_width = box_size[0] - box_size[1] + 5_f32;
-            self.resource_text[id3].wrap_width = box_size[0] - box_size[1] + 5_f32;
-            self.resource_image[id].name = format!("MessageBox_{}", self.resource_image[id].name);
-            self.resource_text[id2].name = format!("MessageBox_{}", self.resource_text[id2].name);
-            self.resource_text[id3].name = format!("MessageBox_{}", self.resource_text[id3].name);
+            self.resource_text.get_mut(id2).center_display = [true, true, false, false];
+            self.resource_text.get_mut(id3).center_display = [true, true, false, false];
+            self.resource_text.get_mut(id2).x_grid = [1, 1];
+            self.resource_text.get_mut(id2).y_grid = [0, 1];
+            self.resource_text.get_mut(id3).x_grid = [1, 1];
+            self.resource_text.get_mut(id3).y_grid = [0, 1];
+            self.resource_text.get_mut(id2).wrap_width = box_size[0] - box_size[1] + 5_f32;
+            self.resource_text.get_mut(id3).wrap_width = box_size[0] - box_size[1] + 5_f32;
+            let image_name = format!("MessageBox_{}", self.resource_image[id].name);
+            let title_name = format!("MessageBox_{}", self.resource_text[id2].name);
+            let content_name = format!("MessageBox_{}", self.resource_text[id3].name);
+            self.resource_image.rename(id, &image_name);
+            self.resource_text.rename(id2, &title_name);
+            self.resource_text.rename(id3, &content_name);
             self.resource_message_box.push(MessageBox {
                 discern_type: "MessageBox".to_string(),
                 name: box_itself_title_content_image_name[0].to_string(),
@@ -4769,44 +7403,51 @@ impl App {
     }
 
     pub fn message_box_display(&mut self, ctx: &egui::Context, ui: &mut Ui) {
+        // 作战中消息框贴靠作战区域的可见部分，而非整个窗口
+        let margin = if self.page == "Operation" {
+            let visible = self.operation_viewport(ctx).visible();
+            [
+                ctx.available_rect().max.x - visible.max.x,
+                visible.min.y - ctx.available_rect().min.y,
+            ]
+        } else {
+            [0_f32, 0_f32]
+        };
         let mut offset = 0_f32;
         let mut delete_count = 0;
         for u in 0..self.resource_message_box.len() {
             let mut deleted = false;
             let i = u - delete_count;
+            let message_box_id = self.resource_message_box.id_at(i).unwrap();
             let id = self
                 .resource_image
-                .iter()
-                .position(|x| x.name == self.resource_message_box[i].box_image_name)
+                .find(&self.resource_message_box[i].box_image_name)
                 .unwrap();
             let id2 = self
                 .resource_rect
-                .iter()
-                .position(|x| x.name == format!("MessageBox_{}", self.resource_message_box[i].name))
+                .find(&format!("MessageBox_{}", self.resource_message_box[i].name))
                 .unwrap();
             let id3 = self
                 .resource_text
-                .iter()
-                .position(|x| x.name == self.resource_message_box[i].box_title_name)
+                .find(&self.resource_message_box[i].box_title_name)
                 .unwrap();
             let id4 = self
                 .resource_text
-                .iter()
-                .position(|x| x.name == self.resource_message_box[i].box_content_name)
+                .find(&self.resource_message_box[i].box_content_name)
                 .unwrap();
             let id5 = self
                 .resource_switch
-                .iter()
-                .position(|x| {
-                    x.name == format!("MessageBox_{}_Close", self.resource_message_box[i].name)
-                })
+                .find(&format!(
+                    "MessageBox_{}_Close",
+                    self.resource_message_box[i].name
+                ))
                 .unwrap();
             let id6 = self
                 .resource_image
-                .iter()
-                .position(|x| {
-                    x.name == format!("MessageBox_{}_Close", self.resource_message_box[i].name)
-                })
+                .find(&format!(
+                    "MessageBox_{}_Close",
+                    self.resource_message_box[i].name
+                ))
                 .unwrap();
             if self.resource_message_box[i].box_size[1]
                 < self.get_text_size(&self.resource_message_box[i].box_title_name.clone(), ui)[1]
@@ -4814,21 +7455,23 @@ impl App {
                         [1]
                     + 10_f32
             {
-                self.resource_message_box[i].box_size[1] = self
+                self.resource_message_box.get_mut(message_box_id).box_size[1] = self
                     .get_text_size(&self.resource_message_box[i].box_title_name.clone(), ui)[1]
                     + self
                         .get_text_size(&self.resource_message_box[i].box_content_name.clone(), ui)
                         [1]
                     + 10_f32;
-                self.resource_rect[id2].size[1] = self.resource_message_box[i].box_size[1];
-                self.resource_image[id].image_size = [
+                self.resource_rect.get_mut(id2).size[1] = self.resource_message_box[i].box_size[1];
+                self.resource_image.get_mut(id).image_size = [
                     self.resource_message_box[i].box_size[1] - 15_f32,
                     self.resource_message_box[i].box_size[1] - 15_f32,
                 ];
-                self.resource_text[id3].wrap_width = self.resource_message_box[i].box_size[0]
+                self.resource_text.get_mut(id3).wrap_width = self.resource_message_box[i].box_size
+                    [0]
                     - self.resource_message_box[i].box_size[1]
                     + 5_f32;
-                self.resource_text[id4].wrap_width = self.resource_message_box[i].box_size[0]
+                self.resource_text.get_mut(id4).wrap_width = self.resource_message_box[i].box_size
+                    [0]
                     - self.resource_message_box[i].box_size[1]
                     + 5_f32;
             };
@@ -4849,19 +7492,26 @@ impl App {
                             + self.resource_message_box[i].box_restore_speed
                             >= offset
                         {
-                            self.resource_message_box[i].box_memory_offset = offset;
+                            self.resource_message_box
+                                .get_mut(message_box_id)
+                                .box_memory_offset = offset;
                         } else {
-                            self.resource_message_box[i].box_memory_offset +=
+                            self.resource_message_box
+                                .get_mut(message_box_id)
+                                .box_memory_offset +=
                                 self.resource_message_box[i].box_restore_speed;
                         };
                     } else if self.resource_message_box[i].box_memory_offset
                         - self.resource_message_box[i].box_restore_speed
                         <= offset
                     {
-                        self.resource_message_box[i].box_memory_offset = offset;
+                        self.resource_message_box
+                            .get_mut(message_box_id)
+                            .box_memory_offset = offset;
                     } else {
-                        self.resource_message_box[i].box_memory_offset -=
-                            self.resource_message_box[i].box_restore_speed;
+                        self.resource_message_box
+                            .get_mut(message_box_id)
+                            .box_memory_offset -= self.resource_message_box[i].box_restore_speed;
                     };
                 };
                 if self.resource_rect[id2].origin_position[0]
@@ -4872,43 +7522,44 @@ impl App {
                             - self.resource_message_box[i].box_speed
                             <= -self.resource_message_box[i].box_size[0] - 5_f32
                         {
-                            self.resource_rect[id2].origin_position[0] =
+                            self.resource_rect.get_mut(id2).origin_position[0] =
                                 -self.resource_message_box[i].box_size[0] - 5_f32;
                             self.add_split_time(
                                 &format!("MessageBox_{}", self.resource_message_box[i].name),
                                 true,
                             );
                         } else {
-                            self.resource_rect[id2].origin_position[0] -=
+                            self.resource_rect.get_mut(id2).origin_position[0] -=
                                 self.resource_message_box[i].box_speed;
                         };
                     } else if self.resource_rect[id2].origin_position[0]
                         + self.resource_message_box[i].box_speed
                         >= 15_f32
                     {
-                        self.resource_rect[id2].origin_position[0] = 15_f32;
+                        self.resource_rect.get_mut(id2).origin_position[0] = 15_f32;
                         delete_count += 1;
                         deleted = true;
                     } else {
-                        self.resource_rect[id2].origin_position[0] +=
+                        self.resource_rect.get_mut(id2).origin_position[0] +=
                             self.resource_message_box[i].box_speed;
                     };
                 };
             };
-            self.resource_rect[id2].origin_position[1] =
-                self.resource_message_box[i].box_memory_offset + 20_f32;
-            self.resource_image[id].origin_position = [
+            self.resource_rect.get_mut(id2).origin_position[0] -= margin[0];
+            self.resource_rect.get_mut(id2).origin_position[1] =
+                self.resource_message_box[i].box_memory_offset + 20_f32 + margin[1];
+            self.resource_image.get_mut(id).origin_position = [
                 self.resource_rect[id2].origin_position[0] + 5_f32,
                 self.resource_rect[id2].origin_position[1]
                     + self.resource_message_box[i].box_size[1] / 2_f32,
             ];
-            self.resource_text[id3].origin_position = [
+            self.resource_text.get_mut(id3).origin_position = [
                 self.resource_image[id].origin_position[0]
                     + self.resource_image[id].image_size[0]
                     + 5_f32,
                 self.resource_rect[id2].origin_position[1] + 5_f32,
             ];
-            self.resource_text[id4].origin_position = [
+            self.resource_text.get_mut(id4).origin_position = [
                 self.resource_image[id].origin_position[0]
                     + self.resource_image[id].image_size[0]
                     + 5_f32,
@@ -4916,23 +7567,23 @@ impl App {
                     + self.get_text_size(&self.resource_message_box[i].box_title_name.clone(), ui)
                         [1],
             ];
-            self.resource_image[id6].origin_position = self.resource_rect[id2].position;
+            self.resource_image.get_mut(id6).origin_position = self.resource_rect[id2].position;
             if !self.resource_message_box[i].box_keep_existing
                 && self.timer.total_time
                     - self.split_time(&format!("MessageBox_{}", self.resource_message_box[i].name))
                         [1]
                     >= self.resource_message_box[i].box_existing_time
                 && self.resource_rect[id2].origin_position[0]
-                    == -self.resource_message_box[i].box_size[0] - 5_f32
+                    == -self.resource_message_box[i].box_size[0] - 5_f32 - margin[0]
             {
-                self.resource_message_box[i].box_exist = false;
+                self.resource_message_box.get_mut(message_box_id).box_exist = false;
                 if self.resource_rect[id2].origin_position[0]
                     + self.resource_message_box[i].box_speed
-                    >= 15_f32
+                    >= 15_f32 - margin[0]
                 {
-                    self.resource_rect[id2].origin_position[0] = 15_f32;
+                    self.resource_rect.get_mut(id2).origin_position[0] = 15_f32 - margin[0];
                 } else {
-                    self.resource_rect[id2].origin_position[0] +=
+                    self.resource_rect.get_mut(id2).origin_position[0] +=
                         self.resource_message_box[i].box_speed;
                 };
             };
@@ -4960,9 +7611,9 @@ impl App {
                     },
                 );
                 if rect.contains(mouse_pos) {
-                    self.resource_switch[id5].appearance[0].color[3] = 200;
+                    self.resource_switch.get_mut(id5).appearance[0].color[3] = 200;
                 } else {
-                    self.resource_switch[id5].appearance[0].color[3] = 0;
+                    self.resource_switch.get_mut(id5).appearance[0].color[3] = 0;
                 };
             };
             if self.switch(
@@ -4973,85 +7624,71 @@ impl App {
                 true,
             )[0] == 0
             {
-                self.resource_message_box[i].box_exist = false;
+                self.resource_message_box.get_mut(message_box_id).box_exist = false;
                 if self.resource_rect[id2].origin_position[0]
                     + self.resource_message_box[i].box_speed
-                    >= 15_f32
+                    >= 15_f32 - margin[0]
                 {
-                    self.resource_rect[id2].origin_position[0] = 15_f32;
+                    self.resource_rect.get_mut(id2).origin_position[0] = 15_f32 - margin[0];
                 } else {
-                    self.resource_rect[id2].origin_position[0] +=
+                    self.resource_rect.get_mut(id2).origin_position[0] +=
                         self.resource_message_box[i].box_speed;
                 };
             };
+            self.resource_rect.get_mut(id2).origin_position[0] += margin[0];
             if deleted {
                 self.resource_switch.remove(
                     self.resource_switch
-                        .iter()
-                        .position(|x| {
-                            x.name
-                                == format!("MessageBox_{}_Close", self.resource_message_box[i].name)
-                        })
+                        .find(&format!(
+                            "MessageBox_{}_Close",
+                            self.resource_message_box[i].name
+                        ))
                         .unwrap(),
                 );
                 self.resource_image.remove(
                     self.resource_image
-                        .iter()
-                        .position(|x| x.name == self.resource_message_box[i].box_image_name)
+                        .find(&self.resource_message_box[i].box_image_name)
                         .unwrap(),
                 );
                 self.resource_image.remove(
                     self.resource_image
-                        .iter()
-                        .position(|x| {
-                            x.name
-                                == format!("MessageBox_{}_Close", self.resource_message_box[i].name)
-                        })
+                        .find(&format!(
+                            "MessageBox_{}_Close",
+                            self.resource_message_box[i].name
+                        ))
                         .unwrap(),
                 );
                 self.resource_text.remove(
                     self.resource_text
-                        .iter()
-                        .position(|x| x.name == self.resource_message_box[i].box_title_name)
+                        .find(&self.resource_message_box[i].box_title_name)
                         .unwrap(),
                 );
                 self.resource_text.remove(
                     self.resource_text
-                        .iter()
-                        .position(|x| x.name == self.resource_message_box[i].box_content_name)
+                        .find(&self.resource_message_box[i].box_content_name)
                         .unwrap(),
                 );
                 self.resource_rect.remove(
                     self.resource_rect
-                        .iter()
-                        .position(|x| {
-                            x.name == format!("MessageBox_{}", self.resource_message_box[i].name)
-                        })
+                        .find(&format!("MessageBox_{}", self.resource_message_box[i].name))
                         .unwrap(),
                 );
                 self.timer.split_time.remove(
                     self.timer
                         .split_time
-                        .iter()
-                        .position(|x| {
-                            x.name
-                                == format!(
-                                    "MessageBox_{}_animation",
-                                    self.resource_message_box[i].name
-                                )
-                        })
+                        .find(&format!(
+                            "MessageBox_{}_animation",
+                            self.resource_message_box[i].name
+                        ))
                         .unwrap(),
                 );
                 self.timer.split_time.remove(
                     self.timer
                         .split_time
-                        .iter()
-                        .position(|x| {
-                            x.name == format!("MessageBox_{}", self.resource_message_box[i].name)
-                        })
+                        .find(&format!("MessageBox_{}", self.resource_message_box[i].name))
                         .unwrap(),
                 );
-                self.resource_message_box.remove(i);
+                self.resource_message_box.remove(message_box_id);
             } else {
                 offset += self.resource_message_box[i].box_size[1] + 15_f32;
             };
@@ -5104,8 +7741,8 @@ impl App {
                 }
             };
         };
-        let id = self.track_resource(self.resource_image.clone(), name_and_switch_image_name[1]);
-        self.resource_image[id].use_overlay_color = true;
+        let id = self.track_resource::<Image>(name_and_switch_image_name[1]);
+        self.resource_image.get_mut(id).use_overlay_color = true;
         self.resource_switch.push(Switch {
             discern_type: "Switch".to_string(),
             name: name_and_switch_image_name[0].to_string(),
@@ -5132,12 +7769,9 @@ impl App {
         play_sound: bool,
     ) -> [usize; 2] {
         let mut activated = [5, 0];
-        let id = self.track_resource(self.resource_switch.clone(), name);
+        let id = self.track_resource::<Switch>(name);
         self.resource_switch[id].reg_render_resource(&mut self.render_resource_list);
-        let id2 = self.track_resource(
-            self.resource_image.clone(),
-            &self.resource_switch[id].switch_image_name.clone(),
-        );
+        let id2 = self.track_resource::<Image>(&self.resource_switch[id].switch_image_name.clone());
         let id3;
         let rect = Rect::from_min_size(
             Pos2::new(
@@ -5163,23 +7797,22 @@ impl App {
                         }));
                         if clicked[u as usize] {
                             active = true;
-                            self.resource_switch[id].last_time_clicked_index = u as usize;
+                            self.resource_switch.get_mut(id).last_time_clicked_index = u as usize;
                             break;
                         };
                     }
                     if active {
-                        self.resource_switch[id].last_time_clicked = true;
+                        self.resource_switch.get_mut(id).last_time_clicked = true;
                         if self.resource_switch[id].enable_hover_click_image[1] {
                             if self.resource_switch[id].enable_hover_click_image[0] {
-                                self.resource_image[id2].overlay_color = self.resource_switch[id]
-                                    .appearance[(self
-                                    .resource_switch[id]
-                                    .state
-                                    * self.resource_switch[id].animation_count
-                                    + 2) as usize]
-                                    .color;
-                                id3 = self.track_resource(
-                                    self.resource_image_texture.clone(),
+                                self.resource_image.get_mut(id2).overlay_color =
+                                    self.resource_switch[id].appearance[(self.resource_switch[id]
+                                        .state
+                                        * self.resource_switch[id].animation_count
+                                        + 2)
+                                        as usize]
+                                        .color;
+                                id3 = self.track_resource::<ImageTexture>(
                                     &self.resource_switch[id].appearance[(self.resource_switch[id]
                                         .state
                                         * self.resource_switch[id].animation_count
@@ -5188,18 +7821,17 @@ impl App {
                                         .texture
                                         .clone(),
                                 );
-                                self.resource_image[id2].image_texture =
+                                self.resource_image.get_mut(id2).image_texture =
                                     self.resource_image_texture[id3].texture.clone();
                             } else {
-                                self.resource_image[id2].overlay_color = self.resource_switch[id]
-                                    .appearance[(self
-                                    .resource_switch[id]
-                                    .state
-                                    * self.resource_switch[id].animation_count
-                                    + 1) as usize]
-                                    .color;
-                                id3 = self.track_resource(
-                                    self.resource_image_texture.clone(),
+                                self.resource_image.get_mut(id2).overlay_color =
+                                    self.resource_switch[id].appearance[(self.resource_switch[id]
+                                        .state
+                                        * self.resource_switch[id].animation_count
+                                        + 1)
+                                        as usize]
+                                        .color;
+                                id3 = self.track_resource::<ImageTexture>(
                                     &self.resource_switch[id].appearance[(self.resource_switch[id]
                                         .state
                                         * self.resource_switch[id].animation_count
@@ -5208,17 +7840,16 @@ impl App {
                                         .texture
                                         .clone(),
                                 );
-                                self.resource_image[id2].image_texture =
+                                self.resource_image.get_mut(id2).image_texture =
                                     self.resource_image_texture[id3].texture.clone();
                             };
                         } else if !self.resource_switch[id].enable_hover_click_image[0] {
-                            self.resource_image[id2].overlay_color =
+                            self.resource_image.get_mut(id2).overlay_color =
                                 self.resource_switch[id].appearance[(self.resource_switch[id].state
                                     * self.resource_switch[id].animation_count)
                                     as usize]
                                     .color;
-                            id3 = self.track_resource(
-                                self.resource_image_texture.clone(),
+                            id3 = self.track_resource::<ImageTexture>(
                                 &self.resource_switch[id].appearance[(self.resource_switch[id]
                                     .state
                                     * self.resource_switch[id].animation_count)
@@ -5226,13 +7857,13 @@ impl App {
                                     .texture
                                     .clone(),
                             );
-                            self.resource_image[id2].image_texture =
+                            self.resource_image.get_mut(id2).image_texture =
                                 self.resource_image_texture[id3].texture.clone();
                         };
                     } else {
                         if self.resource_switch[id].last_time_clicked {
                             if play_sound {
-                                general_click_feedback();
+                                self.general_click_feedback();
                             };
                             let mut count = 1;
                             if self.resource_switch[id].enable_hover_click_image[0] {
@@ -5248,23 +7879,22 @@ impl App {
                                 if self.resource_switch[id].state
                                     < (self.resource_switch[id].appearance.len() / count - 1) as u32
                                 {
-                                    self.resource_switch[id].state += 1;
+                                    self.resource_switch.get_mut(id).state += 1;
                                 } else {
-                                    self.resource_switch[id].state = 0;
+                                    self.resource_switch.get_mut(id).state = 0;
                                 };
                             };
                             activated[0] = self.resource_switch[id].last_time_clicked_index;
-                            self.resource_switch[id].last_time_clicked = false;
+                            self.resource_switch.get_mut(id).last_time_clicked = false;
                         };
                         if self.resource_switch[id].enable_hover_click_image[0] {
-                            self.resource_image[id2].overlay_color = self.resource_switch[id]
-                                .appearance[(self.resource_switch[id]
-                                .state
-                                * self.resource_switch[id].animation_count
-                                + 1) as usize]
-                                .color;
-                            id3 = self.track_resource(
-                                self.resource_image_texture.clone(),
+                            self.resource_image.get_mut(id2).overlay_color =
+                                self.resource_switch[id].appearance[(self.resource_switch[id].state
+                                    * self.resource_switch[id].animation_count
+                                    + 1)
+                                    as usize]
+                                    .color;
+                            id3 = self.track_resource::<ImageTexture>(
                                 &self.resource_switch[id].appearance[(self.resource_switch[id]
                                     .state
                                     * self.resource_switch[id].animation_count
@@ -5273,16 +7903,15 @@ impl App {
                                     .texture
                                     .clone(),
                             );
-                            self.resource_image[id2].image_texture =
+                            self.resource_image.get_mut(id2).image_texture =
                                 self.resource_image_texture[id3].texture.clone();
                         } else {
-                            self.resource_image[id2].overlay_color =
+                            self.resource_image.get_mut(id2).overlay_color =
                                 self.resource_switch[id].appearance[(self.resource_switch[id].state
                                     * self.resource_switch[id].animation_count)
                                     as usize]
                                     .color;
-                            id3 = self.track_resource(
-                                self.resource_image_texture.clone(),
+                            id3 = self.track_resource::<ImageTexture>(
                                 &self.resource_switch[id].appearance[(self.resource_switch[id]
                                     .state
                                     * self.resource_switch[id].animation_count)
@@ -5290,44 +7919,42 @@ impl App {
                                     .texture
                                     .clone(),
                             );
-                            self.resource_image[id2].image_texture =
+                            self.resource_image.get_mut(id2).image_texture =
                                 self.resource_image_texture[id3].texture.clone();
                         };
                     };
                 } else {
-                    self.resource_switch[id].last_time_clicked = false;
-                    self.resource_image[id2].overlay_color = self.resource_switch[id].appearance
-                        [(self.resource_switch[id].state * self.resource_switch[id].animation_count)
-                            as usize]
+                    self.resource_switch.get_mut(id).last_time_clicked = false;
+                    self.resource_image.get_mut(id2).overlay_color = self.resource_switch[id]
+                        .appearance[(self.resource_switch[id].state
+                        * self.resource_switch[id].animation_count)
+                        as usize]
                         .color;
-                    id3 = self.track_resource(
-                        self.resource_image_texture.clone(),
+                    id3 = self.track_resource::<ImageTexture>(
                         &self.resource_switch[id].appearance[(self.resource_switch[id].state
                             * self.resource_switch[id].animation_count)
                             as usize]
                             .texture
                             .clone(),
                     );
-                    self.resource_image[id2].image_texture =
+                    self.resource_image.get_mut(id2).image_texture =
                         self.resource_image_texture[id3].texture.clone();
                 };
             };
         } else {
-            self.resource_switch[id].last_time_clicked = false;
-            self.resource_image[id2].overlay_color =
-                self.resource_switch[id].appearance[(self.resource_switch[id].state
-                    * self.resource_switch[id].animation_count)
+            self.resource_switch.get_mut(id).last_time_clicked = false;
+            self.resource_image.get_mut(id2).overlay_color = self.resource_switch[id].appearance
+                [(self.resource_switch[id].state * self.resource_switch[id].animation_count)
                     as usize]
-                    .color;
-            id3 = self.track_resource(
-                self.resource_image_texture.clone(),
+                .color;
+            id3 = self.track_resource::<ImageTexture>(
                 &self.resource_switch[id].appearance[(self.resource_switch[id].state
                     * self.resource_switch[id].animation_count)
                     as usize]
                     .texture
                     .clone(),
             );
-            self.resource_image[id2].image_texture =
+            self.resource_image.get_mut(id2).image_texture =
                 self.resource_image_texture[id3].texture.clone();
         };
         self.image(ui, &self.resource_switch[id].switch_image_name.clone(), ctx);
@@ -5335,3 +7962,31 @@ impl App {
         activated
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_path_keeps_move_time() {
+        // 斜向移动仍在move_time内走完
+        let diagonal = MovePath::from_json_value(
+            &json::object! { move_status: [false, true, false, true], move_time: 2 },
+            100_f32,
+        )
+        .unwrap();
+        let segment = &diagonal.path_segment[0];
+        assert_eq!(segment.segment_points[0], [200_f32, 200_f32]);
+        let duration = polyline_length(&segment.polyline()) / (100_f32 * segment.segment_speed);
+        assert!((duration - 2_f32).abs() < 1e-4);
+        assert_eq!(segment.segment_wait, 0_f32);
+        // 相反方向相互抵消时原地等待move_time
+        let opposing = MovePath::from_json_value(
+            &json::object! { move_status: [true, true, false, false], move_time: 2 },
+            100_f32,
+        )
+        .unwrap();
+        assert_eq!(opposing.path_segment[0].segment_points[0], [0_f32, 0_f32]);
+        assert_eq!(opposing.path_segment[0].segment_wait, 2_f32);
+    }
+}
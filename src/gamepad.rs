@@ -0,0 +1,250 @@
+//! gamepad.rs is the controller input of the Targeted Vector, it reads gamepads through gilrs and tracks their sticks, triggers and buttons.
+use crate::input::InputAction;
+use egui::Pos2;
+use gilrs::{Axis, Button, Gilrs};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+// 摇杆死区，低于该偏移量视为未推动
+pub const STICK_DEAD_ZONE: f32 = 0.15;
+// 扳机按下超过该比例视为按下
+pub const TRIGGER_THRESHOLD: f32 = 0.5;
+// 摇杆推满时准星每秒移动的距离(点)
+pub const GAMEPAD_CURSOR_SPEED: f32 = 700_f32;
+// 持续推动摇杆时，经过该时长(秒)加速到GAMEPAD_CURSOR_MAX_ACCELERATION倍
+pub const GAMEPAD_CURSOR_ACCELERATION_TIME: f32 = 0.6;
+pub const GAMEPAD_CURSOR_MAX_ACCELERATION: f32 = 2.5;
+// 准星与敌人中心的距离小于敌人尺寸的该倍数时触发辅助瞄准
+pub const AIM_ASSIST_RANGE: f32 = 1.2;
+// 辅助瞄准范围内准星移动速度的倍数
+pub const AIM_ASSIST_FRICTION: f32 = 0.45;
+// 辅助瞄准范围内每秒向敌人中心靠拢的比例
+pub const AIM_ASSIST_PULL: f32 = 3_f32;
+// 手柄按键对应的操作，按下时模拟该操作当前的第一个绑定；南键固定为点击，用于确认菜单
+pub const GAMEPAD_ACTION: [(GamepadButton, InputAction); 9] = [
+    (GamepadButton::RightTrigger, InputAction::Fire),
+    (GamepadButton::LeftTrigger, InputAction::Fire),
+    (GamepadButton::RightBumper, InputAction::NextGun),
+    (GamepadButton::LeftBumper, InputAction::PrevGun),
+    (GamepadButton::West, InputAction::ReloadBullet),
+    (GamepadButton::North, InputAction::FinishReload),
+    (GamepadButton::Start, InputAction::Pause),
+    (GamepadButton::Select, InputAction::ToggleInstrument),
+    (GamepadButton::East, InputAction::CancelInstrument),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    // 下方的面键(A/×)
+    South,
+    // 右方的面键(B/○)
+    East,
+    // 上方的面键(Y/△)
+    North,
+    // 左方的面键(X/□)
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+impl GamepadButton {
+    // gilrs中的LeftTrigger为肩键，LeftTrigger2为扳机
+    fn to_button(self) -> Button {
+        match self {
+            GamepadButton::South => Button::South,
+            GamepadButton::East => Button::East,
+            GamepadButton::North => Button::North,
+            GamepadButton::West => Button::West,
+            GamepadButton::LeftBumper => Button::LeftTrigger,
+            GamepadButton::RightBumper => Button::RightTrigger,
+            GamepadButton::LeftTrigger => Button::LeftTrigger2,
+            GamepadButton::RightTrigger => Button::RightTrigger2,
+            GamepadButton::Select => Button::Select,
+            GamepadButton::Start => Button::Start,
+            GamepadButton::DpadUp => Button::DPadUp,
+            GamepadButton::DpadDown => Button::DPadDown,
+            GamepadButton::DpadLeft => Button::DPadLeft,
+            GamepadButton::DpadRight => Button::DPadRight,
+        }
+    }
+}
+
+// 扳机以外的按键；扳机按照按下的比例判断
+const DIGITAL_BUTTON: [GamepadButton; 12] = [
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::North,
+    GamepadButton::West,
+    GamepadButton::LeftBumper,
+    GamepadButton::RightBumper,
+    GamepadButton::Select,
+    GamepadButton::Start,
+    GamepadButton::DpadUp,
+    GamepadButton::DpadDown,
+    GamepadButton::DpadLeft,
+    GamepadButton::DpadRight,
+];
+
+// 去除死区后重新映射到0~1，保证推出死区时没有跳变
+fn filter_dead_zone(value: f32) -> f32 {
+    let value = value.clamp(-1_f32, 1_f32);
+    if value.abs() < STICK_DEAD_ZONE {
+        0_f32
+    } else {
+        value.signum() * (value.abs() - STICK_DEAD_ZONE) / (1_f32 - STICK_DEAD_ZONE)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Gamepad {
+    // App需要实现Clone，克隆出的副本共享同一个gilrs；首次使用时创建，创建失败时为None且不再重试
+    gilrs: Option<Rc<RefCell<Gilrs>>>,
+    initialized: bool,
+    connected_amount: usize,
+    // [x, y]，范围-1~1，已去除死区，y轴向下为正
+    pub left_stick: [f32; 2],
+    pub right_stick: [f32; 2],
+    // [左, 右]，范围0~1
+    pub trigger: [f32; 2],
+    pub button_down: HashSet<GamepadButton>,
+    pub last_button_down: HashSet<GamepadButton>,
+    // 由手柄控制的准星位置，首次推动摇杆时从指针位置开始
+    pub cursor: Option<Pos2>,
+    // 摇杆持续推动的时长，用于计算加速
+    pub hold_time: f32,
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        Gamepad {
+            gilrs: None,
+            initialized: false,
+            connected_amount: 0,
+            left_stick: [0_f32, 0_f32],
+            right_stick: [0_f32, 0_f32],
+            trigger: [0_f32, 0_f32],
+            button_down: HashSet::new(),
+            last_button_down: HashSet::new(),
+            cursor: None,
+            hold_time: 0_f32,
+        }
+    }
+}
+
+impl Gamepad {
+    pub fn connected(&self) -> bool {
+        self.connected_amount > 0
+    }
+
+    // 每帧调用一次，处理gilrs的全部事件并读取各手柄的状态；多个手柄同时接入时合并输入
+    pub fn poll(&mut self) {
+        self.last_button_down = self.button_down.clone();
+        if !self.initialized {
+            self.initialized = true;
+            // 不支持的平台上gilrs以NotImplemented返回一个不会接入任何手柄的实例
+            self.gilrs = match Gilrs::new() {
+                Ok(gilrs) => Some(Rc::new(RefCell::new(gilrs))),
+                Err(gilrs::Error::NotImplemented(gilrs)) => Some(Rc::new(RefCell::new(gilrs))),
+                Err(_) => None,
+            };
+        };
+        let Some(gilrs) = self.gilrs.clone() else {
+            return;
+        };
+        let mut gilrs = gilrs.borrow_mut();
+        while gilrs.next_event().is_some() {}
+        self.connected_amount = 0;
+        self.left_stick = [0_f32, 0_f32];
+        self.right_stick = [0_f32, 0_f32];
+        self.trigger = [0_f32, 0_f32];
+        self.button_down.clear();
+        let stronger = |a: [f32; 2], b: [f32; 2]| {
+            if b[0] * b[0] + b[1] * b[1] > a[0] * a[0] + a[1] * a[1] {
+                b
+            } else {
+                a
+            }
+        };
+        for (_, gamepad) in gilrs.gamepads() {
+            self.connected_amount += 1;
+            // gilrs中摇杆向上为正，屏幕坐标向下为正
+            self.left_stick = stronger(
+                self.left_stick,
+                [
+                    filter_dead_zone(gamepad.value(Axis::LeftStickX)),
+                    filter_dead_zone(-gamepad.value(Axis::LeftStickY)),
+                ],
+            );
+            self.right_stick = stronger(
+                self.right_stick,
+                [
+                    filter_dead_zone(gamepad.value(Axis::RightStickX)),
+                    filter_dead_zone(-gamepad.value(Axis::RightStickY)),
+                ],
+            );
+            for (index, button) in [GamepadButton::LeftTrigger, GamepadButton::RightTrigger]
+                .into_iter()
+                .enumerate()
+            {
+                let value = gamepad
+                    .button_data(button.to_button())
+                    .map_or(0_f32, |x| x.value())
+                    .clamp(0_f32, 1_f32);
+                self.trigger[index] = self.trigger[index].max(value);
+                if value > TRIGGER_THRESHOLD {
+                    self.button_down.insert(button);
+                };
+            }
+            for button in DIGITAL_BUTTON {
+                if gamepad.is_pressed(button.to_button()) {
+                    self.button_down.insert(button);
+                };
+            }
+        }
+    }
+
+    pub fn pressed(&self, button: GamepadButton) -> bool {
+        self.button_down.contains(&button) && !self.last_button_down.contains(&button)
+    }
+
+    pub fn released(&self, button: GamepadButton) -> bool {
+        !self.button_down.contains(&button) && self.last_button_down.contains(&button)
+    }
+
+    // 两个摇杆中推动幅度较大的一个
+    pub fn aim_stick(&self) -> [f32; 2] {
+        let length = |x: [f32; 2]| x[0] * x[0] + x[1] * x[1];
+        if length(self.right_stick) > length(self.left_stick) {
+            self.right_stick
+        } else {
+            self.left_stick
+        }
+    }
+
+    // 本帧准星的移动速度(点/秒)：推动幅度取平方以便微调，持续推动时逐渐加速
+    pub fn cursor_velocity(&mut self, dt: f32) -> [f32; 2] {
+        let stick = self.aim_stick();
+        let magnitude = (stick[0] * stick[0] + stick[1] * stick[1])
+            .sqrt()
+            .min(1_f32);
+        if magnitude == 0_f32 {
+            self.hold_time = 0_f32;
+            return [0_f32, 0_f32];
+        };
+        self.hold_time += dt;
+        let acceleration = 1_f32
+            + (GAMEPAD_CURSOR_MAX_ACCELERATION - 1_f32)
+                * (self.hold_time / GAMEPAD_CURSOR_ACCELERATION_TIME).min(1_f32);
+        let speed = magnitude * magnitude * GAMEPAD_CURSOR_SPEED * acceleration;
+        [stick[0] / magnitude * speed, stick[1] / magnitude * speed]
+    }
+}
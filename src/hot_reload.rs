@@ -0,0 +1,82 @@
+//! hot_reload.rs is the config watcher of the Targeted Vector, it polls Resources/config for changed files in debug mode.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use walkdir::WalkDir;
+
+// 两次扫描之间的间隔
+pub const HOT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+// 可热重载的配置文件种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotReloadKind {
+    Level,
+    Enemy,
+    Gun,
+    Path,
+    Map,
+    GameText,
+}
+
+impl HotReloadKind {
+    // 按文件名判断配置种类，其余文件不参与热重载
+    pub fn from_path(path: &Path) -> Option<HotReloadKind> {
+        let file_name = path.file_name()?.to_str()?;
+        if !file_name.ends_with(".json") {
+            return None;
+        };
+        if file_name == "GameText.json" {
+            Some(HotReloadKind::GameText)
+        } else if file_name.starts_with("level_") {
+            Some(HotReloadKind::Level)
+        } else if file_name.starts_with("enemy_") {
+            Some(HotReloadKind::Enemy)
+        } else if file_name.starts_with("gun_") {
+            Some(HotReloadKind::Gun)
+        } else if file_name.starts_with("path_") {
+            Some(HotReloadKind::Path)
+        } else if file_name.starts_with("map_") {
+            Some(HotReloadKind::Map)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HotReload {
+    pub modified_time: HashMap<PathBuf, SystemTime>,
+    pub last_scan: Option<Instant>,
+}
+
+impl HotReload {
+    // 扫描目录，返回自上次扫描以来新增或修改过的文件；首次扫描只记录基准，不返回任何文件
+    pub fn scan(&mut self, directory: &Path) -> Vec<(PathBuf, HotReloadKind)> {
+        if self
+            .last_scan
+            .is_some_and(|x| x.elapsed() < HOT_RELOAD_INTERVAL)
+        {
+            return vec![];
+        };
+        let first_scan = self.last_scan.is_none();
+        self.last_scan = Some(Instant::now());
+        let mut changed = Vec::new();
+        for entry in WalkDir::new(directory).into_iter().filter_map(|x| x.ok()) {
+            let path = entry.path().to_path_buf();
+            let Some(kind) = HotReloadKind::from_path(&path) else {
+                continue;
+            };
+            let Some(modified_time) = entry.metadata().ok().and_then(|x| x.modified().ok()) else {
+                continue;
+            };
+            if self.modified_time.get(&path) != Some(&modified_time) {
+                if !first_scan {
+                    changed.push((path.clone(), kind));
+                };
+                self.modified_time.insert(path, modified_time);
+            };
+        }
+        changed.sort_by(|a, b| a.0.cmp(&b.0));
+        changed
+    }
+}
@@ -0,0 +1,320 @@
+//! input.rs is the input action layer of the Targeted Vector, it maps user-configurable key, mouse and scroll bindings to game actions.
+use egui::{Event, InputState, Key, Modifiers, MouseWheelUnit, PointerButton, Pos2, Vec2};
+use std::collections::HashMap;
+
+// 水平滚动超过该值才会触发，避免触控板上下滑动时的水平漂移误触
+pub const HORIZONTAL_SCROLL_THRESHOLD: f32 = 20_f32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    Fire,
+    // 换弹时装入一发子弹
+    ReloadBullet,
+    // 换弹时合上弹仓，结束换弹
+    FinishReload,
+    NextGun,
+    PrevGun,
+    Pause,
+    ToggleInstrument,
+    NextInstrument,
+    PlaceInstrument,
+    CancelInstrument,
+    DebugToggle,
+    // 跳过启动动画
+    Skip,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 12] = [
+        InputAction::Fire,
+        InputAction::ReloadBullet,
+        InputAction::FinishReload,
+        InputAction::NextGun,
+        InputAction::PrevGun,
+        InputAction::Pause,
+        InputAction::ToggleInstrument,
+        InputAction::NextInstrument,
+        InputAction::PlaceInstrument,
+        InputAction::CancelInstrument,
+        InputAction::DebugToggle,
+        InputAction::Skip,
+    ];
+
+    // 在User::settings中保存按键绑定所用的键
+    pub fn setting_key(&self) -> &'static str {
+        match self {
+            InputAction::Fire => "bind_fire",
+            InputAction::ReloadBullet => "bind_reload_bullet",
+            InputAction::FinishReload => "bind_finish_reload",
+            InputAction::NextGun => "bind_next_gun",
+            InputAction::PrevGun => "bind_prev_gun",
+            InputAction::Pause => "bind_pause",
+            InputAction::ToggleInstrument => "bind_toggle_instrument",
+            InputAction::NextInstrument => "bind_next_instrument",
+            InputAction::PlaceInstrument => "bind_place_instrument",
+            InputAction::CancelInstrument => "bind_cancel_instrument",
+            InputAction::DebugToggle => "bind_debug_toggle",
+            InputAction::Skip => "bind_skip",
+        }
+    }
+
+    // 在GameText.json中显示名称所用的键
+    pub fn game_text_key(&self) -> &'static str {
+        match self {
+            InputAction::Fire => "action_fire",
+            InputAction::ReloadBullet => "action_reload_bullet",
+            InputAction::FinishReload => "action_finish_reload",
+            InputAction::NextGun => "action_next_gun",
+            InputAction::PrevGun => "action_prev_gun",
+            InputAction::Pause => "action_pause",
+            InputAction::ToggleInstrument => "action_toggle_instrument",
+            InputAction::NextInstrument => "action_next_instrument",
+            InputAction::PlaceInstrument => "action_place_instrument",
+            InputAction::CancelInstrument => "action_cancel_instrument",
+            InputAction::DebugToggle => "action_debug_toggle",
+            InputAction::Skip => "action_skip",
+        }
+    }
+
+    // 与改版前固定操作一致的默认绑定
+    pub fn default_binding(&self) -> Vec<InputBinding> {
+        match self {
+            InputAction::Fire => vec![InputBinding::Mouse(PointerButton::Primary)],
+            InputAction::ReloadBullet => vec![InputBinding::Scroll(ScrollDirection::Up)],
+            InputAction::FinishReload => vec![InputBinding::Scroll(ScrollDirection::Down)],
+            // 改版前横向滚动与松开鼠标中键都会切换到下一把枪
+            InputAction::NextGun => vec![
+                InputBinding::Scroll(ScrollDirection::Right),
+                InputBinding::Mouse(PointerButton::Middle),
+            ],
+            InputAction::PrevGun => vec![InputBinding::Scroll(ScrollDirection::Left)],
+            InputAction::Pause => vec![InputBinding::Key(Key::Escape)],
+            InputAction::ToggleInstrument => vec![InputBinding::Key(Key::Tab)],
+            InputAction::NextInstrument => vec![InputBinding::Key(Key::Q)],
+            InputAction::PlaceInstrument => vec![InputBinding::Mouse(PointerButton::Primary)],
+            InputAction::CancelInstrument => vec![InputBinding::Mouse(PointerButton::Secondary)],
+            InputAction::DebugToggle => vec![InputBinding::Key(Key::F3)],
+            InputAction::Skip => vec![InputBinding::Key(Key::Space)],
+        }
+    }
+}
+
+// 以egui滚动量的正负区分方向：向上、向左滚动时滚动量为正
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputBinding {
+    Key(Key),
+    Mouse(PointerButton),
+    Scroll(ScrollDirection),
+}
+
+impl InputBinding {
+    // 写法为"key:Escape"、"mouse:Primary"或"scroll:up"
+    pub fn from_setting(setting: &str) -> Option<InputBinding> {
+        let (kind, name) = setting.trim().split_once(':')?;
+        match kind {
+            "key" => Some(InputBinding::Key(Key::from_name(name)?)),
+            "mouse" => Some(InputBinding::Mouse(match name {
+                "Primary" => PointerButton::Primary,
+                "Secondary" => PointerButton::Secondary,
+                "Middle" => PointerButton::Middle,
+                "Extra1" => PointerButton::Extra1,
+                "Extra2" => PointerButton::Extra2,
+                _ => return None,
+            })),
+            "scroll" => Some(InputBinding::Scroll(match name {
+                "up" => ScrollDirection::Up,
+                "down" => ScrollDirection::Down,
+                "left" => ScrollDirection::Left,
+                "right" => ScrollDirection::Right,
+                _ => return None,
+            })),
+            _ => None,
+        }
+    }
+
+    pub fn setting(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("key:{}", key.name()),
+            InputBinding::Mouse(button) => format!("mouse:{:?}", button),
+            InputBinding::Scroll(direction) => format!(
+                "scroll:{}",
+                match direction {
+                    ScrollDirection::Up => "up",
+                    ScrollDirection::Down => "down",
+                    ScrollDirection::Left => "left",
+                    ScrollDirection::Right => "right",
+                }
+            ),
+        }
+    }
+
+    // 滚动没有按下与松开之分，滚动的那一帧同时视为按下、按住与松开
+    fn scrolled(direction: ScrollDirection, input: &InputState) -> bool {
+        let delta = input.smooth_scroll_delta;
+        match direction {
+            ScrollDirection::Up => delta.y > 0_f32,
+            ScrollDirection::Down => delta.y < 0_f32,
+            ScrollDirection::Left => delta.x > HORIZONTAL_SCROLL_THRESHOLD,
+            ScrollDirection::Right => delta.x < -HORIZONTAL_SCROLL_THRESHOLD,
+        }
+    }
+
+    pub fn pressed(&self, input: &InputState) -> bool {
+        match self {
+            InputBinding::Key(key) => input.key_pressed(*key),
+            InputBinding::Mouse(button) => input.pointer.button_pressed(*button),
+            InputBinding::Scroll(direction) => InputBinding::scrolled(*direction, input),
+        }
+    }
+
+    pub fn down(&self, input: &InputState) -> bool {
+        match self {
+            InputBinding::Key(key) => input.key_down(*key),
+            InputBinding::Mouse(button) => input.pointer.button_down(*button),
+            InputBinding::Scroll(direction) => InputBinding::scrolled(*direction, input),
+        }
+    }
+
+    pub fn released(&self, input: &InputState) -> bool {
+        match self {
+            InputBinding::Key(key) => input.key_released(*key),
+            InputBinding::Mouse(button) => input.pointer.button_released(*button),
+            InputBinding::Scroll(direction) => InputBinding::scrolled(*direction, input),
+        }
+    }
+
+    // 模拟该绑定被按下或松开时产生的事件，滚动只在按下时产生一格滚轮
+    pub fn event(&self, pressed: bool, position: Pos2) -> Option<Event> {
+        match self {
+            InputBinding::Key(key) => Some(Event::Key {
+                key: *key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers: Modifiers::default(),
+            }),
+            InputBinding::Mouse(button) => Some(Event::PointerButton {
+                pos: position,
+                button: *button,
+                pressed,
+                modifiers: Modifiers::default(),
+            }),
+            InputBinding::Scroll(direction) => {
+                if !pressed {
+                    return None;
+                };
+                Some(Event::MouseWheel {
+                    unit: MouseWheelUnit::Line,
+                    delta: match direction {
+                        ScrollDirection::Up => Vec2::new(0_f32, 1_f32),
+                        ScrollDirection::Down => Vec2::new(0_f32, -1_f32),
+                        ScrollDirection::Left => Vec2::new(1_f32, 0_f32),
+                        ScrollDirection::Right => Vec2::new(-1_f32, 0_f32),
+                    },
+                    modifiers: Modifiers::default(),
+                })
+            }
+        }
+    }
+
+    // 重新绑定时捕获本帧的第一个按键、鼠标按键或滚动
+    pub fn capture(input: &InputState) -> Option<InputBinding> {
+        for event in &input.events {
+            match event {
+                Event::Key {
+                    key, pressed: true, ..
+                } => return Some(InputBinding::Key(*key)),
+                Event::PointerButton {
+                    button,
+                    pressed: true,
+                    ..
+                } => return Some(InputBinding::Mouse(*button)),
+                _ => {}
+            };
+        }
+        [
+            ScrollDirection::Up,
+            ScrollDirection::Down,
+            ScrollDirection::Left,
+            ScrollDirection::Right,
+        ]
+        .into_iter()
+        .find(|x| InputBinding::scrolled(*x, input))
+        .map(InputBinding::Scroll)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputBindings {
+    pub binding: HashMap<InputAction, Vec<InputBinding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        InputBindings {
+            binding: InputAction::ALL
+                .iter()
+                .map(|x| (*x, x.default_binding()))
+                .collect(),
+        }
+    }
+}
+
+impl InputBindings {
+    // 从用户设置中读取按键绑定，缺失或无法识别的绑定使用默认值
+    pub fn from_settings(settings: &HashMap<String, String>) -> InputBindings {
+        let mut input_bindings = InputBindings::default();
+        for action in InputAction::ALL {
+            if let Some(setting) = settings.get(action.setting_key()) {
+                let binding: Vec<InputBinding> = setting
+                    .split(',')
+                    .filter_map(InputBinding::from_setting)
+                    .collect();
+                if !binding.is_empty() {
+                    input_bindings.binding.insert(action, binding);
+                };
+            };
+        }
+        input_bindings
+    }
+
+    pub fn save(&self, settings: &mut HashMap<String, String>) {
+        for action in InputAction::ALL {
+            settings.insert(
+                action.setting_key().to_string(),
+                self.get(action)
+                    .iter()
+                    .map(|x| x.setting())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            );
+        }
+    }
+
+    pub fn get(&self, action: InputAction) -> Vec<InputBinding> {
+        self.binding
+            .get(&action)
+            .cloned()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn pressed(&self, input: &InputState, action: InputAction) -> bool {
+        self.get(action).iter().any(|x| x.pressed(input))
+    }
+
+    pub fn down(&self, input: &InputState, action: InputAction) -> bool {
+        self.get(action).iter().any(|x| x.down(input))
+    }
+
+    pub fn released(&self, input: &InputState, action: InputAction) -> bool {
+        self.get(action).iter().any(|x| x.released(input))
+    }
+}
@@ -0,0 +1,201 @@
+//! load_error.rs is the loader error of the Targeted Vector, it names the file, field and expected type when a config file cannot be read.
+use json::JsonValue;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Str,
+    Number,
+    Int,
+    UInt,
+    Byte,
+    Bool,
+    Array,
+    Object,
+    StrArray,
+    // [x, y]
+    NumberPair,
+    // [r, g, b, a]
+    Color,
+}
+
+pub fn kind_name(kind: FieldKind) -> &'static str {
+    match kind {
+        FieldKind::Str => "a string",
+        FieldKind::Number => "a number",
+        FieldKind::Int => "an integer",
+        FieldKind::UInt => "an unsigned integer",
+        FieldKind::Byte => "an integer in 0..=255",
+        FieldKind::Bool => "a boolean",
+        FieldKind::Array => "an array",
+        FieldKind::Object => "an object",
+        FieldKind::StrArray => "an array of strings",
+        FieldKind::NumberPair => "an array of two numbers",
+        FieldKind::Color => "an array of four integers in 0..=255",
+    }
+}
+
+// 拼接JSON指针，按规范转义"~"与"/"
+pub fn pointer_join(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadError {
+    // 出错的文件，由读取文件的一方通过in_file补充
+    pub file: String,
+    // RFC 6901格式的JSON指针
+    pub pointer: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl LoadError {
+    pub fn new(expected: &str, found: &JsonValue) -> LoadError {
+        LoadError {
+            file: String::new(),
+            pointer: String::new(),
+            expected: expected.to_string(),
+            found: if found.is_null() {
+                "nothing".to_string()
+            } else {
+                found.dump()
+            },
+        }
+    }
+
+    pub fn expected(kind: FieldKind, found: &JsonValue) -> LoadError {
+        LoadError::new(kind_name(kind), found)
+    }
+
+    // 文件无法读取或不是合法的JSON
+    pub fn unreadable(file: &str, error: &anyhow::Error) -> LoadError {
+        LoadError {
+            file: file.to_string(),
+            pointer: String::new(),
+            expected: "a readable JSON file".to_string(),
+            found: format!("{:#}", error),
+        }
+    }
+
+    // 在指针前补充上一级的字段名或数组序号
+    pub fn at(mut self, key: &str) -> LoadError {
+        self.pointer = format!("{}{}", pointer_join("", key), self.pointer);
+        self
+    }
+
+    pub fn in_file(mut self, file: &str) -> LoadError {
+        self.file = file.to_string();
+        self
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}#{}: expected {}, found {}",
+            self.file, self.pointer, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+// 读取必填字段，类型不符或缺失时返回指向该字段的错误
+pub trait JsonField {
+    fn field<T>(
+        &self,
+        key: &str,
+        kind: FieldKind,
+        convert: impl FnOnce(&JsonValue) -> Option<T>,
+    ) -> Result<T, LoadError>;
+
+    // 读取可选字段，缺失时为None，存在但类型不符时仍返回错误
+    fn optional<T>(
+        &self,
+        key: &str,
+        read: impl FnOnce(&Self, &str) -> Result<T, LoadError>,
+    ) -> Result<Option<T>, LoadError>;
+
+    fn field_str(&self, key: &str) -> Result<String, LoadError> {
+        self.field(key, FieldKind::Str, |x| x.as_str().map(String::from))
+    }
+
+    fn field_f32(&self, key: &str) -> Result<f32, LoadError> {
+        self.field(key, FieldKind::Number, |x| x.as_f32())
+    }
+
+    fn field_u32(&self, key: &str) -> Result<u32, LoadError> {
+        self.field(key, FieldKind::UInt, |x| x.as_u32())
+    }
+
+    fn field_u8(&self, key: &str) -> Result<u8, LoadError> {
+        self.field(key, FieldKind::Byte, |x| x.as_u8())
+    }
+
+    fn field_bool(&self, key: &str) -> Result<bool, LoadError> {
+        self.field(key, FieldKind::Bool, |x| x.as_bool())
+    }
+
+    fn field_str_array(&self, key: &str) -> Result<Vec<String>, LoadError> {
+        self.field(key, FieldKind::StrArray, |x| {
+            if !x.is_array() {
+                return None;
+            };
+            x.members()
+                .map(|x| x.as_str().map(String::from))
+                .collect::<Option<Vec<String>>>()
+        })
+    }
+
+    // [x, y]
+    fn field_pair(&self, key: &str) -> Result<[f32; 2], LoadError> {
+        self.field(key, FieldKind::NumberPair, |x| {
+            Some([x[0].as_f32()?, x[1].as_f32()?])
+        })
+    }
+
+    // [r, g, b, a]
+    fn field_color(&self, key: &str) -> Result<[u8; 4], LoadError> {
+        self.field(key, FieldKind::Color, |x| {
+            Some([x[0].as_u8()?, x[1].as_u8()?, x[2].as_u8()?, x[3].as_u8()?])
+        })
+    }
+}
+
+impl JsonField for JsonValue {
+    fn field<T>(
+        &self,
+        key: &str,
+        kind: FieldKind,
+        convert: impl FnOnce(&JsonValue) -> Option<T>,
+    ) -> Result<T, LoadError> {
+        convert(&self[key]).ok_or_else(|| LoadError::expected(kind, &self[key]).at(key))
+    }
+
+    fn optional<T>(
+        &self,
+        key: &str,
+        read: impl FnOnce(&Self, &str) -> Result<T, LoadError>,
+    ) -> Result<Option<T>, LoadError> {
+        if self[key].is_null() {
+            Ok(None)
+        } else {
+            read(self, key).map(Some)
+        }
+    }
+}
+
+// 逐个解析数组成员，出错时在指针中补充数组名与序号
+pub fn parse_members<T>(
+    value: &JsonValue,
+    key: &str,
+    parse: impl Fn(&JsonValue) -> Result<T, LoadError>,
+) -> Result<Vec<T>, LoadError> {
+    value[key]
+        .members()
+        .enumerate()
+        .map(|(index, member)| parse(member).map_err(|e| e.at(&index.to_string()).at(key)))
+        .collect()
+}
@@ -0,0 +1,140 @@
+//! logger.rs is the log recorder of the Targeted Vector, it keeps rotating log files on disk and writes a crash report when the game panics.
+use chrono::Local;
+use std::collections::VecDeque;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+pub const LOG_DIRECTORY: &str = "Resources/logs";
+pub const LOG_FILE: &str = "Resources/logs/targeted_vector.log";
+// 日志超过该大小(字节)时轮换，最多保留LOG_ROTATE_COUNT个旧日志
+pub const LOG_MAX_SIZE: u64 = 512 * 1024;
+pub const LOG_ROTATE_COUNT: u32 = 5;
+// 崩溃报告中附带的最近问题数量
+pub const CRASH_RECENT_PROBLEM: usize = 20;
+
+// 崩溃时写入报告的游戏状态
+#[derive(Debug, Clone, Default)]
+pub struct LogContext {
+    pub current_page: String,
+    pub login_user: String,
+    pub current_level: String,
+    pub recent_problem: VecDeque<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Logger {
+    // 与panic hook共享，hook中只能通过它读取游戏状态
+    pub context: Arc<Mutex<LogContext>>,
+}
+
+impl Logger {
+    pub fn new() -> Logger {
+        let logger = Logger::default();
+        logger.log("INFO", "Targeted Vector started");
+        logger
+    }
+
+    // 追加一行日志，写入失败时忽略，避免日志问题影响游戏
+    pub fn log(&self, tag: &str, message: &str) {
+        if fs::create_dir_all(LOG_DIRECTORY).is_err() {
+            return;
+        };
+        if fs::metadata(LOG_FILE).is_ok_and(|x| x.len() >= LOG_MAX_SIZE) {
+            rotate_log();
+        };
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(LOG_FILE) {
+            let _ = writeln!(
+                file,
+                "{} [{}] {}",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                tag,
+                message.replace('\n', " | ")
+            );
+        };
+    }
+
+    // 记录问题，并保留最近的若干条供崩溃报告使用
+    pub fn problem(&self, tag: &str, line: &str) {
+        self.log(tag, line);
+        if let Ok(mut context) = self.context.lock() {
+            context
+                .recent_problem
+                .push_back(format!("[{}] {}", tag, line));
+            while context.recent_problem.len() > CRASH_RECENT_PROBLEM {
+                context.recent_problem.pop_front();
+            }
+        };
+    }
+
+    pub fn update_context(&self, current_page: &str, login_user: &str, current_level: &str) {
+        if let Ok(mut context) = self.context.lock() {
+            context.current_page = current_page.to_string();
+            context.login_user = login_user.to_string();
+            context.current_level = current_level.to_string();
+        };
+    }
+
+    // 在默认的panic输出之前写入崩溃报告
+    pub fn install_panic_hook(&self) {
+        let logger = self.clone();
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            // panic可能发生在持有锁时，此时不再等待锁
+            let context = match logger.context.try_lock() {
+                Ok(context) => context.clone(),
+                Err(_) => LogContext::default(),
+            };
+            let path = format!(
+                "{}/crash_{}.log",
+                LOG_DIRECTORY,
+                Local::now().format("%Y%m%d%H%M%S")
+            );
+            let mut report = format!(
+                "Targeted Vector crash report\ntime: {}\npanic: {}\npage: {}\nlogin user: {}\nlevel: {}\n\nrecent problems:\n",
+                Local::now().format("%Y-%m-%d %H:%M:%S"),
+                info,
+                context.current_page,
+                context.login_user,
+                context.current_level
+            );
+            for problem in &context.recent_problem {
+                report.push_str(problem);
+                report.push('\n');
+            }
+            if fs::create_dir_all(LOG_DIRECTORY).is_ok() && fs::write(&path, report).is_ok() {
+                logger.log("CRASH", &format!("{} (report: {})", info, path));
+            };
+            default_hook(info);
+        }));
+    }
+}
+
+// targeted_vector.log依次改名为targeted_vector.log.1、.2……，最旧的日志被删除
+fn rotate_log() {
+    let rotated = |index: u32| format!("{}.{}", LOG_FILE, index);
+    let _ = fs::remove_file(rotated(LOG_ROTATE_COUNT));
+    for index in (1..LOG_ROTATE_COUNT).rev() {
+        let _ = fs::rename(rotated(index), rotated(index + 1));
+    }
+    let _ = fs::rename(LOG_FILE, rotated(1));
+}
+
+pub fn latest_log() -> PathBuf {
+    PathBuf::from(LOG_FILE)
+}
+
+// 使用系统默认程序打开文件
+pub fn open_with_system(path: &Path) -> std::io::Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program).arg(path).spawn().map(|_| ())
+}
@@ -4,8 +4,10 @@
 //! Special Thanks:
 //! 试卷毁灭者: Give me some advice on how to make Targeted Vector.
 //! Gavin: Help me improve some function.
+use crate::cli::{CliOptions, CLI_USAGE};
 use crate::function::GameText;
 use crate::function::User;
+use crate::function::DEFAULT_SAVE_SLOT;
 use egui::IconData;
 use function::read_from_json;
 use function::App;
@@ -17,9 +19,56 @@ use std::sync::Arc;
 // use function::find_app_bundle;
 // use function::write_to_json;
 
+mod audio;
+mod cli;
+mod endless;
 mod function;
+mod gamepad;
+mod hot_reload;
+mod input;
+mod load_error;
+mod logger;
 mod pages;
+mod profile;
+mod score;
+mod script;
+mod simulation;
+mod status;
+mod validate;
+mod viewport;
 fn main() {
+    let cli_options = match CliOptions::parse(std::env::args().skip(1)) {
+        Ok(cli_options) => cli_options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, CLI_USAGE);
+            std::process::exit(2);
+        }
+    };
+    if cli_options.help {
+        println!("{}", CLI_USAGE);
+        return;
+    };
+    // 所有资源路径都相对于Resources所在的目录
+    if let Some(resources) = &cli_options.resources {
+        if let Err(e) = std::env::set_current_dir(resources) {
+            eprintln!("Unable to use resources directory \"{}\": {}", resources, e);
+            std::process::exit(2);
+        };
+    };
+    // 仅校验资源文件，不启动游戏窗口
+    if cli_options.validate {
+        let problems = validate::validate_resources();
+        for problem in &problems {
+            println!("{}#{}: {}", problem.file, problem.pointer, problem.message);
+        }
+        if problems.is_empty() {
+            println!("All files in Resources/config are valid.");
+        } else {
+            println!("{} problem(s) found.", problems.len());
+            std::process::exit(1);
+        };
+        return;
+    };
     let mut config = Config {
         launch_path: "".to_string(),
         language: 0,
@@ -27,6 +76,7 @@ fn main() {
         amount_languages: 0,
         rc_strict_mode: false,
         enable_debug_mode: false,
+        page_music: HashMap::new(),
     };
 
     // Only for macOS app generate.
@@ -47,10 +97,13 @@ fn main() {
     // };
 
     if let Ok(json_value) = read_from_json("Resources/config/Preferences.json") {
-        if let Some(read_config) = Config::from_json_value(&json_value) {
-            config = read_config;
+        // 读取错误会在游戏窗口建立后再次报告，此处只输出到终端
+        match Config::from_json_value(&json_value) {
+            Ok(read_config) => config = read_config,
+            Err(e) => eprintln!("{}", e.in_file("Resources/config/Preferences.json")),
         };
     };
+    cli_options.apply(&mut config);
 
     // Only for macOS app generate.
     // config.launch_path = launch_path;
@@ -60,8 +113,9 @@ fn main() {
         game_text: HashMap::new(),
     };
     if let Ok(json_value) = read_from_json("Resources/config/GameText.json") {
-        if let Some(read_gametext) = GameText::from_json_value(&json_value) {
-            gametext = read_gametext;
+        match GameText::from_json_value(&json_value) {
+            Ok(read_gametext) => gametext = read_gametext,
+            Err(e) => eprintln!("{}", e.in_file("Resources/config/GameText.json")),
         };
     };
     let mut user = User {
@@ -75,14 +129,17 @@ fn main() {
         map_status: vec![],
         settings: HashMap::new(),
         current_level: "".to_string(),
+        currency: 0,
+        endless_status: vec![],
+        current_slot: DEFAULT_SAVE_SLOT.to_string(),
+        save_slot: vec![],
     };
     if !config.login_user_name.is_empty() {
-        if let Ok(json_value) = read_from_json(format!(
-            "Resources/config/user_{}.json",
-            config.login_user_name
-        )) {
-            if let Some(read_user) = User::from_json_value(&json_value) {
-                user = read_user;
+        let user_file = format!("Resources/config/user_{}.json", config.login_user_name);
+        if let Ok(json_value) = read_from_json(&user_file) {
+            match User::from_json_value(&json_value) {
+                Ok(read_user) => user = read_user,
+                Err(e) => eprintln!("{}", e.in_file(&user_file)),
             };
         };
     };
@@ -94,7 +151,7 @@ fn main() {
     let rgba_data = img.into_rgba8();
     let (w, h) = (rgba_data.width(), rgba_data.height());
     let raw_data: Vec<u8> = rgba_data.into_raw();
-    let options = eframe::NativeOptions {
+    let mut options = eframe::NativeOptions {
         centered: true,
         vsync: false,
         viewport: egui::ViewportBuilder::default()
@@ -105,7 +162,7 @@ fn main() {
             }))
             .with_active(true)
             .with_close_button(false)
-            .with_maximized(true)
+            .with_maximized(cli_options.windowed.is_none())
             .with_title(if config.login_user_name.is_empty() {
                 gametext.game_text["debug_game_version"][config.language as usize].clone()
             } else {
@@ -114,11 +171,17 @@ fn main() {
             .with_min_inner_size([1280_f32, 720_f32]),
         ..Default::default()
     };
+    if let Some(size) = cli_options.windowed {
+        options.viewport = options
+            .viewport
+            .with_inner_size(size)
+            .with_min_inner_size([size[0].min(1280_f32), size[1].min(720_f32)]);
+    };
     eframe::run_native(
     "Targeted Vector",
     options,
-    Box::new(|cc: &eframe::CreationContext| -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync>> {
-        let app: App = App::new(cc);
+    Box::new(move |cc: &eframe::CreationContext| -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync>> {
+        let app: App = App::new(cc, cli_options);
         Ok(Box::new(app))
     }),
     ).unwrap();
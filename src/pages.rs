@@ -1,24 +1,38 @@
 //! pages.rs is the core part of the page of the Targeted Vector, mainly the page content.
+use crate::audio::AudioBus;
+use crate::endless::Endless;
 use crate::function::{
     check_file_exists, check_resource_exist, count_files_recursive, create_pretty_json,
-    general_click_feedback, kira_play_wav, list_files_recursive, read_from_json, write_to_json,
-    App, Gun, JsonReadEnemy, Map, Operation, PauseMessage, SeverityLevel, SwitchClickAction,
-    SwitchData, User, UserGunStatus, UserLevelStatus, UserMapStatus, Value,
+    enemy_path_preview, hash_password, list_files_recursive, read_from_json, verify_password,
+    write_to_json, App, CustomRect, Gun, Image, ImageTexture, Instrument, Map, Operation,
+    OperationMessageBox, OperationTargetEnemy, PageData, PauseMessage, Replay, ScrollBackground,
+    SeverityLevel, Switch, SwitchClickAction, SwitchData, Text, User, UserEndlessStatus,
+    UserLevelStatus, UserMapStatus, Value, Variable, DEFAULT_SAVE_SLOT,
 };
+use crate::input::{InputAction, InputBinding, InputBindings};
+use crate::logger::{latest_log, open_with_system};
+use crate::profile::{available_user_name, ProfileArchive, PROFILE_ARCHIVE_EXTENSION};
+use crate::score::star_text;
+use crate::simulation::{Simulation, SimulationEvent, SimulationInput, OPERATION_SIZE};
+use crate::viewport::ScaleMode;
 use chrono::{Local, Timelike};
 use eframe::egui;
 use eframe::epaint::Rounding;
 use egui::{Color32, Frame, PointerButton, Pos2, Shadow, Stroke};
 use rfd::FileDialog;
 use std::{
-    collections::{hash_map, HashMap},
+    collections::HashMap,
     fs,
     path::{Path, PathBuf},
     process::exit,
-    thread,
     vec::Vec,
 };
 impl eframe::App for App {
+    fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        self.gamepad_hook(ctx, raw_input);
+        self.operation_replay_hook(ctx, raw_input);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.update_frame_stats(ctx);
         self.render_resource_list = Vec::new();
@@ -59,6 +73,15 @@ impl eframe::App for App {
                 },
             };
         };
+        self.hot_reload_step();
+        self.launch_page_step();
+        // 严格模式下读取配置文件出错后停留在错误页面，错误页面的资源在启动页面中加载
+        if self.fatal_load_error.is_some()
+            && self.page != "Error"
+            && check_resource_exist(&self.resource_text, "Error_Pages_Reason")
+        {
+            self.switch_page("Error");
+        };
         let game_text = self.game_text.game_text.clone();
         self.update_timer();
         match &*self.page.clone() {
@@ -71,40 +94,32 @@ impl eframe::App for App {
                     self.add_var("debug_resource_list_window", false);
                     self.add_var("debug_render_resource_list_window", false);
                     self.add_var("debug_problem_window", false);
+                    self.add_var("debug_enemy_window", false);
                     self.add_var("cut_to", false);
                     self.add_split_time("0", false);
                     self.add_split_time("fade_animation", false);
                     self.add_split_time("cut_to_animation", false);
                 };
-                let id = self.track_resource(self.resource_rect.clone(), "Background");
-                self.resource_rect[id].size =
+                let id = self.track_resource::<CustomRect>("Background");
+                self.resource_rect.get_mut(id).size =
                     [ctx.available_rect().width(), ctx.available_rect().height()];
-                let mut id = self.track_resource(self.resource_image.clone(), "RC_Logo");
-                let mut id2 = self.track_resource(self.resource_text.clone(), "Powered");
-                let id3 = self.track_resource(self.variables.clone(), "progress");
+                let mut id = self.track_resource::<Image>("RC_Logo");
+                let mut id2 = self.track_resource::<Text>("Powered");
+                let id3 = self.track_resource::<Variable>("progress");
                 if self.var_i("progress") >= 2 && self.var_i("progress") < 4 {
-                    id = self.track_resource(self.resource_image.clone(), "Binder_Logo");
-                    id2 = self.track_resource(self.resource_text.clone(), "Organize");
+                    id = self.track_resource::<Image>("Binder_Logo");
+                    id2 = self.track_resource::<Text>("Organize");
                 } else if self.var_i("progress") >= 4 {
-                    id = self.track_resource(self.resource_image.clone(), "Mouse");
-                    id2 = self.track_resource(self.resource_text.clone(), "Mouse");
+                    id = self.track_resource::<Image>("Mouse");
+                    id2 = self.track_resource::<Text>("Mouse");
                 };
                 egui::CentralPanel::default().show(ctx, |ui| {
                     self.rect(ui, "Background", ctx);
-                    if ui.input(|i| i.key_pressed(egui::Key::Space)) {
+                    if self.action_pressed(ctx, InputAction::Skip) {
                         if self.config.login_user_name.is_empty() {
                             self.switch_page("Login");
                         } else {
-                            if let Ok(json_value) = read_from_json(format!(
-                                "Resources/config/user_{}.json",
-                                self.config.login_user_name
-                            )) {
-                                if let Some(read_user) = User::from_json_value(&json_value) {
-                                    self.login_user_config = read_user;
-                                };
-                            };
-                            self.config.language = self.login_user_config.language;
-                            self.switch_page("Home_Page");
+                            self.login_remembered_user();
                         };
                     };
                     if self.timer.now_time >= 1.0 {
@@ -125,7 +140,7 @@ impl eframe::App for App {
                                         && self.resource_text[id2].rgba[3] == 255
                                         && (self.timer.now_time - self.split_time("0")[0]) >= 4.0
                                     {
-                                        self.variables[id3].value = Value::Int(1);
+                                        self.variables.get_mut(id3).value = Value::Int(1);
                                         self.add_split_time("1", false);
                                     };
                                 }
@@ -134,7 +149,7 @@ impl eframe::App for App {
                                         && self.resource_text[id2].rgba[3] == 0
                                         && (self.timer.now_time - self.split_time("1")[0]) >= 3.0
                                     {
-                                        self.variables[id3].value = Value::Int(2);
+                                        self.variables.get_mut(id3).value = Value::Int(2);
                                         self.add_split_time("2", false);
                                     };
                                 }
@@ -143,7 +158,7 @@ impl eframe::App for App {
                                         && self.resource_text[id2].rgba[3] == 255
                                         && (self.timer.now_time - self.split_time("2")[0]) >= 2.0
                                     {
-                                        self.variables[id3].value = Value::Int(3);
+                                        self.variables.get_mut(id3).value = Value::Int(3);
                                         self.add_split_time("3", false);
                                     };
                                 }
@@ -152,7 +167,7 @@ impl eframe::App for App {
                                         && self.resource_text[id2].rgba[3] == 0
                                         && (self.timer.now_time - self.split_time("3")[0]) >= 3.0
                                     {
-                                        self.variables[id3].value = Value::Int(4);
+                                        self.variables.get_mut(id3).value = Value::Int(4);
                                         self.add_split_time("4", false);
                                     };
                                 }
@@ -161,7 +176,7 @@ impl eframe::App for App {
                                         && self.resource_text[id2].rgba[3] == 255
                                         && (self.timer.now_time - self.split_time("4")[0]) >= 2.0
                                     {
-                                        self.variables[id3].value = Value::Int(5);
+                                        self.variables.get_mut(id3).value = Value::Int(5);
                                         self.add_split_time("5", false);
                                     };
                                 }
@@ -173,18 +188,7 @@ impl eframe::App for App {
                                         if self.config.login_user_name.is_empty() {
                                             self.switch_page("Login");
                                         } else {
-                                            if let Ok(json_value) = read_from_json(format!(
-                                                "Resources/config/user_{}.json",
-                                                self.config.login_user_name
-                                            )) {
-                                                if let Some(read_user) =
-                                                    User::from_json_value(&json_value)
-                                                {
-                                                    self.login_user_config = read_user;
-                                                };
-                                            };
-                                            self.config.language = self.login_user_config.language;
-                                            self.switch_page("Home_Page");
+                                            self.login_remembered_user();
                                         };
                                     };
                                 }
@@ -197,7 +201,7 @@ impl eframe::App for App {
                                 && self.timer.now_time - self.split_time("fade_animation")[0]
                                     >= self.vertrefresh
                             {
-                                self.resource_image[id].alpha -= 5;
+                                self.resource_image.get_mut(id).alpha -= 5;
                                 self.add_split_time("fade_animation", true);
                             };
                             if self.var_i("progress") != 0
@@ -207,7 +211,7 @@ impl eframe::App for App {
                                 && self.timer.now_time - self.split_time("fade_animation")[0]
                                     >= self.vertrefresh
                             {
-                                self.resource_text[id2].rgba[3] -= 5;
+                                self.resource_text.get_mut(id2).rgba[3] -= 5;
                                 self.add_split_time("fade_animation", true);
                             };
                             if self.var_i("progress") != 1
@@ -217,7 +221,7 @@ impl eframe::App for App {
                                 && self.timer.now_time - self.split_time("fade_animation")[0]
                                     >= self.vertrefresh
                             {
-                                self.resource_image[id].alpha += 5;
+                                self.resource_image.get_mut(id).alpha += 5;
                                 self.add_split_time("fade_animation", true);
                             };
                             if self.var_i("progress") != 1
@@ -227,7 +231,7 @@ impl eframe::App for App {
                                 && self.timer.now_time - self.split_time("fade_animation")[0]
                                     >= self.vertrefresh
                             {
-                                self.resource_text[id2].rgba[3] += 5;
+                                self.resource_text.get_mut(id2).rgba[3] += 5;
                                 self.add_split_time("fade_animation", true);
                             };
                         }
@@ -236,8 +240,7 @@ impl eframe::App for App {
                 });
             }
             "Login" => {
-                let scroll_background =
-                    self.track_resource(self.resource_scroll_background.clone(), "ScrollWallpaper");
+                let scroll_background = self.track_resource::<ScrollBackground>("ScrollWallpaper");
                 if !self.check_updated(&self.page.clone()) {
                     self.add_var("account_name_str", "".to_string());
                     self.add_var("account_password_str", "".to_string());
@@ -252,23 +255,24 @@ impl eframe::App for App {
                     self.add_var("login_enable_name_error_message", false);
                     self.add_var("login_enable_password_error_message", false);
                     self.add_var("last_window_size", vec![1280.0, 720.0]);
-                    self.resource_scroll_background[scroll_background].resume_point =
-                        ctx.available_rect().width();
+                    self.resource_scroll_background
+                        .get_mut(scroll_background)
+                        .resume_point = ctx.available_rect().width();
                     for i in 0..self.resource_scroll_background[scroll_background]
                         .image_name
                         .len()
                     {
-                        let id = self.track_resource(
-                            self.resource_image.clone(),
+                        let id = self.track_resource::<Image>(
                             &self.resource_scroll_background[scroll_background].image_name[i]
                                 .clone(),
                         );
-                        self.resource_image[id].image_size =
+                        self.resource_image.get_mut(id).image_size =
                             [ctx.available_rect().width(), ctx.available_rect().height()];
-                        self.resource_image[id].origin_position[0] =
+                        self.resource_image.get_mut(id).origin_position[0] =
                             i as f32 * self.resource_image[id].image_size[0];
-                        self.resource_scroll_background[scroll_background].boundary =
-                            -ctx.available_rect().width();
+                        self.resource_scroll_background
+                            .get_mut(scroll_background)
+                            .boundary = -ctx.available_rect().width();
                     };
                 };
                 let mut input1 = self.var_s("account_name_str");
@@ -282,28 +286,29 @@ impl eframe::App for App {
                         || self.var_decode_f(self.clone().var_v("last_window_size")[1].clone())
                             != ctx.available_rect().height()
                     {
-                        self.resource_scroll_background[scroll_background].resume_point =
-                            ctx.available_rect().width();
+                        self.resource_scroll_background
+                            .get_mut(scroll_background)
+                            .resume_point = ctx.available_rect().width();
                         for i in 0..self.resource_scroll_background[scroll_background]
                             .image_name
                             .len()
                         {
-                            let id = self.track_resource(
-                                self.resource_image.clone(),
+                            let id = self.track_resource::<Image>(
                                 &self.resource_scroll_background[scroll_background].image_name[i]
                                     .clone(),
                             );
-                            self.resource_image[id].image_size =
+                            self.resource_image.get_mut(id).image_size =
                                 [ctx.available_rect().width(), ctx.available_rect().height()];
-                            self.resource_image[id].origin_position[0] =
+                            self.resource_image.get_mut(id).origin_position[0] =
                                 i as f32 * self.resource_image[id].image_size[0];
-                            self.resource_scroll_background[scroll_background].boundary =
-                                -ctx.available_rect().width();
+                            self.resource_scroll_background
+                                .get_mut(scroll_background)
+                                .boundary = -ctx.available_rect().width();
                         }
                     };
                     self.scroll_background(ui, "ScrollWallpaper", ctx);
-                    let id = self.track_resource(self.resource_text.clone(), "Date");
-                    self.resource_text[id].text_content = Local::now()
+                    let id = self.track_resource::<Text>("Date");
+                    self.resource_text.get_mut(id).text_content = Local::now()
                         .format(&format!(
                             "{} {}",
                             &game_text["date"][self.config.language as usize],
@@ -321,15 +326,15 @@ impl eframe::App for App {
                             "Sunday" => "日",
                             _ => "一",
                         };
-                        self.resource_text[id].text_content = format!(
+                        self.resource_text.get_mut(id).text_content = format!(
                             "{} {}{}",
                             Local::now().format(&game_text["date"][self.config.language as usize]),
                             game_text["week"][self.config.language as usize],
                             week
                         );
                     }
-                    let id2 = self.track_resource(self.resource_text.clone(), "Time");
-                    self.resource_text[id2].text_content = Local::now()
+                    let id2 = self.track_resource::<Text>("Time");
+                    self.resource_text.get_mut(id2).text_content = Local::now()
                         .format(&game_text["time"][self.config.language as usize])
                         .to_string();
                     self.text(ui, "Date", ctx);
@@ -406,14 +411,133 @@ impl eframe::App for App {
                                         .clone(),
                                 );
                             };
+                            if !self.var_b("open_reg_window")
+                                && self.profile_import.is_none()
+                                && ui
+                                    .button(
+                                        game_text["import_profile"][self.config.language as usize]
+                                            .clone(),
+                                    )
+                                    .clicked()
+                            {
+                                self.general_click_feedback();
+                                if let Some(path) = FileDialog::new()
+                                    .set_title(
+                                        &game_text["choose_profile"][self.config.language as usize]
+                                            .clone(),
+                                    )
+                                    .add_filter("", &[PROFILE_ARCHIVE_EXTENSION])
+                                    .pick_file()
+                                {
+                                    match ProfileArchive::read(&path) {
+                                        Ok(archive) => {
+                                            let name = archive.user.name.clone();
+                                            if check_file_exists(format!(
+                                                "Resources/config/user_{}.json",
+                                                name
+                                            )) {
+                                                // 重名时等待用户选择覆盖或改名导入
+                                                self.profile_import = Some(archive);
+                                            } else if let Err(e) = archive.install(&name) {
+                                                self.problem_report(
+                                                    &game_text["error_profile_import"]
+                                                        [self.config.language as usize]
+                                                        .clone(),
+                                                    SeverityLevel::MildWarning,
+                                                    &e.to_string(),
+                                                );
+                                            } else {
+                                                input1 = name;
+                                            };
+                                        }
+                                        Err(e) => {
+                                            self.problem_report(
+                                                &game_text["error_profile_import"]
+                                                    [self.config.language as usize]
+                                                    .clone(),
+                                                SeverityLevel::MildWarning,
+                                                &e.to_string(),
+                                            );
+                                        }
+                                    };
+                                };
+                            };
+                        });
+                    let mut profile_import_open = self.profile_import.is_some();
+                    egui::Window::new("ProfileImport")
+                        .open(&mut profile_import_open)
+                        .frame(self.frame)
+                        .resizable(false)
+                        .title_bar(false)
+                        .pivot(egui::Align2::CENTER_CENTER)
+                        .fixed_pos(egui::Pos2::new(
+                            ctx.available_rect().width() / 2_f32,
+                            ctx.available_rect().height() / 2_f32,
+                        ))
+                        .show(ctx, |ui| {
+                            ui.vertical_centered(|ui| {
+                                let Some(archive) = self.profile_import.clone() else {
+                                    return;
+                                };
+                                ui.heading(
+                                    game_text["profile_import_conflict"]
+                                        [self.config.language as usize]
+                                        .clone(),
+                                );
+                                ui.separator();
+                                ui.label(archive.user.name.clone());
+                                let mut install_name = None;
+                                if ui
+                                    .button(
+                                        game_text["profile_import_overwrite"]
+                                            [self.config.language as usize]
+                                            .clone(),
+                                    )
+                                    .double_clicked()
+                                {
+                                    install_name = Some(archive.user.name.clone());
+                                };
+                                if ui
+                                    .button(format!(
+                                        "{}{}",
+                                        game_text["profile_import_rename"]
+                                            [self.config.language as usize],
+                                        available_user_name(&archive.user.name)
+                                    ))
+                                    .clicked()
+                                {
+                                    install_name = Some(available_user_name(&archive.user.name));
+                                };
+                                if ui
+                                    .button(
+                                        game_text["cancel"][self.config.language as usize].clone(),
+                                    )
+                                    .clicked()
+                                {
+                                    self.general_click_feedback();
+                                    self.profile_import = None;
+                                };
+                                if let Some(name) = install_name {
+                                    self.general_click_feedback();
+                                    self.profile_import = None;
+                                    if let Err(e) = archive.install(&name) {
+                                        self.problem_report(
+                                            &game_text["error_profile_import"]
+                                                [self.config.language as usize]
+                                                .clone(),
+                                            SeverityLevel::MildWarning,
+                                            &e.to_string(),
+                                        );
+                                    } else {
+                                        input1 = name;
+                                    };
+                                };
+                            });
                         });
-                    let no_window = !self.var_b("open_reg_window");
+                    let no_window = !self.var_b("open_reg_window") && self.profile_import.is_none();
                     if self.switch("Shutdown", ui, ctx, no_window, true)[0] != 5 {
-                        write_to_json(
-                            "Resources/config/Preferences.json",
-                            self.config.to_json_value(),
-                        )
-                        .unwrap();
+                        self.save_preferences();
+                        self.logger.log("INFO", "Targeted Vector exited");
                         exit(0);
                     };
                     if self.switch("Login", ui, ctx, no_window, true)[0] != 5 {
@@ -428,76 +552,88 @@ impl eframe::App for App {
                             "Resources/config/user_{}.json",
                             input1.replace(" ", "").replace("/", "").replace("\\", "")
                         )) {
-                            let mut user = User {
-                                name: "".to_string(),
-                                password: "".to_string(),
-                                language: 0,
-                                wallpaper: "".to_string(),
-                                current_map: "".to_string(),
-                                level_status: vec![],
-                                gun_status: vec![],
-                                map_status: vec![],
-                                settings: hash_map::HashMap::new(),
-                                current_level: "".to_string(),
-                            };
-                            if let Ok(json_value) = read_from_json(format!(
+                            let user_file = format!(
                                 "Resources/config/user_{}.json",
                                 input1.replace(" ", "").replace("/", "").replace("\\", "")
-                            )) {
-                                if let Some(read_user) = User::from_json_value(&json_value) {
-                                    user = read_user;
-                                }
-                            };
-                            if user.password == input2 {
-                                self.config.login_user_name = user.name;
-                                self.config.language = user.language;
-                                input1 = "".to_string();
-                                input2 = "".to_string();
-                                self.timer.start_time = self.timer.total_time;
-                                self.update_timer();
-                                if check_resource_exist(
-                                    self.timer.split_time.clone(),
-                                    "dock_animation",
-                                ) {
-                                    self.add_split_time("dock_animation", true);
-                                    self.add_split_time("title_animation", true);
-                                };
-                                self.switch_page("Home_Page");
-                                if let Ok(json_value) = read_from_json(format!(
-                                    "Resources/config/user_{}.json",
-                                    self.config.login_user_name
-                                )) {
-                                    if let Some(read_user) = User::from_json_value(&json_value) {
-                                        self.login_user_config = read_user;
+                            );
+                            // 用户文件无法读取时拒绝登录，错误已由load_config报告
+                            if let Some(mut user) =
+                                self.load_config(&user_file, User::from_json_value)
+                            {
+                                let password_status = verify_password(&user.password, &input2);
+                                if password_status[0] {
+                                    if password_status[1] {
+                                        // 旧版明文密码在登录成功后改为散列存储，失败时保留明文，下次登录再试
+                                        if let Err(e) =
+                                            hash_password(&input2).and_then(|password_hash| {
+                                                user.password = password_hash;
+                                                write_to_json(&user_file, user.to_json_value())
+                                            })
+                                        {
+                                            self.problem_report(
+                                                &format!(
+                                                    "{}{}",
+                                                    game_text["error_rehash_password"]
+                                                        [self.config.language as usize]
+                                                        .clone(),
+                                                    user_file
+                                                ),
+                                                SeverityLevel::MildWarning,
+                                                &format!("{:#}", e),
+                                            );
+                                        };
                                     };
-                                };
-                                if check_resource_exist(
-                                    self.resource_image_texture.clone(),
-                                    "Home_Wallpaper",
-                                ) {
-                                    self.add_image_texture(
-                                        "Home_Wallpaper",
-                                        &self.login_user_config.wallpaper.clone(),
-                                        [false, false],
-                                        false,
-                                        ctx,
-                                    );
-                                    let id = self.track_resource(
-                                        self.resource_image_texture.clone(),
-                                        "Home_Wallpaper",
-                                    );
-                                    let id2 = self.track_resource(
-                                        self.resource_image.clone(),
+                                    self.set_login_user(&user.name);
+                                    self.config.language = user.language;
+                                    input1 = "".to_string();
+                                    input2 = "".to_string();
+                                    self.timer.start_time = self.timer.total_time;
+                                    self.update_timer();
+                                    if check_resource_exist(
+                                        &self.timer.split_time,
+                                        "dock_animation",
+                                    ) {
+                                        self.add_split_time("dock_animation", true);
+                                        self.add_split_time("title_animation", true);
+                                    };
+                                    self.switch_page("Home_Page");
+                                    if let Ok(json_value) = read_from_json(format!(
+                                        "Resources/config/user_{}.json",
+                                        self.config.login_user_name
+                                    )) {
+                                        if let Some(read_user) = self.report_load_result(
+                                            &format!(
+                                                "Resources/config/user_{}.json",
+                                                self.config.login_user_name
+                                            ),
+                                            User::from_json_value(&json_value),
+                                        ) {
+                                            self.login_user_config = read_user;
+                                        };
+                                    };
+                                    if check_resource_exist(
+                                        &self.resource_image_texture,
                                         "Home_Wallpaper",
-                                    );
-                                    self.resource_image[id2].image_texture =
-                                        self.resource_image_texture[id].texture.clone();
+                                    ) {
+                                        self.add_image_texture(
+                                            "Home_Wallpaper",
+                                            &self.login_user_config.wallpaper.clone(),
+                                            [false, false],
+                                            false,
+                                            ctx,
+                                        );
+                                        let id =
+                                            self.track_resource::<ImageTexture>("Home_Wallpaper");
+                                        let id2 = self.track_resource::<Image>("Home_Wallpaper");
+                                        self.resource_image.get_mut(id2).image_texture =
+                                            self.resource_image_texture[id].texture.clone();
+                                    };
                                 };
+                                self.modify_var(
+                                    "login_enable_password_error_message",
+                                    !password_status[0],
+                                );
                             };
-                            self.modify_var(
-                                "login_enable_password_error_message",
-                                user.password != input2,
-                            );
                         };
                     };
                     if self.switch("Register", ui, ctx, no_window, true)[0] != 5 {
@@ -603,7 +739,7 @@ impl eframe::App for App {
                                         )
                                         .clicked()
                                     {
-                                        general_click_feedback();
+                                        self.general_click_feedback();
                                         self.modify_var("open_reg_window", false);
                                     };
                                     if ui
@@ -613,7 +749,7 @@ impl eframe::App for App {
                                         )
                                         .clicked()
                                     {
-                                        general_click_feedback();
+                                        self.general_click_feedback();
                                         self.modify_var("reg_enable_name_error_message", false);
                                         self.modify_var("reg_enable_password_error_message", false);
                                         self.modify_var("reg_status", Value::UInt(1));
@@ -626,7 +762,7 @@ impl eframe::App for App {
                                         )
                                         .clicked()
                                     {
-                                        general_click_feedback();
+                                        self.general_click_feedback();
                                         self.modify_var("reg_status", Value::UInt(0));
                                     };
                                     if ui
@@ -636,7 +772,7 @@ impl eframe::App for App {
                                         )
                                         .clicked()
                                     {
-                                        general_click_feedback();
+                                        self.general_click_feedback();
                                         self.modify_var(
                                             "reg_enable_password_error_message",
                                             input4 != input5,
@@ -670,37 +806,57 @@ impl eframe::App for App {
                                                 .replace("\\", "")
                                                 .is_empty()
                                         {
-                                            let hashmap = HashMap::new();
-                                            let user_data = User {
-                                                name: input3
+                                            let user_file = format!(
+                                                "Resources/config/user_{}.json",
+                                                input3
                                                     .replace(" ", "")
                                                     .replace("/", "")
                                                     .replace("\\", "")
-                                                    .clone(),
-                                                password: input4.clone(),
-                                                language: self.config.language,
-                                                wallpaper: "Resources/assets/images/wallpaper.png"
-                                                    .to_string(),
-                                                current_map: "map_tutorial".to_string(),
-                                                gun_status: Vec::new(),
-                                                level_status: Vec::new(),
-                                                map_status: Vec::new(),
-                                                settings: hashmap,
-                                                current_level: "".to_string(),
-                                            }
-                                            .to_json_value();
-                                            create_pretty_json(
-                                                format!(
-                                                    "Resources/config/user_{}.json",
-                                                    input3
-                                                        .replace(" ", "")
-                                                        .replace("/", "")
-                                                        .replace("\\", "")
-                                                ),
-                                                user_data,
-                                            )
-                                            .unwrap();
-                                            self.modify_var("reg_status", Value::UInt(2));
+                                            );
+                                            // 生成散列或写入失败时留在注册窗口，可以再次尝试
+                                            let result =
+                                                hash_password(&input4).and_then(|password_hash| {
+                                                    let hashmap = HashMap::new();
+                                                    let user_data = User {
+                                                        name: input3
+                                                            .replace(" ", "")
+                                                            .replace("/", "")
+                                                            .replace("\\", "")
+                                                            .clone(),
+                                                        password: password_hash,
+                                                        language: self.config.language,
+                                                        wallpaper:
+                                                            "Resources/assets/images/wallpaper.png"
+                                                                .to_string(),
+                                                        current_map: "map_tutorial".to_string(),
+                                                        gun_status: Vec::new(),
+                                                        level_status: Vec::new(),
+                                                        map_status: Vec::new(),
+                                                        settings: hashmap,
+                                                        current_level: "".to_string(),
+                                                        currency: 0,
+                                                        endless_status: vec![],
+                                                        current_slot: DEFAULT_SAVE_SLOT.to_string(),
+                                                        save_slot: vec![],
+                                                    }
+                                                    .to_json_value();
+                                                    create_pretty_json(&user_file, user_data)
+                                                });
+                                            if let Err(e) = result {
+                                                self.problem_report(
+                                                    &format!(
+                                                        "{}{}",
+                                                        game_text["error_register_user"]
+                                                            [self.config.language as usize]
+                                                            .clone(),
+                                                        user_file
+                                                    ),
+                                                    SeverityLevel::MildWarning,
+                                                    &format!("{:#}", e),
+                                                );
+                                            } else {
+                                                self.modify_var("reg_status", Value::UInt(2));
+                                            };
                                         };
                                     };
                                     if self.var_b("reg_enable_password_error_message") {
@@ -727,7 +883,7 @@ impl eframe::App for App {
                                         )
                                         .clicked()
                                     {
-                                        general_click_feedback();
+                                        self.general_click_feedback();
                                         self.modify_var("reg_status", Value::UInt(0));
                                     };
                                     if ui
@@ -738,7 +894,7 @@ impl eframe::App for App {
                                         )
                                         .clicked()
                                     {
-                                        general_click_feedback();
+                                        self.general_click_feedback();
                                         input1 = input3
                                             .replace(" ", "")
                                             .replace("/", "")
@@ -770,6 +926,21 @@ impl eframe::App for App {
                         .settings
                         .entry("enable_timer".to_string())
                         .or_insert("false".to_string());
+                    self.login_user_config
+                        .settings
+                        .entry("scale_mode".to_string())
+                        .or_insert(ScaleMode::Fit.setting().to_string());
+                    self.login_user_config
+                        .settings
+                        .entry("gamepad_aim_assist".to_string())
+                        .or_insert("true".to_string());
+                    if let Ok(mut audio) = self.audio.lock() {
+                        audio.load_volume(&mut self.login_user_config.settings);
+                    };
+                    self.input_bindings =
+                        InputBindings::from_settings(&self.login_user_config.settings);
+                    self.input_bindings
+                        .save(&mut self.login_user_config.settings);
                     self.add_image_texture(
                         "Home_Wallpaper",
                         &self.login_user_config.wallpaper.clone(),
@@ -804,22 +975,22 @@ impl eframe::App for App {
                         &format!("{}_Title", self.login_user_config.language),
                         ctx,
                     );
-                    let id = self.track_resource(
-                        self.resource_image.clone(),
-                        &format!("{}_Title", self.login_user_config.language),
-                    );
+                    let id = self.track_resource::<Image>(&format!(
+                        "{}_Title",
+                        self.login_user_config.language
+                    ));
                     if self.timer.now_time - self.split_time("title_animation")[0]
                         >= self.vertrefresh
                     {
                         self.add_split_time("title_animation", true);
                         if self.var_b("title_float_status") {
                             if self.resource_image[id].origin_position[1] < 5_f32 {
-                                self.resource_image[id].origin_position[1] += 0.05;
+                                self.resource_image.get_mut(id).origin_position[1] += 0.05;
                             } else {
                                 self.modify_var("title_float_status", false);
                             };
                         } else if self.resource_image[id].origin_position[1] > -5_f32 {
-                            self.resource_image[id].origin_position[1] -= 0.05;
+                            self.resource_image.get_mut(id).origin_position[1] -= 0.05;
                         } else {
                             self.modify_var("title_float_status", true);
                         };
@@ -912,7 +1083,7 @@ impl eframe::App for App {
                                 if enable_timer
                                     != (self.login_user_config.settings["enable_timer"] == "true")
                                 {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                 };
                                 self.login_user_config
                                     .settings
@@ -925,6 +1096,159 @@ impl eframe::App for App {
                                         }
                                     });
                             });
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::WidgetText::from(
+                                        game_text["game_gamepad_aim_assist"]
+                                            [self.login_user_config.language as usize]
+                                            .clone()
+                                            .to_string(),
+                                    )
+                                    .text_style(egui::TextStyle::Heading),
+                                );
+                                ui.separator();
+                                let mut aim_assist =
+                                    self.login_user_config.settings["gamepad_aim_assist"] == "true";
+                                if ui.checkbox(&mut aim_assist, "").changed() {
+                                    self.general_click_feedback();
+                                    self.login_user_config.settings.insert(
+                                        "gamepad_aim_assist".to_string(),
+                                        aim_assist.to_string(),
+                                    );
+                                };
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::WidgetText::from(
+                                        game_text["game_scale_mode"]
+                                            [self.login_user_config.language as usize]
+                                            .clone()
+                                            .to_string(),
+                                    )
+                                    .text_style(egui::TextStyle::Heading),
+                                );
+                                ui.separator();
+                                let scale_mode = self.scale_mode();
+                                egui::ComboBox::from_id_salt("scale_mode")
+                                    .selected_text(
+                                        game_text[scale_mode.game_text_key()]
+                                            [self.login_user_config.language as usize]
+                                            .clone(),
+                                    )
+                                    .width(200_f32)
+                                    .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+                                    .show_ui(ui, |ui| {
+                                        for mode in ScaleMode::ALL {
+                                            if ui
+                                                .selectable_label(
+                                                    mode == scale_mode,
+                                                    game_text[mode.game_text_key()]
+                                                        [self.login_user_config.language as usize]
+                                                        .clone(),
+                                                )
+                                                .clicked()
+                                            {
+                                                self.general_click_feedback();
+                                                self.login_user_config.settings.insert(
+                                                    "scale_mode".to_string(),
+                                                    mode.setting().to_string(),
+                                                );
+                                            };
+                                        }
+                                    });
+                            });
+                            for bus in AudioBus::ALL {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        egui::WidgetText::from(
+                                            game_text[bus.game_text_key()]
+                                                [self.login_user_config.language as usize]
+                                                .clone()
+                                                .to_string(),
+                                        )
+                                        .text_style(egui::TextStyle::Heading),
+                                    );
+                                    ui.separator();
+                                    if let Ok(mut audio) = self.audio.lock() {
+                                        let mut volume = audio.volume(bus);
+                                        if ui
+                                            .add(egui::Slider::new(&mut volume, 0.0..=1.0))
+                                            .changed()
+                                        {
+                                            audio.set_volume(bus, volume);
+                                            audio.save_volume(&mut self.login_user_config.settings);
+                                        };
+                                    };
+                                });
+                            }
+                            // 等待新按键时捕获本帧的第一个输入，按Esc取消
+                            if let Some(action) = self.input_rebinding {
+                                if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                                    self.input_rebinding = None;
+                                } else if let Some(binding) = ctx.input(InputBinding::capture) {
+                                    self.input_bindings.binding.insert(action, vec![binding]);
+                                    self.input_bindings
+                                        .save(&mut self.login_user_config.settings);
+                                    self.input_rebinding = None;
+                                    self.general_click_feedback();
+                                };
+                            };
+                            for action in InputAction::ALL {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        egui::WidgetText::from(
+                                            game_text[action.game_text_key()]
+                                                [self.login_user_config.language as usize]
+                                                .clone()
+                                                .to_string(),
+                                        )
+                                        .text_style(egui::TextStyle::Heading),
+                                    );
+                                    ui.separator();
+                                    if self.input_rebinding == Some(action) {
+                                        ui.label(
+                                            game_text["game_rebind_waiting"]
+                                                [self.login_user_config.language as usize]
+                                                .clone(),
+                                        );
+                                    } else {
+                                        ui.label(
+                                            self.input_bindings
+                                                .get(action)
+                                                .iter()
+                                                .map(|x| x.setting())
+                                                .collect::<Vec<String>>()
+                                                .join(", "),
+                                        );
+                                        if ui
+                                            .button(
+                                                game_text["game_rebind"]
+                                                    [self.login_user_config.language as usize]
+                                                    .clone(),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.general_click_feedback();
+                                            self.input_rebinding = Some(action);
+                                        };
+                                        if ui
+                                            .button(
+                                                game_text["return_to_default"]
+                                                    [self.login_user_config.language as usize]
+                                                    .clone(),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.general_click_feedback();
+                                            self.input_bindings
+                                                .binding
+                                                .insert(action, action.default_binding());
+                                            self.input_bindings
+                                                .save(&mut self.login_user_config.settings);
+                                        };
+                                    };
+                                });
+                            }
                             ui.horizontal(|ui| {
                                 ui.label(
                                     egui::WidgetText::from(
@@ -944,7 +1268,7 @@ impl eframe::App for App {
                                     )
                                     .clicked()
                                 {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                     if let Some(path) = FileDialog::new()
                                         .set_title(
                                             &game_text["choose_image"]
@@ -973,15 +1297,10 @@ impl eframe::App for App {
                                             false,
                                             ctx,
                                         );
-                                        let id = self.track_resource(
-                                            self.resource_image_texture.clone(),
-                                            "Home_Wallpaper",
-                                        );
-                                        let id2 = self.track_resource(
-                                            self.resource_image.clone(),
-                                            "Home_Wallpaper",
-                                        );
-                                        self.resource_image[id2].image_texture =
+                                        let id =
+                                            self.track_resource::<ImageTexture>("Home_Wallpaper");
+                                        let id2 = self.track_resource::<Image>("Home_Wallpaper");
+                                        self.resource_image.get_mut(id2).image_texture =
                                             self.resource_image_texture[id].texture.clone();
                                         self.login_user_config.wallpaper = format!(
                                             "Resources/assets/images/{}_new_wallpaper.png",
@@ -997,7 +1316,7 @@ impl eframe::App for App {
                                     )
                                     .clicked()
                                 {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                     self.add_image_texture(
                                         "Home_Wallpaper",
                                         "Resources/assets/images/wallpaper.png",
@@ -1005,15 +1324,9 @@ impl eframe::App for App {
                                         false,
                                         ctx,
                                     );
-                                    let id = self.track_resource(
-                                        self.resource_image_texture.clone(),
-                                        "Home_Wallpaper",
-                                    );
-                                    let id2 = self.track_resource(
-                                        self.resource_image.clone(),
-                                        "Home_Wallpaper",
-                                    );
-                                    self.resource_image[id2].image_texture =
+                                    let id = self.track_resource::<ImageTexture>("Home_Wallpaper");
+                                    let id2 = self.track_resource::<Image>("Home_Wallpaper");
+                                    self.resource_image.get_mut(id2).image_texture =
                                         self.resource_image_texture[id].texture.clone();
                                     self.login_user_config.wallpaper =
                                         "Resources/assets/images/wallpaper.png".to_string();
@@ -1022,7 +1335,7 @@ impl eframe::App for App {
                             ui.horizontal(|ui| {
                                 ui.label(
                                     egui::WidgetText::from(
-                                        game_text["game_logout_account"]
+                                        game_text["game_save_slot"]
                                             [self.login_user_config.language as usize]
                                             .clone()
                                             .to_string(),
@@ -1030,15 +1343,54 @@ impl eframe::App for App {
                                     .text_style(egui::TextStyle::Heading),
                                 );
                                 ui.separator();
+                                let mut switch_to = None;
+                                egui::ComboBox::from_id_salt("save_slot")
+                                    .selected_text(self.login_user_config.current_slot.clone())
+                                    .width(200_f32)
+                                    .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+                                    .show_ui(ui, |ui| {
+                                        for slot_name in self.login_user_config.save_slot_names() {
+                                            if ui
+                                                .selectable_label(
+                                                    slot_name
+                                                        == self.login_user_config.current_slot,
+                                                    slot_name.clone(),
+                                                )
+                                                .clicked()
+                                            {
+                                                switch_to = Some(slot_name);
+                                            };
+                                        }
+                                    });
                                 if ui
                                     .button(
-                                        game_text["game_logout_account"]
+                                        game_text["game_new_save_slot"]
                                             [self.login_user_config.language as usize]
-                                            .clone()
-                                            .to_string(),
+                                            .clone(),
                                     )
                                     .clicked()
                                 {
+                                    let slot_names = self.login_user_config.save_slot_names();
+                                    switch_to = (1..)
+                                        .map(|x: u32| x.to_string())
+                                        .find(|x| !slot_names.contains(x));
+                                };
+                                // 删除当前存档槽前先切换到另一个存档槽，至少保留一个
+                                if !self.login_user_config.save_slot.is_empty()
+                                    && ui
+                                        .button(
+                                            game_text["game_delete_save_slot"]
+                                                [self.login_user_config.language as usize]
+                                                .clone(),
+                                        )
+                                        .double_clicked()
+                                {
+                                    self.general_click_feedback();
+                                    let deleted = self.login_user_config.current_slot.clone();
+                                    let next =
+                                        self.login_user_config.save_slot[0].slot_name.clone();
+                                    self.login_user_config.switch_save_slot(&next);
+                                    self.login_user_config.delete_save_slot(&deleted);
                                     write_to_json(
                                         format!(
                                             "Resources/config/user_{}.json",
@@ -1047,15 +1399,103 @@ impl eframe::App for App {
                                         self.login_user_config.to_json_value(),
                                     )
                                     .unwrap();
-                                    self.config.login_user_name = "".to_string();
-                                    write_to_json(
-                                        "Resources/config/Preferences.json",
-                                        self.config.to_json_value(),
-                                    )
-                                    .unwrap();
-                                    exit(0);
                                 };
-                            });
+                                if let Some(slot_name) = switch_to {
+                                    self.general_click_feedback();
+                                    if self.login_user_config.switch_save_slot(&slot_name) {
+                                        write_to_json(
+                                            format!(
+                                                "Resources/config/user_{}.json",
+                                                self.config.login_user_name
+                                            ),
+                                            self.login_user_config.to_json_value(),
+                                        )
+                                        .unwrap();
+                                    };
+                                };
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::WidgetText::from(
+                                        game_text["game_export_profile"]
+                                            [self.login_user_config.language as usize]
+                                            .clone()
+                                            .to_string(),
+                                    )
+                                    .text_style(egui::TextStyle::Heading),
+                                );
+                                ui.separator();
+                                if ui
+                                    .button(
+                                        game_text["game_export_profile"]
+                                            [self.login_user_config.language as usize]
+                                            .clone(),
+                                    )
+                                    .clicked()
+                                {
+                                    self.general_click_feedback();
+                                    if let Some(path) = FileDialog::new()
+                                        .set_title(
+                                            &game_text["game_export_profile"]
+                                                [self.login_user_config.language as usize]
+                                                .clone(),
+                                        )
+                                        .set_file_name(&format!(
+                                            "{}.{}",
+                                            self.login_user_config.name, PROFILE_ARCHIVE_EXTENSION
+                                        ))
+                                        .add_filter("", &[PROFILE_ARCHIVE_EXTENSION])
+                                        .save_file()
+                                    {
+                                        if let Err(e) =
+                                            ProfileArchive::from_user(&self.login_user_config)
+                                                .export(&path)
+                                        {
+                                            self.problem_report(
+                                                &game_text["error_profile_export"]
+                                                    [self.login_user_config.language as usize]
+                                                    .clone(),
+                                                SeverityLevel::MildWarning,
+                                                &e.to_string(),
+                                            );
+                                        };
+                                    };
+                                };
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::WidgetText::from(
+                                        game_text["game_logout_account"]
+                                            [self.login_user_config.language as usize]
+                                            .clone()
+                                            .to_string(),
+                                    )
+                                    .text_style(egui::TextStyle::Heading),
+                                );
+                                ui.separator();
+                                if ui
+                                    .button(
+                                        game_text["game_logout_account"]
+                                            [self.login_user_config.language as usize]
+                                            .clone()
+                                            .to_string(),
+                                    )
+                                    .clicked()
+                                {
+                                    write_to_json(
+                                        format!(
+                                            "Resources/config/user_{}.json",
+                                            self.config.login_user_name
+                                        ),
+                                        self.login_user_config.to_json_value(),
+                                    )
+                                    .unwrap();
+                                    self.set_login_user("");
+                                    self.save_preferences();
+                                    self.logger.log("INFO", "Targeted Vector exited");
+                                    exit(0);
+                                };
+                            });
                             ui.horizontal(|ui| {
                                 ui.label(
                                     egui::WidgetText::from(
@@ -1081,12 +1521,9 @@ impl eframe::App for App {
                                         self.config.login_user_name
                                     ))
                                     .unwrap();
-                                    self.config.login_user_name = "".to_string();
-                                    write_to_json(
-                                        "Resources/config/Preferences.json",
-                                        self.config.to_json_value(),
-                                    )
-                                    .unwrap();
+                                    self.set_login_user("");
+                                    self.save_preferences();
+                                    self.logger.log("INFO", "Targeted Vector exited");
                                     exit(0);
                                 };
                             });
@@ -1095,6 +1532,135 @@ impl eframe::App for App {
                     self.message_box_display(ctx, ui);
                 });
             }
+            "Home_Armoury" => {
+                if !self.check_updated(&self.page.clone()) {
+                    self.armoury_gun_content = Vec::new();
+                    for gun_path in list_files_recursive(Path::new("Resources/config"), "gun_")
+                        .unwrap_or_default()
+                    {
+                        if let Ok(gun_json_message) = read_from_json(&gun_path) {
+                            if let Some(gun_message) = self.report_load_result(
+                                &gun_path.to_string_lossy(),
+                                Gun::from_json_value(&gun_json_message),
+                            ) {
+                                self.user_gun_level(&gun_message);
+                                self.armoury_gun_content.push(gun_message);
+                            };
+                        };
+                    }
+                };
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    self.wallpaper(ui, ctx);
+                    let language = self.login_user_config.language as usize;
+                    egui::ScrollArea::vertical()
+                        .max_height(ctx.available_rect().height() - 100.0)
+                        .max_width(ctx.available_rect().width() / 4_f32 * 3_f32)
+                        .auto_shrink(false)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::WidgetText::from(
+                                        game_text["armoury_currency"][language].clone(),
+                                    )
+                                    .text_style(egui::TextStyle::Heading),
+                                );
+                                ui.separator();
+                                ui.label(
+                                    egui::WidgetText::from(
+                                        self.login_user_config.currency.to_string(),
+                                    )
+                                    .text_style(egui::TextStyle::Heading),
+                                );
+                            });
+                            for gun_message in self.armoury_gun_content.clone() {
+                                let gun_level = self.user_gun_level(&gun_message);
+                                let levelled_gun = gun_message.levelled(gun_level);
+                                ui.separator();
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        egui::WidgetText::from(
+                                            gun_message.gun_name[language].clone(),
+                                        )
+                                        .text_style(egui::TextStyle::Heading),
+                                    );
+                                    ui.separator();
+                                    if gun_level == -1 {
+                                        ui.label(game_text["armoury_locked"][language].clone());
+                                    } else {
+                                        ui.label(format!(
+                                            "{} {}/{}",
+                                            game_text["armoury_level"][language],
+                                            gun_level,
+                                            gun_message.max_level()
+                                        ));
+                                    };
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(format!(
+                                        "{}: {}",
+                                        game_text["armoury_damage"][language],
+                                        levelled_gun.gun_basic_damage
+                                    ));
+                                    ui.separator();
+                                    ui.label(format!(
+                                        "{}: {}",
+                                        game_text["armoury_catridge_clip"][language],
+                                        levelled_gun.gun_catridge_clip
+                                    ));
+                                    ui.separator();
+                                    ui.label(format!(
+                                        "{}: {:.2}s",
+                                        game_text["armoury_shoot_speed"][language],
+                                        levelled_gun.gun_shoot_speed
+                                    ));
+                                    ui.separator();
+                                    ui.label(format!(
+                                        "{}: {:.2}s",
+                                        game_text["armoury_reload_time"][language],
+                                        levelled_gun.gun_reload_time
+                                    ));
+                                    ui.separator();
+                                    ui.label(format!(
+                                        "{}: {}",
+                                        game_text["armoury_recoil"][language],
+                                        levelled_gun.gun_recoil
+                                    ));
+                                });
+                                let cost = if gun_level == -1 {
+                                    Some(gun_message.gun_unlock_cost)
+                                } else {
+                                    gun_message.upgrade_cost(gun_level)
+                                };
+                                if let Some(cost) = cost {
+                                    let action = if gun_level == -1 {
+                                        game_text["armoury_unlock"][language].clone()
+                                    } else {
+                                        game_text["armoury_upgrade"][language].clone()
+                                    };
+                                    if ui
+                                        .add_enabled(
+                                            self.login_user_config.currency >= cost,
+                                            egui::Button::new(format!("{} ({})", action, cost)),
+                                        )
+                                        .clicked()
+                                        && self.purchase_gun_level(&gun_message)
+                                    {
+                                        self.general_click_feedback();
+                                    };
+                                } else {
+                                    ui.add_enabled(
+                                        false,
+                                        egui::Button::new(
+                                            game_text["armoury_max_level"][language].clone(),
+                                        ),
+                                    );
+                                };
+                            }
+                        });
+                    self.dock(ctx, ui);
+                    self.message_box_display(ctx, ui);
+                });
+            }
             "Home_Select_Map" => {
                 if !self.check_updated(&self.page.clone()) {
                     self.add_split_time("map_select_animation", false);
@@ -1115,6 +1681,8 @@ impl eframe::App for App {
                     map_initial_unlock_status: false,
                     map_unlock_description: vec![],
                     map_lock_intro: "".to_string(),
+                    map_music: "".to_string(),
+                    map_endless: None,
                 };
                 let mut map_intro_window_text = [
                     "".to_string(),
@@ -1131,10 +1699,10 @@ impl eframe::App for App {
                     let mut enable = !self.var_b("cut_to");
                     if self.var_b("refreshed_map_data") {
                         let selected_map = self.var_u("selected_map");
-                        let selected_map_id = self.track_resource(
-                            self.resource_image.clone(),
-                            &format!("Map_{:?}", map_list[selected_map as usize]),
-                        );
+                        let selected_map_id = self.track_resource::<Image>(&format!(
+                            "Map_{:?}",
+                            map_list[selected_map as usize]
+                        ));
                         if self.resource_image[selected_map_id].origin_position[0] != 0_f32
                             && (self.timer.now_time - self.split_time("map_select_animation")[0])
                                 >= self.vertrefresh
@@ -1148,33 +1716,10 @@ impl eframe::App for App {
                         };
                     } else {
                         self.modify_var("selected_map", Value::UInt(0));
-                        for _ in 0..self.resource_image_texture.len() {
-                            if let Some(index) = self
-                                .resource_image_texture
-                                .iter()
-                                .position(|x| x.name.contains("Map_"))
-                            {
-                                self.resource_image_texture.remove(index);
-                            };
-                        }
-                        for _ in 0..self.resource_image.len() {
-                            if let Some(index) = self
-                                .resource_image
-                                .iter()
-                                .position(|x| x.name.contains("Map_"))
-                            {
-                                self.resource_image.remove(index);
-                            };
-                        }
-                        for _ in 0..self.resource_switch.len() {
-                            if let Some(index) = self
-                                .resource_switch
-                                .iter()
-                                .position(|x| x.name.contains("Map_"))
-                            {
-                                self.resource_switch.remove(index);
-                            };
-                        }
+                        self.resource_image_texture
+                            .retain(|x| !x.name.contains("Map_"));
+                        self.resource_image.retain(|x| !x.name.contains("Map_"));
+                        self.resource_switch.retain(|x| !x.name.contains("Map_"));
                     };
                     for (i, _) in map_list.iter().enumerate().take(
                         count_files_recursive(Path::new("Resources/config"), "map_").unwrap_or(0),
@@ -1182,11 +1727,14 @@ impl eframe::App for App {
                         if let Ok(json_value) =
                             read_from_json(map_list[i].to_string_lossy().to_string())
                         {
-                            if let Some(read_map_information) = Map::from_json_value(&json_value) {
+                            if let Some(read_map_information) = self.report_load_result(
+                                &map_list[i].to_string_lossy(),
+                                Map::from_json_value(&json_value),
+                            ) {
                                 map_information = read_map_information;
                             };
                             if !check_resource_exist(
-                                self.resource_image_texture.clone(),
+                                &self.resource_image_texture,
                                 &format!("Map_{:?}", map_list[i]),
                             ) && !self.var_b("refreshed_map_data")
                             {
@@ -1291,14 +1839,12 @@ impl eframe::App for App {
                                 .unwrap()]
                             .map_unlock_status;
                         if map_move_animation != 0 {
-                            let id = self.track_resource(
-                                self.resource_image.clone(),
-                                &format!("Map_{:?}", map_list[i]),
-                            );
+                            let id =
+                                self.track_resource::<Image>(&format!("Map_{:?}", map_list[i]));
                             if map_move_animation == 1 {
-                                self.resource_image[id].origin_position[0] -= 30_f32;
+                                self.resource_image.get_mut(id).origin_position[0] -= 30_f32;
                             } else {
-                                self.resource_image[id].origin_position[0] += 30_f32;
+                                self.resource_image.get_mut(id).origin_position[0] += 30_f32;
                             };
                         };
                         if self.switch(&format!("Map_{:?}", map_list[i]), ui, ctx, map_enable, true)
@@ -1400,14 +1946,15 @@ impl eframe::App for App {
                             if let Ok(json_value) =
                                 read_from_json(&self.login_user_config.current_map)
                             {
-                                if let Some(read_map_information) =
-                                    Map::from_json_value(&json_value)
-                                {
+                                if let Some(read_map_information) = self.report_load_result(
+                                    &self.login_user_config.current_map.clone(),
+                                    Map::from_json_value(&json_value),
+                                ) {
                                     map_information = read_map_information;
                                 }
                             };
                             if !check_resource_exist(
-                                self.resource_image_texture.clone(),
+                                &self.resource_image_texture,
                                 &map_information.map_image,
                             ) {
                                 self.add_image_texture(
@@ -1441,14 +1988,11 @@ impl eframe::App for App {
                             self.timer.start_time = self.timer.total_time;
                             self.update_timer();
                             self.add_split_time("cut_to_animation", true);
-                            if check_resource_exist(
-                                self.timer.split_time.clone(),
-                                "scroll_animation",
-                            ) {
+                            if check_resource_exist(&self.timer.split_time, "scroll_animation") {
                                 self.add_split_time("scroll_animation", true);
                             };
                             if check_resource_exist(
-                                self.timer.split_time.clone(),
+                                &self.timer.split_time,
                                 "opened_level_animation",
                             ) {
                                 self.timer.start_time = self.timer.total_time;
@@ -1485,9 +2029,14 @@ impl eframe::App for App {
                     map_initial_unlock_status: false,
                     map_unlock_description: vec![],
                     map_lock_intro: "".to_string(),
+                    map_music: "".to_string(),
+                    map_endless: None,
                 };
                 if let Ok(json_value) = read_from_json(&self.login_user_config.current_map) {
-                    if let Some(read_map_information) = Map::from_json_value(&json_value) {
+                    if let Some(read_map_information) = self.report_load_result(
+                        &self.login_user_config.current_map.clone(),
+                        Map::from_json_value(&json_value),
+                    ) {
                         map_information = read_map_information;
                     }
                 };
@@ -1501,21 +2050,20 @@ impl eframe::App for App {
                     self.add_var("prepared_operation", false);
                 };
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    let map_background_id = self
-                        .track_resource(self.resource_image.clone(), &map_information.map_image);
-                    let scroll_remind_id =
-                        self.track_resource(self.resource_image.clone(), "Scroll_Forward");
-                    let scroll_remind_id2 =
-                        self.track_resource(self.resource_image.clone(), "Scroll_Backward");
-                    self.resource_image[map_background_id].image_size = [
+                    let map_background_id =
+                        self.track_resource::<Image>(&map_information.map_image);
+                    let scroll_remind_id = self.track_resource::<Image>("Scroll_Forward");
+                    let scroll_remind_id2 = self.track_resource::<Image>("Scroll_Backward");
+                    self.resource_image.get_mut(map_background_id).image_size = [
                         ctx.available_rect().width() + map_information.map_width / 2_f32,
                         ctx.available_rect().height(),
                     ];
-                    self.resource_image[map_background_id].origin_position[0] =
-                        self.var_f("scroll_offset") / 2_f32;
-                    self.resource_image[scroll_remind_id].image_size[1] =
+                    self.resource_image
+                        .get_mut(map_background_id)
+                        .origin_position[0] = self.var_f("scroll_offset") / 2_f32;
+                    self.resource_image.get_mut(scroll_remind_id).image_size[1] =
                         ctx.available_rect().height();
-                    self.resource_image[scroll_remind_id2].image_size[1] =
+                    self.resource_image.get_mut(scroll_remind_id2).image_size[1] =
                         ctx.available_rect().height();
                     self.image(ui, &map_information.map_image, ctx);
                     if self.var_i("opened_level") == -1
@@ -1529,14 +2077,81 @@ impl eframe::App for App {
                         );
                     };
 
-                    // 在未来版本实装
-                    // if self.var_i("opened_level") == -1
-                    //     && self.switch("Editor", ui, ctx, true, true)[0] == 0
-                    // {
-                    //     self.modify_var("fade_in_or_out", true);
-                    //     self.modify_var("cut_to", true);
-                    //     self.modify_var("select_level_switch_target", "Editor".to_string());
-                    // };
+                    if self.var_i("opened_level") == -1
+                        && self.switch("Editor", ui, ctx, true, true)[0] == 0
+                    {
+                        self.modify_var("fade_in_or_out", true);
+                        self.modify_var("cut_to", true);
+                        self.modify_var("select_level_switch_target", "Editor".to_string());
+                    };
+
+                    if self.var_i("opened_level") == -1
+                        && self.switch("Replay", ui, ctx, true, true)[0] == 0
+                    {
+                        if let Some(path) = FileDialog::new()
+                            .set_title(
+                                &game_text["choose_replay"]
+                                    [self.login_user_config.language as usize]
+                                    .clone(),
+                            )
+                            .set_directory("Resources/replay")
+                            .add_filter("", &["json"])
+                            .pick_file()
+                        {
+                            if self.load_replay(&path) {
+                                self.play_sound(
+                                    "Resources/assets/sounds/Operation_Start.wav",
+                                    AudioBus::Sfx,
+                                );
+                                self.modify_var("fade_in_or_out", true);
+                                self.modify_var("cut_to", true);
+                                self.modify_var(
+                                    "select_level_switch_target",
+                                    "Operation".to_string(),
+                                );
+                            };
+                        };
+                    };
+
+                    if let Some(map_endless) = map_information.map_endless.clone() {
+                        if self.var_i("opened_level") == -1
+                            && self.switch("Endless", ui, ctx, true, true)[0] == 0
+                        {
+                            if check_file_exists(&map_endless.endless_level) {
+                                self.play_sound(
+                                    "Resources/assets/sounds/Operation_Start.wav",
+                                    AudioBus::Sfx,
+                                );
+                                self.replay_playback = None;
+                                // 未指定种子时按当前时间生成，种子会写入回放
+                                self.endless_seed = Some(map_endless.endless_seed.unwrap_or(
+                                    Local::now().timestamp_subsec_nanos()
+                                        ^ Local::now().timestamp() as u32,
+                                ));
+                                self.login_user_config.current_level =
+                                    map_endless.endless_level.clone();
+                                self.modify_var("fade_in_or_out", true);
+                                self.modify_var("cut_to", true);
+                                self.modify_var(
+                                    "select_level_switch_target",
+                                    "Operation".to_string(),
+                                );
+                            } else {
+                                self.problem_report(
+                                    &format!(
+                                        "{}{}",
+                                        game_text["error_endless_level"]
+                                            [self.login_user_config.language as usize],
+                                        map_endless.endless_level
+                                    ),
+                                    SeverityLevel::MildWarning,
+                                    &game_text["error_endless_level_annotation"]
+                                        [self.login_user_config.language as usize]
+                                        .clone(),
+                                );
+                            };
+                        };
+                    };
 
                     // 补全缺少的关卡数据
                     for i in 0..map_information.map_content.len() {
@@ -1555,6 +2170,8 @@ impl eframe::App for App {
                                 } else {
                                     -1
                                 },
+                                level_best_score: 0,
+                                level_best_star: 0,
                             });
                         };
                     }
@@ -1622,7 +2239,7 @@ impl eframe::App for App {
                             };
                         }
                         if !check_resource_exist(
-                            self.resource_switch.clone(),
+                            &self.resource_switch,
                             &format!("Node_{}", map_information.map_content[i].level_name),
                         ) && level_status != -1
                         {
@@ -1707,18 +2324,18 @@ impl eframe::App for App {
                                     map_information.map_content[i].level_position[0],
                                 );
                             };
-                            let id = self.track_resource(
-                                self.resource_image.clone(),
-                                &format!("Node_{}", map_information.map_content[i].level_name),
-                            );
-                            let id2 = self.track_resource(
-                                self.resource_switch.clone(),
-                                &format!("Node_{}", map_information.map_content[i].level_name),
-                            );
+                            let id = self.track_resource::<Image>(&format!(
+                                "Node_{}",
+                                map_information.map_content[i].level_name
+                            ));
+                            let id2 = self.track_resource::<Switch>(&format!(
+                                "Node_{}",
+                                map_information.map_content[i].level_name
+                            ));
                             if self.resource_switch[id2].state == 1 {
                                 self.modify_var("opened_level", i as i32);
                             };
-                            self.resource_image[id].origin_position = [
+                            self.resource_image.get_mut(id).origin_position = [
                                 map_information.map_content[i].level_position[0]
                                     * (ctx.available_rect().width() / 1280_f32)
                                     + self.var_f("scroll_offset"),
@@ -1746,31 +2363,24 @@ impl eframe::App for App {
                                         }
                                     }
                                     if u != i && another_level_status != -1 {
-                                        let switch_id = self.track_resource(
-                                            self.resource_switch.clone(),
-                                            &format!(
-                                                "Node_{}",
-                                                map_information.map_content[u].level_name
-                                            ),
-                                        );
-                                        self.resource_switch[switch_id].state = 0;
+                                        let switch_id = self.track_resource::<Switch>(&format!(
+                                            "Node_{}",
+                                            map_information.map_content[u].level_name
+                                        ));
+                                        self.resource_switch.get_mut(switch_id).state = 0;
                                     };
                                 }
                             } else if ui.input(|i| i.pointer.primary_released()) {
                                 if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
-                                    let rect_id = self.track_resource(
-                                        self.resource_rect.clone(),
+                                    let rect_id = self.track_resource::<CustomRect>(
                                         "Level_Information_Background",
                                     );
                                     if mouse_pos.x < self.resource_rect[rect_id].position[0] {
-                                        let id = self.track_resource(
-                                            self.resource_switch.clone(),
-                                            &format!(
-                                                "Node_{}",
-                                                map_information.map_content[i].level_name
-                                            ),
-                                        );
-                                        self.resource_switch[id].state = 0;
+                                        let id = self.track_resource::<Switch>(&format!(
+                                            "Node_{}",
+                                            map_information.map_content[i].level_name
+                                        ));
+                                        self.resource_switch.get_mut(id).state = 0;
                                         self.modify_var("opened_level", -1);
                                     };
                                 };
@@ -1783,40 +2393,36 @@ impl eframe::App for App {
                         farthest_node_position * (ctx.available_rect().width() / 1280_f32)
                             + 100_f32,
                     );
-                    let rect_id = self
-                        .track_resource(self.resource_rect.clone(), "Level_Information_Background");
-                    self.resource_rect[rect_id].size[1] = ctx.available_rect().height();
+                    let rect_id = self.track_resource::<CustomRect>("Level_Information_Background");
+                    self.resource_rect.get_mut(rect_id).size[1] = ctx.available_rect().height();
                     self.rect(ui, "Level_Information_Background", ctx);
                     if self.var_i("opened_level") != -1 {
                         let opened_level = self.var_i("opened_level") as usize;
-                        let text_id =
-                            self.track_resource(self.resource_text.clone(), "Level_Title");
-                        let text_id2 =
-                            self.track_resource(self.resource_text.clone(), "Level_Description");
-                        let image_id =
-                            self.track_resource(self.resource_image.clone(), "Start_Operation");
-                        self.resource_text[text_id].text_content = format!(
+                        let text_id = self.track_resource::<Text>("Level_Title");
+                        let text_id2 = self.track_resource::<Text>("Level_Description");
+                        let image_id = self.track_resource::<Image>("Start_Operation");
+                        self.resource_text.get_mut(text_id).text_content = format!(
                             "{} {}",
                             map_information.map_content[opened_level].level_name,
                             map_information.map_content[opened_level].level_name_expand
                                 [self.login_user_config.language as usize]
                         );
-                        self.resource_text[text_id2].text_content =
+                        self.resource_text.get_mut(text_id2).text_content =
                             map_information.map_content[opened_level].level_description
                                 [self.login_user_config.language as usize]
                                 .clone();
-                        self.resource_text[text_id].origin_position[0] =
+                        self.resource_text.get_mut(text_id).origin_position[0] =
                             self.resource_rect[rect_id].origin_position[0] + 300_f32;
-                        self.resource_text[text_id2].origin_position[0] =
+                        self.resource_text.get_mut(text_id2).origin_position[0] =
                             self.resource_rect[rect_id].origin_position[0] + 300_f32;
-                        self.resource_image[image_id].origin_position[0] =
+                        self.resource_image.get_mut(image_id).origin_position[0] =
                             self.resource_rect[rect_id].origin_position[0] + 300_f32;
                         if self.resource_text[text_id2].position[1]
                             < self.resource_text[text_id].position[1]
                                 + self.get_text_size("Level_Title", ui)[1]
                                 + 10_f32
                         {
-                            self.resource_text[text_id2].origin_position[1] =
+                            self.resource_text.get_mut(text_id2).origin_position[1] =
                                 self.resource_text[text_id].position[1]
                                     + self.get_text_size("Level_Title", ui)[1]
                                     + 10_f32;
@@ -1824,12 +2430,14 @@ impl eframe::App for App {
                         self.text(ui, "Level_Title", ctx);
                         self.text(ui, "Level_Description", ctx);
                         if self.switch("Start_Operation", ui, ctx, true, false)[0] == 0 {
-                            std::thread::spawn(|| {
-                                kira_play_wav("Resources/assets/sounds/Operation_Start.wav")
-                                    .unwrap();
-                            });
+                            self.play_sound(
+                                "Resources/assets/sounds/Operation_Start.wav",
+                                AudioBus::Sfx,
+                            );
                             self.modify_var("cut_to", true);
                             self.modify_var("fade_in_or_out", true);
+                            self.replay_playback = None;
+                            self.endless_seed = None;
                             self.login_user_config.current_level = format!(
                                 "{}_{}.json",
                                 self.login_user_config
@@ -1844,7 +2452,7 @@ impl eframe::App for App {
                                 >= self.vertrefresh
                         {
                             self.add_split_time("opened_level_animation", true);
-                            self.resource_rect[rect_id].origin_position[0] -= 50_f32;
+                            self.resource_rect.get_mut(rect_id).origin_position[0] -= 50_f32;
                         };
                     } else {
                         if self.var_f("scroll_offset") < 0_f32 {
@@ -1855,7 +2463,7 @@ impl eframe::App for App {
                                 >= self.vertrefresh
                         {
                             self.add_split_time("opened_level_animation", true);
-                            self.resource_rect[rect_id].origin_position[0] += 50_f32;
+                            self.resource_rect.get_mut(rect_id).origin_position[0] += 50_f32;
                         } else if self.resource_rect[rect_id].origin_position[0] == 0_f32
                             && self.var_f("scroll_offset")
                                 > ctx.available_rect().width() - map_information.map_width
@@ -1952,6 +2560,8 @@ impl eframe::App for App {
                                     self.modify_var("refreshed_map_data", false);
                                     self.add_split_time("dock_animation", true);
                                     self.add_split_time("map_select_animation", true);
+                                } else if target_page == "Operation" {
+                                    self.modify_var("prepared_operation", false);
                                 };
                             } else {
                                 self.switch_page("Operation");
@@ -1981,7 +2591,6 @@ impl eframe::App for App {
                     self.add_split_time("gun_end_shooting_time", false);
                     self.add_split_time("start_pause_time", false);
                     self.add_split_time("horizontal_scrolling_time", false);
-                    self.add_split_time("cost_recover_time", false);
                     self.add_split_time("operation_start_fade_animation", false);
                     self.add_var("enter_operation_loaded", false);
                     self.add_var("loaded_unlock_list", false);
@@ -2000,6 +2609,13 @@ impl eframe::App for App {
                     self.add_var("gun_selected", Value::UInt(0));
                     self.add_var("gun_selectable_len", Value::UInt(0));
                     self.add_var("forced_cooling", false);
+                    self.add_var("overheat_count", Value::UInt(0));
+                    self.add_var("gun_burst_remaining", Value::UInt(0));
+                    self.add_var("gun_charging", false);
+                    self.add_var("gun_damage_multiplier", Value::Float(1_f32));
+                    self.add_var("instrument_ceiling", Value::UInt(0));
+                    self.add_var("instrument_selected", Value::UInt(0));
+                    self.add_var("instrument_placing", false);
                     self.add_var("pause_total_time", Value::Float(0_f32));
                     self.add_var("operation_runtime", Value::Float(0_f32));
                     self.add_var(
@@ -2017,93 +2633,64 @@ impl eframe::App for App {
                             self.timer.now_time - start_operation_time - pause_total_time,
                         );
                     };
-                    let bar_id =
-                        self.track_resource(self.resource_rect.clone(), "Operation_Status_Bar");
-                    let bar_id2 = self.track_resource(self.resource_image.clone(), "Target_Point");
-                    let bar_id3 = self.track_resource(self.resource_image.clone(), "Target_Enemy");
-                    let bar_id4 = self.track_resource(self.resource_image.clone(), "Bullet");
-                    let bar_id5 = self.track_resource(self.resource_image.clone(), "Cost");
-                    let bar_id6 =
-                        self.track_resource(self.resource_text.clone(), "Target_Point_Text");
-                    let bar_id7 =
-                        self.track_resource(self.resource_text.clone(), "Target_Enemy_Text");
-                    let bar_id8 = self.track_resource(self.resource_text.clone(), "Bullet_Text");
-                    let bar_id9 = self.track_resource(self.resource_text.clone(), "Cost_Text");
+                    let bar_id = self.track_resource::<CustomRect>("Operation_Status_Bar");
+                    let bar_id2 = self.track_resource::<Image>("Target_Point");
+                    let bar_id3 = self.track_resource::<Image>("Target_Enemy");
+                    let bar_id4 = self.track_resource::<Image>("Bullet");
+                    let bar_id5 = self.track_resource::<Image>("Cost");
+                    let bar_id6 = self.track_resource::<Text>("Target_Point_Text");
+                    let bar_id7 = self.track_resource::<Text>("Target_Enemy_Text");
+                    let bar_id8 = self.track_resource::<Text>("Bullet_Text");
+                    let bar_id9 = self.track_resource::<Text>("Cost_Text");
                     if !self.var_b("prepared_operation") {
                         self.resource_image_texture
                             .retain(|x| !x.name.contains("Json_") && !x.name.contains("Enemy_"));
-                        for i in 0..self.resource_message_box.len() {
-                            self.resource_message_box[i].box_exist = false;
+                        for message_box in self.resource_message_box.iter_mut() {
+                            message_box.box_exist = false;
                         }
                         self.resource_image.retain(|x| !x.name.contains("Enemy_"));
                         if let Ok(json_value) =
                             read_from_json(self.login_user_config.current_level.clone())
                         {
-                            if let Some(read_operation) = Operation::from_json_value(&json_value) {
-                                self.enemy_list = Vec::new();
-                                for i in 0..read_operation.target_enemy.len() {
-                                    if !self
-                                        .enemy_list
-                                        .iter()
-                                        .any(|x| x.enemy_name == format!("Enemy_json_{}", i))
-                                    {
-                                        if let Ok(json_value) = read_from_json(
-                                            format!(
-                                                "Resources/config/enemy_{}.json",
-                                                read_operation.target_enemy[i]
-                                                    .enemy_recognition_name
-                                            )
-                                            .to_lowercase(),
-                                        ) {
-                                            if let Some(read_enemy) =
-                                                JsonReadEnemy::from_json_value(&json_value)
-                                            {
-                                                self.add_enemy(
-                                                    [
-                                                        read_enemy.enemy_hp,
-                                                        read_enemy.enemy_def,
-                                                        read_enemy.enemy_speed,
-                                                        read_enemy.enemy_invincible_time,
-                                                        read_operation.target_enemy[i]
-                                                            .enemy_position[0],
-                                                        read_operation.target_enemy[i]
-                                                            .enemy_position[1],
-                                                        read_operation.target_enemy[i]
-                                                            .enemy_approach_time,
-                                                        read_operation.target_enemy[i].enemy_size
-                                                            [0],
-                                                        read_operation.target_enemy[i].enemy_size
-                                                            [1],
-                                                        read_enemy.enemy_walk_interval,
-                                                        read_enemy.enemy_animation_interval,
-                                                    ],
-                                                    [
-                                                        read_enemy.enemy_image_count,
-                                                        read_enemy.enemy_minus_target_point,
-                                                        read_operation.target_enemy[i]
-                                                            .enemy_approach_alpha
-                                                            as u32,
-                                                        read_operation.target_enemy[i]
-                                                            .enemy_increase_alpha_speed
-                                                            as u32,
-                                                    ],
-                                                    [
-                                                        read_enemy.enemy_tag,
-                                                        read_operation.target_enemy[i]
-                                                            .enemy_path
-                                                            .clone(),
-                                                    ],
-                                                    [
-                                                        format!("json_{}", i),
-                                                        read_enemy.enemy_image.clone(),
-                                                        read_enemy.enemy_image_type.clone(),
-                                                    ],
-                                                    [true, false],
-                                                    ctx,
-                                                );
-                                            };
-                                        };
-                                    };
+                            if let Some(mut read_operation) = self.report_load_result(
+                                &self.login_user_config.current_level.clone(),
+                                Operation::from_json_value(&json_value),
+                            ) {
+                                // 无尽模式忽略关卡中的敌人与提示，改由波次生成器逐波补充敌人
+                                let map_endless = if self.endless_seed.is_some() {
+                                    read_from_json(&self.login_user_config.current_map)
+                                        .ok()
+                                        .and_then(|x| Map::from_json_value(&x).ok())
+                                        .and_then(|x| x.map_endless)
+                                } else {
+                                    None
+                                };
+                                if map_endless.is_some() {
+                                    read_operation.target_enemy.clear();
+                                    read_operation.message_box.clear();
+                                    read_operation.wave.clear();
+                                    read_operation.trigger.clear();
+                                } else {
+                                    self.endless_seed = None;
+                                };
+                                self.operation_input = SimulationInput::default();
+                                self.operation_simulation =
+                                    Simulation::new(read_operation.clone(), vec![]);
+                                if let (Some(map_endless), Some(seed)) =
+                                    (map_endless, self.endless_seed)
+                                {
+                                    self.operation_simulation.endless =
+                                        Some(Endless::new(&map_endless, seed));
+                                };
+                                self.logger.log(
+                                    "OPERATION",
+                                    &format!(
+                                        "Start {} (endless seed: {:?})",
+                                        self.login_user_config.current_level, self.endless_seed
+                                    ),
+                                );
+                                for enemy in self.operation_simulation.enemy_list.clone() {
+                                    self.add_enemy(&enemy, ctx);
                                 }
                                 self.operation_preload_message_box.clear();
                                 for i in 0..read_operation.message_box.len() {
@@ -2133,6 +2720,10 @@ impl eframe::App for App {
                                     "cost_recover_speed",
                                     Value::Float(read_operation.global.cost_recover_speed),
                                 );
+                                self.modify_var(
+                                    "instrument_ceiling",
+                                    Value::UInt(read_operation.global.instrument_ceiling),
+                                );
                                 let mut target_line = Vec::new();
                                 for i in 0..read_operation.global.target_line.len() {
                                     target_line.push(Value::Float(
@@ -2172,7 +2763,7 @@ impl eframe::App for App {
                                     ctx,
                                 );
                                 if !check_resource_exist(
-                                    self.resource_scroll_background.clone(),
+                                    &self.resource_scroll_background,
                                     "operation_expand",
                                 ) {
                                     self.add_image(
@@ -2225,59 +2816,40 @@ impl eframe::App for App {
                                         ],
                                     );
                                 };
-                                let id =
-                                    self.track_resource(self.resource_image.clone(), "Operation");
-                                let id2 = self.track_resource(
-                                    self.resource_image_texture.clone(),
-                                    "Operation",
-                                );
-                                let id3 = self.track_resource(
-                                    self.resource_image.clone(),
-                                    "Operation_Expand1",
-                                );
-                                let id4 = self.track_resource(
-                                    self.resource_image_texture.clone(),
-                                    "Operation_Expand1",
-                                );
-                                let id5 = self.track_resource(
-                                    self.resource_image.clone(),
-                                    "Operation_Expand2",
-                                );
-                                let id6 = self.track_resource(
-                                    self.resource_image_texture.clone(),
-                                    "Operation_Expand2",
-                                );
-                                let id7 = self.track_resource(
-                                    self.resource_image.clone(),
-                                    "Operation_Start_Background",
-                                );
-                                let id8 = self.track_resource(
-                                    self.resource_image_texture.clone(),
-                                    "Operation_Start_Background",
-                                );
-                                self.resource_image[id].image_texture =
+                                let id = self.track_resource::<Image>("Operation");
+                                let id2 = self.track_resource::<ImageTexture>("Operation");
+                                let id3 = self.track_resource::<Image>("Operation_Expand1");
+                                let id4 = self.track_resource::<ImageTexture>("Operation_Expand1");
+                                let id5 = self.track_resource::<Image>("Operation_Expand2");
+                                let id6 = self.track_resource::<ImageTexture>("Operation_Expand2");
+                                let id7 =
+                                    self.track_resource::<Image>("Operation_Start_Background");
+                                let id8 = self
+                                    .track_resource::<ImageTexture>("Operation_Start_Background");
+                                self.resource_image.get_mut(id).image_texture =
                                     self.resource_image_texture[id2].texture.clone();
-                                self.resource_image[id3].image_texture =
+                                self.resource_image.get_mut(id3).image_texture =
                                     self.resource_image_texture[id4].texture.clone();
-                                self.resource_image[id5].image_texture =
+                                self.resource_image.get_mut(id5).image_texture =
                                     self.resource_image_texture[id6].texture.clone();
-                                self.resource_image[id7].image_texture =
+                                self.resource_image.get_mut(id7).image_texture =
                                     self.resource_image_texture[id8].texture.clone();
-                                self.resource_image[id7].alpha = 255;
-                                self.resource_image[id7].overlay_color = [255, 255, 255, 255];
+                                self.resource_image.get_mut(id7).alpha = 255;
+                                self.resource_image.get_mut(id7).overlay_color =
+                                    [255, 255, 255, 255];
                             };
                         };
-                        let id = self
-                            .track_resource(self.resource_rect.clone(), "Operation_Win_Background");
-                        let id2 = self.track_resource(
-                            self.resource_rect.clone(),
-                            "Operation_Fail_Background",
-                        );
-                        self.resource_rect[id].origin_position[0] = ctx.available_rect().width();
-                        self.resource_rect[id2].color[3] = 0;
+                        let id = self.track_resource::<CustomRect>("Operation_Win_Background");
+                        let id2 = self.track_resource::<CustomRect>("Operation_Fail_Background");
+                        self.resource_rect.get_mut(id).origin_position[0] =
+                            ctx.available_rect().width();
+                        self.resource_rect.get_mut(id2).color[3] = 0;
                         let gun_list =
                             list_files_recursive(Path::new("Resources/config"), "gun_").unwrap();
                         let mut gun_list_content = Vec::new();
+                        // 未叠加升级的枪械与对应等级，回放时按录制时的等级重新计算
+                        let mut gun_base_content = Vec::new();
+                        let mut gun_level_content = Vec::new();
                         self.resource_image_texture
                             .retain(|x| !x.name.contains("Gun_"));
                         self.resource_image.retain(|x| !x.name.contains("Gun_"));
@@ -2286,43 +2858,29 @@ impl eframe::App for App {
                             !x.name.contains("gun")
                                 || x.name == "gun_shooting_time"
                                 || x.name == "gun_end_shooting_time"
+                                || x.name == "gun_charge_time"
                         });
                         self.variables.retain(|x| {
                             !x.name.contains("gun")
                                 || x.name == "gun_selectable_len"
                                 || x.name == "gun_selected"
+                                || x.name == "gun_burst_remaining"
+                                || x.name == "gun_charging"
+                                || x.name == "gun_damage_multiplier"
                         });
                         for (i, _) in gun_list.iter().enumerate().take(
                             count_files_recursive(Path::new("Resources/config"), "gun_").unwrap(),
                         ) {
                             if let Ok(gun_json_message) = read_from_json(gun_list[i].clone()) {
-                                if let Some(gun_message) = Gun::from_json_value(&gun_json_message) {
-                                    if !self.login_user_config.gun_status.iter().any(|x| {
-                                        x.gun_recognition_name == gun_message.gun_recognition_name
-                                    }) {
-                                        self.login_user_config.gun_status.push(UserGunStatus {
-                                            gun_recognition_name: gun_message
-                                                .gun_recognition_name
-                                                .clone(),
-                                            gun_level: if gun_message.gun_initial_unlock {
-                                                0
-                                            } else {
-                                                -1
-                                            },
-                                        });
-                                    };
-                                    if self.login_user_config.gun_status[self
-                                        .login_user_config
-                                        .gun_status
-                                        .iter()
-                                        .position(|x| {
-                                            x.gun_recognition_name
-                                                == gun_message.gun_recognition_name
-                                        })
-                                        .unwrap()]
-                                    .gun_level
-                                        != -1
-                                    {
+                                if let Some(gun_message) = self.report_load_result(
+                                    &gun_list[i].to_string_lossy(),
+                                    Gun::from_json_value(&gun_json_message),
+                                ) {
+                                    let gun_level = self.user_gun_level(&gun_message);
+                                    if gun_level != -1 {
+                                        gun_base_content.push(gun_message.clone());
+                                        gun_level_content.push(gun_level);
+                                        let gun_message = gun_message.levelled(gun_level);
                                         self.add_image_texture(
                                             &format!(
                                                 "Gun_{}",
@@ -2427,15 +2985,114 @@ impl eframe::App for App {
                                 };
                             };
                         }
+                        let instrument_list =
+                            list_files_recursive(Path::new("Resources/config"), "instrument_")
+                                .unwrap_or_default();
+                        self.resource_image_texture
+                            .retain(|x| !x.name.contains("Instrument_"));
+                        self.resource_image
+                            .retain(|x| !x.name.contains("Instrument_"));
+                        self.storage_instrument_content = Vec::new();
+                        for instrument_path in instrument_list {
+                            if let Ok(instrument_json_message) = read_from_json(&instrument_path) {
+                                if let Some(instrument_message) = self.report_load_result(
+                                    &instrument_path.to_string_lossy(),
+                                    Instrument::from_json_value(&instrument_json_message),
+                                ) {
+                                    self.add_image_texture(
+                                        &format!(
+                                            "Instrument_{}",
+                                            instrument_message.instrument_recognition_name
+                                        ),
+                                        &instrument_message.instrument_image.clone(),
+                                        [false, false],
+                                        true,
+                                        ctx,
+                                    );
+                                    self.storage_instrument_content.push(instrument_message);
+                                };
+                            };
+                        }
+                        self.operation_simulation.instrument_content =
+                            self.storage_instrument_content.clone();
                         self.pause_list = vec![];
+                        self.modify_var("instrument_selected", Value::UInt(0));
+                        self.modify_var("instrument_placing", false);
                         self.modify_var("enter_operation_loaded", false);
                         self.modify_var("gun_selected", Value::UInt(0));
                         self.modify_var("gun_selectable_len", gun_list_content.len() as u32);
+                        let gun_list = gun_list_content
+                            .iter()
+                            .map(|x| x.gun_recognition_name.clone())
+                            .collect::<Vec<String>>();
+                        if let Some(replay) = self.replay_playback.clone() {
+                            self.replay_playback_index = 0;
+                            self.replay_record = None;
+                            // 回放注入的是录制时的原始输入，需要按录制时的绑定解释
+                            self.input_bindings =
+                                InputBindings::from_settings(&replay.input_binding);
+                            if replay.gun_list != gun_list {
+                                self.problem_report(
+                                    &game_text["error_replay_gun_mismatch"]
+                                        [self.config.language as usize]
+                                        .clone(),
+                                    SeverityLevel::MildWarning,
+                                    &game_text["error_replay_gun_mismatch_annotation"]
+                                        [self.config.language as usize]
+                                        .clone(),
+                                );
+                            } else {
+                                if replay.gun_level.len() == gun_list.len()
+                                    && replay.gun_level != gun_level_content
+                                {
+                                    for (i, gun_level) in replay.gun_level.iter().enumerate() {
+                                        gun_list_content[i] =
+                                            gun_base_content[i].levelled(*gun_level);
+                                        self.modify_var(
+                                            &format!("gun{}_surplus_bullets", i),
+                                            Value::UInt(gun_list_content[i].gun_catridge_clip),
+                                        );
+                                    }
+                                };
+                                if replay.gun_selected < gun_list.len() as u32 {
+                                    self.modify_var(
+                                        "gun_selected",
+                                        Value::UInt(replay.gun_selected),
+                                    );
+                                };
+                            };
+                        } else {
+                            self.input_bindings =
+                                InputBindings::from_settings(&self.login_user_config.settings);
+                            let mut input_binding = HashMap::new();
+                            self.input_bindings.save(&mut input_binding);
+                            self.replay_record = Some(Replay {
+                                level_path: self.login_user_config.current_level.clone(),
+                                user_name: self.login_user_config.name.clone(),
+                                record_time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                                gun_list,
+                                gun_level: gun_level_content,
+                                gun_selected: 0,
+                                endless_seed: self.endless_seed,
+                                endless_map: if self.endless_seed.is_some() {
+                                    self.login_user_config.current_map.clone()
+                                } else {
+                                    "".to_string()
+                                },
+                                input_binding,
+                                frames: Vec::new(),
+                            });
+                        };
                         self.modify_var("reseted_operation_start_animation_timer", false);
                         self.storage_gun_content = gun_list_content;
                         self.modify_var("prepared_operation", true);
                         self.modify_var("pause", false);
                         self.modify_var("forced_cooling", false);
+                        self.modify_var("overheat_count", Value::UInt(0));
+                        self.modify_var("gun_burst_remaining", Value::UInt(0));
+                        self.modify_var("gun_charging", false);
+                        self.modify_var("gun_damage_multiplier", Value::Float(1_f32));
+                        self.add_split_time("gun_charge_time", true);
                         self.modify_var("pause_total_time", Value::Float(0_f32));
                         self.modify_var("operation_runtime", Value::Float(0_f32));
                         self.modify_var("perfect_clear", true);
@@ -2445,10 +3102,12 @@ impl eframe::App for App {
                         self.add_split_time("gun_end_shooting_time", true);
                         self.add_split_time("start_pause_time", true);
                         self.add_split_time("horizontal_scrolling_time", true);
-                        self.add_split_time("cost_recover_time", true);
                         self.add_split_time("operation_start_fade_animation", true);
                         self.add_split_time("Operation_Expand", true);
                     } else if self.var_b("in_operation") {
+                        let viewport = self.operation_viewport(ctx);
+                        // 状态栏等界面元素固定在作战区域的可见部分内
+                        let visible = viewport.visible();
                         let operation_refresh_time = self.split_time("operation_refresh_time")[0];
                         let refresh_index = self.find_pause_index(operation_refresh_time);
                         let refresh = if self.var_b("pause") {
@@ -2465,65 +3124,66 @@ impl eframe::App for App {
                         if refresh && !self.var_b("pause") {
                             self.add_split_time("operation_refresh_time", true);
                         };
-                        self.resource_rect[bar_id].origin_position[1] =
-                            ctx.available_rect().height() / 2_f32 - 350_f32;
-                        self.resource_image[bar_id2].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32 + 1280_f32 / 5_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_rect.get_mut(bar_id).origin_position[0] =
+                            visible.center().x - ctx.available_rect().width() / 2_f32;
+                        self.resource_rect.get_mut(bar_id).origin_position[1] =
+                            visible.min.y + 10_f32;
+                        self.resource_image.get_mut(bar_id2).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_image[bar_id3].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32 * 2_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_image.get_mut(bar_id3).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 2_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_image[bar_id4].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32 * 3_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_image.get_mut(bar_id4).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 3_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_image[bar_id5].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32 * 4_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_image.get_mut(bar_id5).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 4_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_text[bar_id6].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32
-                                + 30_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_text.get_mut(bar_id6).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 + 30_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_text[bar_id6].text_content =
+                        self.resource_text.get_mut(bar_id6).text_content =
                             self.var_u("target_point").to_string();
-                        self.resource_text[bar_id7].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32 * 2_f32
-                                + 30_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_text.get_mut(bar_id7).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 2_f32 + 30_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_text[bar_id7].text_content = format!(
-                            "{}/{}",
-                            self.var_u("current_killed_target_enemy"),
-                            self.var_u("target_enemy")
-                        );
-                        self.resource_text[bar_id8].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32 * 3_f32
-                                + 30_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_text.get_mut(bar_id7).text_content = if let Some(endless) =
+                            &self.operation_simulation.endless
+                        {
+                            format!(
+                                "{} {} | {}",
+                                game_text["endless_wave"][self.login_user_config.language as usize],
+                                endless.wave,
+                                self.operation_simulation.enemy_killed
+                            )
+                        } else {
+                            format!(
+                                "{}/{}",
+                                self.var_u("current_killed_target_enemy"),
+                                self.var_u("target_enemy")
+                            )
+                        };
+                        self.resource_text.get_mut(bar_id8).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 3_f32 + 30_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_text[bar_id8].text_content =
+                        self.resource_text.get_mut(bar_id8).text_content =
                             self.var_u("storage_bullet").to_string();
-                        self.resource_text[bar_id9].origin_position = [
-                            ctx.available_rect().width() / 2_f32 - 640_f32
-                                + 1280_f32 / 5_f32 * 4_f32
-                                + 30_f32,
-                            ctx.available_rect().height() / 2_f32 - 340_f32,
+                        self.resource_text.get_mut(bar_id9).origin_position = [
+                            visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 4_f32 + 30_f32,
+                            visible.min.y + 20_f32,
                         ];
-                        self.resource_text[bar_id9].text_content = self.var_u("cost").to_string();
-                        let scroll_background = self.track_resource(
-                            self.resource_scroll_background.clone(),
-                            "Operation_Expand",
-                        );
+                        self.resource_text.get_mut(bar_id9).text_content =
+                            self.var_u("cost").to_string();
+                        let scroll_background =
+                            self.track_resource::<ScrollBackground>("Operation_Expand");
                         if self.var_decode_f(
                             self.clone().var_v("operation_last_window_size")[0].clone(),
                         ) != ctx.available_rect().width()
@@ -2531,77 +3191,61 @@ impl eframe::App for App {
                                 self.clone().var_v("operation_last_window_size")[1].clone(),
                             ) != ctx.available_rect().height()
                         {
-                            self.resource_scroll_background[scroll_background].resume_point =
-                                -ctx.available_rect().height();
+                            self.resource_scroll_background
+                                .get_mut(scroll_background)
+                                .resume_point = -ctx.available_rect().height();
                             for i in 0..self.resource_scroll_background[scroll_background]
                                 .image_name
                                 .len()
                             {
-                                let id = self.track_resource(
-                                    self.resource_image.clone(),
+                                let id = self.track_resource::<Image>(
                                     &self.resource_scroll_background[scroll_background].image_name
                                         [i]
                                         .clone(),
                                 );
-                                self.resource_image[id].image_size = [
+                                self.resource_image.get_mut(id).image_size = [
                                     ctx.available_rect().width(),
                                     ctx.available_rect().height() + 1_f32,
                                 ];
-                                self.resource_image[id].origin_position[1] =
+                                self.resource_image.get_mut(id).origin_position[1] =
                                     i as f32 * self.resource_image[id].image_size[1];
-                                self.resource_scroll_background[scroll_background].boundary =
-                                    ctx.available_rect().height();
+                                self.resource_scroll_background
+                                    .get_mut(scroll_background)
+                                    .boundary = ctx.available_rect().height();
                             }
                         };
                         let id_id = self.var_u("gun_selected") as usize;
-                        let id = self.track_resource(
-                            self.resource_image.clone(),
-                            &format!(
-                                "Gun_{}",
-                                self.storage_gun_content[id_id].gun_recognition_name.clone()
-                            ),
-                        );
+                        let id = self.track_resource::<Image>(&format!(
+                            "Gun_{}",
+                            self.storage_gun_content[id_id].gun_recognition_name.clone()
+                        ));
                         if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
                             if !self.var_b("pause") {
-                                self.resource_image[id].origin_position = [
+                                self.resource_image.get_mut(id).origin_position = [
                                     mouse_pos.x,
                                     mouse_pos.y - self.var_f(&format!("gun{}_recoil", id_id)),
                                 ];
                             };
                         };
-                        if self.resource_image[id].origin_position[0]
-                            - self.resource_image[id].image_size[0] / 2_f32
-                            < ctx.available_rect().width() / 2_f32 - 640_f32
-                        {
-                            self.resource_image[id].origin_position[0] =
-                                ctx.available_rect().width() / 2_f32 - 640_f32
-                                    + self.resource_image[id].image_size[0] / 2_f32;
-                        } else if self.resource_image[id].origin_position[0]
-                            + self.resource_image[id].image_size[0] / 2_f32
-                            > ctx.available_rect().width() / 2_f32 + 640_f32
-                        {
-                            self.resource_image[id].origin_position[0] =
-                                ctx.available_rect().width() / 2_f32 + 640_f32
-                                    - self.resource_image[id].image_size[0] / 2_f32;
-                        };
-                        if self.resource_image[id].origin_position[1]
-                            - self.resource_image[id].image_size[1] / 2_f32
-                            < ctx.available_rect().height() / 2_f32 - 360_f32
-                        {
-                            self.resource_image[id].origin_position[1] =
-                                ctx.available_rect().height() / 2_f32 - 360_f32
-                                    + self.resource_image[id].image_size[1] / 2_f32;
-                        } else if self.resource_image[id].origin_position[1]
-                            + self.resource_image[id].image_size[1] / 2_f32
-                            > ctx.available_rect().height() / 2_f32 + 360_f32
-                        {
-                            self.resource_image[id].origin_position[1] =
-                                ctx.available_rect().height() / 2_f32 + 360_f32
-                                    - self.resource_image[id].image_size[1] / 2_f32;
-                        };
-                        if ctx.available_rect().width() != 1280_f32
-                            || ctx.available_rect().height() != 720_f32
-                        {
+                        // 准星随作战区域缩放，并限制在可见范围内
+                        self.resource_image.get_mut(id).image_size =
+                            viewport.to_screen_size(self.storage_gun_content[id_id].gun_size);
+                        let gun_half_size = [
+                            self.resource_image[id].image_size[0] / 2_f32,
+                            self.resource_image[id].image_size[1] / 2_f32,
+                        ];
+                        self.resource_image.get_mut(id).origin_position = [
+                            self.resource_image[id].origin_position[0]
+                                .min(visible.max.x - gun_half_size[0])
+                                .max(visible.min.x + gun_half_size[0]),
+                            self.resource_image[id].origin_position[1]
+                                .min(visible.max.y - gun_half_size[1])
+                                .max(visible.min.y + gun_half_size[1]),
+                        ];
+                        if viewport.mode == ScaleMode::Letterbox {
+                            ui.painter()
+                                .rect_filled(viewport.screen, 0.0, Color32::BLACK);
+                        } else if viewport.has_margin() {
                             if self.var_b("pause") {
                                 self.image(ui, "Operation_Expand1", ctx);
                                 self.image(ui, "Operation_Expand2", ctx);
@@ -2609,63 +3253,152 @@ impl eframe::App for App {
                                 self.scroll_background(ui, "Operation_Expand", ctx);
                             };
                         };
+                        let operation_background_id = self.track_resource::<Image>("Operation");
+                        let playfield = viewport.playfield();
+                        self.resource_image
+                            .get_mut(operation_background_id)
+                            .origin_position = [
+                            playfield.center().x - ctx.available_rect().width() / 2_f32,
+                            playfield.center().y - ctx.available_rect().height() / 2_f32,
+                        ];
+                        self.resource_image
+                            .get_mut(operation_background_id)
+                            .image_size = [playfield.width(), playfield.height()];
                         self.image(ui, "Operation", ctx);
-                        let gun_id = self.track_resource(
-                            self.resource_switch.clone(),
-                            &format!(
-                                "Gun_{}",
-                                self.storage_gun_content[id_id].gun_recognition_name.clone()
-                            ),
-                        );
-                        if ui.input(|i| i.pointer.button_released(PointerButton::Middle))
-                            && self.resource_switch[gun_id].state == 0
-                            && !self.var_b("pause")
-                        {
-                            if self.var_u("gun_selected") < self.var_u("gun_selectable_len") - 1 {
-                                let gun_selected = self.var_u("gun_selected");
-                                self.modify_var("gun_selected", gun_selected + 1);
-                            } else {
-                                self.modify_var("gun_selected", Value::UInt(0));
-                            };
-                            std::thread::spawn(|| {
-                                kira_play_wav("Resources/assets/sounds/Reload.wav").unwrap();
-                            });
-                        };
-                        self.resource_switch[gun_id].appearance[0].color = [
+                        let gun_id = self.track_resource::<Switch>(&format!(
+                            "Gun_{}",
+                            self.storage_gun_content[id_id].gun_recognition_name.clone()
+                        ));
+                        self.resource_switch.get_mut(gun_id).appearance[0].color = [
                             255,
                             255 - self.var_u(&format!("gun{}_temperature", id_id)) as u8,
                             255 - self.var_u(&format!("gun{}_temperature", id_id)) as u8,
                             255,
                         ];
                         if self.var_b("forced_cooling") {
-                            self.resource_switch[gun_id].appearance[2].color = [
+                            self.resource_switch.get_mut(gun_id).appearance[2].color = [
                                 255,
                                 255 - self.var_u(&format!("gun{}_temperature", id_id)) as u8,
                                 255 - self.var_u(&format!("gun{}_temperature", id_id)) as u8,
                                 255,
                             ];
                         } else {
-                            self.resource_switch[gun_id].appearance[2].color = [0, 0, 0, 255];
+                            self.resource_switch.get_mut(gun_id).appearance[2].color =
+                                [0, 0, 0, 255];
                         };
                         let mut target_line = Vec::new();
                         for i in 0..self.var_v("target_line").len() / 2 {
                             let first_element = self.var_v("target_line")[i * 2].clone();
                             let second_element = self.var_v("target_line")[i * 2 + 1].clone();
-                            target_line.push(Pos2 {
-                                x: self.var_decode_f(first_element)
-                                    + (ctx.available_rect().width() - 1280_f32) / 2_f32,
-                                y: self.var_decode_f(second_element)
-                                    + (ctx.available_rect().height() - 720_f32) / 2_f32,
-                            });
+                            target_line.push(viewport.to_screen([
+                                self.var_decode_f(first_element),
+                                self.var_decode_f(second_element),
+                            ]));
                         }
                         ui.painter().line(
                             target_line,
                             Stroke {
-                                width: 8.0,
+                                width: 8.0 * viewport.scale,
                                 color: Color32::from_rgba_unmultiplied(255, 0, 0, 255),
                             },
                         );
+                        if refresh {
+                            self.operation_step(ctx);
+                        };
+                        self.instrument_refresh(ctx, ui);
                         self.enemy_refresh(ctx, ui, refresh);
+                        self.boss_health_bar(ctx, ui);
+                        if self.action_pressed(ctx, InputAction::ToggleInstrument)
+                            && !self.var_b("pause")
+                            && !self.storage_instrument_content.is_empty()
+                        {
+                            let instrument_placing = self.var_b("instrument_placing");
+                            self.modify_var("instrument_placing", !instrument_placing);
+                            self.general_click_feedback();
+                        };
+                        if self.var_b("instrument_placing") && !self.var_b("pause") {
+                            if self.action_pressed(ctx, InputAction::NextInstrument) {
+                                if self.var_u("instrument_selected")
+                                    < self.storage_instrument_content.len() as u32 - 1
+                                {
+                                    let instrument_selected = self.var_u("instrument_selected");
+                                    self.modify_var("instrument_selected", instrument_selected + 1);
+                                } else {
+                                    self.modify_var("instrument_selected", Value::UInt(0));
+                                };
+                                self.general_click_feedback();
+                            };
+                            let instrument_id = self.var_u("instrument_selected") as usize;
+                            let instrument = self.storage_instrument_content[instrument_id].clone();
+                            if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
+                                // 将指针位置换算为1280x720作战区域内的坐标
+                                let position = viewport.to_level(mouse_pos);
+                                let instrument_size =
+                                    viewport.to_screen_size(instrument.instrument_size);
+                                let placeable = self.operation_simulation.instrument_list.len()
+                                    < self.var_u("instrument_ceiling") as usize
+                                    && self.var_u("cost") >= instrument.instrument_cost
+                                    && self
+                                        .operation_simulation
+                                        .instrument_can_place(instrument_id, position);
+                                let preview_color = if placeable {
+                                    Color32::from_rgba_unmultiplied(94, 203, 118, 200)
+                                } else {
+                                    Color32::from_rgba_unmultiplied(255, 52, 40, 200)
+                                };
+                                ui.painter().rect_stroke(
+                                    egui::Rect::from_center_size(
+                                        mouse_pos,
+                                        egui::Vec2::new(instrument_size[0], instrument_size[1]),
+                                    ),
+                                    0.0,
+                                    Stroke {
+                                        width: 3.0,
+                                        color: preview_color,
+                                    },
+                                );
+                                ui.painter().circle_stroke(
+                                    mouse_pos,
+                                    instrument.instrument_range * viewport.scale,
+                                    Stroke {
+                                        width: 2.0,
+                                        color: preview_color,
+                                    },
+                                );
+                                let text_id = self.track_resource::<Text>("Instrument_Text");
+                                self.resource_text.get_mut(text_id).text_content = format!(
+                                    "{} | {}: {} | {}: {}/{}",
+                                    instrument.instrument_name[self.config.language as usize],
+                                    game_text["instrument_cost"][self.config.language as usize],
+                                    instrument.instrument_cost,
+                                    game_text["instrument_deployed"][self.config.language as usize],
+                                    self.operation_simulation.instrument_list.len(),
+                                    self.var_u("instrument_ceiling")
+                                );
+                                self.resource_text.get_mut(text_id).origin_position = [
+                                    mouse_pos.x,
+                                    mouse_pos.y + instrument_size[1] / 2_f32 + 20_f32,
+                                ];
+                                self.text(ui, "Instrument_Text", ctx);
+                                if self.action_released(ctx, InputAction::PlaceInstrument) {
+                                    let events = if self
+                                        .operation_simulation
+                                        .place_instrument(instrument_id, position)
+                                    {
+                                        vec![SimulationEvent::InstrumentPlaced(
+                                            self.operation_simulation.instrument_list.len() - 1,
+                                        )]
+                                    } else {
+                                        vec![SimulationEvent::InstrumentRejected(instrument_id)]
+                                    };
+                                    self.operation_feedback(ctx, &events);
+                                };
+                            };
+                            if self.action_released(ctx, InputAction::CancelInstrument) {
+                                self.modify_var("instrument_placing", false);
+                                self.general_click_feedback();
+                            };
+                        };
                         self.switch(
                             &format!(
                                 "Gun_{}",
@@ -2676,30 +3409,74 @@ impl eframe::App for App {
                             true,
                             false,
                         );
+                        let crosshair = Pos2 {
+                            x: self.resource_image[id].origin_position[0],
+                            y: self.resource_image[id].origin_position[1],
+                        };
+                        if self.storage_gun_content[id_id].firing_beam()
+                            && self.resource_switch[gun_id].state == 1
+                        {
+                            ui.painter().line(
+                                vec![
+                                    viewport
+                                        .to_screen([OPERATION_SIZE[0] / 2_f32, OPERATION_SIZE[1]]),
+                                    crosshair,
+                                ],
+                                Stroke {
+                                    width: 6_f32 * viewport.scale,
+                                    color: Color32::from_rgba_unmultiplied(120, 220, 255, 180),
+                                },
+                            );
+                        };
+                        if let Some((charge_time, _)) =
+                            self.storage_gun_content[id_id].firing_charge()
+                        {
+                            if self.var_b("gun_charging") {
+                                // 蓄力进度以准星外圈的粗细表示
+                                let charge_progress = ((self.timer.now_time
+                                    - self.split_time("gun_charge_time")[0])
+                                    / charge_time)
+                                    .clamp(0_f32, 1_f32);
+                                ui.painter().circle_stroke(
+                                    crosshair,
+                                    self.resource_image[id].image_size[0] / 2_f32 + 8_f32,
+                                    Stroke {
+                                        width: 1_f32 + 5_f32 * charge_progress,
+                                        color: Color32::from_rgba_unmultiplied(
+                                            255,
+                                            240,
+                                            59,
+                                            (100_f32 + 155_f32 * charge_progress) as u8,
+                                        ),
+                                    },
+                                );
+                            };
+                        };
                         let bullets_id = if self.var_b(&format!("gun{}_reload", id_id)) {
-                            self.track_resource(self.resource_image.clone(), "Bullets_Reload")
+                            self.track_resource::<Image>("Bullets_Reload")
                         } else {
-                            self.track_resource(self.resource_image.clone(), "Bullets")
+                            self.track_resource::<Image>("Bullets")
                         };
-                        let surplus_bullets_id =
-                            self.track_resource(self.resource_text.clone(), "Surplus_Bullets");
-                        self.resource_text[surplus_bullets_id].text_content = format!(
+                        let surplus_bullets_id = self.track_resource::<Text>("Surplus_Bullets");
+                        self.resource_text.get_mut(surplus_bullets_id).text_content = format!(
                             "{}/{}",
                             self.var_u(&format!("gun{}_surplus_bullets", id_id)),
                             self.storage_gun_content[id_id].gun_catridge_clip
                         );
                         let bullets_total_size =
                             30_f32 + self.get_text_size("Surplus_Bullets", ui)[0];
-                        self.resource_text[surplus_bullets_id].origin_position = [
+                        self.resource_text
+                            .get_mut(surplus_bullets_id)
+                            .origin_position = [
                             self.resource_image[id].origin_position[0] + bullets_total_size / 2_f32,
                             self.resource_image[id].origin_position[1]
-                                + self.storage_gun_content[id_id].gun_size[1] / 2_f32
+                                + self.resource_image[id].image_size[1] / 2_f32
                                 + 6_f32,
                         ];
-                        self.resource_image[bullets_id].origin_position = [
+                        self.resource_image.get_mut(bullets_id).origin_position = [
                             self.resource_image[id].origin_position[0] - bullets_total_size / 2_f32,
                             self.resource_image[id].origin_position[1]
-                                + self.storage_gun_content[id_id].gun_size[1] / 2_f32
+                                + self.resource_image[id].image_size[1] / 2_f32
                                 + 10_f32,
                         ];
                         self.text(ui, "Surplus_Bullets", ctx);
@@ -2712,17 +3489,17 @@ impl eframe::App for App {
                             vec![
                                 Pos2 {
                                     x: self.resource_image[id].origin_position[0]
-                                        + self.storage_gun_content[id_id].gun_size[0] / 2_f32
+                                        + self.resource_image[id].image_size[0] / 2_f32
                                         + 10_f32,
                                     y: self.resource_image[id].origin_position[1]
-                                        + self.storage_gun_content[id_id].gun_size[1] / 2_f32,
+                                        + self.resource_image[id].image_size[1] / 2_f32,
                                 },
                                 Pos2 {
                                     x: self.resource_image[id].origin_position[0]
-                                        + self.storage_gun_content[id_id].gun_size[0] / 2_f32
+                                        + self.resource_image[id].image_size[0] / 2_f32
                                         + 10_f32,
                                     y: self.resource_image[id].origin_position[1]
-                                        - self.storage_gun_content[id_id].gun_size[1] / 2_f32,
+                                        - self.resource_image[id].image_size[1] / 2_f32,
                                 },
                             ],
                             Stroke {
@@ -2739,18 +3516,18 @@ impl eframe::App for App {
                             vec![
                                 Pos2 {
                                     x: self.resource_image[id].origin_position[0]
-                                        + self.storage_gun_content[id_id].gun_size[0] / 2_f32
+                                        + self.resource_image[id].image_size[0] / 2_f32
                                         + 10_f32,
                                     y: self.resource_image[id].origin_position[1]
-                                        + self.storage_gun_content[id_id].gun_size[1] / 2_f32,
+                                        + self.resource_image[id].image_size[1] / 2_f32,
                                 },
                                 Pos2 {
                                     x: self.resource_image[id].origin_position[0]
-                                        + self.storage_gun_content[id_id].gun_size[0] / 2_f32
+                                        + self.resource_image[id].image_size[0] / 2_f32
                                         + 10_f32,
                                     y: self.resource_image[id].origin_position[1]
-                                        + self.storage_gun_content[id_id].gun_size[1] / 2_f32
-                                        - self.storage_gun_content[id_id].gun_size[1]
+                                        + self.resource_image[id].image_size[1] / 2_f32
+                                        - self.resource_image[id].image_size[1]
                                             * (self.var_u(&format!("gun{}_temperature", id_id))
                                                 as f32
                                                 / 255_f32),
@@ -2766,7 +3543,8 @@ impl eframe::App for App {
                                 ),
                             },
                         );
-                        let scroll_delta = ui.input(|i| i.smooth_scroll_delta);
+                        let next_gun = self.action_pressed(ctx, InputAction::NextGun);
+                        let prev_gun = self.action_pressed(ctx, InputAction::PrevGun);
                         let horizontal_scrolling_time =
                             self.split_time("horizontal_scrolling_time")[0];
                         let scrolling_index = self.find_pause_index(horizontal_scrolling_time);
@@ -2779,12 +3557,12 @@ impl eframe::App for App {
                             self.timer.now_time - self.split_time("horizontal_scrolling_time")[0]
                                 >= 0.5
                         };
-                        if scroll_delta.x != 0.0
+                        if (next_gun || prev_gun)
                             && scroll_time_waited
                             && self.resource_switch[gun_id].state == 0
                             && !self.var_b("pause")
                         {
-                            if scroll_delta.x < -20.0 {
+                            if next_gun {
                                 self.add_split_time("horizontal_scrolling_time", true);
                                 if self.var_u("gun_selected") < self.var_u("gun_selectable_len") - 1
                                 {
@@ -2793,10 +3571,11 @@ impl eframe::App for App {
                                 } else {
                                     self.modify_var("gun_selected", Value::UInt(0));
                                 };
-                                std::thread::spawn(|| {
-                                    kira_play_wav("Resources/assets/sounds/Reload.wav").unwrap();
-                                });
-                            } else if scroll_delta.x > 20.0 {
+                                self.play_sound(
+                                    "Resources/assets/sounds/Reload.wav",
+                                    AudioBus::Sfx,
+                                );
+                            } else if prev_gun {
                                 self.add_split_time("horizontal_scrolling_time", true);
                                 if self.var_u("gun_selected") > 0 {
                                     let gun_selected = self.var_u("gun_selected");
@@ -2808,27 +3587,12 @@ impl eframe::App for App {
                                         Value::UInt(gun_selectable_len - 1),
                                     );
                                 };
-                                std::thread::spawn(|| {
-                                    kira_play_wav("Resources/assets/sounds/Reload.wav").unwrap();
-                                });
+                                self.play_sound(
+                                    "Resources/assets/sounds/Reload.wav",
+                                    AudioBus::Sfx,
+                                );
                             };
                         };
-                        let cost_recover_time = self.split_time("cost_recover_time")[0];
-                        let cost_recover_time_index = self.find_pause_index(cost_recover_time);
-                        let cost_time_waited = if cost_recover_time_index != -1 {
-                            self.timer.now_time
-                                - self.count_pause_time(cost_recover_time_index as usize)
-                                - self.split_time("cost_recover_time")[0]
-                                >= self.var_f("cost_recover_speed")
-                        } else {
-                            self.timer.now_time - self.split_time("cost_recover_time")[0]
-                                >= self.var_f("cost_recover_speed")
-                        };
-                        if refresh && cost_time_waited && !self.var_b("pause") {
-                            let cost = self.var_u("cost");
-                            self.modify_var("cost", Value::UInt(cost + 1));
-                            self.add_split_time("cost_recover_time", true);
-                        };
                         let gun_reload_interval =
                             self.split_time(&format!("gun{}_reload_interval", id_id))[0];
                         let gun_reload_interval_index = self.find_pause_index(gun_reload_interval);
@@ -2848,9 +3612,11 @@ impl eframe::App for App {
                             && !self.var_b("pause")
                         {
                             self.add_split_time(&format!("gun{}_reload_interval", id_id), true);
-                            if scroll_delta.y != 0.0 {
+                            let reload_bullet = self.action_down(ctx, InputAction::ReloadBullet);
+                            let finish_reload = self.action_down(ctx, InputAction::FinishReload);
+                            if reload_bullet || finish_reload {
                                 let mut sound;
-                                if scroll_delta.y > 0.0 && self.var_u("storage_bullet") > 0 {
+                                if reload_bullet && self.var_u("storage_bullet") > 0 {
                                     let storage_bullet = self.var_u("storage_bullet");
                                     self.modify_var(
                                         "storage_bullet",
@@ -2865,9 +3631,7 @@ impl eframe::App for App {
                                     sound = self.storage_gun_content[id_id]
                                         .gun_reload_bullet_sound
                                         .clone();
-                                    std::thread::spawn(move || {
-                                        kira_play_wav(&sound).unwrap();
-                                    });
+                                    self.play_sound(&sound, AudioBus::Sfx);
                                     if self.var_u(&format!("gun{}_surplus_bullets", id_id))
                                         == self.storage_gun_content[id_id].gun_catridge_clip
                                     {
@@ -2875,94 +3639,86 @@ impl eframe::App for App {
                                         sound = self.storage_gun_content[id_id]
                                             .gun_reload_sound
                                             .clone();
-                                        std::thread::spawn(move || {
-                                            kira_play_wav(&sound).unwrap();
-                                        });
+                                        self.play_sound(&sound, AudioBus::Sfx);
                                     };
                                 } else if self.var_u(&format!("gun{}_surplus_bullets", id_id)) > 0 {
                                     sound =
                                         self.storage_gun_content[id_id].gun_reload_sound.clone();
                                     self.modify_var(&format!("gun{}_reload", id_id), false);
-                                    std::thread::spawn(move || {
-                                        kira_play_wav(&sound).unwrap();
-                                    });
+                                    self.play_sound(&sound, AudioBus::Sfx);
                                 };
                             };
                         };
                         if self.resource_switch[gun_id].state == 0 {
-                            let shoot = self.storage_gun_content[id_id]
-                                .gun_tag
-                                .contains(&"released_shoot".to_string())
-                                && ui.input(|i| i.pointer.button_released(PointerButton::Primary))
-                                || self.storage_gun_content[id_id]
-                                    .gun_tag
-                                    .contains(&"down_shoot".to_string())
-                                    && ui.input(|i| i.pointer.button_down(PointerButton::Primary));
-                            if shoot && !self.var_b("pause") {
-                                if self.var_u(&format!("gun{}_surplus_bullets", id_id)) > 0
-                                    && !self.var_b(&format!("gun{}_reload", id_id))
+                            let gun = self.storage_gun_content[id_id].clone();
+                            let mut shoot = false;
+                            if let Some((charge_time, multiplier)) = gun.firing_charge() {
+                                // 蓄力枪械按下时开始蓄力，松开时按蓄力时长结算伤害倍率
+                                if self.action_pressed(ctx, InputAction::Fire)
+                                    && !self.var_b("pause")
+                                    && !self.var_b("instrument_placing")
                                 {
-                                    let sound =
-                                        self.storage_gun_content[id_id].gun_shoot_sound.clone();
-                                    self.add_split_time("gun_shooting_time", true);
-                                    std::thread::spawn(move || {
-                                        kira_play_wav(&sound).unwrap();
-                                    });
-                                    self.resource_switch[gun_id].state = 1;
-                                    let recoil = self.var_f(&format!("gun{}_recoil", id_id));
+                                    self.add_split_time("gun_charge_time", true);
+                                    self.modify_var("gun_charging", true);
+                                };
+                                if self.action_released(ctx, InputAction::Fire)
+                                    && self.var_b("gun_charging")
+                                {
+                                    self.modify_var("gun_charging", false);
+                                    let charge_progress = ((self.timer.now_time
+                                        - self.split_time("gun_charge_time")[0])
+                                        / charge_time)
+                                        .clamp(0_f32, 1_f32);
                                     self.modify_var(
-                                        &format!("gun{}_recoil", id_id),
+                                        "gun_damage_multiplier",
                                         Value::Float(
-                                            recoil + self.storage_gun_content[id_id].gun_recoil,
+                                            1_f32 + (multiplier - 1_f32) * charge_progress,
                                         ),
                                     );
-                                    for _ in
-                                        0..self.storage_gun_content[id_id].gun_temperature_degree
-                                    {
-                                        if self.var_u(&format!("gun{}_temperature", id_id)) < 255 {
-                                            let temperature =
-                                                self.var_u(&format!("gun{}_temperature", id_id));
-                                            self.modify_var(
-                                                &format!("gun{}_temperature", id_id),
-                                                Value::UInt(temperature + 1),
-                                            );
-                                        } else {
-                                            break;
-                                        };
-                                    }
-                                    let surplus_bullets =
-                                        self.var_u(&format!("gun{}_surplus_bullets", id_id));
-                                    self.modify_var(
-                                        &format!("gun{}_surplus_bullets", id_id),
-                                        surplus_bullets - 1,
-                                    );
-                                    if self.var_u(&format!("gun{}_surplus_bullets", id_id)) == 0 {
-                                        self.modify_var(&format!("gun{}_reload", id_id), true);
+                                    shoot = true;
+                                };
+                            } else {
+                                shoot = gun.firing_released()
+                                    && self.action_released(ctx, InputAction::Fire)
+                                    || gun.firing_hold()
+                                        && self.action_down(ctx, InputAction::Fire);
+                                if shoot {
+                                    self.modify_var("gun_damage_multiplier", Value::Float(1_f32));
+                                };
+                            };
+                            // 连发的后续子弹无需再次扣动扳机
+                            let burst = !shoot && self.var_u("gun_burst_remaining") > 0;
+                            if (shoot || burst)
+                                && !self.var_b("pause")
+                                && !self.var_b("instrument_placing")
+                            {
+                                if self.var_u(&format!("gun{}_surplus_bullets", id_id)) > 0
+                                    && !self.var_b(&format!("gun{}_reload", id_id))
+                                {
+                                    if burst {
+                                        let burst_remaining = self.var_u("gun_burst_remaining");
+                                        self.modify_var(
+                                            "gun_burst_remaining",
+                                            Value::UInt(burst_remaining - 1),
+                                        );
+                                    } else {
+                                        self.modify_var(
+                                            "gun_burst_remaining",
+                                            Value::UInt(gun.firing_burst() - 1),
+                                        );
                                     };
-                                    if self.var_u(&format!("gun{}_temperature", id_id)) == 255 {
-                                        let gun_overheating_sound = self.storage_gun_content[id_id]
-                                            .gun_overheating_sound
-                                            .clone();
-                                        thread::spawn(move || {
-                                            kira_play_wav(&gun_overheating_sound)
-                                        });
-                                        self.modify_var("forced_cooling", true);
+                                    self.resource_switch.get_mut(gun_id).state = 1;
+                                    self.gun_fire_round(id_id);
+                                } else {
+                                    self.modify_var("gun_burst_remaining", Value::UInt(0));
+                                    if self.action_released(ctx, InputAction::Fire)
+                                        && gun.firing_released()
+                                        || self.action_pressed(ctx, InputAction::Fire)
+                                            && !gun.firing_released()
+                                    {
+                                        let sound_path = gun.gun_no_bullet_shoot_sound.clone();
+                                        self.play_sound(&sound_path, AudioBus::Sfx);
                                     };
-                                } else if ui
-                                    .input(|i| i.pointer.button_released(PointerButton::Primary))
-                                    && self.storage_gun_content[id_id]
-                                        .gun_tag
-                                        .contains(&"released_shoot".to_string())
-                                    || ui
-                                        .input(|i| i.pointer.button_pressed(PointerButton::Primary))
-                                        && !self.storage_gun_content[id_id]
-                                            .gun_tag
-                                            .contains(&"released_shoot".to_string())
-                                {
-                                    let sound_path = self.storage_gun_content[id_id]
-                                        .gun_no_bullet_shoot_sound
-                                        .clone();
-                                    std::thread::spawn(move || kira_play_wav(&sound_path));
                                 };
                             };
                         } else if self.resource_switch[gun_id].state == 1 && !self.var_b("pause") {
@@ -2978,8 +3734,18 @@ impl eframe::App for App {
                                     >= self.storage_gun_content[id_id].gun_shoot_speed
                             };
                             if gun_shoot_time_waited {
-                                self.resource_switch[gun_id].state = 2;
-                                self.add_split_time("gun_end_shooting_time", true);
+                                // 光束在按住期间持续输出，每个射击间隔消耗一发子弹
+                                if self.storage_gun_content[id_id].firing_beam()
+                                    && self.action_down(ctx, InputAction::Fire)
+                                    && self.var_u(&format!("gun{}_surplus_bullets", id_id)) > 0
+                                    && !self.var_b(&format!("gun{}_reload", id_id))
+                                    && !self.var_b("forced_cooling")
+                                {
+                                    self.gun_fire_round(id_id);
+                                } else {
+                                    self.resource_switch.get_mut(gun_id).state = 2;
+                                    self.add_split_time("gun_end_shooting_time", true);
+                                };
                             };
                         } else if self.resource_switch[gun_id].state == 2
                             && !self.var_b("forced_cooling")
@@ -2998,7 +3764,7 @@ impl eframe::App for App {
                                     >= self.storage_gun_content[id_id].gun_reload_time
                             };
                             if reload_time_waited {
-                                self.resource_switch[gun_id].state = 0;
+                                self.resource_switch.get_mut(gun_id).state = 0;
                             };
                         };
                         if self.var_f(&format!("gun{}_recoil", id_id)) != 0_f32
@@ -3071,22 +3837,14 @@ impl eframe::App for App {
                         self.text(ui, "Target_Enemy_Text", ctx);
                         self.text(ui, "Bullet_Text", ctx);
                         self.text(ui, "Cost_Text", ctx);
-                        let circle_width = if cost_recover_time_index != -1 {
-                            3_f32
-                                * ((self.timer.now_time
-                                    - self.split_time("cost_recover_time")[0]
-                                    - self.count_pause_time(cost_recover_time_index as usize))
-                                    / self.var_f("cost_recover_speed"))
-                        } else {
-                            3_f32
-                                * ((self.timer.now_time - self.split_time("cost_recover_time")[0])
-                                    / self.var_f("cost_recover_speed"))
-                        };
+                        let circle_width = 3_f32
+                            * ((self.operation_simulation.runtime
+                                - self.operation_simulation.cost_recover_time)
+                                / self.var_f("cost_recover_speed"));
                         ui.painter().circle_stroke(
                             Pos2 {
-                                x: ctx.available_rect().width() / 2_f32 - 640_f32
-                                    + 1280_f32 / 5_f32 * 4_f32,
-                                y: ctx.available_rect().height() / 2_f32 - 350_f32 + 35_f32,
+                                x: visible.center().x - 640_f32 + 1280_f32 / 5_f32 * 4_f32,
+                                y: visible.min.y + 10_f32 + 35_f32,
                             },
                             22_f32,
                             Stroke {
@@ -3105,7 +3863,7 @@ impl eframe::App for App {
                         } else {
                             self.pause_list.clear();
                         }
-                        if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        if self.action_pressed(ctx, InputAction::Pause) {
                             let pause = self.var_b("pause");
                             if !pause {
                                 self.add_split_time("start_pause_time", true);
@@ -3128,13 +3886,10 @@ impl eframe::App for App {
                                 );
                             };
                             self.modify_var("pause", !pause);
-                            let text_id =
-                                self.track_resource(self.resource_text.clone(), "Pause_Text");
-                            self.resource_text[text_id].text_content =
+                            let text_id = self.track_resource::<Text>("Pause_Text");
+                            self.resource_text.get_mut(text_id).text_content =
                                 game_text["pause"][self.config.language as usize].to_string();
-                            std::thread::spawn(|| {
-                                kira_play_wav("Resources/assets/sounds/Pause.wav").unwrap();
-                            });
+                            self.play_sound("Resources/assets/sounds/Pause.wav", AudioBus::Ui);
                         };
                         if self.var_b("pause") {
                             let len = self.pause_list.len();
@@ -3147,39 +3902,34 @@ impl eframe::App for App {
                             ctx.set_cursor_icon(egui::CursorIcon::None);
                         };
                         if self.login_user_config.settings["enable_timer"] == "true" {
-                            let id = self
-                                .resource_text
-                                .iter()
-                                .position(|x| x.name == "Operation_Runtime")
-                                .unwrap();
-                            let id2 = self
-                                .resource_rect
-                                .iter()
-                                .position(|x| x.name == "Operation_Runtime")
-                                .unwrap();
-                            self.resource_text[id].text_content =
+                            let id = self.resource_text.find("Operation_Runtime").unwrap();
+                            let id2 = self.resource_rect.find("Operation_Runtime").unwrap();
+                            self.resource_text.get_mut(id).text_content =
                                 format!("{:.2}", self.var_f("operation_runtime"));
-                            self.resource_rect[id2].size[0] =
+                            self.resource_rect.get_mut(id2).size[0] =
                                 90_f32 + self.get_text_size("Operation_Runtime", ui)[0];
                             self.rect(ui, "Operation_Runtime", ctx);
                             self.image(ui, "Operation_Runtime", ctx);
                             self.text(ui, "Operation_Runtime", ctx);
                         };
-                        if self.var_u("target_point") == 0
-                            || self.var_u("current_killed_target_enemy")
-                                == self.var_u("target_enemy")
-                                && !self.enemy_list.iter().any(|x| x.enemy_activated)
-                        {
+                        if self.operation_simulation.operation_over() {
                             self.add_split_time("operation_over_background_animation", true);
                             self.modify_var("in_operation", false);
+                            let runtime = self.var_f("operation_runtime");
+                            self.logger.log(
+                                "OPERATION",
+                                &format!(
+                                    "Over {} (runtime: {:.2})",
+                                    self.login_user_config.current_level, runtime
+                                ),
+                            );
+                            self.save_replay();
                         };
                         self.operation_message_box_display(ctx, ui);
                     } else {
                         ctx.set_cursor_icon(egui::CursorIcon::None);
-                        let scroll_background = self.track_resource(
-                            self.resource_scroll_background.clone(),
-                            "Operation_Expand",
-                        );
+                        let scroll_background =
+                            self.track_resource::<ScrollBackground>("Operation_Expand");
                         if self.var_decode_f(
                             self.clone().var_v("operation_last_window_size")[0].clone(),
                         ) != ctx.available_rect().width()
@@ -3187,43 +3937,38 @@ impl eframe::App for App {
                                 self.clone().var_v("operation_last_window_size")[1].clone(),
                             ) != ctx.available_rect().height()
                         {
-                            self.resource_scroll_background[scroll_background].resume_point =
-                                -ctx.available_rect().height();
+                            self.resource_scroll_background
+                                .get_mut(scroll_background)
+                                .resume_point = -ctx.available_rect().height();
                             for i in 0..self.resource_scroll_background[scroll_background]
                                 .image_name
                                 .len()
                             {
-                                let id = self.track_resource(
-                                    self.resource_image.clone(),
+                                let id = self.track_resource::<Image>(
                                     &self.resource_scroll_background[scroll_background].image_name
                                         [i]
                                         .clone(),
                                 );
-                                self.resource_image[id].image_size = [
+                                self.resource_image.get_mut(id).image_size = [
                                     ctx.available_rect().width(),
                                     ctx.available_rect().height() + 1_f32,
                                 ];
-                                self.resource_image[id].origin_position[1] =
+                                self.resource_image.get_mut(id).origin_position[1] =
                                     i as f32 * self.resource_image[id].image_size[1];
-                                self.resource_scroll_background[scroll_background].boundary =
-                                    ctx.available_rect().height();
+                                self.resource_scroll_background
+                                    .get_mut(scroll_background)
+                                    .boundary = ctx.available_rect().height();
                             }
                         };
                         self.image(ui, "Operation_Expand1", ctx);
                         self.image(ui, "Operation_Expand2", ctx);
                         self.image(ui, "Operation", ctx);
-                        if self.var_u("target_point") == 0
-                            || self.var_u("current_killed_target_enemy")
-                                == self.var_u("target_enemy")
-                                && !self.enemy_list.iter().any(|x| x.enemy_activated)
-                        {
+                        if self.operation_simulation.operation_over() {
                             if self.var_u("target_point") == 0 {
                                 self.rect(ui, "Operation_Fail_Background", ctx);
-                                let id = self.track_resource(
-                                    self.resource_rect.clone(),
-                                    "Operation_Fail_Background",
-                                );
-                                self.resource_rect[id].size =
+                                let id =
+                                    self.track_resource::<CustomRect>("Operation_Fail_Background");
+                                self.resource_rect.get_mut(id).size =
                                     [ctx.available_rect().width(), ctx.available_rect().height()];
                                 if self.timer.now_time
                                     - self.split_time("operation_over_background_animation")[0]
@@ -3234,7 +3979,7 @@ impl eframe::App for App {
                                         true,
                                     );
                                     if self.resource_rect[id].color[3] >= 255 - 10 {
-                                        self.resource_rect[id].color[3] = 255;
+                                        self.resource_rect.get_mut(id).color[3] = 255;
                                         self.switch_page("Operation_Result");
                                         self.modify_var("cut_to", true);
                                         self.add_split_time("cut_to_animation", true);
@@ -3251,7 +3996,7 @@ impl eframe::App for App {
                                             ctx,
                                         );
                                         if !check_resource_exist(
-                                            self.resource_image.clone(),
+                                            &self.resource_image,
                                             "Operation_Over_Image",
                                         ) {
                                             self.add_image(
@@ -3271,16 +4016,14 @@ impl eframe::App for App {
                                             let id = self
                                                 .resource_image
                                                 .clone()
-                                                .iter()
-                                                .position(|x| x.name == "Operation_Over_Image")
+                                                .find("Operation_Over_Image")
                                                 .unwrap();
                                             let id2 = self
                                                 .resource_image_texture
                                                 .clone()
-                                                .iter()
-                                                .position(|x| x.name == "Operation_Over_Image")
+                                                .find("Operation_Over_Image")
                                                 .unwrap();
-                                            self.resource_image[id].image_texture =
+                                            self.resource_image.get_mut(id).image_texture =
                                                 self.resource_image_texture[id2].texture.clone();
                                         };
                                         self.add_image_texture(
@@ -3290,10 +4033,7 @@ impl eframe::App for App {
                                             false,
                                             ctx,
                                         );
-                                        if !check_resource_exist(
-                                            self.resource_image.clone(),
-                                            "Result",
-                                        ) {
+                                        if !check_resource_exist(&self.resource_image, "Result") {
                                             self.add_image(
                                                 "Result",
                                                 [0_f32, 50_f32, 100_f32, 100_f32],
@@ -3303,34 +4043,24 @@ impl eframe::App for App {
                                                 "Result",
                                             );
                                         } else {
-                                            let id = self
-                                                .resource_image
-                                                .iter()
-                                                .position(|x| x.name == "Result")
-                                                .unwrap();
-                                            let id2 = self
-                                                .resource_image_texture
-                                                .iter()
-                                                .position(|x| x.name == "Result")
-                                                .unwrap();
-                                            self.resource_image[id].image_texture =
+                                            let id = self.resource_image.find("Result").unwrap();
+                                            let id2 =
+                                                self.resource_image_texture.find("Result").unwrap();
+                                            self.resource_image.get_mut(id).image_texture =
                                                 self.resource_image_texture[id2].texture.clone();
                                         };
                                     } else {
-                                        self.resource_rect[id].color[3] += 10;
+                                        self.resource_rect.get_mut(id).color[3] += 10;
                                     };
                                 };
                             } else {
-                                let id = self.track_resource(
-                                    self.resource_rect.clone(),
-                                    "Operation_Win_Background",
-                                );
-                                let id2 = self.track_resource(
-                                    self.resource_text.clone(),
-                                    "Operation_Win_Text",
-                                );
-                                self.resource_rect[id].size[0] = ctx.available_rect().width();
-                                self.resource_text[id2].text_content = game_text["operation_over"]
+                                let id =
+                                    self.track_resource::<CustomRect>("Operation_Win_Background");
+                                let id2 = self.track_resource::<Text>("Operation_Win_Text");
+                                self.resource_rect.get_mut(id).size[0] =
+                                    ctx.available_rect().width();
+                                self.resource_text.get_mut(id2).text_content = game_text
+                                    ["operation_over"]
                                     [self.login_user_config.language as usize]
                                     .clone();
                                 if self.timer.now_time
@@ -3341,22 +4071,24 @@ impl eframe::App for App {
                                         if self.resource_rect[id].origin_position[0] - 100_f32
                                             <= 0_f32
                                         {
-                                            self.resource_rect[id].origin_position[0] = 0_f32;
+                                            self.resource_rect.get_mut(id).origin_position[0] =
+                                                0_f32;
                                             if self.timer.now_time
                                                 - self.split_time(
                                                     "operation_over_background_animation",
                                                 )[0]
                                                 >= self.vertrefresh + 2_f32
                                             {
-                                                self.resource_rect[id].origin_position[0] -=
-                                                    100_f32;
+                                                self.resource_rect.get_mut(id).origin_position
+                                                    [0] -= 100_f32;
                                                 self.add_split_time(
                                                     "operation_over_background_animation",
                                                     true,
                                                 );
                                             };
                                         } else {
-                                            self.resource_rect[id].origin_position[0] -= 100_f32;
+                                            self.resource_rect.get_mut(id).origin_position[0] -=
+                                                100_f32;
                                             self.add_split_time(
                                                 "operation_over_background_animation",
                                                 true,
@@ -3365,20 +4097,21 @@ impl eframe::App for App {
                                     } else if self.resource_rect[id].origin_position[0] - 100_f32
                                         <= -ctx.available_rect().width()
                                     {
-                                        self.resource_rect[id].origin_position[0] =
+                                        self.resource_rect.get_mut(id).origin_position[0] =
                                             -ctx.available_rect().width();
                                         self.modify_var("fade_in_or_out", true);
                                     } else {
-                                        self.resource_rect[id].origin_position[0] -= 100_f32;
+                                        self.resource_rect.get_mut(id).origin_position[0] -=
+                                            100_f32;
                                         self.add_split_time(
                                             "operation_over_background_animation",
                                             true,
                                         );
                                     };
                                 };
-                                self.resource_text[id2].origin_position[0] = self.resource_rect[id]
-                                    .origin_position[0]
-                                    + ctx.available_rect().width() / 2_f32;
+                                self.resource_text.get_mut(id2).origin_position[0] =
+                                    self.resource_rect[id].origin_position[0]
+                                        + ctx.available_rect().width() / 2_f32;
                                 self.rect(ui, "Operation_Win_Background", ctx);
                                 self.text(ui, "Operation_Win_Text", ctx);
                             };
@@ -3395,11 +4128,12 @@ impl eframe::App for App {
                                 if let Ok(json_value) =
                                     read_from_json(&self.login_user_config.current_map)
                                 {
-                                    if let Some(read_map_information) =
-                                        Map::from_json_value(&json_value)
-                                    {
+                                    if let Some(read_map_information) = self.report_load_result(
+                                        &self.login_user_config.current_map.clone(),
+                                        Map::from_json_value(&json_value),
+                                    ) {
                                         if !check_resource_exist(
-                                            self.resource_text.clone(),
+                                            &self.resource_text,
                                             "Operation_Start_Name",
                                         ) {
                                             self.add_text(
@@ -3448,15 +4182,12 @@ impl eframe::App for App {
                                                 [1, 2, 1, 4],
                                             );
                                         } else {
-                                            let id = self.track_resource(
-                                                self.resource_text.clone(),
-                                                "Operation_Start_Name",
-                                            );
-                                            let id2 = self.track_resource(
-                                                self.resource_text.clone(),
+                                            let id =
+                                                self.track_resource::<Text>("Operation_Start_Name");
+                                            let id2 = self.track_resource::<Text>(
                                                 "Operation_Start_Name_Type",
                                             );
-                                            self.resource_text[id].text_content = format!(
+                                            self.resource_text.get_mut(id).text_content = format!(
                                                 "{} {}",
                                                 &level_part,
                                                 &read_map_information.map_content
@@ -3468,16 +4199,16 @@ impl eframe::App for App {
                                                 .level_name_expand
                                                     [self.login_user_config.language as usize]
                                             );
-                                            self.resource_text[id2].text_content = game_text
-                                                [&read_map_information.map_content
+                                            self.resource_text.get_mut(id2).text_content =
+                                                game_text[&read_map_information.map_content
                                                     [read_map_information
                                                         .map_content
                                                         .iter()
                                                         .position(|x| x.level_name == level_part)
                                                         .unwrap()]
                                                 .level_type]
-                                                [self.login_user_config.language as usize]
-                                                .clone();
+                                                    [self.login_user_config.language as usize]
+                                                    .clone();
                                             self.modify_var("enter_operation_loaded", true);
                                             self.add_split_time("start_operation_time", true);
                                         };
@@ -3488,17 +4219,10 @@ impl eframe::App for App {
                             self.image(ui, "Operation_Start_Background", ctx);
                             self.text(ui, "Operation_Start_Name", ctx);
                             self.text(ui, "Operation_Start_Name_Type", ctx);
-                            let id = self.track_resource(
-                                self.resource_image.clone(),
-                                "Operation_Start_Background",
-                            );
-                            let id2 = self
-                                .track_resource(self.resource_text.clone(), "Operation_Start_Name");
-                            let id3 = self.track_resource(
-                                self.resource_text.clone(),
-                                "Operation_Start_Name_Type",
-                            );
-                            self.resource_image[id].image_size =
+                            let id = self.track_resource::<Image>("Operation_Start_Background");
+                            let id2 = self.track_resource::<Text>("Operation_Start_Name");
+                            let id3 = self.track_resource::<Text>("Operation_Start_Name_Type");
+                            self.resource_image.get_mut(id).image_size =
                                 [ctx.available_rect().width(), ctx.available_rect().height()];
                             if self.timer.now_time - self.split_time("start_operation_time")[0]
                                 >= 3_f32
@@ -3507,16 +4231,15 @@ impl eframe::App for App {
                                     >= self.vertrefresh
                             {
                                 if self.var_b("reseted_operation_start_animation_timer") {
-                                    self.resource_image[id].alpha -= 15;
-                                    self.resource_text[id2].rgba[3] -= 15;
-                                    self.resource_text[id3].rgba[3] -= 15;
+                                    self.resource_image.get_mut(id).alpha -= 15;
+                                    self.resource_text.get_mut(id2).rgba[3] -= 15;
+                                    self.resource_text.get_mut(id3).rgba[3] -= 15;
                                     self.add_split_time("operation_start_fade_animation", true);
                                     if self.resource_image[id].alpha == 0 {
-                                        self.resource_image[id].alpha = 255;
-                                        self.resource_text[id2].rgba[3] = 255;
-                                        self.resource_text[id3].rgba[3] = 255;
+                                        self.resource_image.get_mut(id).alpha = 255;
+                                        self.resource_text.get_mut(id2).rgba[3] = 255;
+                                        self.resource_text.get_mut(id3).rgba[3] = 255;
                                         self.modify_var("in_operation", true);
-                                        self.add_split_time("cost_recover_time", true);
                                         self.add_split_time("operation_refresh_time", true);
                                         self.add_split_time("start_operation_time", true);
                                     };
@@ -3526,7 +4249,7 @@ impl eframe::App for App {
                                         true,
                                     );
                                     self.add_split_time("start_operation_time", true);
-                                    self.resource_image[id].overlay_color = [0, 0, 0, 255];
+                                    self.resource_image.get_mut(id).overlay_color = [0, 0, 0, 255];
                                 };
                             };
                         };
@@ -3564,10 +4287,7 @@ impl eframe::App for App {
                             false,
                             ctx,
                         );
-                        if !check_resource_exist(
-                            self.resource_image.clone(),
-                            "Operation_Over_Image",
-                        ) {
+                        if !check_resource_exist(&self.resource_image, "Operation_Over_Image") {
                             self.add_image(
                                 "Operation_Over_Image",
                                 [
@@ -3585,16 +4305,14 @@ impl eframe::App for App {
                             let id = self
                                 .resource_image
                                 .clone()
-                                .iter()
-                                .position(|x| x.name == "Operation_Over_Image")
+                                .find("Operation_Over_Image")
                                 .unwrap();
                             let id2 = self
                                 .resource_image_texture
                                 .clone()
-                                .iter()
-                                .position(|x| x.name == "Operation_Over_Image")
+                                .find("Operation_Over_Image")
                                 .unwrap();
-                            self.resource_image[id].image_texture =
+                            self.resource_image.get_mut(id).image_texture =
                                 self.resource_image_texture[id2].texture.clone();
                         };
                         let mut map_information = Map {
@@ -3610,10 +4328,15 @@ impl eframe::App for App {
                             map_initial_unlock_status: false,
                             map_unlock_description: vec![],
                             map_lock_intro: "".to_string(),
+                            map_music: "".to_string(),
+                            map_endless: None,
                         };
                         if let Ok(json_value) = read_from_json(&self.login_user_config.current_map)
                         {
-                            if let Some(read_map_information) = Map::from_json_value(&json_value) {
+                            if let Some(read_map_information) = self.report_load_result(
+                                &self.login_user_config.current_map.clone(),
+                                Map::from_json_value(&json_value),
+                            ) {
                                 map_information = read_map_information;
                             };
                         };
@@ -3642,7 +4365,7 @@ impl eframe::App for App {
                             false,
                             ctx,
                         );
-                        if !check_resource_exist(self.resource_image.clone(), "Result") {
+                        if !check_resource_exist(&self.resource_image, "Result") {
                             self.add_image(
                                 "Result",
                                 [0_f32, 50_f32, 100_f32, 100_f32],
@@ -3652,17 +4375,9 @@ impl eframe::App for App {
                                 "Result",
                             );
                         } else {
-                            let id = self
-                                .resource_image
-                                .iter()
-                                .position(|x| x.name == "Result")
-                                .unwrap();
-                            let id2 = self
-                                .resource_image_texture
-                                .iter()
-                                .position(|x| x.name == "Result")
-                                .unwrap();
-                            self.resource_image[id].image_texture =
+                            let id = self.resource_image.find("Result").unwrap();
+                            let id2 = self.resource_image_texture.find("Result").unwrap();
+                            self.resource_image.get_mut(id).image_texture =
                                 self.resource_image_texture[id2].texture.clone();
                         };
                     };
@@ -3673,7 +4388,7 @@ impl eframe::App for App {
                 );
             }
             "Operation_Result" => {
-                let id = self.track_resource(self.resource_text.clone(), "Operation_Start_Name");
+                let id = self.track_resource::<Text>("Operation_Start_Name");
                 if !self.check_updated(&self.page.clone()) {
                     self.add_text(
                         [
@@ -3692,23 +4407,197 @@ impl eframe::App for App {
                         [1, 6, 1, 3],
                     );
                     self.add_var("changed_fade", false);
+                    let score = self.operation_score();
+                    let cleared = self.operation_simulation.target_point != 0;
+                    let star = score.star(
+                        &self.operation_simulation.operation.global.star_threshold,
+                        cleared,
+                        self.operation_simulation.perfect_clear,
+                    );
+                    let mut best_score = self
+                        .current_level_status_index()
+                        .map(|x| self.login_user_config.level_status[x].level_best_score)
+                        .unwrap_or(0);
+                    if cleared {
+                        best_score = best_score.max(score.total());
+                    };
+                    let language = self.login_user_config.language as usize;
+                    let mut score_content = format!(
+                                "{}: {}  {}\n{}: {}  {}: {:.1}%  {}: {}  {}: {}  {}: {:.2}s  {}: {}\n{}: {}",
+                                game_text["score_total"][language],
+                                score.total(),
+                                star_text(star),
+                                game_text["score_kills"][language],
+                                score.kills,
+                                game_text["score_accuracy"][language],
+                                score.accuracy() * 100_f32,
+                                game_text["score_target_point"][language],
+                                score.target_point,
+                                game_text["score_storage_bullet"][language],
+                                score.storage_bullet,
+                                game_text["score_time"][language],
+                                score.runtime,
+                                game_text["score_overheats"][language],
+                                score.overheats,
+                                game_text["score_best"][language],
+                                best_score
+                            );
+                    // 无尽模式记录到达的波次与评分，回放不影响记录
+                    if let Some(endless) = self.operation_simulation.endless.clone() {
+                        let current_map = self.login_user_config.current_map.clone();
+                        if !self
+                            .login_user_config
+                            .endless_status
+                            .iter()
+                            .any(|x| x.endless_map == current_map)
+                        {
+                            self.login_user_config
+                                .endless_status
+                                .push(UserEndlessStatus {
+                                    endless_map: current_map.clone(),
+                                    endless_best_wave: 0,
+                                    endless_best_score: 0,
+                                });
+                        };
+                        for endless_status in self.login_user_config.endless_status.iter_mut() {
+                            if endless_status.endless_map == current_map {
+                                if self.replay_playback.is_none() {
+                                    endless_status.endless_best_wave =
+                                        endless_status.endless_best_wave.max(endless.wave);
+                                    endless_status.endless_best_score =
+                                        endless_status.endless_best_score.max(score.total());
+                                };
+                                score_content = format!(
+                                    "{}: {}\n{}\n{}: {}  {}: {}  {}: {}",
+                                    game_text["score_total"][language],
+                                    score.total(),
+                                    score_content.lines().nth(1).unwrap_or_default(),
+                                    game_text["endless_wave"][language],
+                                    endless.wave,
+                                    game_text["endless_best_wave"][language],
+                                    endless_status.endless_best_wave,
+                                    game_text["score_best"][language],
+                                    endless_status.endless_best_score
+                                );
+                            };
+                        }
+                    };
+                    self.resource_text
+                        .retain(|x| x.name != "Operation_Score_Text");
+                    self.add_text(
+                        ["Operation_Score_Text", &score_content],
+                        [0_f32, 60_f32, 22_f32, 1000_f32, 0.0],
+                        [255, 255, 255, 255, 0, 0, 0],
+                        [true, false, false, false],
+                        false,
+                        [1, 6, 1, 3],
+                    );
                 };
-                let id2 = self.track_resource(self.resource_text.clone(), "Operation_Over_Text");
-                self.resource_text[id2].text_content = self.resource_text[id].text_content.clone();
-                let id4 = self.track_resource(self.resource_text.clone(), "Operation_Over_Text2");
-                self.resource_text[id4].text_content =
+                let id2 = self.track_resource::<Text>("Operation_Over_Text");
+                self.resource_text.get_mut(id2).text_content =
+                    self.resource_text[id].text_content.clone();
+                let id4 = self.track_resource::<Text>("Operation_Over_Text2");
+                self.resource_text.get_mut(id4).text_content =
                     game_text["operation_over"][self.login_user_config.language as usize].clone();
-                let id3 = self.track_resource(self.resource_image.clone(), "Operation_Over_Image");
-                self.resource_image[id3].image_size =
+                let id3 = self.track_resource::<Image>("Operation_Over_Image");
+                self.resource_image.get_mut(id3).image_size =
                     [ctx.available_rect().width(), ctx.available_rect().height()];
+                // 回放不影响关卡进度
                 if !self.var_b("loaded_unlock_list")
                     && !self.var_b("cut_to")
                     && self.var_u("target_point") != 0
+                    && self.replay_playback.is_none()
                 {
                     self.modify_var("loaded_unlock_list", true);
-                    if let Ok(json_value) = read_from_json(&self.login_user_config.current_map) {
-                        if let Some(read_map_information) = Map::from_json_value(&json_value) {
-                            let status = if self.var_b("perfect_clear") { 2 } else { 1 };
+                    // 通关奖励货币，完美通关额外获得一半
+                    let mut reward = self.operation_simulation.operation.global.operation_reward;
+                    if self.var_b("perfect_clear") {
+                        reward += reward / 2;
+                    };
+                    self.login_user_config.currency += reward;
+                    // 记录本关的最佳评分与星级
+                    let score = self.operation_score();
+                    let star = score.star(
+                        &self.operation_simulation.operation.global.star_threshold,
+                        true,
+                        self.operation_simulation.perfect_clear,
+                    );
+                    if let Some(index) = self.current_level_status_index() {
+                        let level_status = &mut self.login_user_config.level_status[index];
+                        level_status.level_best_score =
+                            level_status.level_best_score.max(score.total());
+                        level_status.level_best_star = level_status.level_best_star.max(star);
+                    };
+                    self.resource_image.retain(|x| x.name != "Operation_Reward");
+                    self.resource_text
+                        .retain(|x| !x.name.contains("Operation_Reward"));
+                    self.resource_message_box
+                        .retain(|x| x.name != "Operation_Reward");
+                    self.add_image_texture(
+                        "Operation_Reward",
+                        "Resources/assets/images/cost.png",
+                        [false, false],
+                        true,
+                        ctx,
+                    );
+                    self.add_image(
+                        "Operation_Reward",
+                        [0_f32, 0_f32, 50_f32, 50_f32],
+                        [0, 0, 0, 0],
+                        [false, false, true, true, false],
+                        [255, 0, 0, 0, 0],
+                        "Operation_Reward",
+                    );
+                    self.add_text(
+                        [
+                            "Operation_Reward_Title",
+                            &format!(
+                                "{}: +{}",
+                                game_text["operation_reward"]
+                                    [self.login_user_config.language as usize],
+                                reward
+                            ),
+                        ],
+                        [0_f32, 0_f32, 20_f32, 1000_f32, 0.0],
+                        [255, 255, 255, 255, 0, 0, 0],
+                        [false, false, true, true],
+                        false,
+                        [0, 0, 1, 2],
+                    );
+                    self.add_text(
+                        [
+                            "Operation_Reward_Content",
+                            &format!(
+                                "{}: {}",
+                                game_text["armoury_currency"]
+                                    [self.login_user_config.language as usize],
+                                self.login_user_config.currency
+                            ),
+                        ],
+                        [0_f32, 0_f32, 15_f32, 1000_f32, 0.0],
+                        [255, 255, 255, 255, 0, 0, 0],
+                        [false, false, true, true],
+                        false,
+                        [0, 0, 1, 2],
+                    );
+                    self.add_message_box(
+                        [
+                            "Operation_Reward",
+                            "Operation_Reward_Title",
+                            "Operation_Reward_Content",
+                            "Operation_Reward",
+                        ],
+                        [500_f32, 80_f32],
+                        false,
+                        5_f32,
+                        [30_f32, 10_f32],
+                    );
+                    if let Ok(json_value) = read_from_json(&self.login_user_config.current_map) {
+                        if let Some(read_map_information) = self.report_load_result(
+                            &self.login_user_config.current_map.clone(),
+                            Map::from_json_value(&json_value),
+                        ) {
+                            let status = if self.var_b("perfect_clear") { 2 } else { 1 };
                             let level_status = self.login_user_config.level_status.clone();
                             if self.login_user_config.level_status[level_status
                                 .iter()
@@ -3818,7 +4707,7 @@ impl eframe::App for App {
                                             .level_map
                                             .clone(),
                                     ) {
-                                        if let Some(read_map_information2) =
+                                        if let Ok(read_map_information2) =
                                             Map::from_json_value(&json_value)
                                         {
                                             if read_map_information2.map_content.iter().any(|x| {
@@ -3963,6 +4852,8 @@ impl eframe::App for App {
                                                                     .level_map
                                                                     .clone(),
                                                                 level_status: 0,
+                                                                level_best_score: 0,
+                                                                level_best_star: 0,
                                                             },
                                                         );
                                                     } else if self.login_user_config.level_status
@@ -4374,7 +5265,7 @@ impl eframe::App for App {
                                                 .map_name
                                                 .clone(),
                                         ) {
-                                            if let Some(read_map_information2) =
+                                            if let Ok(read_map_information2) =
                                                 Map::from_json_value(&json_value)
                                             {
                                                 self.add_image_texture(
@@ -4452,6 +5343,7 @@ impl eframe::App for App {
                     self.image(ui, "Result", ctx);
                     self.text(ui, "Operation_Over_Text", ctx);
                     self.text(ui, "Operation_Over_Text2", ctx);
+                    self.text(ui, "Operation_Score_Text", ctx);
                     if self.timer.now_time < 2_f32 {
                         self.modify_var("changed_fade", false);
                     } else {
@@ -4459,10 +5351,7 @@ impl eframe::App for App {
                             self.modify_var("fade_in_or_out", false);
                             self.modify_var("changed_fade", true);
                         };
-                        let id = self.track_resource(
-                            self.resource_rect.clone(),
-                            "Operation_Fail_Background",
-                        );
+                        let id = self.track_resource::<CustomRect>("Operation_Fail_Background");
                         if self.var_b("cut_to") || self.resource_rect[id].color[3] != 0 {
                             ctx.set_cursor_icon(egui::CursorIcon::None);
                         };
@@ -4473,9 +5362,9 @@ impl eframe::App for App {
                         {
                             self.add_split_time("operation_over_background_animation", true);
                             if self.resource_rect[id].color[3] <= 10 {
-                                self.resource_rect[id].color[3] = 0;
+                                self.resource_rect.get_mut(id).color[3] = 0;
                             } else {
-                                self.resource_rect[id].color[3] -= 10;
+                                self.resource_rect.get_mut(id).color[3] -= 10;
                             };
                         };
                         if self.resource_rect[id].color[3] == 0 {
@@ -4485,9 +5374,8 @@ impl eframe::App for App {
                             };
                         };
                     };
-                    let id3 = self
-                        .track_resource(self.resource_rect.clone(), "Operation_Fail_Background");
-                    self.resource_rect[id3].size =
+                    let id3 = self.track_resource::<CustomRect>("Operation_Fail_Background");
+                    self.resource_rect.get_mut(id3).size =
                         [ctx.available_rect().width(), ctx.available_rect().height()];
                     self.message_box_display(ctx, ui);
                     self.rect(ui, "Operation_Fail_Background", ctx);
@@ -4527,32 +5415,886 @@ impl eframe::App for App {
             "Editor" => {
                 if !self.check_updated(&self.page.clone()) {
                     self.add_var("editor_state", "Preparation".to_string());
+                    self.add_var("editor_level_path", "".to_string());
+                    self.add_var("editor_selected_type", "Global".to_string());
+                    self.add_var("editor_selected_index", -1_i32);
+                    self.add_var("editor_dragging", false);
+                    self.add_var("editor_enemy_pool", Value::Vec(Vec::new()));
+                    self.add_var("editor_path_pool", Value::Vec(Vec::new()));
+                    self.add_var("editor_new_enemy", Value::UInt(0));
+                    self.add_var("editor_return", false);
+                    self.add_split_time("editor_saved_time", false);
                 };
-                let id = self
-                    .resource_rect
-                    .iter()
-                    .position(|x| x.name == "Editor_Background")
-                    .unwrap();
-                let id2 = self
-                    .resource_rect
-                    .iter()
-                    .position(|x| x.name == "Editor_Left_Sidebar")
-                    .unwrap();
-                let id3 = self
-                    .resource_rect
-                    .iter()
-                    .position(|x| x.name == "Editor_Right_Sidebar")
-                    .unwrap();
-                self.resource_rect[id].size =
+                let id = self.resource_rect.find("Editor_Background").unwrap();
+                let id2 = self.resource_rect.find("Editor_Left_Sidebar").unwrap();
+                let id3 = self.resource_rect.find("Editor_Right_Sidebar").unwrap();
+                self.resource_rect.get_mut(id).size =
                     [ctx.available_rect().width(), ctx.available_rect().height()];
-                self.resource_rect[id2].size = [300_f32, ctx.available_rect().height()];
-                self.resource_rect[id3].size = [300_f32, ctx.available_rect().height()];
+                self.resource_rect.get_mut(id2).size = [300_f32, ctx.available_rect().height()];
+                self.resource_rect.get_mut(id3).size = [300_f32, ctx.available_rect().height()];
                 egui::CentralPanel::default().show(ctx, |ui| {
                     self.rect(ui, "Editor_Background", ctx);
-                    self.image(ui, "Editor_Background", ctx);
+                    if self.var_s("editor_state") == "Preparation" {
+                        self.image(ui, "Editor_Background", ctx);
+                    };
                     self.rect(ui, "Editor_Left_Sidebar", ctx);
                     self.rect(ui, "Editor_Right_Sidebar", ctx);
-                    if self.var_s("editor_state") == "Preparation" {};
+                    let language = self.config.language as usize;
+                    if self.var_s("editor_state") == "Preparation" {
+                        egui::Window::new("editor_level_list")
+                            .frame(self.frame)
+                            .resizable(false)
+                            .title_bar(false)
+                            .fixed_pos(egui::Pos2::new(10_f32, 10_f32))
+                            .fixed_size(egui::Vec2::new(
+                                280_f32,
+                                ctx.available_rect().height() - 20_f32,
+                            ))
+                            .show(ctx, |ui| {
+                                ui.heading(game_text["editor_select_level"][language].clone());
+                                ui.separator();
+                                egui::ScrollArea::vertical().show(ui, |ui| {
+                                    for level_file in list_files_recursive(
+                                        Path::new("Resources/config"),
+                                        "level_",
+                                    )
+                                    .unwrap_or_default()
+                                    {
+                                        if ui
+                                            .button(
+                                                level_file
+                                                    .file_stem()
+                                                    .unwrap_or_default()
+                                                    .to_string_lossy(),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.general_click_feedback();
+                                            self.editor_load_level(
+                                                &level_file.to_string_lossy(),
+                                                ctx,
+                                            );
+                                        };
+                                    }
+                                    ui.separator();
+                                    if ui
+                                        .button(game_text["editor_back"][language].clone())
+                                        .clicked()
+                                        && !self.var_b("cut_to")
+                                    {
+                                        self.general_click_feedback();
+                                        self.modify_var("editor_return", true);
+                                        self.modify_var("fade_in_or_out", true);
+                                    };
+                                });
+                            });
+                    } else if self.var_s("editor_state") == "Editing" {
+                        let mut operation = self.editor_operation.clone().unwrap();
+                        let canvas = self.editor_canvas(ctx);
+                        let to_screen = |point: [f32; 2]| Pos2 {
+                            x: canvas[0] + point[0] * canvas[2],
+                            y: canvas[1] + point[1] * canvas[2],
+                        };
+                        let canvas_rect = egui::Rect::from_min_size(
+                            to_screen([0_f32, 0_f32]),
+                            egui::Vec2::new(1280_f32 * canvas[2], 720_f32 * canvas[2]),
+                        );
+                        let image_id = self.track_resource::<Image>("Editor_Operation");
+                        self.resource_image.get_mut(image_id).origin_position =
+                            [canvas[0], canvas[1]];
+                        self.resource_image.get_mut(image_id).image_size =
+                            [1280_f32 * canvas[2], 720_f32 * canvas[2]];
+                        self.image(ui, "Editor_Operation", ctx);
+                        ui.painter().rect_stroke(
+                            canvas_rect,
+                            0.0,
+                            Stroke {
+                                width: 2.0,
+                                color: Color32::from_rgb(255, 255, 255),
+                            },
+                        );
+                        let selected_type = self.var_s("editor_selected_type");
+                        let selected_index = self.var_i("editor_selected_index");
+                        // 绘制目标线及其节点
+                        ui.painter().line(
+                            operation
+                                .global
+                                .target_line
+                                .iter()
+                                .map(|x| to_screen(*x))
+                                .collect(),
+                            Stroke {
+                                width: 8.0 * canvas[2],
+                                color: Color32::from_rgba_unmultiplied(255, 0, 0, 255),
+                            },
+                        );
+                        for (i, point) in operation.global.target_line.iter().enumerate() {
+                            ui.painter().circle_filled(
+                                to_screen(*point),
+                                6_f32,
+                                if selected_type == "TargetLine" && selected_index == i as i32 {
+                                    Color32::from_rgb(255, 240, 59)
+                                } else {
+                                    Color32::from_rgb(255, 255, 255)
+                                },
+                            );
+                        }
+                        // 绘制敌人、行进路线与出现时间
+                        for (i, target_enemy) in operation.target_enemy.iter().enumerate() {
+                            let selected = selected_type == "Enemy" && selected_index == i as i32;
+                            // 只在敌人种类或路径改变时重新读取文件
+                            let path_preview = self
+                                .editor_path_preview
+                                .entry((
+                                    target_enemy.enemy_recognition_name.clone(),
+                                    target_enemy.enemy_path.clone(),
+                                ))
+                                .or_insert_with(|| {
+                                    enemy_path_preview(
+                                        &target_enemy.enemy_recognition_name,
+                                        &target_enemy.enemy_path,
+                                    )
+                                });
+                            ui.painter().line(
+                                path_preview
+                                    .iter()
+                                    .map(|x| {
+                                        to_screen([
+                                            target_enemy.enemy_position[0] + x[0],
+                                            target_enemy.enemy_position[1] + x[1],
+                                        ])
+                                    })
+                                    .collect(),
+                                Stroke {
+                                    width: 2.0,
+                                    color: if selected {
+                                        Color32::from_rgba_unmultiplied(255, 240, 59, 200)
+                                    } else {
+                                        Color32::from_rgba_unmultiplied(35, 94, 150, 200)
+                                    },
+                                },
+                            );
+                            let enemy_rect = egui::Rect::from_min_max(
+                                to_screen([
+                                    target_enemy.enemy_position[0]
+                                        - target_enemy.enemy_size[0] / 2_f32,
+                                    target_enemy.enemy_position[1] - target_enemy.enemy_size[1],
+                                ]),
+                                to_screen([
+                                    target_enemy.enemy_position[0]
+                                        + target_enemy.enemy_size[0] / 2_f32,
+                                    target_enemy.enemy_position[1],
+                                ]),
+                            );
+                            if let Some(texture_id) = self.resource_image_texture.find(&format!(
+                                "Editor_Enemy_{}",
+                                target_enemy.enemy_recognition_name
+                            )) {
+                                if let Some(texture) =
+                                    &self.resource_image_texture[texture_id].texture
+                                {
+                                    ui.painter().image(
+                                        texture.into(),
+                                        enemy_rect,
+                                        egui::Rect::from_min_max(
+                                            egui::pos2(0.0, 0.0),
+                                            egui::pos2(1.0, 1.0),
+                                        ),
+                                        Color32::from_white_alpha(200),
+                                    );
+                                };
+                            };
+                            ui.painter().rect_stroke(
+                                enemy_rect,
+                                0.0,
+                                Stroke {
+                                    width: if selected { 3.0 } else { 1.0 },
+                                    color: if selected {
+                                        Color32::from_rgb(255, 240, 59)
+                                    } else {
+                                        Color32::from_rgb(255, 255, 255)
+                                    },
+                                },
+                            );
+                            ui.painter().text(
+                                enemy_rect.center_top(),
+                                egui::Align2::CENTER_BOTTOM,
+                                format!("#{} {:.1}s", i, target_enemy.enemy_approach_time),
+                                egui::FontId::proportional(14_f32),
+                                Color32::from_rgb(255, 255, 255),
+                            );
+                        }
+                        // 画布上的选取与拖动
+                        if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
+                            let level_pos = [
+                                ((mouse_pos.x - canvas[0]) / canvas[2]).clamp(0_f32, 1280_f32),
+                                ((mouse_pos.y - canvas[1]) / canvas[2]).clamp(0_f32, 720_f32),
+                            ];
+                            if ui.input(|i| i.pointer.primary_pressed())
+                                && canvas_rect.contains(mouse_pos)
+                            {
+                                if let Some(point_index) = operation
+                                    .global
+                                    .target_line
+                                    .iter()
+                                    .position(|x| to_screen(*x).distance(mouse_pos) <= 8_f32)
+                                {
+                                    self.modify_var(
+                                        "editor_selected_type",
+                                        "TargetLine".to_string(),
+                                    );
+                                    self.modify_var("editor_selected_index", point_index as i32);
+                                    self.modify_var("editor_dragging", true);
+                                } else if let Some(enemy_index) =
+                                    operation.target_enemy.iter().rposition(|x| {
+                                        egui::Rect::from_min_max(
+                                            to_screen([
+                                                x.enemy_position[0] - x.enemy_size[0] / 2_f32,
+                                                x.enemy_position[1] - x.enemy_size[1],
+                                            ]),
+                                            to_screen([
+                                                x.enemy_position[0] + x.enemy_size[0] / 2_f32,
+                                                x.enemy_position[1],
+                                            ]),
+                                        )
+                                        .contains(mouse_pos)
+                                    })
+                                {
+                                    self.modify_var("editor_selected_type", "Enemy".to_string());
+                                    self.modify_var("editor_selected_index", enemy_index as i32);
+                                    self.modify_var("editor_dragging", true);
+                                } else {
+                                    self.modify_var("editor_selected_type", "Global".to_string());
+                                    self.modify_var("editor_selected_index", -1);
+                                };
+                            };
+                            if self.var_b("editor_dragging")
+                                && ui.input(|i| i.pointer.primary_down())
+                            {
+                                let selected_index = self.var_i("editor_selected_index") as usize;
+                                if self.var_s("editor_selected_type") == "Enemy" {
+                                    // 以敌人中心跟随指针
+                                    operation.target_enemy[selected_index].enemy_position = [
+                                        level_pos[0],
+                                        level_pos[1]
+                                            + operation.target_enemy[selected_index].enemy_size[1]
+                                                / 2_f32,
+                                    ];
+                                } else if self.var_s("editor_selected_type") == "TargetLine" {
+                                    operation.global.target_line[selected_index] = level_pos;
+                                };
+                            };
+                        };
+                        if ui.input(|i| i.pointer.primary_released()) {
+                            self.modify_var("editor_dragging", false);
+                        };
+                        // 输入框获得焦点时Delete用于编辑文本
+                        if ui.input(|i| i.key_pressed(egui::Key::Delete))
+                            && ctx.memory(|m| m.focused()).is_none()
+                            && self.var_s("editor_selected_type") == "Enemy"
+                        {
+                            operation
+                                .target_enemy
+                                .remove(self.var_i("editor_selected_index") as usize);
+                            self.modify_var("editor_selected_type", "Global".to_string());
+                            self.modify_var("editor_selected_index", -1);
+                        };
+                        let enemy_pool: Vec<String> = self
+                            .var_v("editor_enemy_pool")
+                            .into_iter()
+                            .map(|x| self.var_decode_s(x))
+                            .collect();
+                        let path_pool: Vec<String> = self
+                            .var_v("editor_path_pool")
+                            .into_iter()
+                            .map(|x| self.var_decode_s(x))
+                            .collect();
+                        egui::Window::new("editor_left_sidebar")
+                            .frame(self.frame)
+                            .resizable(false)
+                            .title_bar(false)
+                            .fixed_pos(egui::Pos2::new(10_f32, 10_f32))
+                            .fixed_size(egui::Vec2::new(
+                                280_f32,
+                                ctx.available_rect().height() - 20_f32,
+                            ))
+                            .show(ctx, |ui| {
+                                ui.heading(self.var_s("editor_level_path"));
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .button(game_text["editor_save"][language].clone())
+                                        .clicked()
+                                    {
+                                        self.general_click_feedback();
+                                        let path = self.var_s("editor_level_path");
+                                        // 文件只读或被占用时保留编辑内容，提示后可再次保存
+                                        if let Err(e) =
+                                            write_to_json(&path, operation.to_json_value())
+                                        {
+                                            self.problem_report(
+                                                &format!(
+                                                    "{}{}",
+                                                    game_text["error_editor_save_level"][language]
+                                                        .clone(),
+                                                    path
+                                                ),
+                                                SeverityLevel::MildWarning,
+                                                &format!("{:#}", e),
+                                            );
+                                        } else {
+                                            self.add_split_time("editor_saved_time", true);
+                                        };
+                                    };
+                                    if ui
+                                        .button(game_text["editor_close_level"][language].clone())
+                                        .clicked()
+                                    {
+                                        self.general_click_feedback();
+                                        self.modify_var("editor_state", "Preparation".to_string());
+                                    };
+                                    if self.timer.now_time - self.split_time("editor_saved_time")[0]
+                                        < 2_f32
+                                    {
+                                        ui.label(game_text["editor_saved"][language].clone());
+                                    };
+                                });
+                                ui.separator();
+                                egui::ScrollArea::vertical().show(ui, |ui| {
+                                    if ui
+                                        .selectable_label(
+                                            self.var_s("editor_selected_type") == "Global",
+                                            game_text["editor_global"][language].clone(),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.modify_var(
+                                            "editor_selected_type",
+                                            "Global".to_string(),
+                                        );
+                                        self.modify_var("editor_selected_index", -1);
+                                    };
+                                    if ui
+                                        .selectable_label(
+                                            self.var_s("editor_selected_type") == "TargetLine",
+                                            game_text["editor_target_line"][language].clone(),
+                                        )
+                                        .clicked()
+                                    {
+                                        self.modify_var(
+                                            "editor_selected_type",
+                                            "TargetLine".to_string(),
+                                        );
+                                        self.modify_var("editor_selected_index", -1);
+                                    };
+                                    ui.separator();
+                                    ui.label(game_text["editor_enemy"][language].clone());
+                                    for (i, target_enemy) in
+                                        operation.target_enemy.iter().enumerate()
+                                    {
+                                        if ui
+                                            .selectable_label(
+                                                self.var_s("editor_selected_type") == "Enemy"
+                                                    && self.var_i("editor_selected_index")
+                                                        == i as i32,
+                                                format!(
+                                                    "#{} {} ({:.1}s)",
+                                                    i,
+                                                    target_enemy.enemy_recognition_name,
+                                                    target_enemy.enemy_approach_time
+                                                ),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.modify_var(
+                                                "editor_selected_type",
+                                                "Enemy".to_string(),
+                                            );
+                                            self.modify_var("editor_selected_index", i as i32);
+                                        };
+                                    }
+                                    ui.horizontal(|ui| {
+                                        let mut new_enemy = self.var_u("editor_new_enemy") as usize;
+                                        egui::ComboBox::from_id_salt("editor_new_enemy")
+                                            .selected_text(
+                                                enemy_pool
+                                                    .get(new_enemy)
+                                                    .cloned()
+                                                    .unwrap_or_default(),
+                                            )
+                                            .show_ui(ui, |ui| {
+                                                for (i, enemy_name) in enemy_pool.iter().enumerate()
+                                                {
+                                                    ui.selectable_value(
+                                                        &mut new_enemy,
+                                                        i,
+                                                        enemy_name.clone(),
+                                                    );
+                                                }
+                                            });
+                                        self.modify_var("editor_new_enemy", new_enemy as u32);
+                                        if ui
+                                            .button(game_text["editor_add"][language].clone())
+                                            .clicked()
+                                            && !enemy_pool.is_empty()
+                                        {
+                                            self.general_click_feedback();
+                                            operation.target_enemy.push(OperationTargetEnemy {
+                                                enemy_recognition_name: enemy_pool[new_enemy]
+                                                    .clone(),
+                                                enemy_position: [640_f32, 100_f32],
+                                                enemy_size: [100_f32, 100_f32],
+                                                enemy_path: vec![],
+                                                enemy_approach_time: operation
+                                                    .target_enemy
+                                                    .iter()
+                                                    .map(|x| x.enemy_approach_time)
+                                                    .fold(0_f32, f32::max),
+                                                enemy_approach_alpha: 0,
+                                                enemy_increase_alpha_speed: 5,
+                                            });
+                                            self.modify_var(
+                                                "editor_selected_type",
+                                                "Enemy".to_string(),
+                                            );
+                                            self.modify_var(
+                                                "editor_selected_index",
+                                                operation.target_enemy.len() as i32 - 1,
+                                            );
+                                        };
+                                    });
+                                    ui.separator();
+                                    ui.label(game_text["editor_message_box"][language].clone());
+                                    for (i, message_box) in operation.message_box.iter().enumerate()
+                                    {
+                                        if ui
+                                            .selectable_label(
+                                                self.var_s("editor_selected_type") == "MessageBox"
+                                                    && self.var_i("editor_selected_index")
+                                                        == i as i32,
+                                                format!(
+                                                    "#{} {} ({:.1}s)",
+                                                    i,
+                                                    message_box
+                                                        .box_title
+                                                        .get(language)
+                                                        .cloned()
+                                                        .unwrap_or_default(),
+                                                    message_box.box_appear_time
+                                                ),
+                                            )
+                                            .clicked()
+                                        {
+                                            self.modify_var(
+                                                "editor_selected_type",
+                                                "MessageBox".to_string(),
+                                            );
+                                            self.modify_var("editor_selected_index", i as i32);
+                                        };
+                                    }
+                                    if ui
+                                        .button(game_text["editor_add"][language].clone())
+                                        .clicked()
+                                    {
+                                        self.general_click_feedback();
+                                        operation.message_box.push(OperationMessageBox {
+                                            box_size: [300_f32, 100_f32],
+                                            box_image_path: "Resources/assets/images/icon.png"
+                                                .to_string(),
+                                            box_title: vec![
+                                                "".to_string();
+                                                self.config.amount_languages as usize
+                                            ],
+                                            box_content: vec![
+                                                "".to_string();
+                                                self.config.amount_languages as usize
+                                            ],
+                                            box_title_color: [255, 255, 255, 255],
+                                            box_content_color: [255, 255, 255, 255],
+                                            box_existing_time: 3_f32,
+                                            box_appear_time: 0_f32,
+                                            box_enable: true,
+                                            box_trigger_phase: "".to_string(),
+                                        });
+                                        self.modify_var(
+                                            "editor_selected_type",
+                                            "MessageBox".to_string(),
+                                        );
+                                        self.modify_var(
+                                            "editor_selected_index",
+                                            operation.message_box.len() as i32 - 1,
+                                        );
+                                    };
+                                });
+                            });
+                        egui::Window::new("editor_right_sidebar")
+                            .frame(self.frame)
+                            .resizable(false)
+                            .title_bar(false)
+                            .fixed_pos(egui::Pos2::new(
+                                ctx.available_rect().width() - 290_f32,
+                                10_f32,
+                            ))
+                            .fixed_size(egui::Vec2::new(
+                                280_f32,
+                                ctx.available_rect().height() - 20_f32,
+                            ))
+                            .show(ctx, |ui| {
+                                egui::ScrollArea::vertical().show(ui, |ui| {
+                                    let selected_type = self.var_s("editor_selected_type");
+                                    let selected_index = self.var_i("editor_selected_index");
+                                    if selected_type == "Global" {
+                                        let global = &mut operation.global;
+                                        ui.heading(game_text["editor_global"][language].clone());
+                                        ui.separator();
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_target_point"][language].clone(),
+                                            );
+                                            ui.add(egui::DragValue::new(&mut global.target_point));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_storage_bullet"][language]
+                                                    .clone(),
+                                            );
+                                            ui.add(egui::DragValue::new(
+                                                &mut global.storage_bullet,
+                                            ));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["instrument_cost"][language].clone(),
+                                            );
+                                            ui.add(egui::DragValue::new(&mut global.cost));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_cost_recover_speed"][language]
+                                                    .clone(),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut global.cost_recover_speed,
+                                                )
+                                                .speed(0.1)
+                                                .range(0.1..=f32::MAX),
+                                            );
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_instrument_ceiling"][language]
+                                                    .clone(),
+                                            );
+                                            ui.add(egui::DragValue::new(
+                                                &mut global.instrument_ceiling,
+                                            ));
+                                        });
+                                        ui.label(
+                                            game_text["editor_operation_background"][language]
+                                                .clone(),
+                                        );
+                                        ui.text_edit_singleline(&mut global.operation_background);
+                                        ui.text_edit_singleline(
+                                            &mut global.operation_background_expand,
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut global.operation_start_background,
+                                        );
+                                        ui.text_edit_singleline(
+                                            &mut global.operation_over_background,
+                                        );
+                                    } else if selected_type == "TargetLine" {
+                                        ui.heading(
+                                            game_text["editor_target_line"][language].clone(),
+                                        );
+                                        ui.separator();
+                                        let mut remove_point = None;
+                                        let point_count = operation.global.target_line.len();
+                                        for (i, point) in
+                                            operation.global.target_line.iter_mut().enumerate()
+                                        {
+                                            ui.horizontal(|ui| {
+                                                ui.label(format!("#{}", i));
+                                                ui.add(
+                                                    egui::DragValue::new(&mut point[0])
+                                                        .range(0_f32..=1280_f32),
+                                                );
+                                                ui.add(
+                                                    egui::DragValue::new(&mut point[1])
+                                                        .range(0_f32..=720_f32),
+                                                );
+                                                if point_count > 2
+                                                    && ui
+                                                        .button(
+                                                            game_text["editor_delete"][language]
+                                                                .clone(),
+                                                        )
+                                                        .clicked()
+                                                {
+                                                    remove_point = Some(i);
+                                                };
+                                            });
+                                        }
+                                        if let Some(i) = remove_point {
+                                            self.general_click_feedback();
+                                            operation.global.target_line.remove(i);
+                                            self.modify_var("editor_selected_index", -1);
+                                        };
+                                        if ui
+                                            .button(game_text["editor_add"][language].clone())
+                                            .clicked()
+                                        {
+                                            self.general_click_feedback();
+                                            let last_point = operation
+                                                .global
+                                                .target_line
+                                                .last()
+                                                .copied()
+                                                .unwrap_or([0_f32, 720_f32]);
+                                            operation.global.target_line.push([
+                                                (last_point[0] + 100_f32).min(1280_f32),
+                                                last_point[1],
+                                            ]);
+                                        };
+                                    } else if selected_type == "Enemy"
+                                        && (selected_index as usize) < operation.target_enemy.len()
+                                    {
+                                        let target_enemy =
+                                            &mut operation.target_enemy[selected_index as usize];
+                                        ui.heading(format!(
+                                            "{} #{}",
+                                            game_text["editor_enemy"][language], selected_index
+                                        ));
+                                        ui.separator();
+                                        egui::ComboBox::from_id_salt("editor_enemy_name")
+                                            .selected_text(
+                                                target_enemy.enemy_recognition_name.clone(),
+                                            )
+                                            .show_ui(ui, |ui| {
+                                                for enemy_name in &enemy_pool {
+                                                    ui.selectable_value(
+                                                        &mut target_enemy.enemy_recognition_name,
+                                                        enemy_name.clone(),
+                                                        enemy_name.clone(),
+                                                    );
+                                                }
+                                            });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_position"][language].clone(),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut target_enemy.enemy_position[0],
+                                                )
+                                                .range(0_f32..=1280_f32),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut target_enemy.enemy_position[1],
+                                                )
+                                                .range(0_f32..=720_f32),
+                                            );
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(game_text["editor_size"][language].clone());
+                                            ui.add(egui::DragValue::new(
+                                                &mut target_enemy.enemy_size[0],
+                                            ));
+                                            ui.add(egui::DragValue::new(
+                                                &mut target_enemy.enemy_size[1],
+                                            ));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_approach_time"][language].clone(),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut target_enemy.enemy_approach_time,
+                                                )
+                                                .speed(0.1)
+                                                .range(0_f32..=f32::MAX),
+                                            );
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_approach_alpha"][language]
+                                                    .clone(),
+                                            );
+                                            ui.add(egui::DragValue::new(
+                                                &mut target_enemy.enemy_approach_alpha,
+                                            ));
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_increase_alpha_speed"][language]
+                                                    .clone(),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut target_enemy.enemy_increase_alpha_speed,
+                                                )
+                                                .range(1..=255),
+                                            );
+                                        });
+                                        ui.separator();
+                                        ui.label(game_text["editor_path"][language].clone());
+                                        let mut remove_path = None;
+                                        for (i, path_name) in
+                                            target_enemy.enemy_path.iter_mut().enumerate()
+                                        {
+                                            ui.horizontal(|ui| {
+                                                egui::ComboBox::from_id_salt(format!(
+                                                    "editor_enemy_path{}",
+                                                    i
+                                                ))
+                                                .selected_text(path_name.clone())
+                                                .show_ui(ui, |ui| {
+                                                    for path in &path_pool {
+                                                        ui.selectable_value(
+                                                            path_name,
+                                                            path.clone(),
+                                                            path.clone(),
+                                                        );
+                                                    }
+                                                });
+                                                if ui
+                                                    .button(
+                                                        game_text["editor_delete"][language]
+                                                            .clone(),
+                                                    )
+                                                    .clicked()
+                                                {
+                                                    remove_path = Some(i);
+                                                };
+                                            });
+                                        }
+                                        if let Some(i) = remove_path {
+                                            self.general_click_feedback();
+                                            target_enemy.enemy_path.remove(i);
+                                        };
+                                        if ui
+                                            .button(game_text["editor_add"][language].clone())
+                                            .clicked()
+                                            && !path_pool.is_empty()
+                                        {
+                                            self.general_click_feedback();
+                                            target_enemy.enemy_path.push(path_pool[0].clone());
+                                        };
+                                        ui.separator();
+                                        if ui
+                                            .button(game_text["editor_delete"][language].clone())
+                                            .clicked()
+                                        {
+                                            self.general_click_feedback();
+                                            operation.target_enemy.remove(selected_index as usize);
+                                            self.modify_var(
+                                                "editor_selected_type",
+                                                "Global".to_string(),
+                                            );
+                                            self.modify_var("editor_selected_index", -1);
+                                        };
+                                    } else if selected_type == "MessageBox"
+                                        && (selected_index as usize) < operation.message_box.len()
+                                    {
+                                        let message_box =
+                                            &mut operation.message_box[selected_index as usize];
+                                        ui.heading(format!(
+                                            "{} #{}",
+                                            game_text["editor_message_box"][language],
+                                            selected_index
+                                        ));
+                                        ui.separator();
+                                        ui.horizontal(|ui| {
+                                            ui.label(game_text["editor_size"][language].clone());
+                                            ui.add(egui::DragValue::new(
+                                                &mut message_box.box_size[0],
+                                            ));
+                                            ui.add(egui::DragValue::new(
+                                                &mut message_box.box_size[1],
+                                            ));
+                                        });
+                                        ui.label(game_text["editor_box_image"][language].clone());
+                                        ui.text_edit_singleline(&mut message_box.box_image_path);
+                                        message_box.box_title.resize(
+                                            self.config.amount_languages as usize,
+                                            "".to_string(),
+                                        );
+                                        message_box.box_content.resize(
+                                            self.config.amount_languages as usize,
+                                            "".to_string(),
+                                        );
+                                        for i in 0..self.config.amount_languages as usize {
+                                            ui.label(format!(
+                                                "{} ({})",
+                                                game_text["editor_box_title"][language],
+                                                game_text["language"][i]
+                                            ));
+                                            ui.text_edit_singleline(&mut message_box.box_title[i]);
+                                            ui.label(format!(
+                                                "{} ({})",
+                                                game_text["editor_box_content"][language],
+                                                game_text["language"][i]
+                                            ));
+                                            ui.text_edit_multiline(&mut message_box.box_content[i]);
+                                        }
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_box_title"][language].clone(),
+                                            );
+                                            ui.color_edit_button_srgba_unmultiplied(
+                                                &mut message_box.box_title_color,
+                                            );
+                                            ui.label(
+                                                game_text["editor_box_content"][language].clone(),
+                                            );
+                                            ui.color_edit_button_srgba_unmultiplied(
+                                                &mut message_box.box_content_color,
+                                            );
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_appear_time"][language].clone(),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut message_box.box_appear_time,
+                                                )
+                                                .speed(0.1)
+                                                .range(0_f32..=f32::MAX),
+                                            );
+                                        });
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                game_text["editor_existing_time"][language].clone(),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut message_box.box_existing_time,
+                                                )
+                                                .speed(0.1)
+                                                .range(0_f32..=f32::MAX),
+                                            );
+                                        });
+                                        ui.separator();
+                                        if ui
+                                            .button(game_text["editor_delete"][language].clone())
+                                            .clicked()
+                                        {
+                                            self.general_click_feedback();
+                                            operation.message_box.remove(selected_index as usize);
+                                            self.modify_var(
+                                                "editor_selected_type",
+                                                "Global".to_string(),
+                                            );
+                                            self.modify_var("editor_selected_index", -1);
+                                        };
+                                    };
+                                });
+                            });
+                        self.editor_operation = Some(operation);
+                    };
                     let fade_in_or_out = self.var_b("fade_in_or_out");
                     if self.fade(
                         fade_in_or_out,
@@ -4574,9 +6316,11 @@ impl eframe::App for App {
                         20,
                     ) == 255
                         && fade_in_or_out
-                        && self.var_b("changed_fade")
+                        && self.var_b("editor_return")
                     {
                         self.modify_var("cut_to", true);
+                        self.modify_var("editor_return", false);
+                        self.modify_var("editor_state", "Preparation".to_string());
                         self.switch_page("Select_Level");
                         self.modify_var("remove_node", true);
                         self.modify_var("fade_in_or_out", false);
@@ -4588,14 +6332,22 @@ impl eframe::App for App {
             }
             "Error" => {
                 self.check_updated(&self.page.clone());
-                let id = self.track_resource(self.resource_text.clone(), "Error_Pages_Reason");
-                let id2 = self.track_resource(self.resource_text.clone(), "Error_Pages_Solution");
-                let id3 = self.track_resource(self.resource_rect.clone(), "Error_Pages_Background");
-                self.resource_text[id].text_content =
-                    game_text["error_pages_reason"][self.config.language as usize].clone();
-                self.resource_text[id2].text_content =
+                let id = self.track_resource::<Text>("Error_Pages_Reason");
+                let id2 = self.track_resource::<Text>("Error_Pages_Solution");
+                let id3 = self.track_resource::<CustomRect>("Error_Pages_Background");
+                self.resource_text.get_mut(id).text_content = if let Some(load_error) =
+                    &self.fatal_load_error
+                {
+                    format!(
+                        "{}\n{}",
+                        game_text["error_pages_reason"][self.config.language as usize], load_error
+                    )
+                } else {
+                    game_text["error_pages_reason"][self.config.language as usize].clone()
+                };
+                self.resource_text.get_mut(id2).text_content =
                     game_text["error_pages_solution"][self.config.language as usize].clone();
-                self.resource_rect[id3].size =
+                self.resource_rect.get_mut(id3).size =
                     [ctx.available_rect().width(), ctx.available_rect().height()];
                 egui::CentralPanel::default().show(ctx, |ui| {
                     self.rect(ui, "Error_Pages_Background", ctx);
@@ -4625,9 +6377,7 @@ impl eframe::App for App {
                             [self.config.language as usize]
                             .clone(),
                     );
-                    std::thread::spawn(|| {
-                        kira_play_wav("Resources/assets/sounds/Error.wav").unwrap();
-                    });
+                    self.play_sound("Resources/assets/sounds/Error.wav", AudioBus::Ui);
                     self.switch_page("Error");
                 };
             }
@@ -4640,10 +6390,8 @@ impl eframe::App for App {
             })
             .show_separator_line(false)
             .show(ctx, |ui| {
-                if ctx.input(|i| i.key_pressed(egui::Key::F3)) && self.config.enable_debug_mode {
-                    std::thread::spawn(|| {
-                        kira_play_wav("Resources/assets/sounds/Notification.wav").unwrap();
-                    });
+                if self.action_pressed(ctx, InputAction::DebugToggle) && self.config.enable_debug_mode {
+                    self.play_sound("Resources/assets/sounds/Notification.wav", AudioBus::Ui);
                     let enable_debug_mode = self.var_b("enable_debug_mode");
                     self.modify_var("enable_debug_mode", !enable_debug_mode);
                 };
@@ -4837,6 +6585,35 @@ impl eframe::App for App {
                                     });
                         });
                     });
+                    egui::Window::new("enemy_status")
+                    .frame(self.frame)
+                    .title_bar(false)
+                    .open(&mut self.var_b("debug_enemy_window"))
+                    .show(ctx, |ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(game_text["debug_enemy_status"][self.config.language as usize].clone());
+                        });
+                        ui.separator();
+                        egui::ScrollArea::vertical()
+                        .max_height(ctx.available_rect().height() - 100.0)
+                        .max_width(ctx.available_rect().width() - 100.0)
+                        .show(ui, |ui| {
+                            self.operation_simulation.enemy_list
+                                    .iter()
+                                    .filter(|t| t.enemy_activated)
+                                    .for_each(|t| {
+                                        ui.label(format!("{}: {}", game_text["debug_resource_name"][self.config.language as usize].clone(), t.enemy_name));
+                                        ui.colored_label(egui::Color32::LIGHT_RED, format!("{}: {:.1}/{:.1}", game_text["debug_enemy_hp"][self.config.language as usize].clone(), t.enemy_hp, t.enemy_initial_hp));
+                                        if t.enemy_max_shield > 0_f32 {
+                                            ui.colored_label(egui::Color32::LIGHT_BLUE, format!("{}: {:.1}/{:.1}", game_text["debug_enemy_shield"][self.config.language as usize].clone(), t.enemy_shield, t.enemy_max_shield));
+                                        };
+                                        ui.colored_label(egui::Color32::LIGHT_RED, format!("{}: {:?}", game_text["debug_enemy_tag"][self.config.language as usize].clone(), t.enemy_behavior));
+                                        ui.colored_label(egui::Color32::LIGHT_RED, format!("{}: {:?}", game_text["debug_enemy_detected"][self.config.language as usize].clone(), t.enemy_detected));
+                                        ui.colored_label(egui::Color32::LIGHT_RED, format!("{}: {:?}", game_text["debug_resource_position"][self.config.language as usize].clone(), t.enemy_position));
+                                        ui.separator();
+                                    });
+                        });
+                    });
                     egui::Window::new("problem_report")
                     .frame(self.frame)
                     .title_bar(false)
@@ -4909,27 +6686,62 @@ impl eframe::App for App {
                             ui.vertical(|ui| {
                                 if ui.button(game_text["debug_frame_number_details"][self.config.language as usize].clone()).clicked()
                                 {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                     let flip = !self.var_b("debug_fps_window");
                                     self.modify_var("debug_fps_window", flip);
                                 };
                                 if ui.button(game_text["debug_resource_list"][self.config.language as usize].clone()).clicked()
                                 {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                     let flip = !self.var_b("debug_resource_list_window");
                                     self.modify_var("debug_resource_list_window", flip);
                                 };
                                 if ui.button(game_text["debug_render_resource_list"][self.config.language as usize].clone()).clicked() {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                     let flip = !self.var_b("debug_render_resource_list_window");
                                     self.modify_var("debug_render_resource_list_window", flip);
                                 };
                                 if ui.button(game_text["debug_problem_report"][self.config.language as usize].clone()).clicked()
                                 {
-                                    general_click_feedback();
+                                    self.general_click_feedback();
                                     let flip = !self.var_b("debug_problem_window");
                                     self.modify_var("debug_problem_window", flip);
                                 };
+                                if ui.button(game_text["debug_enemy_status"][self.config.language as usize].clone()).clicked()
+                                {
+                                    self.general_click_feedback();
+                                    let flip = !self.var_b("debug_enemy_window");
+                                    self.modify_var("debug_enemy_window", flip);
+                                };
+                                if ui.button(game_text["debug_open_log"][self.config.language as usize].clone()).clicked()
+                                {
+                                    self.general_click_feedback();
+                                    if let Err(e) = open_with_system(&latest_log()) {
+                                        self.problem_report(
+                                            &game_text["error_open_log"][self.config.language as usize].clone(),
+                                            SeverityLevel::MildWarning,
+                                            &e.to_string(),
+                                        );
+                                    };
+                                };
+                                if ui.button(game_text["debug_export_log"][self.config.language as usize].clone()).clicked()
+                                {
+                                    self.general_click_feedback();
+                                    if let Some(path) = FileDialog::new()
+                                        .set_title(&game_text["debug_export_log"][self.config.language as usize].clone())
+                                        .set_file_name(&format!("targeted_vector_{}.log", Local::now().format("%Y%m%d%H%M%S")))
+                                        .add_filter("", &["log"])
+                                        .save_file()
+                                    {
+                                        if let Err(e) = fs::copy(latest_log(), &path) {
+                                            self.problem_report(
+                                                &game_text["error_export_log"][self.config.language as usize].clone(),
+                                                SeverityLevel::MildWarning,
+                                                &e.to_string(),
+                                            );
+                                        };
+                                    };
+                                };
                             });
                             ui.vertical(|ui| {
                                 ui.label(
@@ -4967,10 +6779,14 @@ impl eframe::App for App {
                     });
                 };
             });
-        let id = self.track_resource(self.resource_page.clone(), &self.page.clone());
+        let id = self.track_resource::<PageData>(&self.page.clone());
         if self.resource_page[id].forced_update {
             // 请求重新绘制界面
             ctx.request_repaint();
         };
+        if self.gamepad.connected() {
+            // 手柄事件不会唤醒界面，接入手柄时持续重绘以便读取
+            ctx.request_repaint();
+        };
     }
 }
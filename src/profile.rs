@@ -0,0 +1,124 @@
+//! profile.rs is the profile archive of the Targeted Vector, it packs a user and their wallpaper into one portable file.
+use crate::function::{check_file_exists, read_from_json, write_to_json, User};
+use crate::load_error::{FieldKind, JsonField, LoadError};
+use json::JsonValue;
+use std::fs;
+use std::path::Path;
+
+// 当前的存档格式版本，读取更高版本的存档时报错
+pub const PROFILE_ARCHIVE_VERSION: u32 = 1;
+pub const PROFILE_ARCHIVE_EXTENSION: &str = "tvprofile";
+pub const DEFAULT_WALLPAPER: &str = "Resources/assets/images/wallpaper.png";
+
+#[derive(Debug, Clone)]
+pub struct ProfileArchive {
+    pub archive_version: u32,
+    pub user: User,
+    // 自定义壁纸的PNG数据，使用默认壁纸时为空
+    pub wallpaper: Vec<u8>,
+}
+
+impl ProfileArchive {
+    pub fn from_user(user: &User) -> ProfileArchive {
+        ProfileArchive {
+            archive_version: PROFILE_ARCHIVE_VERSION,
+            user: user.clone(),
+            wallpaper: if user.wallpaper != DEFAULT_WALLPAPER {
+                fs::read(&user.wallpaper).unwrap_or_default()
+            } else {
+                vec![]
+            },
+        }
+    }
+
+    pub fn from_json_value(value: &JsonValue) -> Result<ProfileArchive, LoadError> {
+        let archive_version = value.field_u32("archive_version")?;
+        if archive_version > PROFILE_ARCHIVE_VERSION {
+            return Err(LoadError::new(
+                &format!("archive version {} or lower", PROFILE_ARCHIVE_VERSION),
+                &value["archive_version"],
+            )
+            .at("archive_version"));
+        };
+        let mut user = User::from_json_value(&value["user"]).map_err(|e| e.at("user"))?;
+        // 用户名会成为文件名的一部分，与注册时一样去除空格与路径分隔符
+        user.name = user
+            .name
+            .replace(" ", "")
+            .replace("/", "")
+            .replace("\\", "");
+        if user.name.is_empty() {
+            return Err(
+                LoadError::new("a non-empty user name", &value["user"]["name"])
+                    .at("name")
+                    .at("user"),
+            );
+        };
+        Ok(ProfileArchive {
+            archive_version,
+            user,
+            wallpaper: value.field("wallpaper", FieldKind::Str, |x| decode_hex(x.as_str()?))?,
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        json::object! {
+            archive_version: self.archive_version,
+            user: self.user.to_json_value(),
+            wallpaper: encode_hex(&self.wallpaper),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<ProfileArchive, LoadError> {
+        let file = path.display().to_string();
+        let json_value = read_from_json(path).map_err(|e| LoadError::unreadable(&file, &e))?;
+        ProfileArchive::from_json_value(&json_value).map_err(|e| e.in_file(&file))
+    }
+
+    pub fn export(&self, path: &Path) -> anyhow::Result<()> {
+        write_to_json(path, self.to_json_value())
+    }
+
+    // 以name为用户名安装到本机，覆盖同名用户的存档
+    pub fn install(&self, name: &str) -> anyhow::Result<User> {
+        let mut user = self.user.clone();
+        user.name = name.to_string();
+        if !self.wallpaper.is_empty() {
+            let wallpaper = format!("Resources/assets/images/{}_new_wallpaper.png", name);
+            fs::write(&wallpaper, &self.wallpaper)?;
+            user.wallpaper = wallpaper;
+        } else if !check_file_exists(&user.wallpaper) {
+            user.wallpaper = DEFAULT_WALLPAPER.to_string();
+        };
+        write_to_json(
+            format!("Resources/config/user_{}.json", name),
+            user.to_json_value(),
+        )?;
+        Ok(user)
+    }
+}
+
+// 本机已有同名用户时在名称后追加序号，返回第一个未被占用的用户名
+pub fn available_user_name(name: &str) -> String {
+    let mut index = 2;
+    let mut available = name.to_string();
+    while check_file_exists(format!("Resources/config/user_{}.json", available)) {
+        available = format!("{}_{}", name, index);
+        index += 1;
+    }
+    available
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    };
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
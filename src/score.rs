@@ -0,0 +1,76 @@
+//! score.rs is the scoring model of the Targeted Vector, it rates an operation result and converts the score into stars.
+
+// 可获得的最高星级
+pub const MAX_STAR: u8 = 3;
+// 每击毁一个敌人获得的分数
+pub const SCORE_PER_KILL: u32 = 100;
+// 命中率为100%时获得的分数
+pub const SCORE_ACCURACY: f32 = 1000_f32;
+// 每保留一点目标点获得的分数
+pub const SCORE_PER_TARGET_POINT: u32 = 200;
+// 每保留一发储备弹药获得的分数
+pub const SCORE_PER_STORAGE_BULLET: u32 = 5;
+// 用时奖励的上限，每经过一秒扣除SCORE_TIME_PENALTY分
+pub const SCORE_TIME_BONUS: f32 = 1000_f32;
+pub const SCORE_TIME_PENALTY: f32 = 5_f32;
+// 每次枪械过热扣除的分数
+pub const SCORE_PER_OVERHEAT: u32 = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct OperationScore {
+    pub kills: u32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub target_point: u32,
+    pub storage_bullet: u32,
+    pub runtime: f32,
+    pub overheats: u32,
+}
+
+impl OperationScore {
+    // 命中率，未开火时为0
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0_f32
+        } else {
+            self.shots_hit.min(self.shots_fired) as f32 / self.shots_fired as f32
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        let bonus = self.kills * SCORE_PER_KILL
+            + (self.accuracy() * SCORE_ACCURACY) as u32
+            + self.target_point * SCORE_PER_TARGET_POINT
+            + self.storage_bullet * SCORE_PER_STORAGE_BULLET
+            + (SCORE_TIME_BONUS - self.runtime * SCORE_TIME_PENALTY).max(0_f32) as u32;
+        bonus.saturating_sub(self.overheats * SCORE_PER_OVERHEAT)
+    }
+
+    // 按关卡声明的分数线评定星级；关卡未声明分数线时，通关为1星，完美通关为满星
+    pub fn star(&self, star_threshold: &[u32], cleared: bool, perfect_clear: bool) -> u8 {
+        if !cleared {
+            0
+        } else if star_threshold.is_empty() {
+            if perfect_clear {
+                MAX_STAR
+            } else {
+                1
+            }
+        } else {
+            // 通关至少获得1星
+            (star_threshold
+                .iter()
+                .take(MAX_STAR as usize)
+                .filter(|x| self.total() >= **x)
+                .count() as u8)
+                .max(1)
+        }
+    }
+}
+
+// 以实心与空心星号显示星级
+pub fn star_text(star: u8) -> String {
+    (0..MAX_STAR)
+        .map(|x| if x < star { '★' } else { '☆' })
+        .collect()
+}
@@ -0,0 +1,376 @@
+//! script.rs is the level scripting of the Targeted Vector, it describes the waves, spawn groups and triggers that the operation loop evaluates.
+use crate::function::OperationTargetEnemy;
+use crate::load_error::{parse_members, JsonField, LoadError};
+use json::JsonValue;
+
+#[derive(Debug, Clone)]
+pub struct SpawnGroup {
+    // enemy_approach_time为相对于组开始时间的延迟
+    pub group_enemy: OperationTargetEnemy,
+    pub group_count: u32,
+    // 组内相邻两个敌人出现的间隔
+    pub group_interval: f32,
+    // 组内每个敌人相对上一个敌人的位置偏移
+    pub group_spacing: [f32; 2],
+}
+
+impl SpawnGroup {
+    pub fn from_json_value(value: &JsonValue) -> Result<SpawnGroup, LoadError> {
+        Ok(SpawnGroup {
+            group_enemy: OperationTargetEnemy::from_json_value(&value["group_enemy"])
+                .map_err(|e| e.at("group_enemy"))?,
+            group_count: value["group_count"].as_u32().unwrap_or(1).max(1),
+            group_interval: value["group_interval"].as_f32().unwrap_or(1_f32),
+            group_spacing: [
+                value["group_spacing"][0].as_f32().unwrap_or(0_f32),
+                value["group_spacing"][1].as_f32().unwrap_or(0_f32),
+            ],
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        json::object! {
+            group_enemy: self.group_enemy.to_json_value(),
+            group_count: self.group_count,
+            group_interval: self.group_interval,
+            group_spacing: [self.group_spacing[0], self.group_spacing[1]],
+        }
+    }
+
+    // 展开为关卡中的敌人，start为组开始的作战时间
+    pub fn expand(&self, start: f32) -> Vec<OperationTargetEnemy> {
+        (0..self.group_count)
+            .map(|n| {
+                let mut enemy = self.group_enemy.clone();
+                enemy.enemy_position = [
+                    enemy.enemy_position[0] + self.group_spacing[0] * n as f32,
+                    enemy.enemy_position[1] + self.group_spacing[1] * n as f32,
+                ];
+                enemy.enemy_approach_time =
+                    start + enemy.enemy_approach_time + self.group_interval * n as f32;
+                enemy
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WaveStart {
+    // 上一波被清空后开始，第一波以关卡中的固定敌人作为上一波
+    Cleared,
+    // 作战开始后的指定秒数
+    Time(f32),
+    // 只由触发器的start_wave动作开始
+    Trigger,
+}
+
+#[derive(Debug, Clone)]
+pub struct Wave {
+    pub wave_name: String,
+    pub wave_start: WaveStart,
+    // 满足开始条件后再等待的秒数
+    pub wave_delay: f32,
+    pub wave_group: Vec<SpawnGroup>,
+}
+
+impl Wave {
+    pub fn from_json_value(value: &JsonValue) -> Result<Wave, LoadError> {
+        Ok(Wave {
+            wave_name: value.field_str("wave_name")?,
+            wave_start: match value["wave_start"].as_str().unwrap_or("cleared") {
+                "cleared" => WaveStart::Cleared,
+                "time" => WaveStart::Time(value.field_f32("wave_start_time")?),
+                "trigger" => WaveStart::Trigger,
+                _ => {
+                    return Err(LoadError::new(
+                        "\"cleared\", \"time\" or \"trigger\"",
+                        &value["wave_start"],
+                    )
+                    .at("wave_start"))
+                }
+            },
+            wave_delay: value["wave_delay"].as_f32().unwrap_or(0_f32),
+            wave_group: parse_members(value, "wave_group", SpawnGroup::from_json_value)?,
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        let mut value = json::object! {
+            wave_name: self.wave_name.clone(),
+            wave_start: match self.wave_start {
+                WaveStart::Cleared => "cleared",
+                WaveStart::Time(_) => "time",
+                WaveStart::Trigger => "trigger",
+            },
+            wave_delay: self.wave_delay,
+            wave_group: self.wave_group.iter().map(|x| x.to_json_value()).collect::<Vec<_>>(),
+        };
+        if let WaveStart::Time(time) = self.wave_start {
+            value["wave_start_time"] = time.into();
+        };
+        value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerCondition {
+    // 累计击毁的敌人数量达到该值
+    KillCount(u32),
+    // 剩余目标点低于该值
+    TargetPointBelow(u32),
+    // 作战开始后的秒数
+    Timer(f32),
+    // 指定名称的波次被清空
+    WaveCleared(String),
+}
+
+impl TriggerCondition {
+    pub fn from_json_value(value: &JsonValue) -> Result<TriggerCondition, LoadError> {
+        match value.field_str("trigger_condition")?.as_str() {
+            "kill_count" => Ok(TriggerCondition::KillCount(
+                value.field_u32("trigger_value")?,
+            )),
+            "target_point_below" => Ok(TriggerCondition::TargetPointBelow(
+                value.field_u32("trigger_value")?,
+            )),
+            "timer" => Ok(TriggerCondition::Timer(value.field_f32("trigger_value")?)),
+            "wave_cleared" => Ok(TriggerCondition::WaveCleared(
+                value.field_str("trigger_wave")?,
+            )),
+            _ => Err(LoadError::new(
+                "\"kill_count\", \"target_point_below\", \"timer\" or \"wave_cleared\"",
+                &value["trigger_condition"],
+            )
+            .at("trigger_condition")),
+        }
+    }
+
+    // 写入触发器对象中对应的字段
+    pub fn write(&self, value: &mut JsonValue) {
+        match self {
+            TriggerCondition::KillCount(count) => {
+                value["trigger_condition"] = "kill_count".into();
+                value["trigger_value"] = (*count).into();
+            }
+            TriggerCondition::TargetPointBelow(point) => {
+                value["trigger_condition"] = "target_point_below".into();
+                value["trigger_value"] = (*point).into();
+            }
+            TriggerCondition::Timer(time) => {
+                value["trigger_condition"] = "timer".into();
+                value["trigger_value"] = (*time).into();
+            }
+            TriggerCondition::WaveCleared(wave) => {
+                value["trigger_condition"] = "wave_cleared".into();
+                value["trigger_wave"] = wave.clone().into();
+            }
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TriggerAction {
+    // 显示box_trigger_phase与该名称相同的消息框
+    MessageBox(String),
+    // 切换背景音乐，空路径时停止音乐
+    Music(String),
+    GrantBullet(u32),
+    StartWave(String),
+    SpawnGroup(SpawnGroup),
+}
+
+impl TriggerAction {
+    pub fn from_json_value(value: &JsonValue) -> Result<TriggerAction, LoadError> {
+        match value.field_str("action_type")?.as_str() {
+            "message_box" => Ok(TriggerAction::MessageBox(
+                value.field_str("action_message_box")?,
+            )),
+            "music" => Ok(TriggerAction::Music(value.field_str("action_music")?)),
+            "grant_bullet" => Ok(TriggerAction::GrantBullet(
+                value.field_u32("action_amount")?,
+            )),
+            "start_wave" => Ok(TriggerAction::StartWave(value.field_str("action_wave")?)),
+            "spawn_group" => Ok(TriggerAction::SpawnGroup(
+                SpawnGroup::from_json_value(&value["action_group"])
+                    .map_err(|e| e.at("action_group"))?,
+            )),
+            _ => Err(LoadError::new(
+                "\"message_box\", \"music\", \"grant_bullet\", \"start_wave\" or \"spawn_group\"",
+                &value["action_type"],
+            )
+            .at("action_type")),
+        }
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        match self {
+            TriggerAction::MessageBox(name) => json::object! {
+                action_type: "message_box",
+                action_message_box: name.clone(),
+            },
+            TriggerAction::Music(path) => json::object! {
+                action_type: "music",
+                action_music: path.clone(),
+            },
+            TriggerAction::GrantBullet(amount) => json::object! {
+                action_type: "grant_bullet",
+                action_amount: *amount,
+            },
+            TriggerAction::StartWave(wave) => json::object! {
+                action_type: "start_wave",
+                action_wave: wave.clone(),
+            },
+            TriggerAction::SpawnGroup(group) => json::object! {
+                action_type: "spawn_group",
+                action_group: group.to_json_value(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelTrigger {
+    pub trigger_name: String,
+    pub trigger_condition: TriggerCondition,
+    // 条件满足后再等待的秒数
+    pub trigger_delay: f32,
+    pub trigger_action: Vec<TriggerAction>,
+}
+
+impl LevelTrigger {
+    pub fn from_json_value(value: &JsonValue) -> Result<LevelTrigger, LoadError> {
+        Ok(LevelTrigger {
+            trigger_name: value.field_str("trigger_name")?,
+            trigger_condition: TriggerCondition::from_json_value(value)?,
+            trigger_delay: value["trigger_delay"].as_f32().unwrap_or(0_f32),
+            trigger_action: parse_members(value, "trigger_action", TriggerAction::from_json_value)?,
+        })
+    }
+
+    pub fn to_json_value(&self) -> JsonValue {
+        let mut value = json::object! {
+            trigger_name: self.trigger_name.clone(),
+            trigger_delay: self.trigger_delay,
+            trigger_action: self.trigger_action.iter().map(|x| x.to_json_value()).collect::<Vec<_>>(),
+        };
+        self.trigger_condition.write(&mut value);
+        value
+    }
+}
+
+// 作战中波次与触发器的进度，每个触发器只执行一次
+#[derive(Debug, Clone, Default)]
+pub struct ScriptState {
+    // 各波次开始的作战时间，尚未开始时为None
+    pub wave_start_time: Vec<Option<f32>>,
+    // 各波次生成的敌人在enemy_list中的序号
+    pub wave_enemy: Vec<Vec<usize>>,
+    // 被start_wave动作要求开始的波次
+    pub wave_requested: Vec<bool>,
+    // 各触发器条件满足的作战时间
+    pub trigger_met_time: Vec<Option<f32>>,
+    pub trigger_done: Vec<bool>,
+}
+
+impl ScriptState {
+    pub fn new(wave: &[Wave], trigger: &[LevelTrigger]) -> ScriptState {
+        ScriptState {
+            wave_start_time: vec![None; wave.len()],
+            wave_enemy: vec![vec![]; wave.len()],
+            wave_requested: vec![false; wave.len()],
+            trigger_met_time: vec![None; trigger.len()],
+            trigger_done: vec![false; trigger.len()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function::EnemyTag;
+    use crate::simulation::{
+        Simulation, SimulationEvent, SimulationInput, SimulationShot, SIMULATION_DT,
+    };
+    use crate::status::DamageType;
+
+    // 作战开始时生成一个训练靶(生命值15)的波次
+    fn split_wave() -> Wave {
+        Wave {
+            wave_name: "split".to_string(),
+            wave_start: WaveStart::Time(0_f32),
+            wave_delay: 0_f32,
+            wave_group: vec![SpawnGroup {
+                group_enemy: OperationTargetEnemy {
+                    enemy_recognition_name: "Train_Target".to_string(),
+                    enemy_position: [640_f32, 300_f32],
+                    enemy_size: [100_f32, 100_f32],
+                    enemy_path: vec!["down".to_string()],
+                    enemy_approach_time: 0_f32,
+                    enemy_approach_alpha: 0,
+                    enemy_increase_alpha_speed: 255,
+                },
+                group_count: 1,
+                group_interval: 0_f32,
+                group_spacing: [0_f32, 0_f32],
+            }],
+        }
+    }
+
+    #[test]
+    fn wave_round_trips_through_json() {
+        let wave = split_wave();
+        let parsed = Wave::from_json_value(&wave.to_json_value()).unwrap();
+        assert_eq!(parsed.wave_name, wave.wave_name);
+        assert_eq!(parsed.wave_start, WaveStart::Time(0_f32));
+        assert_eq!(parsed.wave_group.len(), 1);
+        // 按时间开始的波次必须给出开始时间
+        let error = Wave::from_json_value(&json::object! {
+            wave_name: "late",
+            wave_start: "time",
+            wave_group: [],
+        })
+        .unwrap_err();
+        assert_eq!(error.pointer, "/wave_start_time");
+    }
+
+    #[test]
+    fn wave_waits_for_splitter_children() {
+        let mut operation = Simulation::default().operation;
+        operation.global.target_point = 3;
+        operation.global.target_line = vec![[0_f32, 720_f32], [1280_f32, 720_f32]];
+        operation.wave = vec![split_wave()];
+        let mut simulation = Simulation::new(operation, vec![]);
+        assert!(
+            simulation.load_error.is_empty(),
+            "{:?}",
+            simulation.load_error
+        );
+        simulation.run_for(0.5, SIMULATION_DT, &SimulationInput::default());
+        assert_eq!(simulation.script.wave_enemy[0], vec![0]);
+        simulation.enemy_list[0]
+            .enemy_behavior
+            .push(EnemyTag::Splitter {
+                child: "Train_Target".to_string(),
+                count: 2,
+            });
+        let events = simulation.step(
+            SIMULATION_DT,
+            &SimulationInput {
+                shots: vec![SimulationShot {
+                    shot_rect: [0_f32, 0_f32, 1280_f32, 700_f32],
+                    shot_damage: 100_f32,
+                    shot_damage_type: DamageType::Kinetic,
+                    shot_status: vec![],
+                    shot_tag: vec![],
+                    shot_pierce: false,
+                    shot_splash: 0_f32,
+                }],
+                instrument_placements: vec![],
+            },
+        );
+        assert!(events.contains(&SimulationEvent::EnemyDeath(0)));
+        // 父敌人已被击毁，但分裂出的子敌人仍在场上
+        assert_eq!(simulation.script.wave_enemy[0], vec![0, 1, 2]);
+        assert!(!simulation.wave_cleared(0));
+    }
+}
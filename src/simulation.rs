@@ -0,0 +1,996 @@
+//! simulation.rs is the combat core of the Targeted Vector, it advances an operation at a fixed timestep without any window or egui state.
+use crate::endless::Endless;
+use crate::function::{
+    read_move_path, Enemy, EnemyBossPhase, EnemyTag, Instrument, Operation, OperationGlobal,
+    OperationTargetEnemy, PlacedInstrument,
+};
+use crate::load_error::LoadError;
+use crate::script::{ScriptState, TriggerAction, TriggerCondition, WaveStart};
+use crate::status::{DamageType, StatusEffect, StatusKind};
+
+// 作战区域的尺寸
+pub const OPERATION_SIZE: [f32; 2] = [1280_f32, 720_f32];
+// 每次推进的固定步长(秒)，与帧率无关
+pub const SIMULATION_DT: f32 = 1_f32 / 120_f32;
+
+#[derive(Debug, Clone)]
+pub struct SimulationShot {
+    // [x, y, w, h]，作战区域坐标
+    pub shot_rect: [f32; 4],
+    pub shot_damage: f32,
+    pub shot_damage_type: DamageType,
+    // 造成伤害时施加的状态
+    pub shot_status: Vec<StatusEffect>,
+    // 伤害来源的标签，用于判断能否击穿装甲
+    pub shot_tag: Vec<String>,
+    // 为true时命中范围内的所有敌人，否则只命中最上层的敌人
+    pub shot_pierce: bool,
+    // 以命中范围中心为圆心的溅射半径，为0时没有溅射
+    pub shot_splash: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimulationInput {
+    pub shots: Vec<SimulationShot>,
+    // [仪器序号, 放置位置]
+    pub instrument_placements: Vec<(usize, [f32; 2])>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulationEvent {
+    EnemyHit(usize),
+    EnemyHitNoDamage(usize),
+    EnemyDeath(usize),
+    EnemyBreach(usize),
+    EnemySpawn(usize),
+    EnemyRevealed(usize),
+    InstrumentFire(usize),
+    InstrumentPlaced(usize),
+    InstrumentRejected(usize),
+    // [敌人序号, 阶段序号]
+    BossPhase(usize, usize),
+    // 以下由关卡脚本产生
+    WaveStart(usize),
+    MessageBox(String),
+    Music(String),
+    GrantBullet(u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub operation: Operation,
+    pub enemy_list: Vec<Enemy>,
+    pub instrument_content: Vec<Instrument>,
+    pub instrument_list: Vec<PlacedInstrument>,
+    pub runtime: f32,
+    pub target_point: u32,
+    pub target_enemy: u32,
+    pub current_killed_target_enemy: u32,
+    pub perfect_clear: bool,
+    pub cost: u32,
+    pub cost_recover_time: f32,
+    // 用于结算评分的射击与击毁统计
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    // 最近射出的一发尚未命中，散射的多个弹丸与持续多帧的伤害判定只计一次命中
+    pub round_pending: bool,
+    pub enemy_killed: u32,
+    // 无尽模式的波次生成器，普通关卡为None
+    pub endless: Option<Endless>,
+    pub script: ScriptState,
+    // 读取敌人或路径文件时的错误，由App取出后报告
+    pub load_error: Vec<LoadError>,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation::new(
+            Operation {
+                global: OperationGlobal {
+                    target_point: 0,
+                    storage_bullet: 0,
+                    cost: 0,
+                    cost_recover_speed: 1_f32,
+                    instrument_ceiling: 0,
+                    target_line: vec![],
+                    operation_background: "".to_string(),
+                    operation_background_expand: "".to_string(),
+                    operation_start_background: "".to_string(),
+                    operation_over_background: "".to_string(),
+                    operation_reward: 0,
+                    star_threshold: vec![],
+                },
+                target_enemy: vec![],
+                message_box: vec![],
+                wave: vec![],
+                trigger: vec![],
+            },
+            vec![],
+        )
+    }
+}
+
+// 判断两个矩形（[x, y, w, h]）是否相交
+pub fn rect_intersects(a: [f32; 4], b: [f32; 4]) -> bool {
+    a[0] < b[0] + b[2] && b[0] < a[0] + a[2] && a[1] < b[1] + b[3] && b[1] < a[1] + a[3]
+}
+
+// 点到线段的最短距离
+pub fn point_segment_distance(point: [f32; 2], start: [f32; 2], end: [f32; 2]) -> f32 {
+    let direction = [end[0] - start[0], end[1] - start[1]];
+    let length_squared = direction[0].powi(2) + direction[1].powi(2);
+    let t = if length_squared == 0_f32 {
+        0_f32
+    } else {
+        (((point[0] - start[0]) * direction[0] + (point[1] - start[1]) * direction[1])
+            / length_squared)
+            .clamp(0_f32, 1_f32)
+    };
+    ((start[0] + direction[0] * t - point[0]).powi(2)
+        + (start[1] + direction[1] * t - point[1]).powi(2))
+    .sqrt()
+}
+
+// 判断线段是否穿过矩形（[x, y, w, h]）
+pub fn segment_intersects_rect(rect: [f32; 4], start: [f32; 2], end: [f32; 2]) -> bool {
+    let inside = |p: [f32; 2]| {
+        p[0] >= rect[0] && p[0] <= rect[0] + rect[2] && p[1] >= rect[1] && p[1] <= rect[1] + rect[3]
+    };
+    if inside(start) || inside(end) {
+        return true;
+    };
+    let cross = |o: [f32; 2], a: [f32; 2], b: [f32; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+    let corners = [
+        [rect[0], rect[1]],
+        [rect[0] + rect[2], rect[1]],
+        [rect[0] + rect[2], rect[1] + rect[3]],
+        [rect[0], rect[1] + rect[3]],
+    ];
+    (0..4).any(|i| {
+        let c = corners[i];
+        let d = corners[(i + 1) % 4];
+        cross(start, end, c) * cross(start, end, d) <= 0_f32
+            && cross(c, d, start) * cross(c, d, end) <= 0_f32
+    })
+}
+
+impl Simulation {
+    pub fn new(operation: Operation, instrument_content: Vec<Instrument>) -> Simulation {
+        let mut enemy_list = Vec::new();
+        let mut load_error = Vec::new();
+        for (i, target_enemy) in operation.target_enemy.iter().enumerate() {
+            match Enemy::from_target_enemy(target_enemy, i) {
+                Ok(enemy) => enemy_list.push(enemy),
+                Err(e) => load_error.push(e.at(&i.to_string()).at("target_enemy")),
+            };
+        }
+        Simulation {
+            script: ScriptState::new(&operation.wave, &operation.trigger),
+            target_point: operation.global.target_point,
+            target_enemy: operation.target_enemy.len() as u32,
+            cost: operation.global.cost,
+            operation,
+            enemy_list,
+            instrument_content,
+            instrument_list: vec![],
+            runtime: 0_f32,
+            current_killed_target_enemy: 0,
+            perfect_clear: true,
+            cost_recover_time: 0_f32,
+            shots_fired: 0,
+            shots_hit: 0,
+            round_pending: false,
+            enemy_killed: 0,
+            endless: None,
+            load_error,
+        }
+    }
+
+    // 敌人在作战区域中的矩形（敌人坐标为底部中点）
+    pub fn enemy_rect(&self, index: usize) -> [f32; 4] {
+        let enemy = &self.enemy_list[index];
+        [
+            enemy.enemy_position[0] - enemy.enemy_size[0] / 2_f32,
+            enemy.enemy_position[1] - enemy.enemy_size[1],
+            enemy.enemy_size[0],
+            enemy.enemy_size[1],
+        ]
+    }
+
+    pub fn enemy_invincible(&self, index: usize) -> bool {
+        self.runtime - self.enemy_list[index].enemy_hit_time
+            < self.enemy_list[index].enemy_invincible_time
+    }
+
+    // 无尽模式只在目标点耗尽时结束，脚本中还有待开始的波次或待执行的触发器时不会结束
+    pub fn operation_over(&self) -> bool {
+        self.target_point == 0
+            || self.endless.is_none()
+                && self.current_killed_target_enemy == self.target_enemy
+                && !self.enemy_list.iter().any(|x| x.enemy_activated)
+                && !self.script_pending()
+    }
+
+    fn script_pending(&self) -> bool {
+        (0..self.operation.wave.len()).any(|i| {
+            self.script.wave_start_time[i].is_none()
+                && match self.operation.wave[i].wave_start {
+                    // 上一波从未开始时这一波也不会开始
+                    WaveStart::Cleared => i == 0 || self.script.wave_start_time[i - 1].is_some(),
+                    WaveStart::Time(_) => true,
+                    WaveStart::Trigger => self.script.wave_requested[i],
+                }
+        }) || (0..self.operation.trigger.len())
+            .any(|i| self.script.trigger_met_time[i].is_some() && !self.script.trigger_done[i])
+    }
+
+    // 波次已开始且生成的敌人均已被击毁或突破防线
+    pub fn wave_cleared(&self, index: usize) -> bool {
+        self.script.wave_start_time[index].is_some()
+            && self.script.wave_enemy[index]
+                .iter()
+                .all(|x| self.enemy_list[*x].enemy_out)
+    }
+
+    // 按关卡中的敌人配置生成敌人，返回其序号
+    fn spawn_enemy(
+        &mut self,
+        target_enemy: &OperationTargetEnemy,
+        events: &mut Vec<SimulationEvent>,
+    ) -> Option<usize> {
+        let index = self.enemy_list.len();
+        match Enemy::from_target_enemy(target_enemy, index) {
+            Ok(enemy) => {
+                self.enemy_list.push(enemy);
+                self.target_enemy += 1;
+                events.push(SimulationEvent::EnemySpawn(index));
+                Some(index)
+            }
+            Err(e) => {
+                self.load_error.push(e);
+                None
+            }
+        }
+    }
+
+    pub fn damage_enemy(
+        &mut self,
+        index: usize,
+        damage: f32,
+        damage_type: DamageType,
+        source_tag: &[String],
+        status: &[StatusEffect],
+    ) -> SimulationEvent {
+        let runtime = self.runtime;
+        let enemy = &mut self.enemy_list[index];
+        enemy.enemy_hit_time = runtime;
+        // 先按抗性与标记调整伤害，再扣除防御力
+        let damage = damage * enemy.damage_multiplier(damage_type);
+        let def = enemy.effective_def();
+        if damage > def && enemy.damageable_by(source_tag) {
+            enemy.take_damage(damage - def);
+            for effect in status {
+                enemy.apply_status(effect, runtime);
+            }
+            SimulationEvent::EnemyHit(index)
+        } else {
+            SimulationEvent::EnemyHitNoDamage(index)
+        }
+    }
+
+    // 处理护盾、回复与隐形等随时间生效的标签
+    fn enemy_tag_step(&mut self, i: usize, dt: f32, events: &mut Vec<SimulationEvent>) {
+        let since_hit = self.runtime - self.enemy_list[i].enemy_hit_time;
+        let position = self.enemy_list[i].enemy_position;
+        let mut reveal = false;
+        for tag in self.enemy_list[i].enemy_behavior.clone() {
+            match tag {
+                EnemyTag::Shielded {
+                    regen_delay,
+                    regen_speed,
+                    ..
+                } => {
+                    if since_hit >= regen_delay {
+                        let enemy = &mut self.enemy_list[i];
+                        enemy.enemy_shield =
+                            (enemy.enemy_shield + regen_speed * dt).min(enemy.enemy_max_shield);
+                    };
+                }
+                EnemyTag::Regenerating(speed) => {
+                    let enemy = &mut self.enemy_list[i];
+                    enemy.enemy_hp = (enemy.enemy_hp + speed * dt).min(enemy.enemy_initial_hp);
+                }
+                EnemyTag::Stealth(reveal_distance) => {
+                    if !self.enemy_list[i].enemy_detected {
+                        reveal = self.operation.global.target_line.windows(2).any(|x| {
+                            point_segment_distance(position, x[0], x[1]) <= reveal_distance
+                        }) || self.instrument_list.iter().any(|x| {
+                            let range =
+                                self.instrument_content[x.instrument_index].instrument_range;
+                            ((position[0] - x.instrument_position[0]).powi(2)
+                                + (position[1] - x.instrument_position[1]).powi(2))
+                            .sqrt()
+                                <= range
+                        });
+                    };
+                }
+                EnemyTag::Armored(_) | EnemyTag::Splitter { .. } => {}
+            };
+        }
+        if reveal {
+            self.enemy_list[i].enemy_detected = true;
+            events.push(SimulationEvent::EnemyRevealed(i));
+        };
+    }
+
+    // 结算持续伤害并移除到期的状态，返回本帧的移动速度比例
+    fn enemy_status_step(&mut self, i: usize, dt: f32) -> f32 {
+        let runtime = self.runtime;
+        let enemy = &mut self.enemy_list[i];
+        enemy
+            .enemy_status
+            .retain(|x| x.status_expire_time > runtime);
+        if let Some(burn) = enemy.status_strength(StatusKind::Burn) {
+            let damage = burn * dt * enemy.damage_multiplier(DamageType::Thermal);
+            enemy.take_damage(damage);
+        };
+        let factor = enemy.movement_factor();
+        // 推迟起步时间，使基于时间的路径行进按比例变慢
+        enemy.enemy_start_walk_time += dt * (1_f32 - factor);
+        factor
+    }
+
+    // 分裂型敌人被击毁后，子敌人沿父敌人的路线与步调继续行进
+    fn enemy_split(&mut self, i: usize, events: &mut Vec<SimulationEvent>) {
+        let parent = self.enemy_list[i].clone();
+        for tag in &parent.enemy_behavior {
+            if let EnemyTag::Splitter { child, count } = tag {
+                self.spawn_child(i, &parent, child, *count, events);
+            };
+        }
+    }
+
+    // 在父敌人处生成子敌人，子敌人沿父敌人的路线与步调行进
+    fn spawn_child(
+        &mut self,
+        parent_index: usize,
+        parent: &Enemy,
+        child: &str,
+        count: u32,
+        events: &mut Vec<SimulationEvent>,
+    ) {
+        for n in 0..count {
+            let index = self.enemy_list.len();
+            match Enemy::from_target_enemy(
+                &OperationTargetEnemy {
+                    enemy_recognition_name: child.to_string(),
+                    enemy_position: parent.enemy_position,
+                    enemy_size: [parent.enemy_size[0] * 0.7, parent.enemy_size[1] * 0.7],
+                    enemy_path: vec![],
+                    enemy_approach_time: self.runtime,
+                    enemy_approach_alpha: 255,
+                    enemy_increase_alpha_speed: parent.enemy_increase_alpha_speed,
+                },
+                index,
+            ) {
+                Ok(mut enemy) => {
+                    // 子敌人左右错开，避免完全重叠
+                    let offset =
+                        (n as f32 - (count - 1) as f32 / 2_f32) * parent.enemy_size[0] * 0.5;
+                    enemy.enemy_position[0] += offset;
+                    enemy.enemy_move_path = parent.enemy_move_path.clone();
+                    enemy.enemy_path_end_behavior = parent.enemy_path_end_behavior;
+                    enemy.enemy_path_origin = [
+                        parent.enemy_path_origin[0] + offset,
+                        parent.enemy_path_origin[1],
+                    ];
+                    enemy.enemy_path_reverse = parent.enemy_path_reverse;
+                    enemy.enemy_current_walk_status = parent.enemy_current_walk_status;
+                    enemy.enemy_start_walk_time = parent.enemy_start_walk_time;
+                    enemy.enemy_speed = parent.enemy_speed;
+                    enemy.enemy_walk_interval = parent.enemy_walk_interval;
+                    enemy.enemy_activated = true;
+                    enemy.enemy_hit_time = self.runtime;
+                    self.enemy_list.push(enemy);
+                    self.target_enemy += 1;
+                    // 子敌人计入父敌人所在的波次，全部被清除后波次才算清空
+                    for wave_enemy in &mut self.script.wave_enemy {
+                        if wave_enemy.contains(&parent_index) {
+                            wave_enemy.push(index);
+                        };
+                    }
+                    events.push(SimulationEvent::EnemySpawn(index));
+                }
+                Err(e) => self.load_error.push(e),
+            };
+        }
+    }
+
+    // Boss进入新阶段：替换贴图、防御、速度与路线，并召唤随从
+    fn boss_phase_step(&mut self, i: usize, events: &mut Vec<SimulationEvent>) {
+        for p in self.enemy_list[i].pending_boss_phase(self.runtime) {
+            let Some(phase) = self.enemy_list[i]
+                .enemy_boss
+                .as_ref()
+                .map(|x| x.boss_phase[p].clone())
+            else {
+                continue;
+            };
+            self.enemy_list[i].enemy_phase_entered[p] = true;
+            self.enter_boss_phase(i, p, &phase);
+            events.push(SimulationEvent::BossPhase(i, p));
+            let parent = self.enemy_list[i].clone();
+            for (minion, count) in &phase.phase_minion {
+                self.spawn_child(i, &parent, minion, *count, events);
+            }
+        }
+    }
+
+    fn enter_boss_phase(&mut self, i: usize, p: usize, phase: &EnemyBossPhase) {
+        let runtime = self.runtime;
+        let enemy = &mut self.enemy_list[i];
+        if let Some(image) = &phase.phase_image {
+            enemy.enemy_image = image.clone();
+            enemy.enemy_texture_name = format!("{}_phase{}", enemy.enemy_name, p + 1);
+        };
+        if let Some(image_count) = phase.phase_image_count {
+            enemy.enemy_image_count = image_count;
+            enemy.enemy_current_animation_count = 0;
+            enemy.enemy_animation_forward = true;
+        };
+        if let Some(def) = phase.phase_def {
+            enemy.enemy_def = def;
+        };
+        if let Some(speed) = phase.phase_speed {
+            // 按新旧速度之比调整起步时间，使敌人在当前位置以新速度继续行进
+            if enemy.enemy_speed > 0_f32 && speed > 0_f32 {
+                enemy.enemy_start_walk_time =
+                    runtime - (runtime - enemy.enemy_start_walk_time) * enemy.enemy_speed / speed;
+            };
+            enemy.enemy_speed = speed;
+        };
+        if !phase.phase_path.is_empty() {
+            match read_move_path(
+                &phase.phase_path,
+                enemy.enemy_speed / enemy.enemy_walk_interval.max(0.01),
+            ) {
+                Ok((move_path, path_end_behavior)) => {
+                    enemy.enemy_move_path = move_path;
+                    enemy.enemy_path_end_behavior = path_end_behavior;
+                    enemy.enemy_path_origin = enemy.enemy_position;
+                    enemy.enemy_path_reverse = false;
+                    enemy.enemy_current_walk_status = 0;
+                    enemy.enemy_start_walk_time = runtime;
+                }
+                Err(e) => self.load_error.push(e),
+            };
+        };
+    }
+
+    pub fn instrument_can_place(&self, instrument_index: usize, position: [f32; 2]) -> bool {
+        let size = self.instrument_content[instrument_index].instrument_size;
+        let rect = [
+            position[0] - size[0] / 2_f32,
+            position[1] - size[1] / 2_f32,
+            size[0],
+            size[1],
+        ];
+        // 仪器必须完整地放在作战区域内，且不能与已放置的仪器重叠
+        if rect[0] < 0_f32
+            || rect[1] < 0_f32
+            || rect[0] + rect[2] > OPERATION_SIZE[0]
+            || rect[1] + rect[3] > OPERATION_SIZE[1]
+        {
+            return false;
+        };
+        !self.instrument_list.iter().any(|x| {
+            let placed_size = self.instrument_content[x.instrument_index].instrument_size;
+            rect_intersects(
+                rect,
+                [
+                    x.instrument_position[0] - placed_size[0] / 2_f32,
+                    x.instrument_position[1] - placed_size[1] / 2_f32,
+                    placed_size[0],
+                    placed_size[1],
+                ],
+            )
+        })
+    }
+
+    // 部署仪器并扣除消耗，返回是否部署成功
+    pub fn place_instrument(&mut self, instrument_index: usize, position: [f32; 2]) -> bool {
+        if instrument_index >= self.instrument_content.len()
+            || self.instrument_list.len() >= self.operation.global.instrument_ceiling as usize
+            || self.cost < self.instrument_content[instrument_index].instrument_cost
+            || !self.instrument_can_place(instrument_index, position)
+        {
+            return false;
+        };
+        self.cost -= self.instrument_content[instrument_index].instrument_cost;
+        self.instrument_list.push(PlacedInstrument {
+            instrument_name: format!("Instrument_{}", self.instrument_list.len()),
+            instrument_index,
+            instrument_position: position,
+            instrument_last_fire_time: self.runtime,
+            instrument_target: None,
+        });
+        true
+    }
+
+    // 推进一帧，返回本帧发生的事件
+    pub fn step(&mut self, dt: f32, input: &SimulationInput) -> Vec<SimulationEvent> {
+        let mut events = Vec::new();
+        self.runtime += dt;
+        if self.runtime - self.cost_recover_time >= self.operation.global.cost_recover_speed {
+            self.cost += 1;
+            self.cost_recover_time = self.runtime;
+        };
+        for (instrument_index, position) in &input.instrument_placements {
+            if self.place_instrument(*instrument_index, *position) {
+                events.push(SimulationEvent::InstrumentPlaced(
+                    self.instrument_list.len() - 1,
+                ));
+            } else {
+                events.push(SimulationEvent::InstrumentRejected(*instrument_index));
+            };
+        }
+        self.endless_step(&mut events);
+        self.script_step(&mut events);
+        self.instrument_step(&mut events);
+        self.shot_step(input, &mut events);
+        for i in 0..self.enemy_list.len() {
+            self.enemy_step(i, dt, &mut events);
+        }
+        events
+    }
+
+    fn endless_step(&mut self, events: &mut Vec<SimulationEvent>) {
+        let field_cleared = self.enemy_list.iter().all(|x| x.enemy_out);
+        let Some(endless) = self.endless.as_mut() else {
+            return;
+        };
+        if !endless.wave_ready(self.runtime, field_cleared) {
+            return;
+        };
+        let wave = endless.generate_wave(self.runtime);
+        let hp_multiplier = endless.wave_hp_multiplier;
+        for target_enemy in wave {
+            if let Some(index) = self.spawn_enemy(&target_enemy, events) {
+                let enemy = &mut self.enemy_list[index];
+                enemy.enemy_hp *= hp_multiplier;
+                enemy.enemy_initial_hp *= hp_multiplier;
+                enemy.enemy_memory_hp *= hp_multiplier;
+            };
+        }
+    }
+
+    // 先执行触发器，再开始满足条件的波次，使start_wave动作在同一帧生效
+    fn script_step(&mut self, events: &mut Vec<SimulationEvent>) {
+        for i in 0..self.operation.trigger.len() {
+            if self.script.trigger_done[i] {
+                continue;
+            };
+            let trigger = self.operation.trigger[i].clone();
+            if self.script.trigger_met_time[i].is_none() {
+                let met = match &trigger.trigger_condition {
+                    TriggerCondition::KillCount(count) => self.enemy_killed >= *count,
+                    TriggerCondition::TargetPointBelow(point) => self.target_point < *point,
+                    TriggerCondition::Timer(time) => self.runtime >= *time,
+                    TriggerCondition::WaveCleared(name) => self
+                        .operation
+                        .wave
+                        .iter()
+                        .position(|x| x.wave_name == *name)
+                        .is_some_and(|x| self.wave_cleared(x)),
+                };
+                if met {
+                    self.script.trigger_met_time[i] = Some(self.runtime);
+                };
+            };
+            if let Some(met_time) = self.script.trigger_met_time[i] {
+                if self.runtime >= met_time + trigger.trigger_delay {
+                    self.script.trigger_done[i] = true;
+                    for action in &trigger.trigger_action {
+                        self.trigger_action(action, events);
+                    }
+                };
+            };
+        }
+        for i in 0..self.operation.wave.len() {
+            if self.script.wave_start_time[i].is_some() {
+                continue;
+            };
+            let ready = match self.operation.wave[i].wave_start {
+                WaveStart::Cleared => {
+                    if i == 0 {
+                        // 第一波等待关卡中的固定敌人全部离场
+                        self.enemy_list
+                            .iter()
+                            .take(self.operation.target_enemy.len())
+                            .all(|x| x.enemy_out)
+                    } else {
+                        self.wave_cleared(i - 1)
+                    }
+                }
+                WaveStart::Time(time) => self.runtime >= time,
+                WaveStart::Trigger => self.script.wave_requested[i],
+            };
+            if ready {
+                self.start_wave(i, events);
+            };
+        }
+    }
+
+    fn trigger_action(&mut self, action: &TriggerAction, events: &mut Vec<SimulationEvent>) {
+        match action {
+            TriggerAction::MessageBox(name) => {
+                events.push(SimulationEvent::MessageBox(name.clone()))
+            }
+            TriggerAction::Music(path) => events.push(SimulationEvent::Music(path.clone())),
+            TriggerAction::GrantBullet(amount) => {
+                events.push(SimulationEvent::GrantBullet(*amount))
+            }
+            TriggerAction::StartWave(name) => {
+                if let Some(i) = self
+                    .operation
+                    .wave
+                    .iter()
+                    .position(|x| x.wave_name == *name)
+                {
+                    self.script.wave_requested[i] = true;
+                };
+            }
+            TriggerAction::SpawnGroup(group) => {
+                for target_enemy in group.expand(self.runtime) {
+                    self.spawn_enemy(&target_enemy, events);
+                }
+            }
+        };
+    }
+
+    // 生成波次中的全部敌人，敌人按各自的延迟依次出现
+    fn start_wave(&mut self, i: usize, events: &mut Vec<SimulationEvent>) {
+        let wave = self.operation.wave[i].clone();
+        let start_time = self.runtime + wave.wave_delay;
+        self.script.wave_start_time[i] = Some(start_time);
+        for group in &wave.wave_group {
+            for target_enemy in group.expand(start_time) {
+                if let Some(index) = self.spawn_enemy(&target_enemy, events) {
+                    self.script.wave_enemy[i].push(index);
+                };
+            }
+        }
+        events.push(SimulationEvent::WaveStart(i));
+    }
+
+    // 射出一发子弹，之后的射击判定中首次命中时计入命中
+    pub fn fire_round(&mut self) {
+        self.shots_fired += 1;
+        self.round_pending = true;
+    }
+
+    // 能被射击命中的敌人：已完全出现、已被侦测且不处于无敌时间
+    fn enemy_shootable(&self, index: usize) -> bool {
+        let enemy = &self.enemy_list[index];
+        enemy.enemy_activated
+            && enemy.enemy_alpha == 255
+            && enemy.enemy_detected
+            && !self.enemy_invincible(index)
+    }
+
+    fn shot_step(&mut self, input: &SimulationInput, events: &mut Vec<SimulationEvent>) {
+        for shot in &input.shots {
+            let mut target: Vec<usize> = (0..self.enemy_list.len())
+                .filter(|i| {
+                    self.enemy_shootable(*i) && rect_intersects(shot.shot_rect, self.enemy_rect(*i))
+                })
+                .collect();
+            if !shot.shot_pierce {
+                // 后生成的敌人绘制在上层，优先被命中
+                target = target.last().copied().into_iter().collect();
+            };
+            if shot.shot_splash > 0_f32 {
+                let center = [
+                    shot.shot_rect[0] + shot.shot_rect[2] / 2_f32,
+                    shot.shot_rect[1] + shot.shot_rect[3] / 2_f32,
+                ];
+                for i in 0..self.enemy_list.len() {
+                    let enemy = &self.enemy_list[i];
+                    let distance = ((enemy.enemy_position[0] - center[0]).powi(2)
+                        + (enemy.enemy_position[1] - enemy.enemy_size[1] / 2_f32 - center[1])
+                            .powi(2))
+                    .sqrt();
+                    if distance <= shot.shot_splash
+                        && !target.contains(&i)
+                        && self.enemy_shootable(i)
+                    {
+                        target.push(i);
+                    };
+                }
+            };
+            if self.round_pending && !target.is_empty() {
+                self.round_pending = false;
+                self.shots_hit += 1;
+            };
+            for i in target {
+                let event = self.damage_enemy(
+                    i,
+                    shot.shot_damage,
+                    shot.shot_damage_type,
+                    &shot.shot_tag,
+                    &shot.shot_status,
+                );
+                events.push(event);
+            }
+        }
+    }
+
+    fn instrument_step(&mut self, events: &mut Vec<SimulationEvent>) {
+        for i in 0..self.instrument_list.len() {
+            let instrument =
+                self.instrument_content[self.instrument_list[i].instrument_index].clone();
+            if self.runtime - self.instrument_list[i].instrument_last_fire_time
+                < instrument.instrument_fire_interval
+            {
+                continue;
+            };
+            // 选取射程内距离最近且已完全出现的敌人
+            let mut target = None;
+            let mut nearest_distance = instrument.instrument_range;
+            for (u, enemy) in self.enemy_list.iter().enumerate() {
+                if enemy.enemy_activated && enemy.enemy_alpha == 255 && enemy.enemy_detected {
+                    let position = self.instrument_list[i].instrument_position;
+                    let distance = ((enemy.enemy_position[0] - position[0]).powi(2)
+                        + (enemy.enemy_position[1] - enemy.enemy_size[1] / 2_f32 - position[1])
+                            .powi(2))
+                    .sqrt();
+                    if distance <= nearest_distance {
+                        nearest_distance = distance;
+                        target = Some(u);
+                    };
+                };
+            }
+            self.instrument_list[i].instrument_target = target;
+            if let Some(u) = target {
+                self.instrument_list[i].instrument_last_fire_time = self.runtime;
+                events.push(SimulationEvent::InstrumentFire(i));
+                if !self.enemy_invincible(u) {
+                    let event = self.damage_enemy(
+                        u,
+                        instrument.instrument_damage,
+                        DamageType::Kinetic,
+                        &["instrument".to_string()],
+                        &[],
+                    );
+                    events.push(event);
+                };
+            };
+        }
+    }
+
+    fn enemy_step(&mut self, i: usize, dt: f32, events: &mut Vec<SimulationEvent>) {
+        if self.enemy_list[i].enemy_activated {
+            if self.enemy_list[i].enemy_alpha == 255 {
+                self.enemy_tag_step(i, dt, events);
+                let movement_factor = self.enemy_status_step(i, dt);
+                let enemy_rect = self.enemy_rect(i);
+                if self
+                    .operation
+                    .global
+                    .target_line
+                    .windows(2)
+                    .any(|x| segment_intersects_rect(enemy_rect, x[0], x[1]))
+                {
+                    self.perfect_clear = false;
+                    // 隐形敌人突破防线时同样暴露；无论是否被发现都计入已离场的敌人，否则作战无法结束
+                    self.enemy_list[i].enemy_detected = true;
+                    self.enemy_list[i].enemy_out = true;
+                    self.enemy_list[i].enemy_activated = false;
+                    self.target_point = self
+                        .target_point
+                        .saturating_sub(self.enemy_list[i].enemy_minus_target_point);
+                    self.current_killed_target_enemy += 1;
+                    events.push(SimulationEvent::EnemyBreach(i));
+                    return;
+                };
+                if self.enemy_list[i].enemy_hp <= 0_f32 {
+                    self.enemy_list[i].enemy_out = true;
+                    self.enemy_list[i].enemy_activated = false;
+                    if self.enemy_list[i].enemy_detected {
+                        self.current_killed_target_enemy += 1;
+                    };
+                    self.enemy_killed += 1;
+                    events.push(SimulationEvent::EnemyDeath(i));
+                    self.enemy_split(i, events);
+                    return;
+                };
+                self.boss_phase_step(i, events);
+                let runtime = self.runtime;
+                let enemy = &mut self.enemy_list[i];
+                if runtime - enemy.enemy_walk_time >= enemy.enemy_walk_interval {
+                    enemy.enemy_walk_time = runtime;
+                    if enemy.path_walk(runtime)
+                        && movement_factor > 0_f32
+                        && runtime - enemy.enemy_animation_change_time
+                            >= enemy.enemy_animation_interval
+                    {
+                        enemy.enemy_animation_change_time = runtime;
+                        enemy.animation_step();
+                    };
+                };
+            } else {
+                let enemy = &mut self.enemy_list[i];
+                enemy.enemy_alpha = enemy
+                    .enemy_alpha
+                    .saturating_add(enemy.enemy_increase_alpha_speed);
+                if enemy.enemy_alpha == 255 {
+                    // 完全显现后才开始沿路径行进
+                    enemy.enemy_start_walk_time = self.runtime;
+                    enemy.enemy_path_origin = enemy.enemy_position;
+                };
+            };
+        } else if self.runtime >= self.enemy_list[i].enemy_activated_time {
+            let enemy = &mut self.enemy_list[i];
+            if enemy.enemy_out {
+                enemy.enemy_alpha = enemy
+                    .enemy_alpha
+                    .saturating_sub(enemy.enemy_increase_alpha_speed);
+            } else {
+                enemy.enemy_activated = true;
+            };
+        };
+    }
+
+    // 以固定步长推进duration内的完整步数，不足一步的时间不推进；输入只在第一步中处理
+    pub fn run_for(
+        &mut self,
+        duration: f32,
+        dt: f32,
+        input: &SimulationInput,
+    ) -> Vec<SimulationEvent> {
+        let mut events = Vec::new();
+        let empty_input = SimulationInput::default();
+        let mut step_input = input;
+        let mut elapsed = dt;
+        while elapsed <= duration && !self.operation_over() {
+            events.extend(self.step(dt, step_input));
+            step_input = &empty_input;
+            elapsed += dt;
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 训练靶：生命值15，防御力0，每秒向下移动100
+    fn train_target(position: [f32; 2], approach_time: f32) -> OperationTargetEnemy {
+        OperationTargetEnemy {
+            enemy_recognition_name: "Train_Target".to_string(),
+            enemy_position: position,
+            enemy_size: [100_f32, 100_f32],
+            enemy_path: vec!["down".to_string()],
+            enemy_approach_time: approach_time,
+            enemy_approach_alpha: 0,
+            enemy_increase_alpha_speed: 255,
+        }
+    }
+
+    fn simulation(target_point: u32, target_enemy: Vec<OperationTargetEnemy>) -> Simulation {
+        let mut operation = Simulation::default().operation;
+        operation.global.target_point = target_point;
+        operation.global.target_line = vec![[0_f32, 720_f32], [1280_f32, 720_f32]];
+        operation.target_enemy = target_enemy;
+        let simulation = Simulation::new(operation, vec![]);
+        assert!(
+            simulation.load_error.is_empty(),
+            "{:?}",
+            simulation.load_error
+        );
+        simulation
+    }
+
+    fn shot(rect: [f32; 4], damage: f32) -> SimulationInput {
+        SimulationInput {
+            shots: vec![SimulationShot {
+                shot_rect: rect,
+                shot_damage: damage,
+                shot_damage_type: DamageType::Kinetic,
+                shot_status: vec![],
+                shot_tag: vec![],
+                shot_pierce: false,
+                shot_splash: 0_f32,
+            }],
+            instrument_placements: vec![],
+        }
+    }
+
+    #[test]
+    fn enemy_spawns_at_approach_time() {
+        let mut simulation = simulation(3, vec![train_target([640_f32, 200_f32], 1_f32)]);
+        simulation.run_for(0.5, SIMULATION_DT, &SimulationInput::default());
+        assert!(!simulation.enemy_list[0].enemy_activated);
+        simulation.run_for(0.6, SIMULATION_DT, &SimulationInput::default());
+        assert!(simulation.enemy_list[0].enemy_activated);
+        assert_eq!(simulation.enemy_list[0].enemy_alpha, 255);
+        // 完全显现后才开始沿路径向下移动
+        assert!(simulation.enemy_list[0].enemy_position[1] > 200_f32);
+        assert!(!simulation.operation_over());
+    }
+
+    #[test]
+    fn breach_costs_target_point() {
+        let mut simulation = simulation(3, vec![train_target([640_f32, 600_f32], 0_f32)]);
+        let events = simulation.run_for(3_f32, SIMULATION_DT, &SimulationInput::default());
+        assert!(events.contains(&SimulationEvent::EnemyBreach(0)));
+        assert_eq!(simulation.target_point, 2);
+        assert!(!simulation.perfect_clear);
+        assert!(simulation.operation_over());
+    }
+
+    #[test]
+    fn losing_all_target_points_ends_operation() {
+        let mut simulation = simulation(
+            1,
+            vec![
+                train_target([640_f32, 600_f32], 0_f32),
+                train_target([200_f32, 100_f32], 0_f32),
+            ],
+        );
+        simulation.run_for(3_f32, SIMULATION_DT, &SimulationInput::default());
+        assert_eq!(simulation.target_point, 0);
+        assert!(simulation.operation_over());
+        // 第二个敌人尚未到达防线
+        assert!(simulation.enemy_list[1].enemy_activated);
+    }
+
+    #[test]
+    fn shot_kills_enemy_with_perfect_clear() {
+        let mut simulation = simulation(3, vec![train_target([640_f32, 300_f32], 0_f32)]);
+        simulation.run_for(0.5, SIMULATION_DT, &SimulationInput::default());
+        simulation.fire_round();
+        // 同一发子弹的两帧判定只计一次命中
+        let mut events = simulation.step(
+            SIMULATION_DT,
+            &shot([0_f32, 0_f32, 1280_f32, 700_f32], 5_f32),
+        );
+        events.extend(simulation.step(
+            SIMULATION_DT,
+            &shot([0_f32, 0_f32, 1280_f32, 700_f32], 5_f32),
+        ));
+        assert!(events.contains(&SimulationEvent::EnemyHit(0)));
+        assert_eq!(simulation.enemy_list[0].enemy_hp, 10_f32);
+        // 等待无敌时间结束
+        simulation.run_for(0.2, SIMULATION_DT, &SimulationInput::default());
+        let events = simulation.step(
+            SIMULATION_DT,
+            &shot([0_f32, 0_f32, 1280_f32, 700_f32], 100_f32),
+        );
+        assert!(events.contains(&SimulationEvent::EnemyDeath(0)));
+        assert_eq!(simulation.shots_fired, 1);
+        assert_eq!(simulation.shots_hit, 1);
+        assert_eq!(simulation.enemy_killed, 1);
+        assert_eq!(simulation.target_point, 3);
+        assert!(simulation.perfect_clear);
+        assert!(simulation.operation_over());
+    }
+
+    #[test]
+    fn shot_outside_enemy_misses() {
+        let mut simulation = simulation(3, vec![train_target([640_f32, 300_f32], 0_f32)]);
+        simulation.run_for(0.5, SIMULATION_DT, &SimulationInput::default());
+        simulation.fire_round();
+        let events = simulation.step(
+            SIMULATION_DT,
+            &shot([0_f32, 0_f32, 100_f32, 100_f32], 100_f32),
+        );
+        assert!(events.is_empty());
+        assert_eq!(simulation.shots_fired, 1);
+        assert_eq!(simulation.shots_hit, 0);
+        assert!(!simulation.operation_over());
+    }
+}
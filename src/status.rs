@@ -0,0 +1,149 @@
+//! status.rs is the damage model of the Targeted Vector, it defines damage types, enemy resistances and the timed status effects that guns apply.
+use crate::load_error::{FieldKind, LoadError};
+use json::JsonValue;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Kinetic,
+    Thermal,
+    Electric,
+    Explosive,
+}
+
+impl DamageType {
+    pub const ALL: [DamageType; 4] = [
+        DamageType::Kinetic,
+        DamageType::Thermal,
+        DamageType::Electric,
+        DamageType::Explosive,
+    ];
+
+    // 在枪械标签与敌人抗性中使用的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Kinetic => "kinetic",
+            DamageType::Thermal => "thermal",
+            DamageType::Electric => "electric",
+            DamageType::Explosive => "explosive",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DamageType> {
+        DamageType::ALL.into_iter().find(|x| x.name() == name)
+    }
+}
+
+// 读取敌人的抗性表：{"thermal": 0.5, "electric": -0.25}
+// 抗性为伤害的减免比例，负数表示弱点，缺省的伤害类型没有抗性
+pub fn parse_resistance(value: &JsonValue) -> Result<HashMap<DamageType, f32>, LoadError> {
+    let mut resistance = HashMap::new();
+    if value.is_null() {
+        return Ok(resistance);
+    };
+    if !value.is_object() {
+        return Err(LoadError::expected(FieldKind::Object, value));
+    };
+    for (name, amount) in value.entries() {
+        let Some(damage_type) = DamageType::from_name(name) else {
+            return Err(LoadError::new(
+                "kinetic, thermal, electric or explosive",
+                &JsonValue::from(name),
+            )
+            .at(name));
+        };
+        let Some(amount) = amount.as_f32() else {
+            return Err(LoadError::expected(FieldKind::Number, amount).at(name));
+        };
+        resistance.insert(damage_type, amount);
+    }
+    Ok(resistance)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    // 移动速度降低的比例
+    Slow,
+    // 每秒造成的热能伤害，无视防御力
+    Burn,
+    // 无法移动
+    Stun,
+    // 防御力降低的数值
+    ArmorBreak,
+    // 受到的伤害提高的比例
+    Mark,
+}
+
+impl StatusKind {
+    pub const ALL: [StatusKind; 5] = [
+        StatusKind::Slow,
+        StatusKind::Burn,
+        StatusKind::Stun,
+        StatusKind::ArmorBreak,
+        StatusKind::Mark,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Slow => "slow",
+            StatusKind::Burn => "burn",
+            StatusKind::Stun => "stun",
+            StatusKind::ArmorBreak => "armor_break",
+            StatusKind::Mark => "mark",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<StatusKind> {
+        StatusKind::ALL.into_iter().find(|x| x.name() == name)
+    }
+
+    // 敌人头顶状态图标的底色与字符
+    pub fn icon(&self) -> ([u8; 3], &'static str) {
+        match self {
+            StatusKind::Slow => ([80, 170, 255], "S"),
+            StatusKind::Burn => ([255, 120, 30], "B"),
+            StatusKind::Stun => ([250, 220, 50], "!"),
+            StatusKind::ArmorBreak => ([170, 170, 170], "A"),
+            StatusKind::Mark => ([220, 50, 90], "M"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusEffect {
+    pub status_kind: StatusKind,
+    pub status_strength: f32,
+    pub status_duration: f32,
+}
+
+impl StatusEffect {
+    // 写法为"slow:比例:秒数"、"burn:每秒伤害:秒数"、"stun:秒数"、"armor_break:数值:秒数"或"mark:比例:秒数"
+    pub fn from_tag(name: &str, args: &[&str]) -> Option<StatusEffect> {
+        let status_kind = StatusKind::from_name(name)?;
+        let arg_f = |index: usize, default: f32| {
+            args.get(index)
+                .and_then(|x| x.parse::<f32>().ok())
+                .unwrap_or(default)
+        };
+        let (status_strength, status_duration) = match status_kind {
+            StatusKind::Slow => (arg_f(0, 0.5).clamp(0_f32, 1_f32), arg_f(1, 2_f32)),
+            StatusKind::Burn => (arg_f(0, 3_f32), arg_f(1, 3_f32)),
+            StatusKind::Stun => (1_f32, arg_f(0, 0.5)),
+            StatusKind::ArmorBreak => (arg_f(0, 2_f32), arg_f(1, 4_f32)),
+            StatusKind::Mark => (arg_f(0, 0.25), arg_f(1, 5_f32)),
+        };
+        Some(StatusEffect {
+            status_kind,
+            status_strength,
+            status_duration: status_duration.max(0_f32),
+        })
+    }
+}
+
+// 敌人身上正在生效的状态，同种状态只保留一个
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveStatus {
+    pub status_kind: StatusKind,
+    pub status_strength: f32,
+    pub status_expire_time: f32,
+}
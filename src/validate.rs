@@ -0,0 +1,697 @@
+//! validate.rs checks every file in Resources/config and the references between them, it is used by the --validate launch option.
+use crate::function::{
+    list_files_recursive, read_from_json, Config, EnemyTag, GameText, Gun, GunTag, Instrument,
+    JsonReadEnemy, Map, MovePath, Operation, OperationTargetEnemy, User,
+};
+use crate::load_error::{pointer_join, LoadError};
+use crate::score::MAX_STAR;
+use crate::script::{SpawnGroup, TriggerAction, TriggerCondition};
+use crate::status::DamageType;
+use json::JsonValue;
+use std::collections::HashMap;
+use std::path::Path;
+
+// 地图文件名去掉目录与".json"后的部分；引用地图时既有完整路径也有"map_tutorial"这样的名称
+pub fn map_key(name: &str) -> String {
+    Path::new(name)
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// 以map_key为键收集各地图文件中的关卡名
+pub fn map_level_names(files: &[String]) -> HashMap<String, Vec<String>> {
+    let mut map_level = HashMap::new();
+    for file in files {
+        if let Some(map) = read_from_json(file)
+            .ok()
+            .and_then(|x| Map::from_json_value(&x).ok())
+        {
+            map_level.insert(
+                map_key(file),
+                map.map_content
+                    .iter()
+                    .map(|x| x.level_name.clone())
+                    .collect::<Vec<String>>(),
+            );
+        };
+    }
+    map_level
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationProblem {
+    pub file: String,
+    // RFC 6901格式的JSON指针
+    pub pointer: String,
+    pub message: String,
+}
+
+// 字段的格式由游戏使用的from_json_value检查，这里只补充跨文件的引用与数值上的检查
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+    pub problems: Vec<ValidationProblem>,
+    pub amount_languages: usize,
+    pub game_text_key: Vec<String>,
+}
+
+impl Validator {
+    pub fn report(&mut self, file: &str, pointer: &str, message: &str) {
+        self.problems.push(ValidationProblem {
+            file: file.to_string(),
+            pointer: pointer.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    pub fn read(&mut self, file: &str) -> Option<JsonValue> {
+        match read_from_json(file) {
+            Ok(json_value) => Some(json_value),
+            Err(e) => {
+                self.report(file, "", &format!("{:#}", e));
+                None
+            }
+        }
+    }
+
+    // 使用游戏读取该文件时的函数解析，失败时报告出错的字段
+    pub fn load<T>(
+        &mut self,
+        file: &str,
+        parse: impl FnOnce(&JsonValue) -> Result<T, LoadError>,
+    ) -> Option<T> {
+        let value = self.read(file)?;
+        match parse(&value) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.report(
+                    file,
+                    &e.pointer,
+                    &format!("expected {}, found {}", e.expected, e.found),
+                );
+                None
+            }
+        }
+    }
+
+    // 检查引用的资源文件是否存在
+    pub fn asset(&mut self, file: &str, pointer: &str, path: &str) {
+        if !Path::new(path).is_file() {
+            self.report(file, pointer, &format!("file not found: {}", path));
+        };
+    }
+
+    // 检查多语言文本数组的长度是否与语言数量一致
+    pub fn localized(&mut self, file: &str, pointer: &str, text: &[String]) {
+        if text.len() != self.amount_languages {
+            self.report(
+                file,
+                pointer,
+                &format!(
+                    "expected {} languages, found {}",
+                    self.amount_languages,
+                    text.len()
+                ),
+            );
+        };
+    }
+
+    // 检查动画的每一帧是否存在
+    pub fn animation(&mut self, file: &str, pointer: &str, image: &str, count: u32, kind: &str) {
+        for i in 0..count {
+            let frame = format!("{}_{}{}", image, i, kind);
+            if !Path::new(&frame).is_file() {
+                self.report(
+                    file,
+                    pointer,
+                    &format!("animation frame not found: {}", frame),
+                );
+            };
+        }
+    }
+
+    pub fn validate_preferences(&mut self, file: &str) {
+        if let Some(config) = self.load(file, Config::from_json_value) {
+            self.amount_languages = config.amount_languages as usize;
+            let mut page_music: Vec<(String, String)> = config.page_music.into_iter().collect();
+            page_music.sort();
+            for (page, music) in page_music {
+                self.asset(file, &pointer_join("/page_music", &page), &music);
+            }
+        };
+    }
+
+    pub fn validate_game_text(&mut self, file: &str) {
+        if let Some(game_text) = self.load(file, GameText::from_json_value) {
+            let mut game_text: Vec<(String, Vec<String>)> =
+                game_text.game_text.into_iter().collect();
+            game_text.sort();
+            for (key, text) in game_text {
+                self.localized(file, &pointer_join("/game_text", &key), &text);
+                self.game_text_key.push(key);
+            }
+        };
+    }
+
+    pub fn validate_enemy(&mut self, file: &str) {
+        let Some(enemy) = self.load(file, JsonReadEnemy::from_json_value) else {
+            return;
+        };
+        // 关卡通过识别名的小写形式定位敌人文件
+        let expected = format!("enemy_{}.json", enemy.enemy_recognition_name.to_lowercase());
+        if Path::new(file)
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            != Some(expected.clone())
+        {
+            self.report(
+                file,
+                "/enemy_recognition_name",
+                &format!("levels will look for this enemy in {}", expected),
+            );
+        };
+        self.localized(file, "/enemy_name", &enemy.enemy_name);
+        self.animation(
+            file,
+            "/enemy_image",
+            &enemy.enemy_image,
+            enemy.enemy_image_count,
+            &enemy.enemy_image_type,
+        );
+        for (i, tag) in enemy.enemy_tag.iter().enumerate() {
+            let pointer = format!("/enemy_tag/{}", i);
+            match EnemyTag::from_tag(tag, enemy.enemy_hp) {
+                Some(EnemyTag::Splitter { child, .. }) => {
+                    self.enemy_reference(file, &pointer, &child);
+                }
+                Some(_) => {}
+                None => {
+                    self.report(file, &pointer, &format!("unknown enemy tag {}", tag));
+                }
+            };
+        }
+        if let Some(boss) = &enemy.enemy_boss {
+            self.localized(file, "/enemy_boss/boss_name", &boss.boss_name);
+            for (i, phase) in boss.boss_phase.iter().enumerate() {
+                let pointer = format!("/enemy_boss/boss_phase/{}", i);
+                if let Some(phase_image) = &phase.phase_image {
+                    // 未指定帧数时沿用敌人原本的帧数
+                    self.animation(
+                        file,
+                        &pointer_join(&pointer, "phase_image"),
+                        phase_image,
+                        phase.phase_image_count.unwrap_or(enemy.enemy_image_count),
+                        &enemy.enemy_image_type,
+                    );
+                };
+                for (u, path_name) in phase.phase_path.iter().enumerate() {
+                    self.path_reference(file, &format!("{}/phase_path/{}", pointer, u), path_name);
+                }
+                for (u, (minion, _)) in phase.phase_minion.iter().enumerate() {
+                    self.enemy_reference(
+                        file,
+                        &format!("{}/phase_minion/{}/minion_recognition_name", pointer, u),
+                        minion,
+                    );
+                }
+            }
+        };
+    }
+
+    pub fn enemy_reference(&mut self, file: &str, pointer: &str, name: &str) {
+        let path = format!("Resources/config/enemy_{}.json", name.to_lowercase());
+        if !Path::new(&path).is_file() {
+            self.report(
+                file,
+                pointer,
+                &format!("unknown enemy {} ({} not found)", name, path),
+            );
+        };
+    }
+
+    pub fn path_reference(&mut self, file: &str, pointer: &str, name: &str) {
+        let path = format!("Resources/config/path_{}.json", name.to_lowercase());
+        if !Path::new(&path).is_file() {
+            self.report(
+                file,
+                pointer,
+                &format!("unknown path {} ({} not found)", name, path),
+            );
+        };
+    }
+
+    pub fn validate_gun(&mut self, file: &str) {
+        let Some(gun) = self.load(file, Gun::from_json_value) else {
+            return;
+        };
+        self.localized(file, "/gun_name", &gun.gun_name);
+        for (key, path) in [
+            ("gun_image", &gun.gun_image),
+            ("gun_shoot_sound", &gun.gun_shoot_sound),
+            ("gun_no_bullet_shoot_sound", &gun.gun_no_bullet_shoot_sound),
+            ("gun_reload_bullet_sound", &gun.gun_reload_bullet_sound),
+            ("gun_reload_sound", &gun.gun_reload_sound),
+            ("gun_overheating_sound", &gun.gun_overheating_sound),
+        ] {
+            self.asset(file, &pointer_join("", key), path);
+        }
+        if !gun.gun_firing.iter().any(|x| {
+            matches!(
+                x,
+                GunTag::ReleasedShoot | GunTag::DownShoot | GunTag::Charge { .. } | GunTag::Beam
+            )
+        }) {
+            self.report(
+                file,
+                "/gun_tag",
+                "needs \"released_shoot\", \"down_shoot\", \"charge\" or \"beam\", otherwise the gun never fires",
+            );
+        };
+        for (i, tag) in gun.gun_tag.iter().enumerate() {
+            if let Some(damage_type) = tag.strip_prefix("damage:") {
+                if DamageType::from_name(damage_type).is_none() {
+                    self.report(
+                        file,
+                        &format!("/gun_tag/{}", i),
+                        &format!("unknown damage type {}", damage_type),
+                    );
+                };
+            };
+        }
+    }
+
+    pub fn validate_instrument(&mut self, file: &str) {
+        let Some(instrument) = self.load(file, Instrument::from_json_value) else {
+            return;
+        };
+        self.localized(file, "/instrument_name", &instrument.instrument_name);
+        self.asset(file, "/instrument_image", &instrument.instrument_image);
+        self.asset(
+            file,
+            "/instrument_shoot_sound",
+            &instrument.instrument_shoot_sound,
+        );
+    }
+
+    pub fn validate_path(&mut self, file: &str) {
+        // 旧版路径的换算速度不影响格式检查
+        let Some(path) = self.load(file, |x| MovePath::from_json_value(x, 1_f32)) else {
+            return;
+        };
+        if path.path_segment.is_empty() {
+            self.report(file, "/path_segment", "a path needs at least one segment");
+        };
+    }
+
+    pub fn validate_level(&mut self, file: &str) {
+        let Some(operation) = self.load(file, Operation::from_json_value) else {
+            return;
+        };
+        let global = &operation.global;
+        for (key, path) in [
+            ("operation_background", &global.operation_background),
+            (
+                "operation_background_expand",
+                &global.operation_background_expand,
+            ),
+            (
+                "operation_start_background",
+                &global.operation_start_background,
+            ),
+            (
+                "operation_over_background",
+                &global.operation_over_background,
+            ),
+        ] {
+            self.asset(file, &pointer_join("/global", key), path);
+        }
+        let mut last = 0;
+        for (i, threshold) in global.star_threshold.iter().enumerate() {
+            if *threshold < last {
+                self.report(
+                    file,
+                    &format!("/global/star_threshold/{}", i),
+                    "star thresholds should not decrease",
+                );
+            };
+            last = *threshold;
+        }
+        if global.star_threshold.len() > MAX_STAR as usize {
+            self.report(
+                file,
+                "/global/star_threshold",
+                &format!("only the first {} thresholds are used", MAX_STAR),
+            );
+        };
+        for (i, enemy) in operation.target_enemy.iter().enumerate() {
+            self.level_enemy(file, enemy, &format!("/target_enemy/{}", i));
+        }
+        for (i, message_box) in operation.message_box.iter().enumerate() {
+            let pointer = format!("/message_box/{}", i);
+            self.asset(
+                file,
+                &pointer_join(&pointer, "box_image_path"),
+                &message_box.box_image_path,
+            );
+            self.localized(
+                file,
+                &pointer_join(&pointer, "box_title"),
+                &message_box.box_title,
+            );
+            self.localized(
+                file,
+                &pointer_join(&pointer, "box_content"),
+                &message_box.box_content,
+            );
+        }
+        self.validate_script(file, &operation);
+    }
+
+    // 检查关卡中的一个敌人引用的敌人与路径，关卡的敌人列表与脚本中的生成组共用
+    fn level_enemy(&mut self, file: &str, enemy: &OperationTargetEnemy, pointer: &str) {
+        self.enemy_reference(
+            file,
+            &pointer_join(pointer, "enemy_recognition_name"),
+            &enemy.enemy_recognition_name,
+        );
+        for (u, path_name) in enemy.enemy_path.iter().enumerate() {
+            self.path_reference(file, &format!("{}/enemy_path/{}", pointer, u), path_name);
+        }
+    }
+
+    fn validate_script(&mut self, file: &str, operation: &Operation) {
+        // 触发器通过名称引用波次与消息框
+        let wave_name: Vec<&String> = operation.wave.iter().map(|x| &x.wave_name).collect();
+        let box_name: Vec<&String> = operation
+            .message_box
+            .iter()
+            .map(|x| &x.box_trigger_phase)
+            .filter(|x| !x.is_empty())
+            .collect();
+        for (i, wave) in operation.wave.iter().enumerate() {
+            for (u, group) in wave.wave_group.iter().enumerate() {
+                self.spawn_group(file, group, &format!("/wave/{}/wave_group/{}", i, u));
+            }
+        }
+        for (i, trigger) in operation.trigger.iter().enumerate() {
+            let pointer = format!("/trigger/{}", i);
+            if let TriggerCondition::WaveCleared(name) = &trigger.trigger_condition {
+                if !wave_name.contains(&name) {
+                    self.report(
+                        file,
+                        &pointer_join(&pointer, "trigger_wave"),
+                        &format!("unknown wave {}", name),
+                    );
+                };
+            };
+            for (u, action) in trigger.trigger_action.iter().enumerate() {
+                let action_pointer = format!("{}/trigger_action/{}", pointer, u);
+                match action {
+                    TriggerAction::MessageBox(name) => {
+                        if !box_name.contains(&name) {
+                            self.report(
+                                file,
+                                &pointer_join(&action_pointer, "action_message_box"),
+                                &format!("no message box has box_trigger_phase {}", name),
+                            );
+                        };
+                    }
+                    TriggerAction::Music(path) => {
+                        // 空路径表示停止音乐
+                        if !path.is_empty() {
+                            self.asset(file, &pointer_join(&action_pointer, "action_music"), path);
+                        };
+                    }
+                    TriggerAction::GrantBullet(_) => {}
+                    TriggerAction::StartWave(name) => {
+                        if !wave_name.contains(&name) {
+                            self.report(
+                                file,
+                                &pointer_join(&action_pointer, "action_wave"),
+                                &format!("unknown wave {}", name),
+                            );
+                        };
+                    }
+                    TriggerAction::SpawnGroup(group) => {
+                        self.spawn_group(
+                            file,
+                            group,
+                            &pointer_join(&action_pointer, "action_group"),
+                        );
+                    }
+                };
+            }
+        }
+    }
+
+    fn spawn_group(&mut self, file: &str, group: &SpawnGroup, pointer: &str) {
+        self.level_enemy(
+            file,
+            &group.group_enemy,
+            &pointer_join(pointer, "group_enemy"),
+        );
+    }
+
+    // map_level为map_level_names收集的各地图关卡名，用于检查跨地图的解锁引用
+    pub fn validate_map(&mut self, file: &str, map_level: &HashMap<String, Vec<String>>) {
+        let Some(map) = self.load(file, Map::from_json_value) else {
+            return;
+        };
+        self.localized(file, "/map_name", &map.map_name);
+        self.localized(file, "/map_description", &map.map_description);
+        self.localized(file, "/map_unlock_description", &map.map_unlock_description);
+        self.asset(file, "/map_image", &map.map_image);
+        self.asset(file, "/map_intro", &map.map_intro);
+        self.asset(file, "/map_lock_intro", &map.map_lock_intro);
+        if !map.map_music.is_empty() {
+            self.asset(file, "/map_music", &map.map_music);
+        };
+        if let Some(endless) = &map.map_endless {
+            self.asset(file, "/map_endless/endless_level", &endless.endless_level);
+            for (i, name) in endless.endless_enemy_pool.iter().enumerate() {
+                self.enemy_reference(
+                    file,
+                    &format!("/map_endless/endless_enemy_pool/{}", i),
+                    name,
+                );
+            }
+            for (i, name) in endless.endless_path_pool.iter().enumerate() {
+                self.path_reference(file, &format!("/map_endless/endless_path_pool/{}", i), name);
+            }
+        };
+        for (i, level) in map.map_content.iter().enumerate() {
+            let pointer = format!("/map_content/{}", i);
+            // 与Select_Level页面拼接关卡路径的方式保持一致
+            let level_path = format!(
+                "{}_{}.json",
+                file.replace("map_", "level_").replace(".json", ""),
+                level.level_name
+            );
+            if !Path::new(&level_path).is_file() {
+                self.report(
+                    file,
+                    &pointer_join(&pointer, "level_name"),
+                    &format!("level file not found: {}", level_path),
+                );
+            };
+            self.localized(
+                file,
+                &pointer_join(&pointer, "level_name_expand"),
+                &level.level_name_expand,
+            );
+            self.localized(
+                file,
+                &pointer_join(&pointer, "level_description"),
+                &level.level_description,
+            );
+            if !self.game_text_key.contains(&level.level_type) {
+                self.report(
+                    file,
+                    &pointer_join(&pointer, "level_type"),
+                    &format!(
+                        "level type {} has no entry in GameText.json",
+                        level.level_type
+                    ),
+                );
+            };
+            for status in 0..=2 {
+                self.asset(
+                    file,
+                    &pointer_join(&pointer, "level_type"),
+                    &format!(
+                        "Resources/assets/images/level_{}{}.png",
+                        level.level_type, status
+                    ),
+                );
+            }
+            for (u, unlock_map) in level.unlock_map.iter().enumerate() {
+                if !map_level.contains_key(&map_key(&unlock_map.map_name)) {
+                    self.report(
+                        file,
+                        &format!("{}/unlock_map/{}/map_name", pointer, u),
+                        &format!("unknown map {}", unlock_map.map_name),
+                    );
+                };
+            }
+            for (u, unlock_level) in level.unlock_level.iter().enumerate() {
+                let unlock_pointer = format!("{}/unlock_level/{}", pointer, u);
+                match map_level.get(&map_key(&unlock_level.level_map)) {
+                    Some(level_list) => {
+                        if !level_list.contains(&unlock_level.level_name) {
+                            self.report(
+                                file,
+                                &pointer_join(&unlock_pointer, "level_name"),
+                                &format!(
+                                    "{} has no level {}",
+                                    unlock_level.level_map, unlock_level.level_name
+                                ),
+                            );
+                        };
+                    }
+                    None => self.report(
+                        file,
+                        &pointer_join(&unlock_pointer, "level_map"),
+                        &format!("unknown map {}", unlock_level.level_map),
+                    ),
+                };
+            }
+        }
+        for (i, line) in map.map_connecting_line.iter().enumerate() {
+            for (u, level_name) in line.iter().enumerate() {
+                if !map.map_content.iter().any(|x| &x.level_name == level_name) {
+                    self.report(
+                        file,
+                        &format!("/map_connecting_line/{}/{}", i, u),
+                        &format!("unknown level {} in this map", level_name),
+                    );
+                };
+            }
+        }
+    }
+
+    pub fn validate_user(&mut self, file: &str, map_level: &HashMap<String, Vec<String>>) {
+        let Some(user) = self.load(file, User::from_json_value) else {
+            return;
+        };
+        if !user.current_map.is_empty() && !map_level.contains_key(&map_key(&user.current_map)) {
+            self.report(
+                file,
+                "/current_map",
+                &format!("unknown map {}", user.current_map),
+            );
+        };
+    }
+}
+
+// 校验Resources/config下的全部文件
+pub fn validate_resources() -> Vec<ValidationProblem> {
+    let config_dir = Path::new("Resources/config");
+    let list = |prefix: &str| -> Vec<String> {
+        let mut files: Vec<String> = list_files_recursive(config_dir, prefix)
+            .unwrap_or_default()
+            .iter()
+            .map(|x| x.display().to_string())
+            .collect();
+        files.sort();
+        files
+    };
+    let mut validator = Validator::default();
+    validator.validate_preferences("Resources/config/Preferences.json");
+    validator.validate_game_text("Resources/config/GameText.json");
+    // 先收集各地图的关卡名，解锁条件可能引用其他地图
+    let map_level = map_level_names(&list("map_"));
+    for file in list("map_") {
+        validator.validate_map(&file, &map_level);
+    }
+    for file in list("level_") {
+        validator.validate_level(&file);
+    }
+    for file in list("enemy_") {
+        validator.validate_enemy(&file);
+    }
+    for file in list("path_") {
+        validator.validate_path(&file);
+    }
+    for file in list("gun_") {
+        validator.validate_gun(&file);
+    }
+    for file in list("instrument_") {
+        validator.validate_instrument(&file);
+    }
+    for file in list("user_") {
+        validator.validate_user(&file, &map_level);
+    }
+    validator.problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::function::DEFAULT_SAVE_SLOT;
+
+    fn map_level() -> HashMap<String, Vec<String>> {
+        map_level_names(&["Resources/config/map_tutorial.json".to_string()])
+    }
+
+    fn write_temp(name: &str, value: &JsonValue) -> String {
+        let file = std::env::temp_dir().join(name).display().to_string();
+        std::fs::write(&file, value.dump()).unwrap();
+        file
+    }
+
+    #[test]
+    fn registered_user_has_known_map() {
+        // 与注册新用户时写入的内容一致
+        let user = User {
+            name: "validate_test".to_string(),
+            password: "".to_string(),
+            language: 0,
+            wallpaper: "Resources/assets/images/wallpaper.png".to_string(),
+            current_map: "map_tutorial".to_string(),
+            level_status: vec![],
+            gun_status: vec![],
+            map_status: vec![],
+            settings: HashMap::new(),
+            current_level: "".to_string(),
+            currency: 0,
+            endless_status: vec![],
+            current_slot: DEFAULT_SAVE_SLOT.to_string(),
+            save_slot: vec![],
+        };
+        let file = write_temp("tv_validate_user.json", &user.to_json_value());
+        let mut validator = Validator::default();
+        validator.validate_user(&file, &map_level());
+        assert!(validator.problems.is_empty(), "{:?}", validator.problems);
+    }
+
+    #[test]
+    fn unlock_reference_accepts_map_name_and_path() {
+        let mut map = read_from_json("Resources/config/map_tutorial.json").unwrap();
+        map["map_content"][0]["unlock_map"] = json::array![{
+            map_name: "map_tutorial",
+            require_perfect_clear: false,
+        }];
+        map["map_content"][0]["unlock_level"] = json::array![
+            {
+                level_name: "1-2",
+                level_map: "map_tutorial",
+                require_perfect_clear: false,
+            },
+            {
+                level_name: "1-3",
+                level_map: "Resources/config/map_tutorial.json",
+                require_perfect_clear: false,
+            },
+        ];
+        let file = write_temp("tv_validate_map.json", &map);
+        let mut validator = Validator::default();
+        validator.validate_map(&file, &map_level());
+        // 临时文件旁没有关卡文件，只检查解锁引用
+        let unlock_problems: Vec<&ValidationProblem> = validator
+            .problems
+            .iter()
+            .filter(|x| x.pointer.contains("/unlock_"))
+            .collect();
+        assert!(unlock_problems.is_empty(), "{:?}", unlock_problems);
+    }
+}
@@ -0,0 +1,130 @@
+//! viewport.rs is the operation camera of the Targeted Vector, it maps level coordinates to the screen.
+use crate::simulation::OPERATION_SIZE;
+use egui::{Pos2, Rect, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    // 等比缩放至完整显示，空白处显示扩展背景
+    Fit,
+    // 等比缩放至铺满窗口，超出部分被裁切
+    Fill,
+    // 按整数倍缩放，保证像素清晰
+    IntegerScale,
+    // 与Fit相同，但空白处以黑边填充
+    Letterbox,
+}
+
+impl ScaleMode {
+    pub const ALL: [ScaleMode; 4] = [
+        ScaleMode::Fit,
+        ScaleMode::Fill,
+        ScaleMode::IntegerScale,
+        ScaleMode::Letterbox,
+    ];
+
+    pub fn from_setting(setting: &str) -> ScaleMode {
+        match setting {
+            "fill" => ScaleMode::Fill,
+            "integer_scale" => ScaleMode::IntegerScale,
+            "letterbox" => ScaleMode::Letterbox,
+            _ => ScaleMode::Fit,
+        }
+    }
+
+    // 在User::settings中保存缩放模式所用的值
+    pub fn setting(&self) -> &'static str {
+        match self {
+            ScaleMode::Fit => "fit",
+            ScaleMode::Fill => "fill",
+            ScaleMode::IntegerScale => "integer_scale",
+            ScaleMode::Letterbox => "letterbox",
+        }
+    }
+
+    // 在GameText.json中显示名称所用的键
+    pub fn game_text_key(&self) -> &'static str {
+        match self {
+            ScaleMode::Fit => "scale_mode_fit",
+            ScaleMode::Fill => "scale_mode_fill",
+            ScaleMode::IntegerScale => "scale_mode_integer_scale",
+            ScaleMode::Letterbox => "scale_mode_letterbox",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub mode: ScaleMode,
+    pub screen: Rect,
+    pub origin: [f32; 2],
+    pub scale: f32,
+}
+
+impl Viewport {
+    pub fn new(screen: Rect, mode: ScaleMode) -> Viewport {
+        let fit = (screen.width() / OPERATION_SIZE[0]).min(screen.height() / OPERATION_SIZE[1]);
+        let scale = match mode {
+            ScaleMode::Fit | ScaleMode::Letterbox => fit,
+            ScaleMode::Fill => {
+                (screen.width() / OPERATION_SIZE[0]).max(screen.height() / OPERATION_SIZE[1])
+            }
+            // 窗口小于作战区域时无法整数缩放，退回到等比缩放
+            ScaleMode::IntegerScale => {
+                if fit >= 1_f32 {
+                    fit.floor()
+                } else {
+                    fit
+                }
+            }
+        }
+        .max(0.01);
+        Viewport {
+            mode,
+            screen,
+            origin: [
+                screen.min.x + (screen.width() - OPERATION_SIZE[0] * scale) / 2_f32,
+                screen.min.y + (screen.height() - OPERATION_SIZE[1] * scale) / 2_f32,
+            ],
+            scale,
+        }
+    }
+
+    pub fn to_screen(self, position: [f32; 2]) -> Pos2 {
+        Pos2 {
+            x: self.origin[0] + position[0] * self.scale,
+            y: self.origin[1] + position[1] * self.scale,
+        }
+    }
+
+    pub fn to_level(self, position: Pos2) -> [f32; 2] {
+        [
+            (position.x - self.origin[0]) / self.scale,
+            (position.y - self.origin[1]) / self.scale,
+        ]
+    }
+
+    pub fn to_screen_size(self, size: [f32; 2]) -> [f32; 2] {
+        [size[0] * self.scale, size[1] * self.scale]
+    }
+
+    // 作战区域在屏幕上的完整范围，Fill模式下可能超出窗口
+    pub fn playfield(&self) -> Rect {
+        Rect::from_min_size(
+            self.to_screen([0_f32, 0_f32]),
+            Vec2::new(
+                OPERATION_SIZE[0] * self.scale,
+                OPERATION_SIZE[1] * self.scale,
+            ),
+        )
+    }
+
+    // 作战区域在窗口内实际可见的部分
+    pub fn visible(&self) -> Rect {
+        self.playfield().intersect(self.screen)
+    }
+
+    // 是否需要在作战区域外绘制扩展背景或黑边
+    pub fn has_margin(&self) -> bool {
+        self.visible() != self.screen
+    }
+}